@@ -24,7 +24,7 @@ fn record_iterator(file_path: &str, date_format: Option<String>) -> Result<(Reco
                 None => Err(ParserError{message: "File is empty".to_string() })
             }
         },
-        Err(error) => return Err(error)
+        Err(error) => Err(error)
     }
 }
 
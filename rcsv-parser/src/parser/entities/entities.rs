@@ -1,4 +1,4 @@
-#[derive(Debug, PartialEq)]
+#[derive(Debug, PartialEq, Clone)]
 pub enum Value {
     String(String),
     Int(i64),
@@ -18,6 +18,8 @@ pub struct Record {
     pub columns: Vec<Value>
 }
 
+// not built by the parsers yet
+#[allow(dead_code)]
 pub struct Table {
     columns: Vec<String>,
     records: Vec<Record>
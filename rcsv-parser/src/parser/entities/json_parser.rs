@@ -5,7 +5,7 @@ use crate::parser::error::ParserError;
 pub struct JSONParser {}
 
 impl Parse for JSONParser {
-    fn parse(&mut self, file_path: &str, date_format: Option<String>) -> Result<(RecordIterator, Option<Vec<String>>), ParserError> {
+    fn parse(&mut self, _file_path: &str, _date_format: Option<String>) -> Result<(RecordIterator, Option<Vec<String>>), ParserError> {
         todo!()
     }
 }
\ No newline at end of file
@@ -1,8 +1,9 @@
 pub mod parser;
+#[allow(clippy::module_inception)]
 pub mod entities;
 pub mod file_types;
 
 pub use entities::Record;
 pub mod csv_parser;
 pub mod json_parser;
-mod record_iterator;
\ No newline at end of file
+pub mod record_iterator;
\ No newline at end of file
@@ -4,15 +4,25 @@ use chrono::NaiveDate;
 use crate::parser::entities::entities::Value;
 use crate::parser::entities::Record;
 
+// decides whether a record is kept, only the columns it asked for are converted when it is called
+pub type RecordFilter = Box<dyn FnMut(&Record) -> bool>;
+
 pub struct RecordIterator {
     lines: Lines<BufReader<File>>,
     date_format: Option<String>,
+    // columns to convert, the others are left as Null; None converts every column
+    projection: Option<Vec<usize>>,
+    filter: Option<(Vec<usize>, RecordFilter)>,
+    bytes_read: u64,
 }
 
 pub fn create_record_iterator(lines: Lines<BufReader<File>>, date_format: Option<String>) -> RecordIterator {
     RecordIterator {
-        lines: lines,
-        date_format: date_format
+        lines,
+        date_format,
+        projection: None,
+        filter: None,
+        bytes_read: 0
     }
 }
 
@@ -23,7 +33,11 @@ impl Iterator for RecordIterator {
         while let Some(line) = self.lines.next() {
             match line {
                 Ok(line) => {
-                    return Some(self.line_to_record(line));
+                    // the line terminator is not part of the line, count it as one byte
+                    self.bytes_read += line.len() as u64 + 1;
+                    if let Some(record) = self.line_to_record(line) {
+                        return Some(record);
+                    }
                 },
                 Err(e) => {
                     eprintln!("Skipping line due to error: {}", e);
@@ -36,15 +50,50 @@ impl Iterator for RecordIterator {
 }
 
 impl RecordIterator {
-    fn line_to_record(&self, line: String) -> Record {
-        let raw_values: Vec<String> = line.split(",")
-            .map(|s| s.trim().to_string())
-            .collect();
-        let values: Vec<Value> = raw_values.iter()
-            .map(|raw_value| to_value(&raw_value, &self.date_format))
+    // bytes of the records read so far, including records dropped by the filter
+    pub fn bytes_read(&self) -> u64 {
+        self.bytes_read
+    }
+
+    pub fn with_projection(mut self, columns: Vec<usize>) -> Self {
+        self.projection = Some(columns);
+        self
+    }
+
+    // records rejected by the filter are dropped before the rest of their columns are converted
+    pub fn with_filter(mut self, columns: Vec<usize>, filter: RecordFilter) -> Self {
+        self.filter = Some((columns, filter));
+        self
+    }
+
+    fn line_to_record(&mut self, line: String) -> Option<Record> {
+        let raw_values: Vec<&str> = line.split(",")
+            .map(|s| s.trim())
             .collect();
+        let mut converted = vec![false; raw_values.len()];
+        let mut record = Record { columns: raw_values.iter().map(|_| Value::Null).collect() };
+
+        if let Some((columns, filter)) = &mut self.filter {
+            for index in columns.iter().filter(|index| **index < raw_values.len()) {
+                record.columns[*index] = to_value(raw_values[*index], &self.date_format);
+                converted[*index] = true;
+            }
+            if !filter(&record) {
+                return None;
+            }
+        }
 
-        Record { columns: values }
+        for (index, raw_value) in raw_values.iter().enumerate() {
+            let projected = match &self.projection {
+                Some(columns) => columns.contains(&index),
+                None => true
+            };
+            if projected && !converted[index] {
+                record.columns[index] = to_value(raw_value, &self.date_format);
+            }
+        }
+
+        Some(record)
     }
 }
 fn to_value (raw: &str, date_format: &Option<String>) -> Value {
@@ -67,16 +116,8 @@ fn to_value (raw: &str, date_format: &Option<String>) -> Value {
 
 fn is_valid_date(date_format: &Option<String>, input: &str) -> Option<NaiveDate> {
     match date_format {
-        Some(date_format) => {
-            let possible_date = NaiveDate::parse_from_str(input, date_format);
-            match possible_date{
-                Ok(date) => Some(date),
-                Err(e) => {
-                    eprintln!("{} is not a valid date: {}", input, e);
-                    None
-                }
-            }
-        },
+        // most cells which are not dates are plain strings, so a failed parse is not worth reporting
+        Some(date_format) => NaiveDate::parse_from_str(input, date_format).ok(),
         None => None
     }
 }
\ No newline at end of file
@@ -8,6 +8,5 @@ pub fn get_parser(file_type: &FileType) -> Result<Box<dyn Parse>, ParserError> {
     match file_type {
         FileType::CSV => Ok(Box::new(CsvParser {})),
         FileType::JSON => Ok(Box::new(JSONParser {})),
-        _ => Err(ParserError{message: "Unsupported file type".to_string()})
     }
 }
\ No newline at end of file
@@ -18,23 +18,24 @@ fn small_structured_file() {
             assert_eq!(header.unwrap().len(), 9);
             let cols =iter.next().unwrap().columns;
             assert_eq!(cols.len(), 9);
-            let mut expected: Vec<Value> = Vec::new();
-            expected.push(Value::Int(1));
-            expected.push(Value::String("8717bbf45cCDbEe".to_string()));
-            expected.push(Value::String("Shelia".to_string()));
-            expected.push(Value::String("Mahoney".to_string()));
-            expected.push(Value::String("Male".to_string()));
-            expected.push(Value::String("pwarner@example.org".to_string()));
-            expected.push(Value::String("857.139.8239".to_string()));
-            expected.push(Value::Date(chrono::NaiveDate::from_ymd_opt(2014, 01, 27).unwrap()));
-            expected.push(Value::String("Probation officer".to_string()));
+            let expected: Vec<Value> = vec![
+                Value::Int(1),
+                Value::String("8717bbf45cCDbEe".to_string()),
+                Value::String("Shelia".to_string()),
+                Value::String("Mahoney".to_string()),
+                Value::String("Male".to_string()),
+                Value::String("pwarner@example.org".to_string()),
+                Value::String("857.139.8239".to_string()),
+                Value::Date(chrono::NaiveDate::from_ymd_opt(2014, 1, 27).unwrap()),
+                Value::String("Probation officer".to_string()),
+            ];
             check_for_equality(&cols, &expected);
         },
         Err(e) => panic!("Test failed with parser error: {}", e.message)
     }
 }
 
-fn check_for_equality(actual: &Vec<Value>, expected: &Vec<Value>) {
+fn check_for_equality(actual: &[Value], expected: &[Value]) {
     assert_eq!(actual.len(), expected.len());
     for (actual, expected) in actual.iter().zip(expected.iter()) {
         assert_eq!(actual, expected);
@@ -91,4 +92,34 @@ fn file_with_missing_column() {
         },
         Err(e) => panic!("Test failed with parser error: {}", e.message)
     }
-}
\ No newline at end of file
+}
+#[test]
+fn projection_converts_only_listed_columns() {
+    let mut csv_parser = get_parser(&FileType::CSV).ok().expect("CSV parser expected");
+    let test_data = "src/parser/tests/test_data/people-02.csv";
+
+    let (iter, _) = csv_parser.parse(test_data, None).ok().expect("Records expected");
+    let first = iter.with_projection(vec![1]).next().unwrap().columns;
+    check_for_equality(&first, &[Value::Null, Value::String("Shelia".to_string()), Value::Null]);
+}
+
+#[test]
+fn filter_drops_records_before_conversion() {
+    let mut csv_parser = get_parser(&FileType::CSV).ok().expect("CSV parser expected");
+    let test_data = "src/parser/tests/test_data/people-02.csv";
+
+    let (iter, _) = csv_parser.parse(test_data, None).ok().expect("Records expected");
+    let records: Vec<Vec<Value>> = iter
+        .with_projection(vec![1])
+        .with_filter(vec![2], Box::new(|record| {
+            // only the filter column is converted at this point
+            assert_eq!(record.columns[1], Value::Null);
+            matches!(record.columns[2], Value::Int(age) if age > 30)
+        }))
+        .map(|record| record.columns)
+        .collect();
+
+    assert_eq!(records.len(), 2);
+    check_for_equality(&records[0], &[Value::Null, Value::String("Shelia".to_string()), Value::Int(34)]);
+    check_for_equality(&records[1], &[Value::Null, Value::String("Julie".to_string()), Value::Int(45)]);
+}
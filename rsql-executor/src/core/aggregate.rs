@@ -0,0 +1,448 @@
+use std::cmp::Ordering;
+use std::collections::{HashMap, HashSet};
+use rsql_parser::ast::constructs::{AggregateFunc, Value};
+use crate::core::errors::ExecutorError;
+use crate::core::evaluator::{compare, evaluate};
+use crate::core::metrics::row_size;
+use crate::core::plan::{AggregateCall, ResolvedExpr};
+use crate::core::sketch::{HyperLogLog, SpaceSaving, TDigest};
+
+// running state of one aggregate function within one group
+#[derive(Debug, Clone)]
+pub enum Accumulator {
+    Count(i64),
+    Sum(Option<Value>),
+    Avg { sum: f64, count: i64 },
+    Min(Option<Value>),
+    Max(Option<Value>),
+    // Welford's running mean and sum of squared deviations, which does not lose precision to cancellation the way
+    // the sum of squares does
+    Variance { count: i64, mean: f64, m2: f64, population: bool, root: bool },
+    // the values are kept until the end, where they are sorted
+    Percentile { values: Vec<Value>, fraction: f64, continuous: bool },
+    // each distinct value with how often it was seen, in the order they were first seen
+    Mode { positions: HashMap<KeyValue, usize>, counts: Vec<(Value, i64)> },
+    StringAgg { separator: String, joined: Option<String> },
+    ArrayAgg(Vec<Value>),
+    BoolAnd(Option<bool>),
+    BoolOr(Option<bool>),
+    CountIf(i64),
+    ApproxCountDistinct(HyperLogLog),
+    ApproxPercentile { digest: TDigest, fraction: f64 },
+    ApproxTopK { counters: SpaceSaving, k: usize }
+}
+
+impl Accumulator {
+    pub fn new(func: &AggregateFunc) -> Self {
+        match func {
+            AggregateFunc::Count => Accumulator::Count(0),
+            AggregateFunc::Sum => Accumulator::Sum(None),
+            AggregateFunc::Avg => Accumulator::Avg { sum: 0.0, count: 0 },
+            AggregateFunc::Min => Accumulator::Min(None),
+            AggregateFunc::Max => Accumulator::Max(None),
+            AggregateFunc::Stddev => Accumulator::variance(false, true),
+            AggregateFunc::StddevPop => Accumulator::variance(true, true),
+            AggregateFunc::Variance => Accumulator::variance(false, false),
+            AggregateFunc::VarPop => Accumulator::variance(true, false),
+            AggregateFunc::Median => Accumulator::Percentile { values: Vec::new(), fraction: 0.5, continuous: true },
+            AggregateFunc::PercentileCont(fraction) => Accumulator::Percentile { values: Vec::new(), fraction: *fraction, continuous: true },
+            AggregateFunc::PercentileDisc(fraction) => Accumulator::Percentile { values: Vec::new(), fraction: *fraction, continuous: false },
+            AggregateFunc::Mode => Accumulator::Mode { positions: HashMap::new(), counts: Vec::new() },
+            AggregateFunc::StringAgg(separator) => Accumulator::StringAgg { separator: separator.clone(), joined: None },
+            AggregateFunc::ArrayAgg => Accumulator::ArrayAgg(Vec::new()),
+            AggregateFunc::BoolAnd => Accumulator::BoolAnd(None),
+            AggregateFunc::BoolOr => Accumulator::BoolOr(None),
+            AggregateFunc::CountIf => Accumulator::CountIf(0),
+            AggregateFunc::ApproxCountDistinct => Accumulator::ApproxCountDistinct(HyperLogLog::new()),
+            AggregateFunc::ApproxPercentile(fraction) => Accumulator::ApproxPercentile { digest: TDigest::new(), fraction: *fraction },
+            // the counters beyond the k reported take in the rarer values, which keeps the counts of the top ones close
+            AggregateFunc::ApproxTopK(k) => {
+                let k = usize::try_from(*k).unwrap_or(usize::MAX);
+                Accumulator::ApproxTopK { counters: SpaceSaving::new(k.saturating_mul(10).max(100)), k }
+            }
+        }
+    }
+
+    fn variance(population: bool, root: bool) -> Self {
+        Accumulator::Variance { count: 0, mean: 0.0, m2: 0.0, population, root }
+    }
+
+    // whether the accumulator keeps the values it is given, so that its size grows with them
+    pub fn holds_values(&self) -> bool {
+        matches!(self, Accumulator::Percentile { .. } | Accumulator::Mode { .. } | Accumulator::StringAgg { .. } | Accumulator::ArrayAgg(_))
+    }
+
+    // `value` is None for COUNT(*), NULL values are ignored by every aggregate but ARRAY_AGG
+    pub fn update(&mut self, value: Option<Value>) -> Result<(), ExecutorError> {
+        if let (Accumulator::ArrayAgg(values), Some(value)) = (&mut *self, &value) {
+            values.push(value.clone());
+            return Ok(())
+        }
+        let value = match value {
+            None => {
+                if let Accumulator::Count(count) = self {
+                    *count += 1;
+                }
+                return Ok(())
+            },
+            Some(Value::Null) => return Ok(()),
+            Some(value) => value
+        };
+        match self {
+            Accumulator::Count(count) => *count += 1,
+            Accumulator::Sum(sum) => {
+                *sum = Some(match (sum.take(), value) {
+                    (None, value @ (Value::Int(_) | Value::Float(_))) => value,
+                    (Some(Value::Int(l)), Value::Int(r)) => Value::Int(l.checked_add(r).ok_or_else(|| error("integer overflow in SUM"))?),
+                    (Some(Value::Int(l)), Value::Float(r)) => Value::Float(l as f64 + r),
+                    (Some(Value::Float(l)), Value::Int(r)) => Value::Float(l + r as f64),
+                    (Some(Value::Float(l)), Value::Float(r)) => Value::Float(l + r),
+                    (_, value) => return Err(error(&format!("SUM expects numbers, found {}", value)))
+                })
+            },
+            Accumulator::Avg { sum, count } => {
+                *sum += match value {
+                    Value::Int(i) => i as f64,
+                    Value::Float(f) => f,
+                    value => return Err(error(&format!("AVG expects numbers, found {}", value)))
+                };
+                *count += 1;
+            },
+            Accumulator::Min(current) => keep(current, value, Ordering::Less)?,
+            Accumulator::Max(current) => keep(current, value, Ordering::Greater)?,
+            Accumulator::Variance { count, mean, m2, root, .. } => {
+                let value = number(value, if *root { "STDDEV" } else { "VARIANCE" })?;
+                *count += 1;
+                let delta = value - *mean;
+                *mean += delta / *count as f64;
+                *m2 += delta * (value - *mean);
+            },
+            Accumulator::Percentile { values, continuous: true, .. } => {
+                values.push(Value::Float(number(value, "PERCENTILE_CONT")?));
+            },
+            Accumulator::Percentile { values, continuous: false, .. } => {
+                // the values are sorted at the end, where comparing can no longer fail
+                if let Some(first) = values.first() {
+                    compare(&value, first)?;
+                }
+                values.push(value);
+            },
+            Accumulator::Mode { positions, counts } => {
+                let position = *positions.entry(KeyValue::from(&value)).or_insert_with(|| {
+                    counts.push((value, 0));
+                    counts.len() - 1
+                });
+                counts[position].1 += 1;
+            },
+            Accumulator::StringAgg { separator, joined } => {
+                let Value::String(value) = value else {
+                    return Err(error(&format!("STRING_AGG expects strings, found {}", value)))
+                };
+                match joined {
+                    Some(joined) => {
+                        joined.push_str(separator);
+                        joined.push_str(&value);
+                    },
+                    None => *joined = Some(value)
+                }
+            },
+            Accumulator::ArrayAgg(_) => {},
+            Accumulator::BoolAnd(all) => *all = Some(all.unwrap_or(true) && boolean(value, "BOOL_AND")?),
+            Accumulator::BoolOr(any) => *any = Some(any.unwrap_or(false) || boolean(value, "BOOL_OR")?),
+            Accumulator::CountIf(count) => if boolean(value, "COUNT_IF")? {
+                *count += 1;
+            },
+            Accumulator::ApproxCountDistinct(sketch) => sketch.insert(&KeyValue::from(&value)),
+            Accumulator::ApproxPercentile { digest, .. } => digest.insert(number(value, "APPROX_PERCENTILE")?),
+            Accumulator::ApproxTopK { counters, .. } => counters.insert(value)
+        }
+
+        Ok(())
+    }
+
+    // takes in the state of the same aggregate over other rows, so that the rows can be aggregated in parts
+    pub fn merge(&mut self, other: Accumulator) -> Result<(), ExecutorError> {
+        match (self, other) {
+            (Accumulator::Count(count), Accumulator::Count(other)) | (Accumulator::CountIf(count), Accumulator::CountIf(other)) => {
+                *count += other
+            },
+            (this @ Accumulator::Sum(_), Accumulator::Sum(other))
+            | (this @ Accumulator::Min(_), Accumulator::Min(other))
+            | (this @ Accumulator::Max(_), Accumulator::Max(other)) => this.update(other)?,
+            (this @ Accumulator::BoolAnd(_), Accumulator::BoolAnd(other)) | (this @ Accumulator::BoolOr(_), Accumulator::BoolOr(other)) => {
+                this.update(other.map(Value::Bool))?
+            },
+            (Accumulator::Avg { sum, count }, Accumulator::Avg { sum: other_sum, count: other_count }) => {
+                *sum += other_sum;
+                *count += other_count;
+            },
+            // Chan's combination of the means and deviations of both parts
+            (Accumulator::Variance { count, mean, m2, .. }, Accumulator::Variance { count: other_count, mean: other_mean, m2: other_m2, .. }) => {
+                let total = *count + other_count;
+                if total == 0 {
+                    return Ok(())
+                }
+                let delta = other_mean - *mean;
+                *mean += delta * other_count as f64 / total as f64;
+                *m2 += other_m2 + delta * delta * (*count as f64) * (other_count as f64) / total as f64;
+                *count = total;
+            },
+            (Accumulator::Percentile { values, .. }, Accumulator::Percentile { values: other, .. })
+            | (Accumulator::ArrayAgg(values), Accumulator::ArrayAgg(other)) => values.extend(other),
+            (Accumulator::Mode { positions, counts }, Accumulator::Mode { counts: other, .. }) => {
+                for (value, count) in other {
+                    let position = *positions.entry(KeyValue::from(&value)).or_insert_with(|| {
+                        counts.push((value, 0));
+                        counts.len() - 1
+                    });
+                    counts[position].1 += count;
+                }
+            },
+            (this @ Accumulator::StringAgg { .. }, Accumulator::StringAgg { joined: other, .. }) => this.update(other.map(Value::String))?,
+            (Accumulator::ApproxCountDistinct(sketch), Accumulator::ApproxCountDistinct(other)) => sketch.merge(&other),
+            (Accumulator::ApproxPercentile { digest, .. }, Accumulator::ApproxPercentile { digest: other, .. }) => digest.merge(&other),
+            (Accumulator::ApproxTopK { counters, .. }, Accumulator::ApproxTopK { counters: other, .. }) => counters.merge(&other),
+            _ => return Err(error("can not merge the states of different aggregates"))
+        }
+        Ok(())
+    }
+
+    pub fn finish(self) -> Value {
+        match self {
+            Accumulator::Count(count) => Value::Int(count),
+            Accumulator::Avg { count: 0, .. } => Value::Null,
+            Accumulator::Avg { sum, count } => Value::Float(sum / count as f64),
+            Accumulator::Sum(value) | Accumulator::Min(value) | Accumulator::Max(value) => value.unwrap_or(Value::Null),
+            // the sample variance needs two values at least
+            Accumulator::Variance { count, m2, population, root, .. } => {
+                let divisor = if population { count } else { count - 1 };
+                if divisor <= 0 {
+                    return Value::Null
+                }
+                let variance = m2 / divisor as f64;
+                Value::Float(if root { variance.sqrt() } else { variance })
+            },
+            Accumulator::Percentile { values, .. } if values.is_empty() => Value::Null,
+            Accumulator::Percentile { mut values, fraction, continuous } => {
+                values.sort_by(|left, right| compare(left, right).unwrap_or(Ordering::Equal));
+                if !continuous {
+                    // the first value whose cumulative distribution reaches the fraction
+                    let position = (fraction * values.len() as f64).ceil() as usize;
+                    return values.swap_remove(position.saturating_sub(1))
+                }
+                let position = fraction * (values.len() - 1) as f64;
+                let (lower, upper) = (as_number(&values[position.floor() as usize]), as_number(&values[position.ceil() as usize]));
+                Value::Float(lower + (upper - lower) * position.fract())
+            },
+            Accumulator::Mode { counts, .. } => counts.into_iter()
+                .fold(None, |most: Option<(Value, i64)>, (value, count)| match most {
+                    Some(most) if most.1 >= count => Some(most),
+                    _ => Some((value, count))
+                })
+                .map(|(value, _)| value)
+                .unwrap_or(Value::Null),
+            Accumulator::StringAgg { joined, .. } => joined.map(Value::String).unwrap_or(Value::Null),
+            Accumulator::ArrayAgg(values) if values.is_empty() => Value::Null,
+            Accumulator::ArrayAgg(values) => Value::List(values),
+            Accumulator::BoolAnd(value) | Accumulator::BoolOr(value) => value.map(Value::Bool).unwrap_or(Value::Null),
+            Accumulator::CountIf(count) => Value::Int(count),
+            Accumulator::ApproxCountDistinct(sketch) => Value::Int(sketch.estimate()),
+            Accumulator::ApproxPercentile { mut digest, fraction } => digest.quantile(fraction).map(Value::Float).unwrap_or(Value::Null),
+            Accumulator::ApproxTopK { counters, k } => match counters.top(k) {
+                values if values.is_empty() => Value::Null,
+                values => Value::List(values)
+            }
+        }
+    }
+}
+
+fn number(value: Value, func: &str) -> Result<f64, ExecutorError> {
+    match value {
+        Value::Int(i) => Ok(i as f64),
+        Value::Float(f) => Ok(f),
+        value => Err(error(&format!("{} expects numbers, found {}", func, value)))
+    }
+}
+
+fn as_number(value: &Value) -> f64 {
+    match value {
+        Value::Int(i) => *i as f64,
+        Value::Float(f) => *f,
+        _ => f64::NAN
+    }
+}
+
+fn boolean(value: Value, func: &str) -> Result<bool, ExecutorError> {
+    match value {
+        Value::Bool(b) => Ok(b),
+        value => Err(error(&format!("{} expects booleans, found {}", func, value)))
+    }
+}
+
+fn keep(current: &mut Option<Value>, value: Value, wanted: Ordering) -> Result<(), ExecutorError> {
+    let replace = match current {
+        None => true,
+        Some(existing) => compare(&value, existing)? == wanted
+    };
+    if replace {
+        *current = Some(value);
+    }
+    Ok(())
+}
+
+fn error(message: &str) -> ExecutorError {
+    ExecutorError { message: message.to_string() }
+}
+
+// hashable form of a value which follows the equality of values: 1 and 1.0 are the same key, as are 0.0 and -0.0,
+// NULL is a key of its own, so all NULLs fall in one group
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum KeyValue {
+    Null,
+    Bool(bool),
+    Int(i64),
+    Float(u64),
+    String(String),
+    Date(chrono::NaiveDate),
+    List(Vec<KeyValue>)
+}
+
+impl From<&Value> for KeyValue {
+    fn from(value: &Value) -> Self {
+        match value {
+            Value::Null => KeyValue::Null,
+            Value::Bool(b) => KeyValue::Bool(*b),
+            Value::Int(i) => KeyValue::Int(*i),
+            Value::Float(f) if f.fract() == 0.0 && *f >= i64::MIN as f64 && *f < i64::MAX as f64 => KeyValue::Int(*f as i64),
+            Value::Float(f) if f.is_nan() => KeyValue::Float(f64::NAN.to_bits()),
+            Value::Float(f) => KeyValue::Float(f.to_bits()),
+            Value::String(s) => KeyValue::String(s.clone()),
+            Value::Date(d) => KeyValue::Date(*d),
+            Value::List(values) => KeyValue::List(values.iter().map(KeyValue::from).collect())
+        }
+    }
+}
+
+pub type GroupKey = Vec<KeyValue>;
+
+// hash aggregation, groups come out in the order they were first seen; with grouping sets each row falls in one
+// group of every set, so all the sets are aggregated in a single pass over the rows
+pub struct HashAggregator {
+    group_by: Vec<ResolvedExpr>,
+    grouping_sets: Vec<Vec<usize>>,
+    aggregates: Vec<AggregateCall>,
+    groups: HashMap<GroupKey, usize>,
+    states: Vec<GroupState>,
+    memory: usize
+}
+
+struct GroupState {
+    values: Vec<Value>,
+    // the position of the grouping set of the group, None without grouping sets
+    set: Option<usize>,
+    accumulators: Vec<Accumulator>,
+    // values already aggregated by each DISTINCT aggregate, None for the others
+    seen: Vec<Option<HashSet<KeyValue>>>
+}
+
+impl GroupState {
+    fn new(values: Vec<Value>, set: Option<usize>, aggregates: &[AggregateCall]) -> Self {
+        GroupState {
+            values,
+            set,
+            accumulators: aggregates.iter().map(|call| Accumulator::new(&call.func)).collect(),
+            seen: aggregates.iter().map(|call| call.distinct.then(HashSet::new)).collect()
+        }
+    }
+}
+
+impl HashAggregator {
+    pub fn new(group_by: Vec<ResolvedExpr>, grouping_sets: Vec<Vec<usize>>, aggregates: Vec<AggregateCall>) -> Self {
+        HashAggregator { group_by, grouping_sets, aggregates, groups: HashMap::new(), states: Vec::new(), memory: 0 }
+    }
+
+    pub fn update(&mut self, row: &[Value]) -> Result<(), ExecutorError> {
+        let values = self.group_by.iter()
+            .map(|expr| evaluate(expr, row))
+            .collect::<Result<Vec<Value>, ExecutorError>>()?;
+        let args = self.aggregates.iter()
+            .map(|call| call.arg.as_ref().map(|arg| evaluate(arg, row)).transpose())
+            .collect::<Result<Vec<Option<Value>>, ExecutorError>>()?;
+        if self.grouping_sets.is_empty() {
+            return self.accumulate(values, None, &args)
+        }
+        for set in 0..self.grouping_sets.len() {
+            // the values grouped by in other sets only are NULL in this one
+            let in_set: Vec<Value> = values.iter()
+                .enumerate()
+                .map(|(index, value)| match self.grouping_sets[set].contains(&index) {
+                    true => value.clone(),
+                    false => Value::Null
+                })
+                .collect();
+            self.accumulate(in_set, Some(set), &args)?;
+        }
+        Ok(())
+    }
+
+    fn accumulate(&mut self, values: Vec<Value>, set: Option<usize>, args: &[Option<Value>]) -> Result<(), ExecutorError> {
+        let mut key: GroupKey = values.iter().map(KeyValue::from).collect();
+        if let Some(set) = set {
+            key.push(KeyValue::Int(set as i64));
+        }
+        let index = match self.groups.get(&key) {
+            Some(index) => *index,
+            None => {
+                self.memory += 2 * row_size(&values) + self.aggregates.len() * std::mem::size_of::<Accumulator>();
+                self.states.push(GroupState::new(values, set, &self.aggregates));
+                self.groups.insert(key, self.states.len() - 1);
+                self.states.len() - 1
+            }
+        };
+
+        let state = &mut self.states[index];
+        for (position, value) in args.iter().enumerate() {
+            if let (Some(seen), Some(value)) = (&mut state.seen[position], value)
+                && !matches!(value, Value::Null) {
+                if !seen.insert(KeyValue::from(value)) {
+                    continue
+                }
+                self.memory += row_size(std::slice::from_ref(value));
+            }
+            if state.accumulators[position].holds_values()
+                && let Some(value) = value {
+                self.memory += row_size(std::slice::from_ref(value));
+            }
+            state.accumulators[position].update(value.clone())?;
+        }
+
+        Ok(())
+    }
+
+    // estimated size of the groups held so far, the group values are stored in the key and in the state
+    pub fn memory(&self) -> usize {
+        self.memory
+    }
+
+    // without GROUP BY there is exactly one group, even when there were no rows, and so is there for each empty
+    // grouping set; rows of grouping sets end with the position of their set
+    pub fn finish(mut self) -> Vec<Vec<Value>> {
+        if self.states.is_empty() && self.group_by.is_empty() && self.grouping_sets.is_empty() {
+            self.states.push(GroupState::new(Vec::new(), None, &self.aggregates));
+        }
+        for (set, positions) in self.grouping_sets.iter().enumerate() {
+            if positions.is_empty() && !self.states.iter().any(|state| state.set == Some(set)) {
+                self.states.push(GroupState::new(vec![Value::Null; self.group_by.len()], Some(set), &self.aggregates));
+            }
+        }
+
+        self.states.into_iter()
+            .map(|state| {
+                let mut values = state.values;
+                values.extend(state.accumulators.into_iter().map(Accumulator::finish));
+                values.extend(state.set.map(|set| Value::Int(set as i64)));
+                values
+            })
+            .collect()
+    }
+}
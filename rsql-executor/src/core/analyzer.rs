@@ -0,0 +1,1444 @@
+use std::collections::HashMap;
+use std::path::Path;
+use rsql_parser::ast::constructs::{AggregateFunc, BinaryOperator, Cte, Expr, FrameBound, FrameUnits, FromClause, GroupByItem, JoinKind, SelectItem, SelectStatement, SetOperation, SetOperator, UnaryOperator, Value, WindowFrame, WindowFunc, WindowSpec, With};
+use crate::core::cast::can_cast;
+use crate::core::errors::AnalyzerError;
+use crate::core::functions;
+use crate::core::plan::{conjunction, AggregateCall, LogicalPlan, ResolvedExpr, SortKey, WindowCall};
+use crate::core::schema::{DataType, Field, Schema};
+use crate::core::settings::{DivisionByZero, Settings};
+
+// turns a parsed statement into a plan whose columns are bound and whose expressions are type checked,
+// so that mistakes in the query are reported before the file is scanned
+pub struct Analyzer {
+    schema: Schema,
+    // schemas of the files the statement reads, by source
+    tables: HashMap<String, Schema>,
+    settings: Settings,
+    // rows of the enclosing query, a correlated subquery reads them as columns past those of its own rows
+    outer: Option<Schema>,
+    // scalar subqueries joined to the rows, with the column holding their value
+    subqueries: Vec<(Expr, ResolvedExpr)>,
+    // columns at the end of the rows which * leaves out, the values of the subqueries
+    hidden: usize,
+    // window functions computed over the output rows, with the column holding their value
+    windows: Vec<(Expr, ResolvedExpr)>,
+    // common table expressions of the enclosing WITH clauses, by lowercase name
+    ctes: HashMap<String, CteBinding>
+}
+
+// a common table expression read once is bound where it is read, in the scope of its WITH; the others are
+// computed once and read by name
+#[derive(Clone)]
+enum CteBinding {
+    Inline {
+        cte: Cte,
+        ctes: HashMap<String, CteBinding>
+    },
+    Materialized {
+        name: String,
+        schema: Schema
+    }
+}
+
+// ROLLUP and CUBE multiply quickly, each grouping set adds its groups to every row
+const MAX_GROUPING_SETS: usize = 4096;
+
+// the aggregation step of a grouped query, select items and ORDER BY are bound against its output
+struct Grouping {
+    group_by: Vec<ResolvedExpr>,
+    // the positions in group_by of each grouping set, empty when the rows are grouped by all of group_by
+    sets: Vec<Vec<usize>>,
+    aggregates: Vec<AggregateCall>,
+    schema: Schema
+}
+
+// the WHERE of a subquery split into the conditions over its own rows and those which also read the outer rows,
+// the latter are bound over the rows of the subquery followed by the outer ones
+struct Correlation {
+    width: usize,
+    local: Vec<Expr>,
+    correlated: Vec<(Expr, ResolvedExpr)>
+}
+
+impl Analyzer {
+    pub fn new(tables: HashMap<String, Schema>) -> Self {
+        Analyzer {
+            schema: Schema::default(),
+            tables,
+            settings: Settings::default(),
+            outer: None,
+            subqueries: Vec::new(),
+            hidden: 0,
+            windows: Vec::new(),
+            ctes: HashMap::new()
+        }
+    }
+
+    pub fn with_settings(self, settings: Settings) -> Self {
+        Analyzer { settings, ..self }
+    }
+
+    pub fn analyze(&self, select: &SelectStatement) -> Result<LogicalPlan, AnalyzerError> {
+        if let Some(with) = &select.with {
+            return self.bind_with(with, &SelectStatement { with: None, ..select.clone() })
+        }
+        if !select.set_operations.is_empty() {
+            return self.bind_set_operations(select)
+        }
+        let from = select.from.as_ref().ok_or_else(|| error("Data source is required."))?;
+        let plan = self.bind_table(from, &mut Vec::new())?;
+        let plan = self.scope(plan.schema().clone()).bind_select(plan, select)?;
+        Ok(limited(plan, select.limit))
+    }
+
+    // the selects are combined from left to right, the columns are named after those of the first one and ORDER BY
+    // and LIMIT apply to the combined rows
+    fn bind_set_operations(&self, select: &SelectStatement) -> Result<LogicalPlan, AnalyzerError> {
+        let first = SelectStatement { set_operations: Vec::new(), order_by: None, limit: None, ..select.clone() };
+        let mut plan = self.analyze(&first)?;
+        for SetOperation { operator, all, query } in &select.set_operations {
+            let right = self.analyze(query)?;
+            let (left_fields, right_fields) = (&plan.schema().fields, &right.schema().fields);
+            if left_fields.len() != right_fields.len() {
+                return Err(error(&format!(
+                    "each side of {} must return the same number of columns, found {} and {}",
+                    operator, left_fields.len(), right_fields.len()
+                )))
+            }
+            let mut fields = Vec::new();
+            for (position, (field, other)) in left_fields.iter().zip(right_fields.iter()).enumerate() {
+                let data_type = field.data_type.unify(other.data_type).ok_or_else(|| error(&format!(
+                    "column {} of {} is {} on the left and {} on the right", position + 1, operator, field.data_type, other.data_type
+                )))?;
+                fields.push(Field::new(field.name.clone(), data_type));
+            }
+            let schema = Schema::new(fields);
+            plan = LogicalPlan::SetOperation { left: Box::new(plan), right: Box::new(right), operator: *operator, all: *all, schema };
+        }
+
+        // ORDER BY reads the combined rows, by the names of their columns
+        let scope = self.scope(plan.schema().clone());
+        let mut keys = Vec::new();
+        for item in select.order_by.iter().flatten() {
+            let mut expr = scope.bind(&item.expr, "ORDER BY")?;
+            if self.settings.division_by_zero == DivisionByZero::Null {
+                expr = expr.transform_up(&null_on_division_by_zero);
+            }
+            keys.push(SortKey { expr, asc: item.asc });
+        }
+        if !keys.is_empty() {
+            plan = LogicalPlan::Sort { input: Box::new(plan), keys };
+        }
+        Ok(limited(plan, select.limit))
+    }
+
+    // binds against the rows of another schema, such as the joined rows of FROM
+    fn scope(&self, schema: Schema) -> Analyzer {
+        Analyzer { schema, ctes: self.ctes.clone(), ..Analyzer::new(self.tables.clone()).with_settings(self.settings.clone()) }
+    }
+
+    // binds the statement in the scope of the common table expressions, those read more than once and the
+    // recursive ones are computed before it
+    fn bind_with(&self, with: &With, body: &SelectStatement) -> Result<LogicalPlan, AnalyzerError> {
+        let mut scope = self.scope(Schema::default());
+        let mut materialized = Vec::new();
+        for (position, cte) in with.ctes.iter().enumerate() {
+            let named = |source: &&str| source.eq_ignore_ascii_case(&cte.name);
+            if with.ctes[..position].iter().any(|other| other.name.eq_ignore_ascii_case(&cte.name)) {
+                return Err(error(&format!("common table expression `{}` is defined more than once", cte.name)))
+            }
+            let reads = with.ctes[position + 1..].iter()
+                .map(|later| later.query.as_ref())
+                .chain(std::iter::once(body))
+                .flat_map(|query| query.sources())
+                .filter(named)
+                .count();
+            let key = cte.name.to_ascii_lowercase();
+            if with.recursive && cte.query.sources().iter().any(named) {
+                let plan = scope.bind_recursive(cte)?;
+                scope.ctes.insert(key, CteBinding::Materialized { name: cte.name.clone(), schema: plan.schema().clone() });
+                materialized.push((cte.name.clone(), plan));
+            } else if reads > 1 {
+                let plan = scope.analyze(&cte.query)?;
+                let schema = renamed(plan.schema(), cte)?;
+                scope.ctes.insert(key, CteBinding::Materialized { name: cte.name.clone(), schema });
+                materialized.push((cte.name.clone(), plan));
+            } else {
+                // one which is never read is still checked
+                if reads == 0 {
+                    renamed(scope.analyze(&cte.query)?.schema(), cte)?;
+                }
+                let binding = CteBinding::Inline { cte: cte.clone(), ctes: scope.ctes.clone() };
+                scope.ctes.insert(key, binding);
+            }
+        }
+
+        let input = scope.analyze(body)?;
+        if materialized.is_empty() {
+            return Ok(input)
+        }
+        Ok(LogicalPlan::With { ctes: materialized, input: Box::new(input) })
+    }
+
+    // name AS (anchor UNION [ALL] step), the step reads the rows it added last under the name, starting with
+    // those of the anchor
+    fn bind_recursive(&self, cte: &Cte) -> Result<LogicalPlan, AnalyzerError> {
+        let [SetOperation { operator: SetOperator::Union, all, query: step }] = cte.query.set_operations.as_slice() else {
+            return Err(error(&format!(
+                "recursive common table expression `{}` must be written as SELECT ... UNION [ALL] SELECT ...", cte.name
+            )))
+        };
+        if cte.query.order_by.is_some() {
+            return Err(error(&format!("recursive common table expression `{}` can not have ORDER BY", cte.name)))
+        }
+        if cte.query.limit.is_some() {
+            return Err(error(&format!("recursive common table expression `{}` can not have LIMIT", cte.name)))
+        }
+        let anchor = self.analyze(&SelectStatement { set_operations: Vec::new(), ..cte.query.as_ref().clone() })?;
+        let mut schema = renamed(anchor.schema(), cte)?;
+        let mut scope = self.scope(Schema::default());
+        scope.ctes.insert(cte.name.to_ascii_lowercase(), CteBinding::Materialized { name: cte.name.clone(), schema: schema.clone() });
+        let step = scope.analyze(step)?;
+        if step.schema().len() != schema.len() {
+            return Err(error(&format!(
+                "recursive common table expression `{}` returns {} columns in its first part and {} in its recursive part",
+                cte.name, schema.len(), step.schema().len()
+            )))
+        }
+        for (field, other) in schema.fields.iter_mut().zip(step.schema().fields.iter()) {
+            field.data_type = field.data_type.unify(other.data_type).ok_or_else(|| error(&format!(
+                "column `{}` of recursive common table expression `{}` is {} in its first part and {} in its recursive part",
+                field.name, cte.name, field.data_type, other.data_type
+            )))?;
+        }
+
+        Ok(LogicalPlan::RecursiveCte { name: cte.name.clone(), anchor: Box::new(anchor), step: Box::new(step), distinct: !all, schema })
+    }
+
+    // the rows of a table expression, joins are bound from left to right and the columns are qualified by the alias
+    // of their table, or the file name without extension
+    fn bind_table(&self, from: &FromClause, qualifiers: &mut Vec<String>) -> Result<LogicalPlan, AnalyzerError> {
+        match from {
+            FromClause::File { source, alias } if self.ctes.contains_key(&source.to_ascii_lowercase()) => {
+                let qualifier = qualify(alias.clone().unwrap_or_else(|| source.clone()), qualifiers)?;
+                match &self.ctes[&source.to_ascii_lowercase()] {
+                    CteBinding::Inline { cte, ctes } => {
+                        let input = Analyzer { ctes: ctes.clone(), ..self.scope(Schema::default()) }.analyze(&cte.query)?;
+                        let schema = renamed(input.schema(), cte)?.qualified(&qualifier);
+                        let exprs = schema.fields.iter().enumerate().map(|(index, field)| column(index, field)).collect();
+                        Ok(LogicalPlan::Project { input: Box::new(input), exprs, schema })
+                    },
+                    CteBinding::Materialized { name, schema } => {
+                        Ok(LogicalPlan::CteScan { name: name.clone(), schema: schema.clone().qualified(&qualifier) })
+                    }
+                }
+            },
+            FromClause::File { source, alias } => {
+                let schema = self.tables.get(source)
+                    .ok_or_else(|| error(&format!("schema of `{}` is not known", source)))?;
+                let qualifier = match alias {
+                    Some(alias) => alias.clone(),
+                    None => Path::new(source).file_stem().map(|stem| stem.to_string_lossy().to_string()).unwrap_or_else(|| source.clone())
+                };
+                let schema = schema.clone().qualified(&qualify(qualifier, qualifiers)?);
+                Ok(LogicalPlan::Scan { source: source.clone(), schema, projection: None, filter: None })
+            },
+            FromClause::Subquery { query, alias } => {
+                let input = self.analyze(query)?;
+                let schema = input.schema().clone().qualified(&qualify(alias.clone(), qualifiers)?);
+                let exprs = schema.fields.iter().enumerate().map(|(index, field)| column(index, field)).collect();
+                Ok(LogicalPlan::Project { input: Box::new(input), exprs, schema })
+            },
+            FromClause::Join { left, kind, right, condition } => {
+                let left = self.bind_table(left, qualifiers)?;
+                let right = self.bind_table(right, qualifiers)?;
+                let schema = left.schema().join(right.schema());
+                let condition = match condition {
+                    Some(condition) => {
+                        let condition = self.scope(schema.clone()).bind(condition, "JOIN")?;
+                        expect_boolean(&condition, "JOIN")?;
+                        Some(condition)
+                    },
+                    None => None
+                };
+                Ok(LogicalPlan::Join { left: Box::new(left), right: Box::new(right), kind: *kind, condition, schema })
+            },
+            FromClause::Pivot { input, aggregate, column, values, alias } => {
+                let input = self.bind_table(input, qualifiers)?;
+                let qualifier = alias.as_ref().map(|alias| qualify(alias.clone(), qualifiers)).transpose()?;
+                self.bind_pivot(input, aggregate, column, values, qualifier)
+            },
+            FromClause::Unpivot { input, value, name, columns, include_nulls, alias } => {
+                let input = self.bind_table(input, qualifiers)?;
+                let schema = input.schema();
+                let mut indices = Vec::new();
+                let mut data_type = DataType::Null;
+                for column in columns {
+                    let index = schema.resolve(column)?;
+                    if indices.contains(&index) {
+                        return Err(error(&format!("column `{}` is unpivoted more than once", column)))
+                    }
+                    let field = &schema.fields[index];
+                    data_type = data_type.unify(field.data_type).ok_or_else(|| error(&format!(
+                        "the columns of UNPIVOT must have a common type, found {} and {}", data_type, field.data_type
+                    )))?;
+                    indices.push(index);
+                }
+                let mut fields: Vec<Field> = schema.fields.iter()
+                    .enumerate()
+                    .filter(|(index, _)| !indices.contains(index))
+                    .map(|(_, field)| field.clone())
+                    .collect();
+                fields.push(Field::new(name.clone(), DataType::String));
+                fields.push(Field::new(value.clone(), data_type));
+                let schema = match alias {
+                    Some(alias) => Schema::new(fields).qualified(&qualify(alias.clone(), qualifiers)?),
+                    None => Schema::new(fields)
+                };
+                Ok(LogicalPlan::Unpivot { input: Box::new(input), columns: indices, include_nulls: *include_nulls, schema })
+            }
+        }
+    }
+
+    // PIVOT groups the rows by the columns which are neither pivoted nor aggregated, with an aggregate for each value
+    // which only reads the rows where the pivoted column has that value: SUM(amount) FOR month IN ('jan') becomes
+    // SUM(CASE WHEN month = 'jan' THEN amount END), so that all the values are aggregated in one pass
+    fn bind_pivot(
+        &self,
+        input: LogicalPlan,
+        aggregate: &Expr,
+        pivoted: &str,
+        values: &[(Expr, Option<String>)],
+        qualifier: Option<String>
+    ) -> Result<LogicalPlan, AnalyzerError> {
+        let Expr::Aggregate { func, expr: arg, distinct } = aggregate else {
+            return Err(error(&format!("PIVOT expects an aggregate function, found {}", aggregate)))
+        };
+        let scope = self.scope(input.schema().clone());
+        let mut read = vec![scope.schema.resolve(pivoted)?];
+        if let Some(arg) = arg {
+            read.extend(scope.bind(arg, "PIVOT")?.columns());
+        }
+        let mut fields = Vec::new();
+        let mut group_by = Vec::new();
+        for (index, field) in scope.schema.fields.iter().enumerate().filter(|(index, _)| !read.contains(index)) {
+            fields.push(field.clone());
+            group_by.push(column(index, field));
+        }
+
+        let mut aggregates = Vec::new();
+        for (value, name) in values {
+            let condition = Expr::BinaryOp {
+                left: Box::new(Expr::Column(pivoted.to_string())),
+                operator: BinaryOperator::Equals,
+                right: Box::new(value.clone())
+            };
+            // COUNT(*) counts the rows with the value
+            let then = arg.as_deref().cloned().unwrap_or(Expr::Literal(Value::Int(1)));
+            let case = Expr::Case { operand: None, branches: vec![(condition, then)], else_result: None };
+            let call = scope.bind_aggregate(&Expr::Aggregate { func: func.clone(), expr: Some(Box::new(case)), distinct: *distinct })?;
+            let name = match (name, value) {
+                (Some(name), _) => name.clone(),
+                (None, Expr::Literal(Value::String(value))) => value.clone(),
+                (None, value) => value.to_string()
+            };
+            if fields.iter().any(|field| field.name.eq_ignore_ascii_case(&name)) {
+                return Err(error(&format!("PIVOT returns more than one column named `{}`", name)))
+            }
+            fields.push(Field::new(name, call.data_type));
+            aggregates.push(call);
+        }
+
+        let schema = match qualifier {
+            Some(qualifier) => Schema::new(fields).qualified(&qualifier),
+            None => Schema::new(fields)
+        };
+        Ok(LogicalPlan::Aggregate { input: Box::new(input), group_by, grouping_sets: Vec::new(), aggregates, schema })
+    }
+
+    fn bind_select(&self, mut plan: LogicalPlan, select: &SelectStatement) -> Result<LogicalPlan, AnalyzerError> {
+        // EXISTS and IN subqueries among the conditions of WHERE become semi and anti joins, which keep the rows
+        // with or without a match
+        let mut filters = Vec::new();
+        let mut conditions = Vec::new();
+        for condition in select.where_clause.iter().flat_map(split_conjunction) {
+            match filtering_subquery(condition) {
+                Some(filter) => filters.push(filter),
+                None => conditions.push(condition.clone())
+            }
+        }
+        let where_clause = conditions.into_iter().reduce(|left, right| Expr::BinaryOp {
+            left: Box::new(left),
+            operator: BinaryOperator::And,
+            right: Box::new(right)
+        });
+
+        // scalar subqueries are joined to the rows as columns which hold their value
+        let mut scalars = Vec::new();
+        let items = select.columns.iter().filter_map(|item| match item {
+            SelectItem::Aggregate { expr: Some(expr), .. } => Some(expr.as_ref()),
+            SelectItem::Expr { expr, .. } => Some(expr),
+            _ => None
+        });
+        for expr in items
+            .chain(where_clause.iter())
+            .chain(select.group_by.iter().flatten().flat_map(|item| item.exprs()))
+            .chain(select.order_by.iter().flatten().map(|item| &item.expr)) {
+            scalar_subqueries(expr, &mut scalars);
+        }
+        let mut subqueries = Vec::new();
+        for expr in scalars {
+            let Expr::Subquery(query) = &expr else { continue };
+            let value;
+            (plan, value) = self.scope(plan.schema().clone()).join_subquery(plan, query, self.schema.len())?;
+            subqueries.push((expr, value));
+        }
+        let scope = Analyzer {
+            hidden: plan.schema().len() - self.schema.len(),
+            subqueries,
+            ..self.scope(plan.schema().clone())
+        };
+
+        if let Some(condition) = &where_clause {
+            let predicate = scope.bind(condition, "WHERE")?;
+            expect_boolean(&predicate, "WHERE")?;
+            plan = LogicalPlan::Filter { input: Box::new(plan), predicate };
+        }
+        for (query, value, anti) in filters {
+            plan = scope.semi_join(plan, query, value, anti)?;
+        }
+
+        scope.bind_output_rows(plan, select)
+    }
+
+    fn bind_output_rows(&self, mut plan: LogicalPlan, select: &SelectStatement) -> Result<LogicalPlan, AnalyzerError> {
+        let items = self.expand_select_items(&select.columns);
+        let group_by = select.group_by.clone().unwrap_or_default();
+        let order_by = select.order_by.clone().unwrap_or_default();
+        let aggregating = !group_by.is_empty()
+            || items.iter().any(|(expr, _)| contains_aggregate(expr))
+            || order_by.iter().any(|item| contains_aggregate(&item.expr));
+
+        let grouping = if aggregating {
+            let grouping = self.group(&group_by, items.iter().map(|(expr, _)| expr).chain(order_by.iter().map(|item| &item.expr)))?;
+            plan = LogicalPlan::Aggregate {
+                input: Box::new(plan),
+                group_by: grouping.group_by.clone(),
+                grouping_sets: grouping.sets.clone(),
+                aggregates: grouping.aggregates.clone(),
+                schema: grouping.schema.clone()
+            };
+            Some(grouping)
+        } else {
+            None
+        };
+
+        // window functions are computed over the rows once grouped, each adds a column to them which the select
+        // items and ORDER BY read
+        let mut windows = Vec::new();
+        for expr in items.iter().map(|(expr, _)| expr).chain(order_by.iter().map(|item| &item.expr)) {
+            window_functions(expr, &mut windows);
+        }
+        let mut bound_windows = Vec::new();
+        if !windows.is_empty() {
+            let width = plan.schema().len();
+            let mut fields = plan.schema().fields.clone();
+            let mut calls = Vec::new();
+            for expr in windows {
+                let call = self.bind_window(&expr, grouping.as_ref())?;
+                let field = Field::new(expr.to_string(), call.data_type);
+                bound_windows.push((expr, column(width + calls.len(), &field)));
+                fields.push(field);
+                calls.push(call);
+            }
+            plan = LogicalPlan::Window { input: Box::new(plan), calls, schema: Schema::new(fields) };
+        }
+        let this = Analyzer {
+            outer: self.outer.clone(),
+            subqueries: self.subqueries.clone(),
+            hidden: self.hidden,
+            windows: bound_windows,
+            ..self.scope(self.schema.clone())
+        };
+
+        let mut exprs = Vec::new();
+        let mut fields = Vec::new();
+        for (expr, name) in &items {
+            let bound = this.bind_output(expr, grouping.as_ref())?;
+            fields.push(Field::new(name.clone(), bound.data_type()));
+            exprs.push(bound);
+        }
+
+        // ORDER BY may name an output column (alias) or use any expression over the input, expressions which are
+        // not selected are carried as hidden columns and dropped after sorting
+        let visible = exprs.len();
+        let output = Schema::new(fields.clone());
+        let mut keys = Vec::new();
+        for item in &order_by {
+            let by_name = match &item.expr {
+                Expr::Column(name) => output.index_of(name),
+                _ => None
+            };
+            let index = match by_name {
+                Some(index) => index,
+                None => {
+                    let bound = this.bind_output(&item.expr, grouping.as_ref())?;
+                    match exprs.iter().position(|expr| expr == &bound) {
+                        Some(index) => index,
+                        None => {
+                            fields.push(Field::new(item.expr.to_string(), bound.data_type()));
+                            exprs.push(bound);
+                            exprs.len() - 1
+                        }
+                    }
+                }
+            };
+            keys.push(SortKey { expr: column(index, &fields[index]), asc: item.asc });
+        }
+
+        plan = LogicalPlan::Project { input: Box::new(plan), exprs, schema: Schema::new(fields.clone()) };
+        if select.distinct {
+            // a hidden sort column would make rows distinct which look the same
+            if fields.len() > visible {
+                return Err(error("for SELECT DISTINCT, ORDER BY expressions must appear in the select list"))
+            }
+            plan = LogicalPlan::Distinct { input: Box::new(plan) };
+        }
+        if !keys.is_empty() {
+            plan = LogicalPlan::Sort { input: Box::new(plan), keys };
+        }
+        if fields.len() > visible {
+            plan = LogicalPlan::Project {
+                input: Box::new(plan),
+                exprs: fields[..visible].iter().enumerate().map(|(index, field)| column(index, field)).collect(),
+                schema: output
+            };
+        }
+        if self.settings.division_by_zero == DivisionByZero::Null {
+            plan = plan.transform_exprs(&null_on_division_by_zero);
+        }
+
+        Ok(plan)
+    }
+
+    // splits the WHERE of a subquery of these rows, a condition which fails to bind is kept as local so that the
+    // subquery reports it
+    fn correlate(&self, query: &SelectStatement) -> Result<Correlation, AnalyzerError> {
+        let from = query.from.as_ref().ok_or_else(|| error("Data source is required."))?;
+        // the common table expressions, set operations and LIMIT of the subquery are bound with it as a whole
+        if query.with.is_some() || !query.set_operations.is_empty() || query.limit.is_some() {
+            return Ok(Correlation { width: 0, local: Vec::new(), correlated: Vec::new() })
+        }
+        let rows = self.bind_table(from, &mut Vec::new())?;
+        let inner = Analyzer { outer: Some(self.schema.clone()), ..self.scope(rows.schema().clone()) };
+        let width = rows.schema().len();
+        let (mut local, mut correlated) = (Vec::new(), Vec::new());
+        for condition in query.where_clause.iter().flat_map(split_conjunction) {
+            match inner.bind(condition, "WHERE") {
+                Ok(bound) if condition.subqueries().is_empty() && bound.columns().iter().any(|index| *index >= width) => {
+                    correlated.push((condition.clone(), bound))
+                },
+                _ => local.push(condition.clone())
+            }
+        }
+        Ok(Correlation { width, local, correlated })
+    }
+
+    // keeps the rows for which the subquery has a row, or has none when `anti`; with a value the row of the
+    // subquery must also hold it, x IN (SELECT y ...)
+    fn semi_join(&self, plan: LogicalPlan, query: &SelectStatement, value: Option<&Expr>, anti: bool) -> Result<LogicalPlan, AnalyzerError> {
+        let width = self.schema.len();
+        let correlation = self.correlate(query)?;
+        let mut conditions = Vec::new();
+        let (right, found) = if correlation.correlated.is_empty() {
+            let right = self.analyze(query)?;
+            let found = right.schema().fields.first().map(|field| column(width, field));
+            if value.is_some() && right.schema().len() != 1 {
+                return Err(error(&format!("subquery must return only one column, found {}", right.schema().len())))
+            }
+            (right, found)
+        } else {
+            // the rows of the subquery are those of its FROM, the correlated conditions become the join condition
+            if query.distinct || query.group_by.is_some() || query_aggregates(query) {
+                return Err(error("correlated EXISTS and IN subqueries can not aggregate or use DISTINCT"))
+            }
+            let mut columns = vec![SelectItem::Wildcard];
+            if value.is_some() {
+                let rows = self.bind_table(query.from.as_ref().expect("correlated subquery has FROM"), &mut Vec::new())?;
+                let items = self.scope(rows.schema().clone()).expand_select_items(&query.columns);
+                let [(expr, _)] = items.as_slice() else {
+                    return Err(error(&format!("subquery must return only one column, found {}", items.len())))
+                };
+                columns.push(SelectItem::Expr { expr: expr.clone(), alias: None });
+            }
+            let right = self.analyze(&SelectStatement {
+                with: query.with.clone(),
+                distinct: false,
+                columns,
+                from: query.from.clone(),
+                where_clause: and_all(correlation.local),
+                group_by: None,
+                set_operations: Vec::new(),
+                order_by: None,
+                limit: None
+            })?;
+            let inner = correlation.width;
+            for (_, condition) in correlation.correlated {
+                conditions.push(condition.map_columns(&|index| if index < inner { width + index } else { index - inner }));
+            }
+            let found = right.schema().fields.get(inner).map(|field| column(width + inner, field));
+            (right, found)
+        };
+
+        // NOT IN drops the row when either value is NULL, as x <> NULL is never true
+        if let (Some(value), Some(found)) = (value, found) {
+            let value = self.bind(value, "WHERE")?;
+            let found = coerce_to_date(found, value.data_type())?;
+            expect_comparable(&value, &found, "IN")?;
+            let mut condition = binary(value.clone(), BinaryOperator::Equals, found.clone())?;
+            if anti {
+                for operand in [value, found] {
+                    let is_null = ResolvedExpr::IsNull { expr: Box::new(operand), negated: false };
+                    condition = binary(condition, BinaryOperator::Or, is_null)?;
+                }
+            }
+            conditions.insert(0, condition);
+        }
+
+        let kind = if anti { JoinKind::Anti } else { JoinKind::Semi };
+        let schema = plan.schema().clone();
+        Ok(LogicalPlan::Join { left: Box::new(plan), right: Box::new(right), kind, condition: conjunction(conditions), schema })
+    }
+
+    // joins the value of a scalar subquery to the rows, the rows of the enclosing query are the first `width`
+    // columns; a correlated subquery is grouped on the outer values it is compared to and joined on them
+    fn join_subquery(&self, plan: LogicalPlan, query: &SelectStatement, width: usize) -> Result<(LogicalPlan, ResolvedExpr), AnalyzerError> {
+        let outer = Analyzer { schema: Schema::new(self.schema.fields[..width].to_vec()), ..self.scope(Schema::default()) };
+        let correlation = outer.correlate(query)?;
+        let position = self.schema.len();
+        if correlation.correlated.is_empty() {
+            let right = self.analyze(query)?;
+            if right.schema().len() != 1 {
+                return Err(error(&format!("subquery must return only one column, found {}", right.schema().len())))
+            }
+            let value = column(position, &right.schema().fields[0]);
+            let right = LogicalPlan::SingleRow { input: Box::new(right) };
+            let schema = with_subquery(plan.schema(), right.schema());
+            return Ok((LogicalPlan::Join { left: Box::new(plan), right: Box::new(right), kind: JoinKind::Left, condition: None, schema }, value))
+        }
+
+        let [item] = query.columns.as_slice() else {
+            return Err(error(&format!("subquery must return only one column, found {}", query.columns.len())))
+        };
+        if query.distinct || query.group_by.is_some() || !query_aggregates(query) {
+            return Err(error("a correlated subquery used as a value must aggregate its rows, without GROUP BY or DISTINCT"))
+        }
+        // inner = outer, the inner expressions become the groups
+        let inner = correlation.width;
+        let mut keys = Vec::new();
+        let mut conditions = Vec::new();
+        for (condition, bound) in correlation.correlated {
+            let key = match (condition, bound) {
+                (Expr::BinaryOp { left, operator: BinaryOperator::Equals, right }, ResolvedExpr::BinaryOp { left: l, right: r, .. }) => {
+                    let reads_inner = |expr: &ResolvedExpr| expr.columns().iter().all(|index| *index < inner);
+                    let reads_outer = |expr: &ResolvedExpr| !expr.columns().is_empty() && expr.columns().iter().all(|index| *index >= inner);
+                    if reads_inner(&l) && reads_outer(&r) {
+                        Some((*left, *r))
+                    } else if reads_inner(&r) && reads_outer(&l) {
+                        Some((*right, *l))
+                    } else {
+                        None
+                    }
+                },
+                _ => None
+            };
+            let (key, value) = key.ok_or_else(|| error("a correlated subquery used as a value may only compare its columns to outer ones with ="))?;
+            let value = value.map_columns(&|index| index - inner);
+            conditions.push((value, position + keys.len()));
+            keys.push(key);
+        }
+        let mut columns: Vec<SelectItem> = keys.iter().map(|key| SelectItem::Expr { expr: key.clone(), alias: None }).collect();
+        columns.push(item.clone());
+        let right = self.analyze(&SelectStatement {
+            with: query.with.clone(),
+            distinct: false,
+            columns,
+            from: query.from.clone(),
+            where_clause: and_all(correlation.local),
+            group_by: Some(keys.iter().cloned().map(GroupByItem::Expr).collect()),
+            set_operations: Vec::new(),
+            order_by: None,
+            limit: None
+        })?;
+        let fields = &right.schema().fields;
+        let mut condition = Vec::new();
+        for (value, index) in conditions {
+            let found = coerce_to_date(column(index, &fields[index - position]), value.data_type())?;
+            expect_comparable(&value, &found, "=")?;
+            condition.push(binary(value, BinaryOperator::Equals, found)?);
+        }
+        let mut value = column(position + keys.len(), &fields[keys.len()]);
+
+        // a group which is not there aggregates no rows, where COUNT is 0 rather than NULL
+        if let Some(empty) = empty_aggregate(&right, keys.len()) {
+            let missing = ResolvedExpr::IsNull { expr: Box::new(column(position, &fields[0])), negated: false };
+            let data_type = value.data_type().unify(empty.data_type()).unwrap_or(DataType::Any);
+            value = ResolvedExpr::Case { operand: None, branches: vec![(missing, empty)], else_result: Some(Box::new(value)), data_type };
+        }
+
+        let schema = with_subquery(plan.schema(), right.schema());
+        Ok((LogicalPlan::Join { left: Box::new(plan), right: Box::new(right), kind: JoinKind::Left, condition: conjunction(condition), schema }, value))
+    }
+
+    fn expand_select_items(&self, items: &[SelectItem]) -> Vec<(Expr, String)> {
+        let mut expanded = Vec::new();
+        for item in items {
+            match item {
+                SelectItem::Wildcard => {
+                    // columns of joined tables which share a name are told apart by their table
+                    for (index, field) in self.schema.fields[..self.schema.len() - self.hidden].iter().enumerate() {
+                        let name = match self.schema.index_of(&field.name) {
+                            Some(found) if found == index => field.name.clone(),
+                            _ => field.qualified_name()
+                        };
+                        expanded.push((Expr::Column(name), field.name.clone()));
+                    }
+                },
+                SelectItem::Column(name) => expanded.push((Expr::Column(name.clone()), name.clone())),
+                SelectItem::Aggregate { func, expr, distinct } => {
+                    let aggregate = Expr::Aggregate { func: func.clone(), expr: expr.clone(), distinct: *distinct };
+                    let name = aggregate.to_string();
+                    expanded.push((aggregate, name));
+                },
+                SelectItem::Expr { expr, alias } => {
+                    let name = alias.clone().unwrap_or_else(|| expr.to_string());
+                    expanded.push((expr.clone(), name));
+                }
+            }
+        }
+
+        expanded
+    }
+
+    fn group<'a>(&self, group_by: &[GroupByItem], outputs: impl Iterator<Item = &'a Expr>) -> Result<Grouping, AnalyzerError> {
+        let mut fields = Vec::new();
+        let mut bound_group_by = Vec::new();
+        let plain = group_by.iter().all(|item| matches!(item, GroupByItem::Expr(_)));
+        for expr in group_by.iter().flat_map(|item| item.exprs()) {
+            let bound = self.bind(expr, "GROUP BY")?;
+            // with grouping sets an expression is grouped by once, whichever sets it appears in
+            if !plain && bound_group_by.contains(&bound) {
+                continue
+            }
+            let name = match &bound {
+                ResolvedExpr::Column { name, .. } => name.clone(),
+                _ => expr.to_string()
+            };
+            fields.push(Field::new(name, bound.data_type()));
+            bound_group_by.push(bound);
+        }
+
+        // the rows are grouped by each combination of one set from every item
+        let mut sets: Vec<Vec<usize>> = Vec::new();
+        if !plain {
+            sets.push(Vec::new());
+            for item in group_by {
+                if sets.len().saturating_mul(set_count(item)) > MAX_GROUPING_SETS {
+                    return Err(error(&format!("GROUP BY can not expand to more than {} grouping sets", MAX_GROUPING_SETS)))
+                }
+                let mut item_sets = Vec::new();
+                for set in item.sets() {
+                    let mut positions = Vec::new();
+                    for expr in &set {
+                        let bound = self.bind(expr, "GROUP BY")?;
+                        positions.extend(bound_group_by.iter().position(|group| group == &bound));
+                    }
+                    item_sets.push(positions);
+                }
+                sets = sets.iter()
+                    .flat_map(|set| item_sets.iter().map(move |item_set| {
+                        let mut combined = set.clone();
+                        combined.extend(item_set.iter().filter(|position| !set.contains(position)));
+                        combined
+                    }))
+                    .collect();
+            }
+        }
+
+        let mut aggregates = Vec::new();
+        for expr in outputs {
+            self.collect_aggregates(expr, &mut aggregates)?;
+        }
+        for aggregate in &aggregates {
+            fields.push(Field::new(aggregate.to_string(), aggregate.data_type));
+        }
+
+        if !sets.is_empty() {
+            fields.push(Field::new("grouping set".to_string(), DataType::Int));
+        }
+
+        Ok(Grouping { group_by: bound_group_by, sets, aggregates, schema: Schema::new(fields) })
+    }
+
+    fn collect_aggregates(&self, expr: &Expr, aggregates: &mut Vec<AggregateCall>) -> Result<(), AnalyzerError> {
+        if let Expr::Aggregate { .. } = expr {
+            let call = self.bind_aggregate(expr)?;
+            if !aggregates.contains(&call) {
+                aggregates.push(call);
+            }
+            return Ok(())
+        }
+        for child in expr.children() {
+            self.collect_aggregates(child, aggregates)?;
+        }
+        Ok(())
+    }
+
+    fn bind_aggregate(&self, expr: &Expr) -> Result<AggregateCall, AnalyzerError> {
+        let Expr::Aggregate { func, expr: arg, distinct } = expr else {
+            return Err(error(&format!("{} is not an aggregate", expr)))
+        };
+        let arg = match arg {
+            Some(arg) if contains_aggregate(arg) => {
+                return Err(error(&format!("aggregate function calls can not be nested: {}", expr)))
+            },
+            Some(arg) => Some(self.bind(arg, "an aggregate argument")?),
+            None => None
+        };
+        let data_type = aggregate_type(func, arg.as_ref())?;
+
+        Ok(AggregateCall { func: func.clone(), arg, distinct: *distinct, data_type })
+    }
+
+    // the arguments and the keys of a window function read the rows it is computed over, the groups when the
+    // query aggregates
+    fn bind_window(&self, expr: &Expr, grouping: Option<&Grouping>) -> Result<WindowCall, AnalyzerError> {
+        let Expr::Window { func, args, over } = expr else {
+            return Err(error(&format!("{} is not a window function", expr)))
+        };
+        if expr.children().into_iter().any(contains_window) {
+            return Err(error(&format!("window function calls can not be nested: {}", expr)))
+        }
+        let args = args.iter().map(|arg| self.bind_output(arg, grouping)).collect::<Result<Vec<_>, _>>()?;
+        let partition_by = over.partition_by.iter().map(|expr| self.bind_output(expr, grouping)).collect::<Result<Vec<_>, _>>()?;
+        let mut order_by = Vec::new();
+        for item in &over.order_by {
+            order_by.push(SortKey { expr: self.bind_output(&item.expr, grouping)?, asc: item.asc });
+        }
+        let data_type = window_type(func, &args)?;
+        let frame = window_frame(over, &order_by)?;
+
+        Ok(WindowCall { func: func.clone(), args, partition_by, order_by, frame, data_type })
+    }
+
+    // binds against the rows of the source, where aggregates have no meaning
+    fn bind(&self, expr: &Expr, clause: &str) -> Result<ResolvedExpr, AnalyzerError> {
+        match expr {
+            Expr::Column(name) => match (self.schema.resolve(name), &self.outer) {
+                (Ok(index), _) => Ok(column(index, &self.schema.fields[index])),
+                (Err(e), Some(outer)) => {
+                    let index = outer.resolve(name).map_err(|_| e)?;
+                    Ok(column(self.schema.len() + index, &outer.fields[index]))
+                },
+                (Err(e), None) => Err(e)
+            },
+            Expr::Literal(value) => Ok(ResolvedExpr::Literal(value.clone())),
+            Expr::Aggregate { func, .. } => {
+                Err(error(&format!("aggregate function {} is not allowed in {}", func, clause)))
+            },
+            Expr::Subquery(_) => self.subqueries.iter()
+                .find(|(subquery, _)| subquery == expr)
+                .map(|(_, value)| value.clone())
+                .ok_or_else(|| error(&format!("subqueries are not supported in {}", clause))),
+            Expr::Exists(_) | Expr::InSubquery { .. } => {
+                Err(error("EXISTS and IN subqueries are only supported as conditions of WHERE, combined with AND"))
+            },
+            Expr::Window { .. } => self.windows.iter()
+                .find(|(window, _)| window == expr)
+                .map(|(_, value)| value.clone())
+                .ok_or_else(|| error(&format!("window functions are not allowed in {}", clause))),
+            Expr::Function { name, .. } if name.eq_ignore_ascii_case("grouping") => {
+                Err(error(&format!("GROUPING is only allowed over the groups of a query, not in {}", clause)))
+            },
+            _ => bind_operation(expr, &mut |operand| self.bind(operand, clause))
+        }
+    }
+
+    fn bind_output(&self, expr: &Expr, grouping: Option<&Grouping>) -> Result<ResolvedExpr, AnalyzerError> {
+        match grouping {
+            Some(grouping) => self.bind_grouped(expr, grouping),
+            None => self.bind(expr, "SELECT")
+        }
+    }
+
+    // binds against the output of the aggregation, only grouped expressions and aggregates are available
+    fn bind_grouped(&self, expr: &Expr, grouping: &Grouping) -> Result<ResolvedExpr, AnalyzerError> {
+        if !contains_aggregate(expr)
+            && let Ok(bound) = self.bind(expr, "GROUP BY")
+            && let Some(index) = grouping.group_by.iter().position(|group| group == &bound) {
+            return Ok(column(index, &grouping.schema.fields[index]))
+        }
+
+        match expr {
+            Expr::Aggregate { .. } => {
+                let call = self.bind_aggregate(expr)?;
+                let index = grouping.group_by.len() + grouping.aggregates.iter()
+                    .position(|aggregate| aggregate == &call)
+                    .ok_or_else(|| error(&format!("aggregate {} was not collected", expr)))?;
+                Ok(column(index, &grouping.schema.fields[index]))
+            },
+            Expr::Column(name) => {
+                self.schema.resolve(name)?;
+                Err(error(&format!(
+                    "column `{}` must appear in the GROUP BY clause or be used in an aggregate function", name
+                )))
+            },
+            Expr::Subquery(_) | Expr::Exists(_) | Expr::InSubquery { .. } => {
+                self.bind(expr, "SELECT")?;
+                Err(error(&format!(
+                    "subquery {} must appear in the GROUP BY clause or be used in an aggregate function", expr
+                )))
+            },
+            Expr::Literal(value) => Ok(ResolvedExpr::Literal(value.clone())),
+            Expr::Window { .. } => self.bind(expr, "SELECT"),
+            Expr::Function { name, args } if name.eq_ignore_ascii_case("grouping") => self.bind_grouping(args, grouping),
+            _ => bind_operation(expr, &mut |operand| self.bind_grouped(operand, grouping))
+        }
+    }
+
+    // GROUPING(a, b) has a bit for each argument, the first one highest, which is set when the row is not grouped by
+    // it; the value is looked up from the grouping set of the row
+    fn bind_grouping(&self, args: &[Expr], grouping: &Grouping) -> Result<ResolvedExpr, AnalyzerError> {
+        if args.is_empty() || args.len() > 63 {
+            return Err(error(&format!("GROUPING expects between 1 and 63 arguments, found {}", args.len())))
+        }
+        let mut positions = Vec::new();
+        for arg in args {
+            let bound = self.bind(arg, "GROUPING")?;
+            let position = grouping.group_by.iter()
+                .position(|group| group == &bound)
+                .ok_or_else(|| error(&format!("arguments of GROUPING must be grouping expressions, found {}", arg)))?;
+            positions.push(position);
+        }
+        if grouping.sets.is_empty() {
+            return Ok(ResolvedExpr::Literal(Value::Int(0)))
+        }
+
+        let index = grouping.group_by.len() + grouping.aggregates.len();
+        let branches = grouping.sets.iter()
+            .enumerate()
+            .map(|(position, set)| {
+                let bits = positions.iter().fold(0, |bits, grouped| (bits << 1) | i64::from(!set.contains(grouped)));
+                (ResolvedExpr::Literal(Value::Int(position as i64)), ResolvedExpr::Literal(Value::Int(bits)))
+            })
+            .collect();
+        Ok(ResolvedExpr::Case {
+            operand: Some(Box::new(column(index, &grouping.schema.fields[index]))),
+            branches,
+            else_result: None,
+            data_type: DataType::Int
+        })
+    }
+}
+
+// the schema of a common table expression, whose columns may be renamed
+fn renamed(schema: &Schema, cte: &Cte) -> Result<Schema, AnalyzerError> {
+    if cte.columns.is_empty() {
+        return Ok(schema.clone())
+    }
+    if cte.columns.len() != schema.len() {
+        return Err(error(&format!(
+            "common table expression `{}` names {} columns but its query returns {}", cte.name, cte.columns.len(), schema.len()
+        )))
+    }
+    let fields = schema.fields.iter().zip(cte.columns.iter())
+        .map(|(field, name)| Field::new(name.clone(), field.data_type))
+        .collect();
+    Ok(Schema::new(fields))
+}
+
+// the qualifier of a table in FROM, which must tell it apart from the others
+fn qualify(qualifier: String, qualifiers: &mut Vec<String>) -> Result<String, AnalyzerError> {
+    if qualifiers.iter().any(|other| other.eq_ignore_ascii_case(&qualifier)) {
+        return Err(error(&format!("table name `{}` is used more than once, give each of them an alias", qualifier)))
+    }
+    qualifiers.push(qualifier.clone());
+    Ok(qualifier)
+}
+
+// the operands of a chain of ANDs, or the condition itself
+fn split_conjunction(condition: &Expr) -> Vec<&Expr> {
+    match condition {
+        Expr::BinaryOp { left, operator: BinaryOperator::And, right } => {
+            let mut conditions = split_conjunction(left);
+            conditions.extend(split_conjunction(right));
+            conditions
+        },
+        condition => vec![condition]
+    }
+}
+
+fn and_all(conditions: Vec<Expr>) -> Option<Expr> {
+    conditions.into_iter().reduce(|left, right| Expr::BinaryOp { left: Box::new(left), operator: BinaryOperator::And, right: Box::new(right) })
+}
+
+// the subquery of an EXISTS or IN condition, the value IN looks for and whether the condition is negated
+fn filtering_subquery(condition: &Expr) -> Option<(&SelectStatement, Option<&Expr>, bool)> {
+    match condition {
+        Expr::Exists(query) => Some((query, None, false)),
+        Expr::InSubquery { expr, query, negated } => Some((query, Some(expr), *negated)),
+        Expr::UnaryOp { operator: UnaryOperator::Not, expr } => {
+            filtering_subquery(expr).map(|(query, value, negated)| (query, value, !negated))
+        },
+        _ => None
+    }
+}
+
+fn scalar_subqueries(expr: &Expr, found: &mut Vec<Expr>) {
+    if let Expr::Subquery(_) = expr {
+        if !found.contains(expr) {
+            found.push(expr.clone());
+        }
+        return
+    }
+    for child in expr.children() {
+        scalar_subqueries(child, found);
+    }
+}
+
+fn query_aggregates(query: &SelectStatement) -> bool {
+    query.columns.iter().any(|item| match item {
+        SelectItem::Aggregate { .. } => true,
+        SelectItem::Expr { expr, .. } => contains_aggregate(expr),
+        _ => false
+    })
+}
+
+// the value of a grouped subquery over no rows at all, where the counts are 0 and the other aggregates are NULL; None
+// when that is NULL anyway or the value reads the groups
+fn empty_aggregate(plan: &LogicalPlan, position: usize) -> Option<ResolvedExpr> {
+    let LogicalPlan::Project { input, exprs, .. } = plan else { return None };
+    let LogicalPlan::Aggregate { group_by, aggregates, .. } = input.as_ref() else { return None };
+    let value = exprs.get(position)?;
+    let counts = value.columns().iter().any(|index| {
+        *index >= group_by.len() && matches!(
+            aggregates[*index - group_by.len()].func, AggregateFunc::Count | AggregateFunc::CountIf | AggregateFunc::ApproxCountDistinct
+        )
+    });
+    if !counts || value.columns().iter().any(|index| *index < group_by.len()) {
+        return None
+    }
+    Some(value.clone().transform_up(&|expr| match expr {
+        ResolvedExpr::Column { index, .. } => match aggregates[index - group_by.len()].func {
+            AggregateFunc::Count | AggregateFunc::CountIf | AggregateFunc::ApproxCountDistinct => ResolvedExpr::Literal(Value::Int(0)),
+            _ => ResolvedExpr::Literal(Value::Null)
+        },
+        expr => expr
+    }))
+}
+
+// the number of grouping sets an item of GROUP BY stands for
+fn set_count(item: &GroupByItem) -> usize {
+    match item {
+        GroupByItem::Expr(_) => 1,
+        GroupByItem::Rollup(exprs) => exprs.len() + 1,
+        GroupByItem::Cube(exprs) => 1usize.checked_shl(exprs.len() as u32).unwrap_or(usize::MAX),
+        GroupByItem::GroupingSets(sets) => sets.len()
+    }
+}
+
+fn error(message: &str) -> AnalyzerError {
+    AnalyzerError { message: message.to_string() }
+}
+
+// the rows joined with the columns of a scalar subquery, which are renamed so that the names in the query still
+// resolve to the columns of the rows, amount > (SELECT amount ...)
+fn with_subquery(rows: &Schema, subquery: &Schema) -> Schema {
+    let hidden = subquery.fields.iter()
+        .enumerate()
+        .map(|(index, field)| Field::new(format!("__subquery_{}", rows.len() + index), field.data_type));
+    Schema::new(rows.fields.iter().cloned().chain(hidden).collect())
+}
+
+fn column(index: usize, field: &Field) -> ResolvedExpr {
+    ResolvedExpr::Column { index, name: field.name.clone(), data_type: field.data_type }
+}
+
+pub fn contains_aggregate(expr: &Expr) -> bool {
+    matches!(expr, Expr::Aggregate { .. }) || expr.children().into_iter().any(contains_aggregate)
+}
+
+fn contains_window(expr: &Expr) -> bool {
+    matches!(expr, Expr::Window { .. }) || expr.children().into_iter().any(contains_window)
+}
+
+// the window functions of the expression, each once
+fn window_functions(expr: &Expr, found: &mut Vec<Expr>) {
+    if let Expr::Window { .. } = expr {
+        if !found.contains(expr) {
+            found.push(expr.clone());
+        }
+        return
+    }
+    for child in expr.children() {
+        window_functions(child, found);
+    }
+}
+
+// binds an expression computed from other expressions, `bind` binds the operands in the scope of the clause
+fn bind_operation(
+    expr: &Expr,
+    bind: &mut impl FnMut(&Expr) -> Result<ResolvedExpr, AnalyzerError>
+) -> Result<ResolvedExpr, AnalyzerError> {
+    match expr {
+        Expr::BinaryOp { left, operator, right } => binary(bind(left)?, operator.clone(), bind(right)?),
+        Expr::UnaryOp { operator, expr } => unary(operator.clone(), bind(expr)?),
+        Expr::InList { expr, list, negated } => {
+            let expr = bind(expr)?;
+            let mut bound_list = Vec::new();
+            for item in list {
+                let item = coerce_to_date(bind(item)?, expr.data_type())?;
+                expect_comparable(&expr, &item, "IN")?;
+                bound_list.push(item);
+            }
+            Ok(ResolvedExpr::InList { expr: Box::new(expr), list: bound_list, negated: *negated })
+        },
+        Expr::Between { expr, low, high, negated } => {
+            let expr = bind(expr)?;
+            let low = coerce_to_date(bind(low)?, expr.data_type())?;
+            let high = coerce_to_date(bind(high)?, expr.data_type())?;
+            expect_comparable(&expr, &low, "BETWEEN")?;
+            expect_comparable(&expr, &high, "BETWEEN")?;
+            Ok(ResolvedExpr::Between { expr: Box::new(expr), low: Box::new(low), high: Box::new(high), negated: *negated })
+        },
+        Expr::Like { expr, pattern, case_insensitive, negated } => {
+            let (expr, pattern) = (bind(expr)?, bind(pattern)?);
+            let operator = if *case_insensitive { "ILIKE" } else { "LIKE" };
+            for operand in [&expr, &pattern] {
+                if !matches!(operand.data_type(), DataType::String | DataType::Null | DataType::Any) {
+                    return Err(error(&format!("{} expects VARCHAR operands, found {}", operator, operand.data_type())))
+                }
+            }
+            Ok(ResolvedExpr::Like {
+                expr: Box::new(expr),
+                pattern: Box::new(pattern),
+                case_insensitive: *case_insensitive,
+                negated: *negated
+            })
+        },
+        Expr::IsNull { expr, negated } => Ok(ResolvedExpr::IsNull { expr: Box::new(bind(expr)?), negated: *negated }),
+        Expr::Regexp { expr, pattern, negated } => {
+            let (expr, pattern) = (bind(expr)?, bind(pattern)?);
+            for operand in [&expr, &pattern] {
+                if !matches!(operand.data_type(), DataType::String | DataType::Null | DataType::Any) {
+                    return Err(error(&format!("REGEXP expects VARCHAR operands, found {}", operand.data_type())))
+                }
+            }
+            Ok(ResolvedExpr::Regexp { expr: Box::new(expr), pattern: Box::new(functions::regexp::compile(pattern)?), negated: *negated })
+        },
+        Expr::Cast { expr, type_name, try_cast } => {
+            let expr = bind(expr)?;
+            let data_type = DataType::from(*type_name);
+            if !can_cast(expr.data_type(), data_type) {
+                return Err(error(&format!("cannot cast {} to {}", expr.data_type(), data_type)))
+            }
+            Ok(ResolvedExpr::Cast { expr: Box::new(expr), data_type, try_cast: *try_cast })
+        },
+        Expr::Case { operand, branches, else_result } => bind_case(operand.as_deref(), branches, else_result.as_deref(), bind),
+        // IF(condition, a, b) is CASE WHEN condition THEN a ELSE b END
+        Expr::Function { name, args } if name.eq_ignore_ascii_case("if") => match args.as_slice() {
+            [condition, then, otherwise] => bind_case(None, &[(condition.clone(), then.clone())], Some(otherwise), bind),
+            _ => Err(error(&format!("IF expects 3 arguments, found {}", args.len())))
+        },
+        Expr::Function { name, args } => {
+            let args = args.iter().map(&mut *bind).collect::<Result<Vec<_>, _>>()?;
+            functions::resolve(name, args)
+        },
+        Expr::Column(_) | Expr::Literal(_) | Expr::Aggregate { .. }
+        | Expr::Subquery(_) | Expr::Exists(_) | Expr::InSubquery { .. } | Expr::Window { .. } => bind(expr)
+    }
+}
+
+// with an operand the WHEN values are compared to it, otherwise they are conditions, all results share one type
+fn bind_case(
+    operand: Option<&Expr>,
+    branches: &[(Expr, Expr)],
+    else_result: Option<&Expr>,
+    bind: &mut impl FnMut(&Expr) -> Result<ResolvedExpr, AnalyzerError>
+) -> Result<ResolvedExpr, AnalyzerError> {
+    let operand = operand.map(&mut *bind).transpose()?;
+    let mut data_type = DataType::Null;
+    let mut result_type = |result: &ResolvedExpr| -> Result<(), AnalyzerError> {
+        data_type = data_type.unify(result.data_type()).ok_or_else(|| error(&format!(
+            "CASE results must have a common type, found {} and {}", data_type, result.data_type()
+        )))?;
+        Ok(())
+    };
+
+    let mut bound_branches = Vec::new();
+    for (when, then) in branches {
+        let when = match &operand {
+            Some(operand) => {
+                let when = coerce_to_date(bind(when)?, operand.data_type())?;
+                expect_comparable(operand, &when, "CASE")?;
+                when
+            },
+            None => {
+                let when = bind(when)?;
+                expect_boolean(&when, "CASE WHEN")?;
+                when
+            }
+        };
+        let then = bind(then)?;
+        result_type(&then)?;
+        bound_branches.push((when, then));
+    }
+    let else_result = match else_result {
+        Some(result) => {
+            let result = bind(result)?;
+            result_type(&result)?;
+            Some(Box::new(result))
+        },
+        None => None
+    };
+
+    Ok(ResolvedExpr::Case { operand: operand.map(Box::new), branches: bound_branches, else_result, data_type })
+}
+
+fn limited(plan: LogicalPlan, limit: Option<u64>) -> LogicalPlan {
+    match limit {
+        Some(count) => LogicalPlan::Limit { input: Box::new(plan), count },
+        None => plan
+    }
+}
+
+// a / b becomes SAFE_DIVIDE(a, b) and a % b or MOD(a, b) becomes SAFE_MODULO(a, b), which are NULL instead of an
+// error when b is zero
+fn null_on_division_by_zero(expr: ResolvedExpr) -> ResolvedExpr {
+    match expr {
+        ResolvedExpr::BinaryOp { left, operator: BinaryOperator::Divide, right, data_type } => {
+            ResolvedExpr::Function { name: "SAFE_DIVIDE".to_string(), args: vec![*left, *right], data_type }
+        },
+        ResolvedExpr::BinaryOp { left, operator: BinaryOperator::Modulo, right, data_type } => {
+            ResolvedExpr::Function { name: "SAFE_MODULO".to_string(), args: vec![*left, *right], data_type }
+        },
+        ResolvedExpr::Function { name, args, data_type } if name == "MOD" => {
+            ResolvedExpr::Function { name: "SAFE_MODULO".to_string(), args, data_type }
+        },
+        expr => expr
+    }
+}
+
+fn expect_comparable(left: &ResolvedExpr, right: &ResolvedExpr, operator: &str) -> Result<(), AnalyzerError> {
+    if left.data_type().is_comparable_with(right.data_type()) {
+        Ok(())
+    } else {
+        Err(error(&format!("cannot compare {} with {} using {}", left.data_type(), right.data_type(), operator)))
+    }
+}
+
+fn expect_boolean(expr: &ResolvedExpr, clause: &str) -> Result<(), AnalyzerError> {
+    if expr.data_type().is_boolean() {
+        Ok(())
+    } else {
+        Err(error(&format!("{} condition must be BOOLEAN, found {}", clause, expr.data_type())))
+    }
+}
+
+fn is_comparison(operator: &BinaryOperator) -> bool {
+    matches!(operator,
+        BinaryOperator::Equals | BinaryOperator::NotEquals |
+        BinaryOperator::GreaterThan | BinaryOperator::LessThan |
+        BinaryOperator::GreaterThanOrEquals | BinaryOperator::LessThanOrEquals
+    )
+}
+
+fn binary(mut left: ResolvedExpr, operator: BinaryOperator, mut right: ResolvedExpr) -> Result<ResolvedExpr, AnalyzerError> {
+    if is_comparison(&operator) {
+        left = coerce_to_date(left, right.data_type())?;
+        right = coerce_to_date(right, left.data_type())?;
+    }
+    let (left_type, right_type) = (left.data_type(), right.data_type());
+    let data_type = match operator {
+        _ if is_comparison(&operator) => {
+            if !left_type.is_comparable_with(right_type) {
+                return Err(error(&format!("cannot compare {} with {} using {}", left_type, right_type, operator)))
+            }
+            DataType::Bool
+        },
+        BinaryOperator::And | BinaryOperator::Or => {
+            if !left_type.is_boolean() || !right_type.is_boolean() {
+                return Err(error(&format!("{} expects BOOLEAN operands, found {} and {}", operator, left_type, right_type)))
+            }
+            DataType::Bool
+        },
+        BinaryOperator::Concat => DataType::String,
+        _ => {
+            if !left_type.is_numeric() || !right_type.is_numeric() {
+                return Err(error(&format!("operator {} can not be applied to {} and {}", operator, left_type, right_type)))
+            }
+            left_type.unify(right_type).unwrap_or(DataType::Any)
+        }
+    };
+
+    Ok(ResolvedExpr::BinaryOp { left: Box::new(left), operator, right: Box::new(right), data_type })
+}
+
+fn unary(operator: UnaryOperator, expr: ResolvedExpr) -> Result<ResolvedExpr, AnalyzerError> {
+    let data_type = match operator {
+        UnaryOperator::Minus if expr.data_type().is_numeric() => expr.data_type(),
+        UnaryOperator::Not if expr.data_type().is_boolean() => DataType::Bool,
+        _ => return Err(error(&format!("operator {:?} can not be applied to {}", operator, expr.data_type())))
+    };
+
+    Ok(ResolvedExpr::UnaryOp { operator, expr: Box::new(expr), data_type })
+}
+
+// dates are written as strings in queries, hire_date >= '2020-01-01'
+pub(crate) fn coerce_to_date(expr: ResolvedExpr, other: DataType) -> Result<ResolvedExpr, AnalyzerError> {
+    match expr {
+        ResolvedExpr::Literal(Value::String(text)) if other == DataType::Date => {
+            chrono::NaiveDate::parse_from_str(&text, "%Y-%m-%d")
+                .map(|date| ResolvedExpr::Literal(Value::Date(date)))
+                .map_err(|_| error(&format!("'{}' is not a valid date, expected YYYY-MM-DD", text)))
+        },
+        expr => Ok(expr)
+    }
+}
+
+fn window_type(func: &WindowFunc, args: &[ResolvedExpr]) -> Result<DataType, AnalyzerError> {
+    let expect_args = |range: std::ops::RangeInclusive<usize>| {
+        if range.contains(&args.len()) {
+            return Ok(())
+        }
+        let expected = match (range.start(), range.end()) {
+            (0, 0) => "no arguments".to_string(),
+            (start, end) if start == end => format!("{} argument{}", start, if *start == 1 { "" } else { "s" }),
+            (start, end) => format!("{} to {} arguments", start, end)
+        };
+        Err(error(&format!("{} expects {}, found {}", func, expected, args.len())))
+    };
+    // NTILE and the offset of LAG and LEAD are the same for every row
+    let expect_count = |arg: &ResolvedExpr, positive: bool| match arg {
+        ResolvedExpr::Literal(Value::Int(count)) if *count > 0 || !positive && *count == 0 => Ok(()),
+        arg => Err(error(&format!(
+            "{} expects a {} integer constant, found {}", func, if positive { "positive" } else { "non-negative" }, arg
+        )))
+    };
+    match func {
+        WindowFunc::RowNumber | WindowFunc::Rank | WindowFunc::DenseRank => {
+            expect_args(0..=0)?;
+            Ok(DataType::Int)
+        },
+        WindowFunc::Ntile => {
+            expect_args(1..=1)?;
+            expect_count(&args[0], true)?;
+            Ok(DataType::Int)
+        },
+        WindowFunc::Lag | WindowFunc::Lead => {
+            expect_args(1..=3)?;
+            if let Some(offset) = args.get(1) {
+                expect_count(offset, false)?;
+            }
+            match args.get(2) {
+                Some(default) => args[0].data_type().unify(default.data_type()).ok_or_else(|| error(&format!(
+                    "the default of {} must have the type of its value, found {} and {}", func, args[0].data_type(), default.data_type()
+                ))),
+                None => Ok(args[0].data_type())
+            }
+        },
+        WindowFunc::FirstValue | WindowFunc::LastValue => {
+            expect_args(1..=1)?;
+            Ok(args[0].data_type())
+        },
+        WindowFunc::Aggregate(AggregateFunc::Count) => {
+            expect_args(0..=1)?;
+            Ok(DataType::Int)
+        },
+        WindowFunc::Aggregate(aggregate) => {
+            expect_args(1..=1)?;
+            aggregate_type(aggregate, args.first())
+        }
+    }
+}
+
+// the frame written, or the rows up to the last peer of the current one with ORDER BY and the whole partition
+// without it
+fn window_frame(over: &WindowSpec, order_by: &[SortKey]) -> Result<WindowFrame, AnalyzerError> {
+    let Some(frame) = over.frame.clone() else {
+        return Ok(match order_by.is_empty() {
+            true => WindowFrame { units: FrameUnits::Rows, start: FrameBound::UnboundedPreceding, end: FrameBound::UnboundedFollowing },
+            false => WindowFrame { units: FrameUnits::Range, start: FrameBound::UnboundedPreceding, end: FrameBound::CurrentRow }
+        })
+    };
+    let place = |bound: &FrameBound| match bound {
+        FrameBound::UnboundedPreceding => 0,
+        FrameBound::Preceding(_) => 1,
+        FrameBound::CurrentRow => 2,
+        FrameBound::Following(_) => 3,
+        FrameBound::UnboundedFollowing => 4
+    };
+    if frame.start == FrameBound::UnboundedFollowing {
+        return Err(error("a window frame can not start at UNBOUNDED FOLLOWING"))
+    }
+    if frame.end == FrameBound::UnboundedPreceding {
+        return Err(error("a window frame can not end at UNBOUNDED PRECEDING"))
+    }
+    if place(&frame.start) > place(&frame.end) {
+        return Err(error(&format!("a window frame can not start after its end, found {}", frame)))
+    }
+    for bound in [&frame.start, &frame.end] {
+        let (FrameBound::Preceding(offset) | FrameBound::Following(offset)) = bound else { continue };
+        match frame.units {
+            FrameUnits::Rows => if !matches!(offset, Value::Int(offset) if *offset >= 0) {
+                return Err(error(&format!("ROWS frame offsets must be non-negative integers, found {}", offset)))
+            },
+            FrameUnits::Range => {
+                let [key] = order_by else {
+                    return Err(error("RANGE with an offset needs exactly one ORDER BY expression"))
+                };
+                let numeric = matches!(offset, Value::Int(offset) if *offset >= 0) || matches!(offset, Value::Float(offset) if *offset >= 0.0);
+                let fits = match key.expr.data_type() {
+                    DataType::Date => matches!(offset, Value::Int(_)),
+                    data_type => data_type.is_numeric()
+                };
+                if !numeric || !fits {
+                    return Err(error(&format!(
+                        "RANGE offset {} does not apply to an ORDER BY expression of type {}, it needs a non-negative number \
+                         for numbers and a number of days for dates", offset, key.expr.data_type()
+                    )))
+                }
+            }
+        }
+    }
+    Ok(frame)
+}
+
+fn aggregate_type(func: &AggregateFunc, arg: Option<&ResolvedExpr>) -> Result<DataType, AnalyzerError> {
+    let arg_type = arg.map(|arg| arg.data_type()).unwrap_or(DataType::Null);
+    match func {
+        AggregateFunc::Count => Ok(DataType::Int),
+        AggregateFunc::Sum | AggregateFunc::Avg | AggregateFunc::Stddev | AggregateFunc::StddevPop | AggregateFunc::Variance
+        | AggregateFunc::VarPop | AggregateFunc::Median | AggregateFunc::PercentileCont(_)
+        | AggregateFunc::ApproxPercentile(_) if !arg_type.is_numeric() => {
+            Err(error(&format!("{} expects a numeric argument, found {}", func, arg_type)))
+        },
+        AggregateFunc::BoolAnd | AggregateFunc::BoolOr | AggregateFunc::CountIf if !arg_type.is_boolean() => {
+            Err(error(&format!("{} expects a boolean argument, found {}", func, arg_type)))
+        },
+        AggregateFunc::StringAgg(_) if !matches!(arg_type, DataType::String | DataType::Null | DataType::Any) => {
+            Err(error(&format!("{} expects a string argument, found {}", func, arg_type)))
+        },
+        AggregateFunc::Sum => Ok(if arg_type == DataType::Null { DataType::Int } else { arg_type }),
+        AggregateFunc::Avg | AggregateFunc::Stddev | AggregateFunc::StddevPop | AggregateFunc::Variance | AggregateFunc::VarPop
+        | AggregateFunc::Median | AggregateFunc::PercentileCont(_) | AggregateFunc::ApproxPercentile(_) => {
+            Ok(if arg_type == DataType::Any { DataType::Any } else { DataType::Float })
+        },
+        AggregateFunc::Min | AggregateFunc::Max | AggregateFunc::PercentileDisc(_) | AggregateFunc::Mode => Ok(arg_type),
+        AggregateFunc::StringAgg(_) => Ok(DataType::String),
+        AggregateFunc::ArrayAgg | AggregateFunc::ApproxTopK(_) => Ok(DataType::List),
+        AggregateFunc::BoolAnd | AggregateFunc::BoolOr => Ok(DataType::Bool),
+        AggregateFunc::CountIf | AggregateFunc::ApproxCountDistinct => Ok(DataType::Int)
+    }
+}
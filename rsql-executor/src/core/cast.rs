@@ -0,0 +1,58 @@
+use chrono::NaiveDate;
+use rsql_parser::ast::constructs::Value;
+use crate::core::errors::ExecutorError;
+use crate::core::evaluator::text;
+use crate::core::schema::DataType;
+
+// dates only convert to and from text, every other pair of types converts for at least some values
+pub fn can_cast(from: DataType, to: DataType) -> bool {
+    !matches!((from, to),
+        (DataType::Date, DataType::Bool | DataType::Int | DataType::Float)
+        | (DataType::Bool | DataType::Int | DataType::Float, DataType::Date)
+    )
+}
+
+// converts a value to the type, NULL stays NULL
+pub fn cast(value: Value, to: DataType) -> Result<Value, ExecutorError> {
+    let converted = match (&value, to) {
+        (Value::Null, _) | (_, DataType::Any | DataType::Null) => Some(value.clone()),
+        (_, DataType::String) => Some(Value::String(text(&value))),
+
+        (Value::Bool(_), DataType::Bool) => Some(value.clone()),
+        (Value::Int(i), DataType::Bool) => Some(Value::Bool(*i != 0)),
+        (Value::Float(f), DataType::Bool) => Some(Value::Bool(*f != 0.0)),
+        (Value::String(s), DataType::Bool) => match s.trim().to_ascii_lowercase().as_str() {
+            "true" | "t" | "yes" | "y" | "1" => Some(Value::Bool(true)),
+            "false" | "f" | "no" | "n" | "0" => Some(Value::Bool(false)),
+            _ => None
+        },
+
+        (Value::Bool(b), DataType::Int) => Some(Value::Int(*b as i64)),
+        (Value::Int(_), DataType::Int) => Some(value.clone()),
+        // doubles are rounded half away from zero, as ROUND does
+        (Value::Float(f), DataType::Int) => float_to_int(*f),
+        (Value::String(s), DataType::Int) => s.trim().parse::<i64>().ok().map(Value::Int),
+
+        (Value::Bool(b), DataType::Float) => Some(Value::Float(if *b { 1.0 } else { 0.0 })),
+        (Value::Int(i), DataType::Float) => Some(Value::Float(*i as f64)),
+        (Value::Float(_), DataType::Float) => Some(value.clone()),
+        (Value::String(s), DataType::Float) => s.trim().parse::<f64>().ok().map(Value::Float),
+
+        (Value::Date(_), DataType::Date) => Some(value.clone()),
+        (Value::String(s), DataType::Date) => NaiveDate::parse_from_str(s.trim(), "%Y-%m-%d").ok().map(Value::Date),
+
+        _ => None
+    };
+
+    converted.ok_or_else(|| ExecutorError { message: format!("cannot cast {} to {}", value, to) })
+}
+
+fn float_to_int(value: f64) -> Option<Value> {
+    let rounded = value.round();
+    // i64::MAX is not exactly representable, the bound is the first double past it
+    if rounded.is_finite() && rounded >= i64::MIN as f64 && rounded < i64::MAX as f64 {
+        Some(Value::Int(rounded as i64))
+    } else {
+        None
+    }
+}
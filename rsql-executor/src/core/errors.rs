@@ -1,3 +1,16 @@
+#[derive(Debug)]
 pub struct ExecutorError {
     pub message: String
-}
\ No newline at end of file
+}
+
+// raised while binding a statement to the source schema, before any row is read
+#[derive(Debug, Clone, PartialEq)]
+pub struct AnalyzerError {
+    pub message: String
+}
+
+impl From<AnalyzerError> for ExecutorError {
+    fn from(error: AnalyzerError) -> Self {
+        ExecutorError { message: error.message }
+    }
+}
@@ -0,0 +1,295 @@
+use std::cmp::Ordering;
+use rsql_parser::ast::constructs::{BinaryOperator, UnaryOperator, Value};
+use crate::core::cast::cast;
+use crate::core::errors::ExecutorError;
+use crate::core::functions;
+use crate::core::plan::ResolvedExpr;
+use crate::core::schema::DataType;
+
+pub fn evaluate(expr: &ResolvedExpr, row: &[Value]) -> Result<Value, ExecutorError> {
+    match expr {
+        ResolvedExpr::Column { index, name, .. } => row.get(*index)
+            .cloned()
+            .ok_or_else(|| error(&format!("column `{}` is missing from the row", name))),
+        ResolvedExpr::Literal(value) => Ok(value.clone()),
+        ResolvedExpr::BinaryOp { left, operator, right, .. } => {
+            let left = evaluate(left, row)?;
+            // AND / OR are evaluated lazily, as long as the result is already decided
+            match (operator, &left) {
+                (BinaryOperator::And, Value::Bool(false)) => return Ok(Value::Bool(false)),
+                (BinaryOperator::Or, Value::Bool(true)) => return Ok(Value::Bool(true)),
+                _ => {}
+            }
+            let right = evaluate(right, row)?;
+            binary(&left, operator, &right)
+        },
+        ResolvedExpr::UnaryOp { operator, expr, .. } => {
+            let value = evaluate(expr, row)?;
+            match (operator, value) {
+                (_, Value::Null) => Ok(Value::Null),
+                (UnaryOperator::Not, Value::Bool(b)) => Ok(Value::Bool(!b)),
+                (UnaryOperator::Minus, Value::Int(i)) => i.checked_neg()
+                    .map(Value::Int)
+                    .ok_or_else(|| error("integer overflow")),
+                (UnaryOperator::Minus, Value::Float(f)) => Ok(Value::Float(-f)),
+                (operator, value) => Err(error(&format!("operator {:?} can not be applied to {}", operator, value)))
+            }
+        },
+        ResolvedExpr::InList { expr, list, negated } => {
+            let value = evaluate(expr, row)?;
+            if matches!(value, Value::Null) {
+                return Ok(Value::Null)
+            }
+            // without a match, a NULL in the list makes the answer unknown
+            let mut unknown = false;
+            for item in list {
+                let item = evaluate(item, row)?;
+                if matches!(item, Value::Null) {
+                    unknown = true;
+                } else if compare(&value, &item)? == Ordering::Equal {
+                    return Ok(Value::Bool(!negated))
+                }
+            }
+            Ok(if unknown { Value::Null } else { Value::Bool(*negated) })
+        },
+        ResolvedExpr::Between { expr, low, high, negated } => {
+            let value = evaluate(expr, row)?;
+            let above_low = binary(&value, &BinaryOperator::GreaterThanOrEquals, &evaluate(low, row)?)?;
+            let below_high = binary(&value, &BinaryOperator::LessThanOrEquals, &evaluate(high, row)?)?;
+            match logical(&above_low, &below_high, false)? {
+                Value::Bool(between) => Ok(Value::Bool(between != *negated)),
+                other => Ok(other)
+            }
+        },
+        ResolvedExpr::Like { expr, pattern, case_insensitive, negated } => {
+            match (evaluate(expr, row)?, evaluate(pattern, row)?) {
+                (Value::Null, _) | (_, Value::Null) => Ok(Value::Null),
+                (value, pattern) => {
+                    let (value, pattern) = if *case_insensitive {
+                        (text(&value).to_lowercase(), text(&pattern).to_lowercase())
+                    } else {
+                        (text(&value), text(&pattern))
+                    };
+                    Ok(Value::Bool(like(&value, &pattern) != *negated))
+                }
+            }
+        },
+        ResolvedExpr::IsNull { expr, negated } => {
+            let value = evaluate(expr, row)?;
+            Ok(Value::Bool(matches!(value, Value::Null) != *negated))
+        },
+        ResolvedExpr::Regex(regex) => Ok(Value::String(regex.0.as_str().to_string())),
+        ResolvedExpr::Regexp { expr, pattern, negated } => {
+            let value = evaluate(expr, row)?;
+            match (value, functions::regexp::regex(pattern, row)?) {
+                (Value::Null, _) | (_, None) => Ok(Value::Null),
+                (value, Some(regex)) => Ok(Value::Bool(regex.is_match(&text(&value)) != *negated))
+            }
+        },
+        ResolvedExpr::Function { name, args, data_type } => functions::call(name, args, *data_type, row),
+        ResolvedExpr::Cast { expr, data_type, try_cast } => match cast(evaluate(expr, row)?, *data_type) {
+            Err(_) if *try_cast => Ok(Value::Null),
+            result => result
+        },
+        ResolvedExpr::Case { operand, branches, else_result, data_type } => {
+            let operand = operand.as_ref().map(|operand| evaluate(operand, row)).transpose()?;
+            // branches after the first match are not evaluated
+            for (when, then) in branches {
+                let matched = match &operand {
+                    Some(Value::Null) => false,
+                    Some(value) => match evaluate(when, row)? {
+                        Value::Null => false,
+                        when => compare(value, &when)? == Ordering::Equal
+                    },
+                    None => is_true(&evaluate(when, row)?)
+                };
+                if matched {
+                    return Ok(widen(evaluate(then, row)?, *data_type))
+                }
+            }
+            match else_result {
+                Some(result) => Ok(widen(evaluate(result, row)?, *data_type)),
+                None => Ok(Value::Null)
+            }
+        }
+    }
+}
+
+// an INTEGER produced by a CASE typed DOUBLE, one which mixes both, is returned as a DOUBLE
+fn widen(value: Value, data_type: DataType) -> Value {
+    match (value, data_type) {
+        (Value::Int(i), DataType::Float) => Value::Float(i as f64),
+        (value, _) => value
+    }
+}
+
+// LIKE matching: % is any run of characters, _ is one character, a backslash makes the next character literal
+pub fn like(value: &str, pattern: &str) -> bool {
+    let value: Vec<char> = value.chars().collect();
+    let pattern: Vec<char> = pattern.chars().collect();
+    let (mut v, mut p) = (0, 0);
+    // where the last % was seen, and the value position it is currently matched up to
+    let mut backtrack: Option<(usize, usize)> = None;
+
+    while v < value.len() {
+        match pattern.get(p) {
+            Some('%') => {
+                backtrack = Some((p, v));
+                p += 1;
+                continue
+            },
+            Some('_') => {
+                p += 1;
+                v += 1;
+                continue
+            },
+            Some('\\') if p + 1 < pattern.len() && pattern[p + 1] == value[v] => {
+                p += 2;
+                v += 1;
+                continue
+            },
+            Some(c) if *c != '\\' && *c == value[v] => {
+                p += 1;
+                v += 1;
+                continue
+            },
+            _ => {}
+        }
+        // mismatch, let the last % swallow one more character
+        match backtrack {
+            Some((percent, matched)) => {
+                p = percent + 1;
+                v = matched + 1;
+                backtrack = Some((percent, matched + 1));
+            },
+            None => return false
+        }
+    }
+
+    pattern[p..].iter().all(|c| *c == '%')
+}
+
+// a row passes a predicate only when it evaluates to TRUE, NULL filters it out like FALSE
+pub fn is_true(value: &Value) -> bool {
+    matches!(value, Value::Bool(true))
+}
+
+fn error(message: &str) -> ExecutorError {
+    ExecutorError { message: message.to_string() }
+}
+
+fn binary(left: &Value, operator: &BinaryOperator, right: &Value) -> Result<Value, ExecutorError> {
+    match operator {
+        BinaryOperator::And => logical(left, right, false),
+        BinaryOperator::Or => logical(left, right, true),
+        _ if matches!(left, Value::Null) || matches!(right, Value::Null) => Ok(Value::Null),
+        BinaryOperator::Equals => Ok(Value::Bool(compare(left, right)? == Ordering::Equal)),
+        BinaryOperator::NotEquals => Ok(Value::Bool(compare(left, right)? != Ordering::Equal)),
+        BinaryOperator::GreaterThan => Ok(Value::Bool(compare(left, right)? == Ordering::Greater)),
+        BinaryOperator::LessThan => Ok(Value::Bool(compare(left, right)? == Ordering::Less)),
+        BinaryOperator::GreaterThanOrEquals => Ok(Value::Bool(compare(left, right)? != Ordering::Less)),
+        BinaryOperator::LessThanOrEquals => Ok(Value::Bool(compare(left, right)? != Ordering::Greater)),
+        BinaryOperator::Concat => Ok(Value::String(format!("{}{}", text(left), text(right)))),
+        _ => arithmetic(left, operator, right)
+    }
+}
+
+// three valued logic, `decisive` is the value which decides the result on its own (TRUE for OR, FALSE for AND)
+fn logical(left: &Value, right: &Value, decisive: bool) -> Result<Value, ExecutorError> {
+    let truth = |value: &Value| match value {
+        Value::Bool(b) => Ok(Some(*b)),
+        Value::Null => Ok(None),
+        other => Err(error(&format!("expected a boolean, found {}", other)))
+    };
+    match (truth(left)?, truth(right)?) {
+        (Some(l), _) if l == decisive => Ok(Value::Bool(decisive)),
+        (_, Some(r)) if r == decisive => Ok(Value::Bool(decisive)),
+        (Some(_), Some(_)) => Ok(Value::Bool(!decisive)),
+        _ => Ok(Value::Null)
+    }
+}
+
+// integers stay integers, so 7 / 2 is 3: the quotient is truncated toward zero
+pub(crate) fn arithmetic(left: &Value, operator: &BinaryOperator, right: &Value) -> Result<Value, ExecutorError> {
+    match (left, right) {
+        (Value::Int(l), Value::Int(r)) => {
+            let result = match operator {
+                BinaryOperator::Add => l.checked_add(*r),
+                BinaryOperator::Subtract => l.checked_sub(*r),
+                BinaryOperator::Multiply => l.checked_mul(*r),
+                BinaryOperator::Divide | BinaryOperator::Modulo if *r == 0 => return Err(error("division by zero")),
+                BinaryOperator::Divide => l.checked_div(*r),
+                BinaryOperator::Modulo => l.checked_rem(*r),
+                _ => return Err(error(&format!("operator {} can not be applied to integers", operator)))
+            };
+            result.map(Value::Int).ok_or_else(|| error("integer overflow"))
+        },
+        (Value::Int(_) | Value::Float(_), Value::Int(_) | Value::Float(_)) => {
+            let (l, r) = (as_float(left), as_float(right));
+            let result = match operator {
+                BinaryOperator::Add => l + r,
+                BinaryOperator::Subtract => l - r,
+                BinaryOperator::Multiply => l * r,
+                BinaryOperator::Divide | BinaryOperator::Modulo if r == 0.0 => return Err(error("division by zero")),
+                BinaryOperator::Divide => l / r,
+                BinaryOperator::Modulo => l % r,
+                _ => return Err(error(&format!("operator {} can not be applied to numbers", operator)))
+            };
+            Ok(Value::Float(result))
+        },
+        _ => Err(error(&format!("operator {} can not be applied to {} and {}", operator, left, right)))
+    }
+}
+
+fn as_float(value: &Value) -> f64 {
+    match value {
+        Value::Int(i) => *i as f64,
+        Value::Float(f) => *f,
+        _ => f64::NAN
+    }
+}
+
+// string form of a value without the quotes used by Display
+pub fn text(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        Value::Null => String::new(),
+        Value::Date(date) => date.to_string(),
+        other => other.to_string()
+    }
+}
+
+// ordering of two non null values, integers and floats compare by their numeric value
+pub fn compare(left: &Value, right: &Value) -> Result<Ordering, ExecutorError> {
+    match (left, right) {
+        (Value::Int(l), Value::Int(r)) => Ok(l.cmp(r)),
+        (Value::Int(_) | Value::Float(_), Value::Int(_) | Value::Float(_)) => Ok(as_float(left).total_cmp(&as_float(right))),
+        (Value::String(l), Value::String(r)) => Ok(l.cmp(r)),
+        (Value::Bool(l), Value::Bool(r)) => Ok(l.cmp(r)),
+        (Value::Date(l), Value::Date(r)) => Ok(l.cmp(r)),
+        (Value::Null, Value::Null) => Ok(Ordering::Equal),
+        // lists compare value by value, a list sorts before the longer ones it starts
+        (Value::List(l), Value::List(r)) => {
+            for (left, right) in l.iter().zip(r) {
+                let ordering = sort_order(left, right, true)?;
+                if ordering != Ordering::Equal {
+                    return Ok(ordering)
+                }
+            }
+            Ok(l.len().cmp(&r.len()))
+        },
+        _ => Err(error(&format!("cannot compare {} with {}", left, right)))
+    }
+}
+
+// ordering used by ORDER BY, NULLs sort after every value in ascending order and first in descending order
+pub fn sort_order(left: &Value, right: &Value, asc: bool) -> Result<Ordering, ExecutorError> {
+    let ordering = match (left, right) {
+        (Value::Null, Value::Null) => Ordering::Equal,
+        (Value::Null, _) => Ordering::Greater,
+        (_, Value::Null) => Ordering::Less,
+        _ => compare(left, right)?
+    };
+
+    Ok(if asc { ordering } else { ordering.reverse() })
+}
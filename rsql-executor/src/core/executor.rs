@@ -1,55 +1,485 @@
+use crate::core::aggregate::{GroupKey, HashAggregator, KeyValue};
+use crate::core::analyzer::Analyzer;
 use crate::core::dataframe::{DataFrame, Row};
 use crate::core::errors::ExecutorError;
+use crate::core::evaluator::{evaluate, is_true};
+use crate::core::metrics::{format_duration, row_size, OperatorMetrics};
+use crate::core::optimizer::{OptimizedPlan, Optimizer};
+use crate::core::join::{JoinStream, JoinTable, MergeJoinStream};
+use crate::core::physical::PhysicalPlan;
+use crate::core::plan::{JoinSide, LogicalPlan};
+use crate::core::schema::Schema;
+use crate::core::settings::Settings;
+use crate::core::sort::Sorter;
+use crate::core::window::evaluate_windows;
 use rcsv_parser::parser::entities::entities::Value as CsvValue;
 use rcsv_parser::parser::entities::parser::Parse;
+use rcsv_parser::parser::entities::record_iterator::RecordIterator;
 use rcsv_parser::parser::entities::Record;
-use rsql_parser::ast::constructs::{SelectStatement, Statement, Value};
+use rsql_parser::ast::constructs::{SelectStatement, SetOperator, Statement, Value};
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
+use std::rc::Rc;
+use std::time::{Duration, Instant};
+
+// number of records read to guess the type of each column
+const SCHEMA_SAMPLE_SIZE: usize = 100;
+
+// cells written in this format are read as dates
+const DATE_FORMAT: &str = "%Y-%m-%d";
+
+// runs of the step of a recursive common table expression before it is taken to never end
+const MAX_RECURSION: usize = 1000;
+
+pub type RowIterator = Box<dyn Iterator<Item=Result<Vec<Value>, ExecutorError>>>;
 
 pub struct Executor {
-    pub parser: Box<dyn Parse>
+    pub parser: Box<dyn Parse>,
+    pub settings: Settings,
+    // rows of the common table expressions computed by the running plan, by name
+    ctes: HashMap<String, Rc<Vec<Vec<Value>>>>
 }
 
 impl Executor {
+    pub fn new(parser: Box<dyn Parse>) -> Self {
+        Executor { parser, settings: Settings::default(), ctes: HashMap::new() }
+    }
+
     pub fn execute(&mut self, statement: Statement) -> Result<DataFrame, ExecutorError> {
         match statement {
             Statement::Select(select) => self.execute_select(select),
-            _ => Err(ExecutorError {
-                message: "Unsupported statement.".to_string(),
-            })
+            Statement::Explain { analyze, statement } => self.explain(*statement, analyze)
         }
     }
 
-    fn execute_select(&mut self, select: SelectStatement) -> Result<DataFrame, ExecutorError> {
-        match select.from {
-            Some(from) => {
-                match self.parser.parse(&*from.source, None) {
-                    Ok((mut record_iterator, header)) => {
-                        Ok(DataFrame {
-                            columns: header.unwrap(),
-                            rows: consume(Box::new(record_iterator))
-                        })
-                    },
-                    Err(pe) => return Err(ExecutorError {message: pe.message})
+    // the plan is returned as a single column table, one line of text per row
+    fn explain(&mut self, statement: Statement, analyze: bool) -> Result<DataFrame, ExecutorError> {
+        let Statement::Select(select) = statement else {
+            return Err(ExecutorError { message: "EXPLAIN supports only SELECT statements".to_string() })
+        };
+        let optimized = self.optimized_plan(&select)?;
+        let physical = PhysicalPlan::from_logical(&optimized.plan, &self.settings);
+
+        let mut lines = vec!["Logical plan:".to_string()];
+        lines.extend(optimized.plan.to_string().lines().map(|line| format!("  {}", line)));
+        if !optimized.applied_rules.is_empty() {
+            lines.push(format!("Optimizer rules: {}", optimized.applied_rules.join(", ")));
+        }
+
+        if analyze {
+            let mut profile = Vec::new();
+            let started = Instant::now();
+            let mut rows = 0;
+            for values in self.run(&physical, &mut profile, 0)? {
+                values?;
+                rows += 1;
+            }
+            let elapsed = started.elapsed();
+
+            lines.push("Physical plan:".to_string());
+            for operator in &profile {
+                let metrics = &operator.metrics;
+                let mut line = format!("{}{} (rows={}", "  ".repeat(operator.depth + 1), operator.description, metrics.rows());
+                if metrics.bytes_read() > 0 {
+                    line.push_str(&format!(", bytes={}, parse={}", metrics.bytes_read(), format_duration(metrics.parse_time())));
                 }
-            },
-            None => return Err(ExecutorError {
+                line.push_str(&format!(", eval={}", format_duration(metrics.eval_time())));
+                if metrics.peak_memory() > 0 {
+                    line.push_str(&format!(", memory={}", metrics.peak_memory()));
+                }
+                line.push(')');
+                lines.push(line);
+            }
+            let total = |measure: fn(&OperatorMetrics) -> Duration| -> Duration {
+                profile.iter().map(|operator| measure(&operator.metrics)).sum()
+            };
+            lines.push(format!("Rows: {}", rows));
+            lines.push(format!("Bytes read: {}", profile.iter().map(|operator| operator.metrics.bytes_read()).sum::<u64>()));
+            lines.push(format!("Parse time: {}", format_duration(total(OperatorMetrics::parse_time))));
+            lines.push(format!("Evaluation time: {}", format_duration(total(OperatorMetrics::eval_time))));
+            lines.push(format!("Total time: {}", format_duration(elapsed)));
+            // buffering operators of one pipeline hold their rows at the same time, so the peaks add up
+            lines.push(format!("Peak memory: {} bytes", profile.iter().map(|operator| operator.metrics.peak_memory()).sum::<usize>()));
+        } else {
+            lines.push("Physical plan:".to_string());
+            lines.extend(physical.to_string().lines().map(|line| format!("  {}", line)));
+        }
+
+        Ok(DataFrame {
+            columns: vec!["plan".to_string()],
+            rows: lines.into_iter().map(|line| Row { values: vec![Value::String(line)] }).collect()
+        })
+    }
+
+    fn execute_select(&mut self, select: SelectStatement) -> Result<DataFrame, ExecutorError> {
+        let plan = self.optimized_plan(&select)?.plan;
+        let columns = plan.schema().names();
+        let rows = self.run(&PhysicalPlan::from_logical(&plan, &self.settings), &mut Vec::new(), 0)?
+            .map(|values| values.map(|values| Row { values }))
+            .collect::<Result<Vec<Row>, ExecutorError>>()?;
+
+        Ok(DataFrame { columns, rows })
+    }
+
+    // binds the statement to the schema of its source, no rows are read past the schema sample
+    pub fn plan(&mut self, select: &SelectStatement) -> Result<LogicalPlan, ExecutorError> {
+        if select.from.is_none() {
+            return Err(ExecutorError {
                 message: "Data source is required.".to_string()
             })
         }
+        let mut tables = HashMap::new();
+        for source in select.sources() {
+            if !tables.contains_key(source) {
+                tables.insert(source.to_string(), self.read_schema(source)?);
+            }
+        }
+
+        Ok(Analyzer::new(tables).with_settings(self.settings.clone()).analyze(select)?)
+    }
+
+    pub fn optimized_plan(&mut self, select: &SelectStatement) -> Result<OptimizedPlan, ExecutorError> {
+        let plan = self.plan(select)?;
+        Ok(Optimizer::default().optimize(plan))
+    }
+
+    pub fn read_schema(&mut self, source: &str) -> Result<Schema, ExecutorError> {
+        let (record_iterator, header) = self.parser.parse(source, Some(DATE_FORMAT.to_string()))
+            .map_err(|pe| ExecutorError { message: pe.message })?;
+        let sample: Vec<Vec<Value>> = record_iterator.take(SCHEMA_SAMPLE_SIZE).map(to_values).collect();
+        let header = header.unwrap_or_else(|| {
+            let width = sample.iter().map(|values| values.len()).max().unwrap_or(0);
+            (1..=width).map(|index| format!("column{}", index)).collect()
+        });
+
+        let mut schema = Schema::infer(header, &sample);
+        for name in self.settings.sorted_sources.get(source).into_iter().flatten() {
+            let index = schema.resolve(name)
+                .map_err(|e| ExecutorError { message: format!("{}, it is declared sorted in `{}`", e.message, source) })?;
+            if !schema.sorted.contains(&index) {
+                schema.sorted.push(index);
+            }
+        }
+        Ok(schema)
+    }
+
+    fn run(&mut self, plan: &PhysicalPlan, profile: &mut Vec<OperatorProfile>, depth: usize) -> Result<RowIterator, ExecutorError> {
+        let metrics = Rc::new(OperatorMetrics::default());
+        profile.push(OperatorProfile { description: plan.describe(), depth, metrics: Rc::clone(&metrics) });
+
+        let rows: RowIterator = match plan {
+            PhysicalPlan::FileScan { source, projection, filter } => {
+                let (mut record_iterator, _) = self.parser.parse(source, Some(DATE_FORMAT.to_string()))
+                    .map_err(|pe| ExecutorError { message: pe.message })?;
+                if let Some(projection) = projection {
+                    record_iterator = record_iterator.with_projection(projection.clone());
+                }
+                // the record filter can only keep or drop a record, an evaluation error keeps the record and is
+                // reported in its place
+                let failure: Rc<RefCell<Option<ExecutorError>>> = Rc::new(RefCell::new(None));
+                if let Some(predicate) = filter {
+                    let predicate = predicate.clone();
+                    let filter_failure = Rc::clone(&failure);
+                    let filter_metrics = Rc::clone(&metrics);
+                    record_iterator = record_iterator.with_filter(predicate.columns(), Box::new(move |record| {
+                        let values: Vec<Value> = record.columns.iter().cloned().map(rawparser_val_to_datafame_val).collect();
+                        match filter_metrics.time_eval(|| evaluate(&predicate, &values)) {
+                            Ok(result) => is_true(&result),
+                            Err(e) => {
+                                filter_failure.borrow_mut().get_or_insert(e);
+                                true
+                            }
+                        }
+                    }));
+                }
+                Box::new(FileScan { records: record_iterator, failure, metrics: Rc::clone(&metrics) })
+            },
+            PhysicalPlan::Filter { input, predicate } => {
+                let predicate = predicate.clone();
+                let filter_metrics = Rc::clone(&metrics);
+                Box::new(self.run(input, profile, depth + 1)?.filter_map(move |values| {
+                    let values = match values {
+                        Ok(values) => values,
+                        Err(e) => return Some(Err(e))
+                    };
+                    match filter_metrics.time_eval(|| evaluate(&predicate, &values)) {
+                        Ok(result) if is_true(&result) => Some(Ok(values)),
+                        Ok(_) => None,
+                        Err(e) => Some(Err(e))
+                    }
+                }))
+            },
+            PhysicalPlan::Projection { input, exprs } => {
+                let exprs = exprs.clone();
+                let project_metrics = Rc::clone(&metrics);
+                Box::new(self.run(input, profile, depth + 1)?.map(move |values| {
+                    let values = values?;
+                    project_metrics.time_eval(|| exprs.iter().map(|expr| evaluate(expr, &values)).collect())
+                }))
+            },
+            PhysicalPlan::HashAggregate { input, group_by, grouping_sets, aggregates } => {
+                let mut aggregator = HashAggregator::new(group_by.clone(), grouping_sets.clone(), aggregates.clone());
+                for values in self.run(input, profile, depth + 1)? {
+                    let values = values?;
+                    let before = aggregator.memory();
+                    metrics.time_eval(|| aggregator.update(&values))?;
+                    metrics.grow_memory(aggregator.memory() - before);
+                }
+                let groups = metrics.time_eval(|| aggregator.finish());
+                Box::new(groups.into_iter().map(Ok))
+            },
+            PhysicalPlan::Sort { input, keys } => {
+                let mut sorter = Sorter::new(keys.clone(), self.settings.memory_budget);
+                for values in self.run(input, profile, depth + 1)? {
+                    let values = values?;
+                    metrics.time_eval(|| sorter.push(values))?;
+                    // a spilled run no longer takes memory
+                    metrics.release_memory();
+                    metrics.grow_memory(sorter.memory());
+                }
+                metrics.time_eval(|| sorter.finish())?
+            },
+            PhysicalPlan::HashDistinct { input } => {
+                let mut seen: HashSet<GroupKey> = HashSet::new();
+                let distinct_metrics = Rc::clone(&metrics);
+                Box::new(self.run(input, profile, depth + 1)?.filter(move |values| match values {
+                    Ok(values) => distinct_metrics.time_eval(|| {
+                        let first = seen.insert(values.iter().map(KeyValue::from).collect());
+                        if first {
+                            distinct_metrics.grow_memory(row_size(values));
+                        }
+                        first
+                    }),
+                    Err(_) => true
+                }))
+            },
+            PhysicalPlan::SingleRow { input } => {
+                let mut rows = 0;
+                Box::new(self.run(input, profile, depth + 1)?.map(move |values| {
+                    rows += 1;
+                    match values {
+                        Ok(_) if rows > 1 => Err(ExecutorError { message: "subquery used as a value returned more than one row".to_string() }),
+                        values => values
+                    }
+                }))
+            },
+            PhysicalPlan::HashJoin { left, right, kind, on, filter, build, widths } => {
+                let (left, right) = (self.run(left, profile, depth + 1)?, self.run(right, profile, depth + 1)?);
+                let (build_rows, probe) = match build {
+                    JoinSide::Left => (left, right),
+                    JoinSide::Right => (right, left)
+                };
+                let table = build_join_table(JoinTable::new(*build, on), build_rows, &metrics)?;
+                Box::new(JoinStream::new(probe, table, *kind, filter.clone(), *widths, Rc::clone(&metrics)))
+            },
+            PhysicalPlan::SortMergeJoin { left, right, kind, on, filter, widths } => {
+                let inputs = (self.run(left, profile, depth + 1)?, self.run(right, profile, depth + 1)?);
+                Box::new(MergeJoinStream::new(inputs, on.clone(), *kind, filter.clone(), *widths, Rc::clone(&metrics)))
+            },
+            PhysicalPlan::NestedLoopJoin { left, right, kind, condition, widths } => {
+                let (left, right) = (self.run(left, profile, depth + 1)?, self.run(right, profile, depth + 1)?);
+                let table = build_join_table(JoinTable::new(JoinSide::Right, &[]), right, &metrics)?;
+                Box::new(JoinStream::new(left, table, *kind, condition.clone(), *widths, Rc::clone(&metrics)))
+            },
+            // the rows of a common table expression are only visible to the operators below the one which
+            // computes them, the input reads them while it is set up
+            PhysicalPlan::Materialize { ctes, input } => {
+                let visible = self.ctes.clone();
+                let rows = self.materialize(ctes, input, profile, depth, &metrics);
+                self.ctes = visible;
+                rows?
+            },
+            PhysicalPlan::CteScan { name } => {
+                let rows = self.ctes.get(name).cloned().ok_or_else(|| ExecutorError {
+                    message: format!("rows of common table expression `{}` were not computed", name)
+                })?;
+                Box::new((0..rows.len()).map(move |index| Ok(rows[index].clone())))
+            },
+            PhysicalPlan::RecursiveCte { name, anchor, step, distinct } => {
+                let visible = self.ctes.clone();
+                let rows = self.recurse(name, (anchor, step), *distinct, profile, depth, &metrics);
+                self.ctes = visible;
+                Box::new(rows?.into_iter().map(Ok))
+            },
+            PhysicalPlan::SetOperation { left, right, operator: SetOperator::Union, .. } => {
+                let (left, right) = (self.run(left, profile, depth + 1)?, self.run(right, profile, depth + 1)?);
+                Box::new(left.chain(right))
+            },
+            // a left row is found while the right rows hold a copy of it not matched yet, with ALL each copy
+            // matches one left row
+            PhysicalPlan::SetOperation { left, right, operator, all } => {
+                let left = self.run(left, profile, depth + 1)?;
+                let mut counts: HashMap<GroupKey, usize> = HashMap::new();
+                for values in self.run(right, profile, depth + 1)? {
+                    let values = values?;
+                    let count = counts.entry(values.iter().map(KeyValue::from).collect()).or_insert_with(|| {
+                        metrics.grow_memory(row_size(&values));
+                        0
+                    });
+                    *count += 1;
+                }
+                let (intersect, all) = (*operator == SetOperator::Intersect, *all);
+                let mut returned: HashSet<GroupKey> = HashSet::new();
+                let set_metrics = Rc::clone(&metrics);
+                Box::new(left.filter(move |values| match values {
+                    Ok(values) => set_metrics.time_eval(|| {
+                        let key: GroupKey = values.iter().map(KeyValue::from).collect();
+                        let found = match counts.get_mut(&key) {
+                            Some(count) if *count > 0 => {
+                                if all {
+                                    *count -= 1;
+                                }
+                                true
+                            },
+                            _ => false
+                        };
+                        found == intersect && (all || returned.insert(key))
+                    }),
+                    Err(_) => true
+                }))
+            },
+            PhysicalPlan::Limit { input, count } => Box::new(self.run(input, profile, depth + 1)?.take(*count as usize)),
+            PhysicalPlan::Window { input, calls } => {
+                let rows = self.run(input, profile, depth + 1)?.collect::<Result<Vec<Vec<Value>>, ExecutorError>>()?;
+                metrics.grow_memory(rows.iter().map(|values| row_size(values)).sum());
+                let rows = metrics.time_eval(|| evaluate_windows(rows, calls))?;
+                Box::new(rows.into_iter().map(Ok))
+            },
+            PhysicalPlan::Unpivot { input, columns, names, include_nulls } => {
+                let (columns, names, include_nulls) = (columns.clone(), names.clone(), *include_nulls);
+                Box::new(self.run(input, profile, depth + 1)?.flat_map(move |values| {
+                    let values = match values {
+                        Ok(values) => values,
+                        Err(e) => return vec![Err(e)]
+                    };
+                    let kept: Vec<Value> = values.iter()
+                        .enumerate()
+                        .filter(|(index, _)| !columns.contains(index))
+                        .map(|(_, value)| value.clone())
+                        .collect();
+                    columns.iter()
+                        .zip(&names)
+                        .filter(|(index, _)| include_nulls || !matches!(values[**index], Value::Null))
+                        .map(|(index, name)| {
+                            let mut row = kept.clone();
+                            row.push(Value::String(name.clone()));
+                            row.push(values[*index].clone());
+                            Ok(row)
+                        })
+                        .collect()
+                }))
+            }
+        };
+
+        Ok(Box::new(rows.inspect(move |values| {
+            if values.is_ok() {
+                metrics.add_row();
+            }
+        })))
+    }
+
+    fn materialize(
+        &mut self,
+        ctes: &[(String, PhysicalPlan)],
+        input: &PhysicalPlan,
+        profile: &mut Vec<OperatorProfile>,
+        depth: usize,
+        metrics: &OperatorMetrics
+    ) -> Result<RowIterator, ExecutorError> {
+        for (name, plan) in ctes {
+            let rows = self.run(plan, profile, depth + 1)?.collect::<Result<Vec<Vec<Value>>, ExecutorError>>()?;
+            metrics.grow_memory(rows.iter().map(|values| row_size(values)).sum());
+            self.ctes.insert(name.clone(), Rc::new(rows));
+        }
+        self.run(input, profile, depth + 1)
+    }
+
+    // the operators of the step are profiled on its first run
+    fn recurse(
+        &mut self,
+        name: &str,
+        (anchor, step): (&PhysicalPlan, &PhysicalPlan),
+        distinct: bool,
+        profile: &mut Vec<OperatorProfile>,
+        depth: usize,
+        metrics: &OperatorMetrics
+    ) -> Result<Vec<Vec<Value>>, ExecutorError> {
+        let mut seen: HashSet<GroupKey> = HashSet::new();
+        let mut added = |rows: Vec<Vec<Value>>| -> Vec<Vec<Value>> {
+            if !distinct {
+                return rows
+            }
+            rows.into_iter().filter(|values| seen.insert(values.iter().map(KeyValue::from).collect())).collect()
+        };
+
+        let mut rows = added(self.run(anchor, profile, depth + 1)?.collect::<Result<Vec<Vec<Value>>, ExecutorError>>()?);
+        let mut last = rows.clone();
+        let mut runs = 0;
+        while !last.is_empty() {
+            if runs == MAX_RECURSION {
+                return Err(ExecutorError {
+                    message: format!("recursive common table expression `{}` still adds rows after {} steps", name, MAX_RECURSION)
+                })
+            }
+            self.ctes.insert(name.to_string(), Rc::new(last));
+            let step_profile = if runs == 0 { &mut *profile } else { &mut Vec::new() };
+            last = added(self.run(step, step_profile, depth + 1)?.collect::<Result<Vec<Vec<Value>>, ExecutorError>>()?);
+            rows.extend(last.iter().cloned());
+            runs += 1;
+        }
+        metrics.grow_memory(rows.iter().map(|values| row_size(values)).sum());
+        Ok(rows)
     }
 }
 
-fn consume(mut iterator: Box<dyn Iterator<Item=Record>>) -> Vec<Row> {
-    let mut rows: Vec<Row> = Vec::new();
-    while let Some(record) = iterator.next() {
-        let df_vals = record.columns
-            .into_iter()
-            .map(|val| rawparser_val_to_datafame_val(val))
-            .collect();
-        let row  = Row {values: df_vals};
-        rows.push(row)
+fn build_join_table(mut table: JoinTable, rows: RowIterator, metrics: &OperatorMetrics) -> Result<JoinTable, ExecutorError> {
+    for values in rows {
+        let values = values?;
+        let before = table.memory();
+        metrics.time_eval(|| table.insert(values))?;
+        metrics.grow_memory(table.memory() - before);
     }
-    return rows
+    Ok(table)
+}
+
+// an operator of the plan together with what it did while the query ran
+pub struct OperatorProfile {
+    pub description: String,
+    pub depth: usize,
+    pub metrics: Rc<OperatorMetrics>
+}
+
+struct FileScan {
+    records: RecordIterator,
+    failure: Rc<RefCell<Option<ExecutorError>>>,
+    metrics: Rc<OperatorMetrics>
+}
+
+impl Iterator for FileScan {
+    type Item = Result<Vec<Value>, ExecutorError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let started = Instant::now();
+        let eval_time = self.metrics.eval_time();
+        let record = self.records.next();
+        // time spent in the pushed down filter is evaluation, the rest of reading the record is parsing
+        let filter_time = self.metrics.eval_time() - eval_time;
+        self.metrics.add_parse_time(started.elapsed().saturating_sub(filter_time));
+        self.metrics.set_bytes_read(self.records.bytes_read());
+
+        let record = record?;
+        Some(match self.failure.borrow_mut().take() {
+            Some(e) => Err(e),
+            None => Ok(to_values(record))
+        })
+    }
+}
+
+fn to_values(record: Record) -> Vec<Value> {
+    record.columns
+        .into_iter()
+        .map(rawparser_val_to_datafame_val)
+        .collect()
 }
 
 fn rawparser_val_to_datafame_val(val: CsvValue) -> Value {
@@ -73,4 +503,4 @@ fn rawparser_val_to_datafame_val(val: CsvValue) -> Value {
             Value::Date(date)
         }
     }
-}
\ No newline at end of file
+}
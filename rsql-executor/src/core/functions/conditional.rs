@@ -0,0 +1,29 @@
+use std::cmp::Ordering;
+use rsql_parser::ast::constructs::Value;
+use crate::core::errors::ExecutorError;
+use crate::core::evaluator::{compare, evaluate};
+use crate::core::plan::ResolvedExpr;
+
+// the first argument which is not NULL, the ones after it are not evaluated
+pub fn coalesce(args: &[ResolvedExpr], row: &[Value]) -> Result<Value, ExecutorError> {
+    for arg in args {
+        match evaluate(arg, row)? {
+            Value::Null => continue,
+            value => return Ok(value)
+        }
+    }
+    Ok(Value::Null)
+}
+
+// NULLIF(a, b) is NULL when a equals b and a otherwise
+pub fn nullif(args: &[ResolvedExpr], row: &[Value]) -> Result<Value, ExecutorError> {
+    let value = evaluate(&args[0], row)?;
+    if matches!(value, Value::Null) {
+        return Ok(value)
+    }
+    match evaluate(&args[1], row)? {
+        Value::Null => Ok(value),
+        other if compare(&value, &other)? == Ordering::Equal => Ok(Value::Null),
+        _ => Ok(value)
+    }
+}
@@ -0,0 +1,138 @@
+use std::fmt::Write;
+use chrono::format::{Item, StrftimeItems};
+use chrono::{Datelike, Days, Months, NaiveDate};
+use rsql_parser::ast::constructs::Value;
+use crate::core::errors::ExecutorError;
+use crate::core::evaluator::text;
+use crate::core::functions::{error, integer};
+
+enum Unit {
+    Year,
+    Quarter,
+    Month,
+    Week,
+    Day
+}
+
+fn unit(value: &Value, function: &str) -> Result<Unit, ExecutorError> {
+    match text(value).to_ascii_lowercase().as_str() {
+        "year" => Ok(Unit::Year),
+        "quarter" => Ok(Unit::Quarter),
+        "month" => Ok(Unit::Month),
+        "week" => Ok(Unit::Week),
+        "day" => Ok(Unit::Day),
+        other => Err(error(&format!("{} unit must be one of year, quarter, month, week or day, found '{}'", function, other)))
+    }
+}
+
+fn date(value: &Value, function: &str) -> Result<NaiveDate, ExecutorError> {
+    match value {
+        Value::Date(date) => Ok(*date),
+        other => Err(error(&format!("{} expects a date, found {}", function, other)))
+    }
+}
+
+// first day of the unit the date falls in, weeks start on Monday
+fn truncate(date: NaiveDate, unit: &Unit) -> NaiveDate {
+    let first_of_month = |month: u32| NaiveDate::from_ymd_opt(date.year(), month, 1).unwrap_or(date);
+    match unit {
+        Unit::Year => first_of_month(1),
+        Unit::Quarter => first_of_month((date.month0() / 3) * 3 + 1),
+        Unit::Month => first_of_month(date.month()),
+        Unit::Week => date - Days::new(date.weekday().num_days_from_monday() as u64),
+        Unit::Day => date
+    }
+}
+
+fn months(date: NaiveDate) -> i64 {
+    date.year() as i64 * 12 + date.month0() as i64
+}
+
+// DATE_TRUNC(unit, date)
+pub fn date_trunc(args: &[Value]) -> Result<Value, ExecutorError> {
+    let unit = unit(&args[0], "DATE_TRUNC")?;
+    Ok(Value::Date(truncate(date(&args[1], "DATE_TRUNC")?, &unit)))
+}
+
+// EXTRACT(field FROM date), DOW counts from Sunday as 0 and WEEK is the ISO week
+pub fn extract(args: &[Value]) -> Result<Value, ExecutorError> {
+    let date = date(&args[1], "EXTRACT")?;
+    let field = match text(&args[0]).to_ascii_uppercase().as_str() {
+        "YEAR" => date.year(),
+        "QUARTER" => (date.month0() / 3 + 1) as i32,
+        "MONTH" => date.month() as i32,
+        "WEEK" => date.iso_week().week() as i32,
+        "DAY" => date.day() as i32,
+        "DOW" => date.weekday().num_days_from_sunday() as i32,
+        "DOY" => date.ordinal() as i32,
+        other => return Err(error(&format!(
+            "EXTRACT field must be one of YEAR, QUARTER, MONTH, WEEK, DAY, DOW or DOY, found {}", other
+        )))
+    };
+    Ok(Value::Int(field as i64))
+}
+
+// DATE_ADD(unit, amount, date), adding months keeps the day unless the month is shorter
+pub fn date_add(args: &[Value]) -> Result<Value, ExecutorError> {
+    let unit = unit(&args[0], "DATE_ADD")?;
+    let amount = integer(&args[1], "DATE_ADD")?;
+    let date = date(&args[2], "DATE_ADD")?;
+    let add_months = |months: i64| {
+        let count = Months::new(u32::try_from(months.unsigned_abs()).ok()?);
+        if months < 0 { date.checked_sub_months(count) } else { date.checked_add_months(count) }
+    };
+    let add_days = |days: i64| {
+        let count = Days::new(days.unsigned_abs());
+        if days < 0 { date.checked_sub_days(count) } else { date.checked_add_days(count) }
+    };
+    let result = match unit {
+        Unit::Year => amount.checked_mul(12).and_then(add_months),
+        Unit::Quarter => amount.checked_mul(3).and_then(add_months),
+        Unit::Month => add_months(amount),
+        Unit::Week => amount.checked_mul(7).and_then(add_days),
+        Unit::Day => add_days(amount)
+    };
+    result.map(Value::Date).ok_or_else(|| error("DATE_ADD result is out of the supported date range"))
+}
+
+// DATE_DIFF(unit, start, end) counts the unit boundaries between the dates, negative when end is before start
+pub fn date_diff(args: &[Value]) -> Result<Value, ExecutorError> {
+    let unit = unit(&args[0], "DATE_DIFF")?;
+    let (start, end) = (date(&args[1], "DATE_DIFF")?, date(&args[2], "DATE_DIFF")?);
+    let difference = match unit {
+        Unit::Year => (end.year() - start.year()) as i64,
+        Unit::Quarter => months(end) / 3 - months(start) / 3,
+        Unit::Month => months(end) - months(start),
+        Unit::Week => (truncate(end, &Unit::Week) - truncate(start, &Unit::Week)).num_days() / 7,
+        Unit::Day => (end - start).num_days()
+    };
+    Ok(Value::Int(difference))
+}
+
+pub fn current_date(_args: &[Value]) -> Result<Value, ExecutorError> {
+    Ok(Value::Date(chrono::Local::now().date_naive()))
+}
+
+// TO_DATE(text[, format]), the format uses strftime specifiers and defaults to %Y-%m-%d
+pub fn to_date(args: &[Value]) -> Result<Value, ExecutorError> {
+    let value = text(&args[0]);
+    let format = args.get(1).map(text).unwrap_or_else(|| "%Y-%m-%d".to_string());
+    NaiveDate::parse_from_str(&value, &format)
+        .map(Value::Date)
+        .map_err(|e| error(&format!("'{}' does not match the date format '{}': {}", value, format, e)))
+}
+
+// STRFTIME(date, format)
+pub fn strftime(args: &[Value]) -> Result<Value, ExecutorError> {
+    let date = date(&args[0], "STRFTIME")?;
+    let format = text(&args[1]);
+    let items: Vec<Item> = StrftimeItems::new(&format).collect();
+    let invalid = || error(&format!("'{}' is not a valid date format", format));
+    if items.contains(&Item::Error) {
+        return Err(invalid())
+    }
+    // specifiers such as %H have nothing to format for a date and fail here
+    let mut result = String::new();
+    write!(result, "{}", date.format_with_items(items.into_iter())).map_err(|_| invalid())?;
+    Ok(Value::String(result))
+}
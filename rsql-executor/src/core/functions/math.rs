@@ -0,0 +1,152 @@
+use std::cmp::Ordering;
+use rsql_parser::ast::constructs::{BinaryOperator, Value};
+use crate::core::errors::ExecutorError;
+use crate::core::evaluator::{arithmetic, compare, evaluate};
+use crate::core::functions::{error, integer};
+use crate::core::plan::ResolvedExpr;
+
+// integer arguments give integer results where the function has one, a DOUBLE argument makes the result DOUBLE
+
+fn number(value: &Value, function: &str) -> Result<f64, ExecutorError> {
+    match value {
+        Value::Int(i) => Ok(*i as f64),
+        Value::Float(f) => Ok(*f),
+        other => Err(error(&format!("{} expects a number, found {}", function, other)))
+    }
+}
+
+pub fn abs(args: &[Value]) -> Result<Value, ExecutorError> {
+    match &args[0] {
+        Value::Int(i) => i.checked_abs().map(Value::Int).ok_or_else(|| error("integer overflow")),
+        other => Ok(Value::Float(number(other, "ABS")?.abs()))
+    }
+}
+
+// ROUND(x[, digits]) rounds halves away from zero, negative digits round to tens, hundreds, ...
+pub fn round(args: &[Value]) -> Result<Value, ExecutorError> {
+    let digits = match args.get(1) {
+        Some(digits) => integer(digits, "ROUND")?,
+        None => 0
+    };
+    match &args[0] {
+        Value::Int(i) if digits >= 0 => Ok(Value::Int(*i)),
+        Value::Int(i) => {
+            let Some(factor) = u32::try_from(digits.unsigned_abs()).ok().and_then(|exponent| 10i128.checked_pow(exponent)) else {
+                return Ok(Value::Int(0))
+            };
+            let value = *i as i128;
+            let rounded = (value + value.signum() * factor / 2) / factor * factor;
+            i64::try_from(rounded).map(Value::Int).map_err(|_| error("integer overflow"))
+        },
+        other => {
+            let value = number(other, "ROUND")?;
+            let factor = 10f64.powi(digits.clamp(-308, 308) as i32);
+            let rounded = (value * factor).round() / factor;
+            // digits beyond the precision of a double leave the value as it is
+            Ok(Value::Float(if rounded.is_finite() { rounded } else { value }))
+        }
+    }
+}
+
+pub fn floor(args: &[Value]) -> Result<Value, ExecutorError> {
+    match &args[0] {
+        Value::Int(i) => Ok(Value::Int(*i)),
+        other => Ok(Value::Float(number(other, "FLOOR")?.floor()))
+    }
+}
+
+pub fn ceil(args: &[Value]) -> Result<Value, ExecutorError> {
+    match &args[0] {
+        Value::Int(i) => Ok(Value::Int(*i)),
+        other => Ok(Value::Float(number(other, "CEIL")?.ceil()))
+    }
+}
+
+pub fn power(args: &[Value]) -> Result<Value, ExecutorError> {
+    let (base, exponent) = (number(&args[0], "POWER")?, number(&args[1], "POWER")?);
+    let result = base.powf(exponent);
+    if result.is_nan() && !base.is_nan() && !exponent.is_nan() {
+        return Err(error("POWER of a negative number to a fractional exponent is undefined"))
+    }
+    Ok(Value::Float(result))
+}
+
+pub fn sqrt(args: &[Value]) -> Result<Value, ExecutorError> {
+    let value = number(&args[0], "SQRT")?;
+    if value < 0.0 {
+        return Err(error("cannot take the square root of a negative number"))
+    }
+    Ok(Value::Float(value.sqrt()))
+}
+
+fn logarithm(value: f64) -> Result<f64, ExecutorError> {
+    if value <= 0.0 {
+        return Err(error("cannot take the logarithm of zero or a negative number"))
+    }
+    Ok(value.ln())
+}
+
+pub fn ln(args: &[Value]) -> Result<Value, ExecutorError> {
+    Ok(Value::Float(logarithm(number(&args[0], "LN")?)?))
+}
+
+// LOG(x) is the base 10 logarithm, LOG(base, x) uses the given base
+pub fn log(args: &[Value]) -> Result<Value, ExecutorError> {
+    let (base, value) = match args {
+        [value] => (10.0, number(value, "LOG")?),
+        [base, value, ..] => (number(base, "LOG")?, number(value, "LOG")?),
+        [] => return Err(error("LOG expects an argument"))
+    };
+    if base == 1.0 {
+        return Err(error("the base of a logarithm can not be 1"))
+    }
+    let natural = logarithm(value)?;
+    // log10 is exact for powers of ten, the quotient of natural logarithms is not
+    if base == 10.0 {
+        return Ok(Value::Float(value.log10()))
+    }
+    Ok(Value::Float(natural / logarithm(base)?))
+}
+
+pub fn modulo(args: &[Value]) -> Result<Value, ExecutorError> {
+    arithmetic(&args[0], &BinaryOperator::Modulo, &args[1])
+}
+
+// a / b, but NULL when b is zero
+pub fn safe_divide(args: &[Value]) -> Result<Value, ExecutorError> {
+    if number(&args[1], "SAFE_DIVIDE")? == 0.0 {
+        return Ok(Value::Null)
+    }
+    arithmetic(&args[0], &BinaryOperator::Divide, &args[1])
+}
+
+// a % b, but NULL when b is zero
+pub fn safe_modulo(args: &[Value]) -> Result<Value, ExecutorError> {
+    if number(&args[1], "SAFE_MODULO")? == 0.0 {
+        return Ok(Value::Null)
+    }
+    arithmetic(&args[0], &BinaryOperator::Modulo, &args[1])
+}
+
+pub fn greatest(args: &[ResolvedExpr], row: &[Value]) -> Result<Value, ExecutorError> {
+    extreme(args, row, Ordering::Greater)
+}
+
+pub fn least(args: &[ResolvedExpr], row: &[Value]) -> Result<Value, ExecutorError> {
+    extreme(args, row, Ordering::Less)
+}
+
+// NULL arguments are skipped, the result is NULL only when all of them are
+fn extreme(args: &[ResolvedExpr], row: &[Value], wanted: Ordering) -> Result<Value, ExecutorError> {
+    let mut result = Value::Null;
+    for arg in args {
+        let value = evaluate(arg, row)?;
+        if matches!(value, Value::Null) {
+            continue
+        }
+        if matches!(result, Value::Null) || compare(&value, &result)? == wanted {
+            result = value;
+        }
+    }
+    Ok(result)
+}
@@ -0,0 +1,238 @@
+use rsql_parser::ast::constructs::Value;
+use rsql_parser::suggest::closest_match;
+use crate::core::analyzer::coerce_to_date;
+use crate::core::errors::{AnalyzerError, ExecutorError};
+use crate::core::evaluator::evaluate;
+use crate::core::plan::ResolvedExpr;
+use crate::core::schema::DataType;
+
+pub mod regexp;
+mod string;
+mod date;
+mod math;
+mod conditional;
+
+const VARCHAR: DataType = DataType::String;
+const INTEGER: DataType = DataType::Int;
+const BOOLEAN: DataType = DataType::Bool;
+const DATE: DataType = DataType::Date;
+// also accepts INTEGER arguments
+const DOUBLE: DataType = DataType::Float;
+const ANY: DataType = DataType::Any;
+
+// built-in scalar functions, calls are checked against these by the analyzer and run by the evaluator
+const FUNCTIONS: &[Function] = &[
+    Function::strict("UPPER", &[VARCHAR], VARCHAR, string::upper),
+    Function::strict("LOWER", &[VARCHAR], VARCHAR, string::lower),
+    Function::strict("TRIM", &[VARCHAR, VARCHAR], VARCHAR, string::trim).optional(1),
+    Function::strict("LTRIM", &[VARCHAR, VARCHAR], VARCHAR, string::ltrim).optional(1),
+    Function::strict("RTRIM", &[VARCHAR, VARCHAR], VARCHAR, string::rtrim).optional(1),
+    Function::strict("LENGTH", &[VARCHAR], INTEGER, string::length),
+    Function::strict("SUBSTRING", &[VARCHAR, INTEGER, INTEGER], VARCHAR, string::substring).optional(1),
+    Function::strict("REPLACE", &[VARCHAR, VARCHAR, VARCHAR], VARCHAR, string::replace),
+    Function::lazy("CONCAT", &[ANY], VARCHAR, string::concat).variadic(),
+    Function::strict("SPLIT_PART", &[VARCHAR, VARCHAR, INTEGER], VARCHAR, string::split_part),
+    Function::strict("LPAD", &[VARCHAR, INTEGER, VARCHAR], VARCHAR, string::lpad).optional(1),
+    Function::strict("RPAD", &[VARCHAR, INTEGER, VARCHAR], VARCHAR, string::rpad).optional(1),
+    Function::strict("STARTS_WITH", &[VARCHAR, VARCHAR], BOOLEAN, string::starts_with),
+    Function::strict("POSITION", &[VARCHAR, VARCHAR], INTEGER, string::position),
+    Function::strict("DATE_TRUNC", &[VARCHAR, DATE], DATE, date::date_trunc),
+    Function::strict("EXTRACT", &[VARCHAR, DATE], INTEGER, date::extract),
+    Function::strict("DATE_ADD", &[VARCHAR, INTEGER, DATE], DATE, date::date_add),
+    Function::strict("DATE_DIFF", &[VARCHAR, DATE, DATE], INTEGER, date::date_diff),
+    Function::strict("CURRENT_DATE", &[], DATE, date::current_date),
+    Function::strict("TO_DATE", &[VARCHAR, VARCHAR], DATE, date::to_date).optional(1),
+    Function::strict("STRFTIME", &[DATE, VARCHAR], VARCHAR, date::strftime),
+    Function::strict("ABS", &[DOUBLE], DOUBLE, math::abs).common_type(),
+    Function::strict("ROUND", &[DOUBLE, INTEGER], DOUBLE, math::round).optional(1).common_type(),
+    Function::strict("FLOOR", &[DOUBLE], DOUBLE, math::floor).common_type(),
+    Function::strict("CEIL", &[DOUBLE], DOUBLE, math::ceil).common_type(),
+    Function::strict("CEILING", &[DOUBLE], DOUBLE, math::ceil).common_type(),
+    Function::strict("POWER", &[DOUBLE, DOUBLE], DOUBLE, math::power),
+    Function::strict("SQRT", &[DOUBLE], DOUBLE, math::sqrt),
+    Function::strict("LN", &[DOUBLE], DOUBLE, math::ln),
+    Function::strict("LOG", &[DOUBLE, DOUBLE], DOUBLE, math::log).optional(1),
+    Function::strict("MOD", &[DOUBLE, DOUBLE], DOUBLE, math::modulo).common_type(),
+    Function::lazy("GREATEST", &[ANY], ANY, math::greatest).variadic().common_type(),
+    Function::lazy("LEAST", &[ANY], ANY, math::least).variadic().common_type(),
+    Function::lazy("COALESCE", &[ANY], ANY, conditional::coalesce).variadic().common_type(),
+    Function::lazy("NULLIF", &[ANY, ANY], ANY, conditional::nullif).common_type(),
+    Function::lazy("REGEXP_EXTRACT", &[VARCHAR, VARCHAR, INTEGER], VARCHAR, regexp::extract).optional(1).pattern(1),
+    Function::lazy("REGEXP_REPLACE", &[VARCHAR, VARCHAR, VARCHAR], VARCHAR, regexp::replace).pattern(1)
+];
+
+// targets of the analyzer's rewrites, they can be run but not called by name or suggested
+const INTERNAL: &[Function] = &[
+    Function::strict("SAFE_DIVIDE", &[DOUBLE, DOUBLE], DOUBLE, math::safe_divide).common_type(),
+    Function::strict("SAFE_MODULO", &[DOUBLE, DOUBLE], DOUBLE, math::safe_modulo).common_type()
+];
+
+pub struct Function {
+    pub name: &'static str,
+    // ANY accepts arguments of every type
+    parameters: &'static [DataType],
+    // parameters after the required ones can be left out
+    required: usize,
+    // the last parameter can be repeated
+    variadic: bool,
+    // argument which is a regular expression, compiled once when it is a literal
+    pattern: Option<usize>,
+    pub returns: DataType,
+    // the result has the common type of the arguments instead, ABS of an INTEGER is an INTEGER
+    common_type: bool,
+    body: Body
+}
+
+enum Body {
+    // the result is NULL when any argument is NULL
+    Strict(fn(&[Value]) -> Result<Value, ExecutorError>),
+    // evaluates the arguments itself, for functions which treat NULL differently or use a compiled pattern
+    Lazy(fn(&[ResolvedExpr], &[Value]) -> Result<Value, ExecutorError>)
+}
+
+impl Function {
+    const fn strict(
+        name: &'static str,
+        parameters: &'static [DataType],
+        returns: DataType,
+        body: fn(&[Value]) -> Result<Value, ExecutorError>
+    ) -> Function {
+        Function { name, parameters, required: parameters.len(), variadic: false, pattern: None, returns, common_type: false, body: Body::Strict(body) }
+    }
+
+    const fn lazy(
+        name: &'static str,
+        parameters: &'static [DataType],
+        returns: DataType,
+        body: fn(&[ResolvedExpr], &[Value]) -> Result<Value, ExecutorError>
+    ) -> Function {
+        Function { name, parameters, required: parameters.len(), variadic: false, pattern: None, returns, common_type: false, body: Body::Lazy(body) }
+    }
+
+    const fn optional(self, count: usize) -> Function {
+        Function { required: self.parameters.len() - count, ..self }
+    }
+
+    const fn variadic(self) -> Function {
+        Function { variadic: true, ..self }
+    }
+
+    const fn pattern(self, position: usize) -> Function {
+        Function { pattern: Some(position), ..self }
+    }
+
+    const fn common_type(self) -> Function {
+        Function { common_type: true, ..self }
+    }
+
+    fn parameter(&self, position: usize) -> DataType {
+        self.parameters.get(position).or(self.parameters.last()).copied().unwrap_or(ANY)
+    }
+
+    fn check_arity(&self, count: usize) -> Result<(), AnalyzerError> {
+        let maximum = self.parameters.len();
+        if count >= self.required && (self.variadic || count <= maximum) {
+            return Ok(())
+        }
+        let expected = if self.variadic {
+            format!("at least {}", arguments(self.required))
+        } else if self.required == maximum {
+            arguments(maximum)
+        } else {
+            format!("{} to {}", self.required, arguments(maximum))
+        };
+        Err(AnalyzerError { message: format!("{} expects {}, found {}", self.name, expected, count) })
+    }
+}
+
+fn arguments(count: usize) -> String {
+    if count == 1 { "1 argument".to_string() } else { format!("{} arguments", count) }
+}
+
+pub fn lookup(name: &str) -> Option<&'static Function> {
+    FUNCTIONS.iter().find(|function| function.name.eq_ignore_ascii_case(name))
+}
+
+// checks the arguments of a call, the resolved call names the function in upper case
+pub fn resolve(name: &str, mut args: Vec<ResolvedExpr>) -> Result<ResolvedExpr, AnalyzerError> {
+    let Some(function) = lookup(name) else {
+        let names: Vec<&str> = FUNCTIONS.iter().map(|function| function.name).collect();
+        let message = match closest_match(&name.to_ascii_uppercase(), &names) {
+            Some(suggestion) => format!("unknown function `{}`, did you mean {}?", name, suggestion),
+            None => format!("unknown function `{}`", name)
+        };
+        return Err(AnalyzerError { message })
+    };
+
+    function.check_arity(args.len())?;
+    for (position, arg) in args.iter_mut().enumerate() {
+        let parameter = function.parameter(position);
+        // a date can be written as a string, DATE_TRUNC('month', '2024-03-15')
+        if parameter == DATE {
+            let literal = std::mem::replace(arg, ResolvedExpr::Literal(Value::Null));
+            *arg = coerce_to_date(literal, DATE)?;
+        }
+        let accepted = parameter == ANY
+            || matches!(arg.data_type(), DataType::Null | DataType::Any)
+            || arg.data_type() == parameter
+            || (parameter == DOUBLE && arg.data_type() == INTEGER);
+        if !accepted {
+            return Err(AnalyzerError {
+                message: format!("argument {} of {} must be {}, found {}", position + 1, function.name, parameter, arg.data_type())
+            })
+        }
+    }
+    if let Some(position) = function.pattern {
+        let pattern = std::mem::replace(&mut args[position], ResolvedExpr::Literal(Value::Null));
+        args[position] = regexp::compile(pattern)?;
+    }
+
+    let mut data_type = function.returns;
+    if function.common_type {
+        data_type = DataType::Null;
+        for arg in &args {
+            data_type = data_type.unify(arg.data_type()).ok_or_else(|| AnalyzerError {
+                message: format!("arguments of {} must have a common type, found {} and {}", function.name, data_type, arg.data_type())
+            })?;
+        }
+    }
+
+    Ok(ResolvedExpr::Function { name: function.name.to_string(), args, data_type })
+}
+
+// runs a resolved call, the result has the data type it was resolved with
+pub fn call(name: &str, args: &[ResolvedExpr], data_type: DataType, row: &[Value]) -> Result<Value, ExecutorError> {
+    let function = lookup(name)
+        .or_else(|| INTERNAL.iter().find(|function| function.name == name))
+        .ok_or_else(|| error(&format!("unknown function `{}`", name)))?;
+    let value = match function.body {
+        Body::Strict(body) => {
+            let mut values = Vec::with_capacity(args.len());
+            for arg in args {
+                match evaluate(arg, row)? {
+                    Value::Null => return Ok(Value::Null),
+                    value => values.push(value)
+                }
+            }
+            body(&values)?
+        },
+        Body::Lazy(body) => body(args, row)?
+    };
+    // the common type of INTEGER and DOUBLE arguments is DOUBLE, LEAST(1, 2.5) is 1.0
+    match (value, data_type) {
+        (Value::Int(i), DOUBLE) => Ok(Value::Float(i as f64)),
+        (value, _) => Ok(value)
+    }
+}
+
+fn error(message: &str) -> ExecutorError {
+    ExecutorError { message: message.to_string() }
+}
+
+// integer argument of a function, columns of mixed types are only checked when the query runs
+fn integer(value: &Value, function: &str) -> Result<i64, ExecutorError> {
+    match value {
+        Value::Int(i) => Ok(*i),
+        other => Err(error(&format!("{} expects an integer, found {}", function, other)))
+    }
+}
@@ -0,0 +1,68 @@
+use std::borrow::Cow;
+use regex::Regex;
+use rsql_parser::ast::constructs::Value;
+use crate::core::errors::{AnalyzerError, ExecutorError};
+use crate::core::evaluator::{evaluate, text};
+use crate::core::functions::{error, integer};
+use crate::core::plan::{CompiledRegex, ResolvedExpr};
+
+// a pattern written as a literal is compiled once, other patterns are compiled for each row
+pub fn compile(pattern: ResolvedExpr) -> Result<ResolvedExpr, AnalyzerError> {
+    match pattern {
+        ResolvedExpr::Literal(Value::String(pattern)) => Regex::new(&pattern)
+            .map(|regex| ResolvedExpr::Regex(CompiledRegex(regex)))
+            .map_err(|e| AnalyzerError { message: format!("invalid regular expression '{}': {}", pattern, e) }),
+        pattern => Ok(pattern)
+    }
+}
+
+// the regular expression a pattern evaluates to, None for a NULL pattern
+pub fn regex<'a>(pattern: &'a ResolvedExpr, row: &[Value]) -> Result<Option<Cow<'a, Regex>>, ExecutorError> {
+    match pattern {
+        ResolvedExpr::Regex(CompiledRegex(regex)) => Ok(Some(Cow::Borrowed(regex))),
+        pattern => match evaluate(pattern, row)? {
+            Value::Null => Ok(None),
+            pattern => Regex::new(&text(&pattern))
+                .map(|regex| Some(Cow::Owned(regex)))
+                .map_err(|e| error(&format!("invalid regular expression '{}': {}", text(&pattern), e)))
+        }
+    }
+}
+
+// the text and the pattern of a call, None when either is NULL
+fn operands<'a>(args: &'a [ResolvedExpr], row: &[Value]) -> Result<Option<(String, Cow<'a, Regex>)>, ExecutorError> {
+    let value = evaluate(&args[0], row)?;
+    match (value, regex(&args[1], row)?) {
+        (Value::Null, _) | (_, None) => Ok(None),
+        (value, Some(regex)) => Ok(Some((text(&value), regex)))
+    }
+}
+
+// REGEXP_EXTRACT(s, pattern[, group]), the whole match when no group is given
+pub fn extract(args: &[ResolvedExpr], row: &[Value]) -> Result<Value, ExecutorError> {
+    let Some((value, regex)) = operands(args, row)? else { return Ok(Value::Null) };
+    let group = match args.get(2).map(|group| evaluate(group, row)).transpose()? {
+        None => 0,
+        Some(Value::Null) => return Ok(Value::Null),
+        Some(group) => integer(&group, "REGEXP_EXTRACT")?
+    };
+    let groups = regex.captures_len();
+    let group = usize::try_from(group).ok().filter(|group| *group < groups).ok_or_else(|| {
+        error(&format!("REGEXP_EXTRACT group {} is out of range, the pattern has {} groups", group, groups - 1))
+    })?;
+
+    // no match, or a group which took no part in the match, is NULL
+    Ok(regex.captures(&value)
+        .and_then(|captures| captures.get(group))
+        .map(|matched| Value::String(matched.as_str().to_string()))
+        .unwrap_or(Value::Null))
+}
+
+// REGEXP_REPLACE(s, pattern, replacement) replaces every match, $1 in the replacement refers to the first group
+pub fn replace(args: &[ResolvedExpr], row: &[Value]) -> Result<Value, ExecutorError> {
+    let Some((value, regex)) = operands(args, row)? else { return Ok(Value::Null) };
+    match evaluate(&args[2], row)? {
+        Value::Null => Ok(Value::Null),
+        replacement => Ok(Value::String(regex.replace_all(&value, text(&replacement).as_str()).into_owned()))
+    }
+}
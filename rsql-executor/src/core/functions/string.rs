@@ -0,0 +1,140 @@
+use rsql_parser::ast::constructs::Value;
+use crate::core::errors::ExecutorError;
+use crate::core::evaluator::{evaluate, text};
+use crate::core::functions::{error, integer};
+use crate::core::plan::ResolvedExpr;
+
+// positions and lengths count characters, not bytes, and positions start at 1
+
+pub fn upper(args: &[Value]) -> Result<Value, ExecutorError> {
+    Ok(Value::String(text(&args[0]).to_uppercase()))
+}
+
+pub fn lower(args: &[Value]) -> Result<Value, ExecutorError> {
+    Ok(Value::String(text(&args[0]).to_lowercase()))
+}
+
+pub fn trim(args: &[Value]) -> Result<Value, ExecutorError> {
+    trim_sides(args, true, true)
+}
+
+pub fn ltrim(args: &[Value]) -> Result<Value, ExecutorError> {
+    trim_sides(args, true, false)
+}
+
+pub fn rtrim(args: &[Value]) -> Result<Value, ExecutorError> {
+    trim_sides(args, false, true)
+}
+
+// removes whitespace, or any of the characters given as the second argument
+fn trim_sides(args: &[Value], start: bool, end: bool) -> Result<Value, ExecutorError> {
+    let value = text(&args[0]);
+    let characters = args.get(1).map(text);
+    let trimmed = |c: char| match &characters {
+        Some(characters) => characters.contains(c),
+        None => c.is_whitespace()
+    };
+    let mut result = value.as_str();
+    if start {
+        result = result.trim_start_matches(trimmed);
+    }
+    if end {
+        result = result.trim_end_matches(trimmed);
+    }
+    Ok(Value::String(result.to_string()))
+}
+
+pub fn length(args: &[Value]) -> Result<Value, ExecutorError> {
+    Ok(Value::Int(text(&args[0]).chars().count() as i64))
+}
+
+// SUBSTRING(s, start[, length]), a start before the first character shortens the result like it does in Postgres
+pub fn substring(args: &[Value]) -> Result<Value, ExecutorError> {
+    let value: Vec<char> = text(&args[0]).chars().collect();
+    let start = integer(&args[1], "SUBSTRING")?;
+    let end = match args.get(2) {
+        Some(length) => {
+            let length = integer(length, "SUBSTRING")?;
+            if length < 0 {
+                return Err(error("SUBSTRING length can not be negative"))
+            }
+            start.saturating_add(length)
+        },
+        None => i64::MAX
+    };
+    let first = start.max(1) as usize - 1;
+    let last = (end.max(1) as usize - 1).min(value.len());
+    let result = if first < last { value[first..last].iter().collect() } else { String::new() };
+    Ok(Value::String(result))
+}
+
+pub fn replace(args: &[Value]) -> Result<Value, ExecutorError> {
+    let (value, from, to) = (text(&args[0]), text(&args[1]), text(&args[2]));
+    if from.is_empty() {
+        return Ok(Value::String(value))
+    }
+    Ok(Value::String(value.replace(&from, &to)))
+}
+
+// CONCAT skips NULL arguments instead of returning NULL as || does
+pub fn concat(args: &[ResolvedExpr], row: &[Value]) -> Result<Value, ExecutorError> {
+    let mut result = String::new();
+    for arg in args {
+        result.push_str(&text(&evaluate(arg, row)?));
+    }
+    Ok(Value::String(result))
+}
+
+// SPLIT_PART(s, delimiter, n) is the nth field, counted from the end when n is negative, empty when there is no such field
+pub fn split_part(args: &[Value]) -> Result<Value, ExecutorError> {
+    let (value, delimiter) = (text(&args[0]), text(&args[1]));
+    let field = integer(&args[2], "SPLIT_PART")?;
+    let fields: Vec<&str> = if delimiter.is_empty() { vec![value.as_str()] } else { value.split(delimiter.as_str()).collect() };
+    let index = match field {
+        0 => return Err(error("SPLIT_PART field position must not be zero")),
+        field if field > 0 => usize::try_from(field - 1).ok(),
+        field => fields.len().checked_sub(field.unsigned_abs() as usize)
+    };
+    let part = index.and_then(|index| fields.get(index)).copied().unwrap_or("");
+    Ok(Value::String(part.to_string()))
+}
+
+pub fn lpad(args: &[Value]) -> Result<Value, ExecutorError> {
+    pad(args, "LPAD", true)
+}
+
+pub fn rpad(args: &[Value]) -> Result<Value, ExecutorError> {
+    pad(args, "RPAD", false)
+}
+
+// characters LPAD and RPAD may pad to, a larger length would try to allocate it all at once
+const MAX_PAD_LENGTH: usize = 10_000_000;
+
+// fills the string up to the length with the fill characters (a space by default), longer strings are cut to the length
+fn pad(args: &[Value], function: &str, left: bool) -> Result<Value, ExecutorError> {
+    let value: Vec<char> = text(&args[0]).chars().collect();
+    let length = usize::try_from(integer(&args[1], function)?).unwrap_or(0);
+    if length > MAX_PAD_LENGTH {
+        return Err(error(&format!("{} length too large, at most {} characters", function, MAX_PAD_LENGTH)))
+    }
+    let fill: Vec<char> = args.get(2).map(text).unwrap_or_else(|| " ".to_string()).chars().collect();
+    if value.len() >= length || fill.is_empty() {
+        return Ok(Value::String(value.iter().take(length).collect()))
+    }
+    let padding = fill.iter().cycle().take(length - value.len());
+    let result = if left { padding.chain(value.iter()).collect() } else { value.iter().chain(padding).collect() };
+    Ok(Value::String(result))
+}
+
+pub fn starts_with(args: &[Value]) -> Result<Value, ExecutorError> {
+    Ok(Value::Bool(text(&args[0]).starts_with(text(&args[1]).as_str())))
+}
+
+// POSITION(substring IN s), 0 when the substring does not occur
+pub fn position(args: &[Value]) -> Result<Value, ExecutorError> {
+    let (substring, value) = (text(&args[0]), text(&args[1]));
+    let position = value.find(substring.as_str())
+        .map(|index| value[..index].chars().count() as i64 + 1)
+        .unwrap_or(0);
+    Ok(Value::Int(position))
+}
@@ -0,0 +1,377 @@
+use std::cmp::Ordering;
+use std::collections::{HashMap, VecDeque};
+use std::rc::Rc;
+use rsql_parser::ast::constructs::{JoinKind, Value};
+use crate::core::aggregate::{GroupKey, KeyValue};
+use crate::core::errors::ExecutorError;
+use crate::core::evaluator::{compare, evaluate, is_true};
+use crate::core::executor::RowIterator;
+use crate::core::metrics::{row_size, OperatorMetrics};
+use crate::core::plan::{JoinSide, ResolvedExpr};
+
+// the buffered input of a join, with whether each of its rows found a partner
+pub struct JoinTable {
+    build: JoinSide,
+    rows: Vec<Vec<Value>>,
+    matched: Vec<bool>,
+    // positions of the rows by the values of their keys, without keys every row is a candidate
+    keys: Vec<ResolvedExpr>,
+    probe_keys: Vec<ResolvedExpr>,
+    index: HashMap<GroupKey, Vec<usize>>,
+    memory: usize
+}
+
+impl JoinTable {
+    // `on` pairs the keys of the left row with those of the right row
+    pub fn new(build: JoinSide, on: &[(ResolvedExpr, ResolvedExpr)]) -> Self {
+        let (left, right): (Vec<ResolvedExpr>, Vec<ResolvedExpr>) = on.iter().cloned().unzip();
+        let (keys, probe_keys) = if build == JoinSide::Left { (left, right) } else { (right, left) };
+        JoinTable { build, rows: Vec::new(), matched: Vec::new(), keys, probe_keys, index: HashMap::new(), memory: 0 }
+    }
+
+    pub fn insert(&mut self, row: Vec<Value>) -> Result<(), ExecutorError> {
+        if !self.keys.is_empty() {
+            // a NULL key equals nothing, the row can only come out unmatched
+            if let Some(key) = key_of(&self.keys, &row)? {
+                self.index.entry(key).or_default().push(self.rows.len());
+            }
+        }
+        self.memory += row_size(&row) + std::mem::size_of::<usize>();
+        self.matched.push(false);
+        self.rows.push(row);
+        Ok(())
+    }
+
+    pub fn memory(&self) -> usize {
+        self.memory
+    }
+
+    fn candidates(&self, probe: &[Value]) -> Result<Vec<usize>, ExecutorError> {
+        if self.keys.is_empty() {
+            return Ok((0..self.rows.len()).collect())
+        }
+        Ok(key_of(&self.probe_keys, probe)?.and_then(|key| self.index.get(&key).cloned()).unwrap_or_default())
+    }
+}
+
+fn key_of(keys: &[ResolvedExpr], row: &[Value]) -> Result<Option<GroupKey>, ExecutorError> {
+    let mut key = Vec::with_capacity(keys.len());
+    for expr in keys {
+        match evaluate(expr, row)? {
+            Value::Null => return Ok(None),
+            value => key.push(KeyValue::from(&value))
+        }
+    }
+    Ok(Some(key))
+}
+
+// streams the other input of a join through the table, rows of an outer side without a partner come out padded
+// with NULLs: those of the streamed input right away, those of the table once the stream has ended
+pub struct JoinStream {
+    probe: RowIterator,
+    table: JoinTable,
+    // over the joined row
+    condition: Option<ResolvedExpr>,
+    kind: JoinKind,
+    keep_probe: bool,
+    keep_build: bool,
+    widths: (usize, usize),
+    pending: VecDeque<Vec<Value>>,
+    // position in the table while its unmatched rows come out
+    unmatched: Option<usize>,
+    metrics: Rc<OperatorMetrics>
+}
+
+impl JoinStream {
+    pub fn new(
+        probe: RowIterator,
+        table: JoinTable,
+        kind: JoinKind,
+        condition: Option<ResolvedExpr>,
+        widths: (usize, usize),
+        metrics: Rc<OperatorMetrics>
+    ) -> Self {
+        let keeps = |side: JoinSide| matches!((kind, side), (JoinKind::Full, _) | (JoinKind::Left, JoinSide::Left) | (JoinKind::Right, JoinSide::Right));
+        let probe_side = match table.build {
+            JoinSide::Left => JoinSide::Right,
+            JoinSide::Right => JoinSide::Left
+        };
+        JoinStream {
+            probe,
+            keep_probe: keeps(probe_side),
+            keep_build: keeps(table.build),
+            table,
+            condition,
+            kind,
+            widths,
+            pending: VecDeque::new(),
+            unmatched: None,
+            metrics
+        }
+    }
+
+    // the output row, the left row first; a missing row is padded with NULLs
+    fn joined(&self, probe: Option<&[Value]>, build: Option<&[Value]>) -> Vec<Value> {
+        let (left, right) = match self.table.build {
+            JoinSide::Left => (build, probe),
+            JoinSide::Right => (probe, build)
+        };
+        let mut row = Vec::with_capacity(self.widths.0 + self.widths.1);
+        match left {
+            Some(left) => row.extend_from_slice(left),
+            None => row.resize(self.widths.0, Value::Null)
+        }
+        match right {
+            Some(right) => row.extend_from_slice(right),
+            None => row.resize(self.widths.0 + self.widths.1, Value::Null)
+        }
+        row
+    }
+
+    fn probe(&mut self, row: Vec<Value>) -> Result<(), ExecutorError> {
+        let mut found = false;
+        for position in self.table.candidates(&row)? {
+            let joined = self.joined(Some(&row), Some(&self.table.rows[position]));
+            let matches = match &self.condition {
+                Some(condition) => is_true(&evaluate(condition, &joined)?),
+                None => true
+            };
+            if matches {
+                found = true;
+                if matches!(self.kind, JoinKind::Semi | JoinKind::Anti) {
+                    break
+                }
+                self.table.matched[position] = true;
+                self.pending.push_back(joined);
+            }
+        }
+        match self.kind {
+            // the streamed rows are the left ones, see PhysicalPlan::from_logical
+            JoinKind::Semi | JoinKind::Anti => if found == (self.kind == JoinKind::Semi) {
+                self.pending.push_back(row);
+            },
+            _ => if !found && self.keep_probe {
+                let joined = self.joined(Some(&row), None);
+                self.pending.push_back(joined);
+            }
+        }
+        Ok(())
+    }
+
+    fn next_unmatched(&mut self, start: usize) -> Option<Vec<Value>> {
+        let position = (start..self.table.rows.len()).find(|position| !self.table.matched[*position]);
+        self.unmatched = Some(position.map_or(self.table.rows.len(), |position| position + 1));
+        position.map(|position| self.joined(None, Some(&self.table.rows[position])))
+    }
+}
+
+impl Iterator for JoinStream {
+    type Item = Result<Vec<Value>, ExecutorError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(row) = self.pending.pop_front() {
+                return Some(Ok(row))
+            }
+            if let Some(start) = self.unmatched {
+                return self.next_unmatched(start).map(Ok)
+            }
+            match self.probe.next() {
+                Some(Ok(row)) => {
+                    let metrics = Rc::clone(&self.metrics);
+                    if let Err(e) = metrics.time_eval(|| self.probe(row)) {
+                        return Some(Err(e))
+                    }
+                },
+                Some(Err(e)) => return Some(Err(e)),
+                None if self.keep_build => self.unmatched = Some(0),
+                None => return None
+            }
+        }
+    }
+}
+
+// one input of a merge join, checked to come in ascending order of its key; rows with a NULL key may come anywhere
+struct SortedInput {
+    rows: RowIterator,
+    key: ResolvedExpr,
+    last: Option<Value>,
+    peeked: Option<(Value, Vec<Value>)>
+}
+
+impl SortedInput {
+    fn new(rows: RowIterator, key: ResolvedExpr) -> Self {
+        SortedInput { rows, key, last: None, peeked: None }
+    }
+
+    fn peek(&mut self) -> Result<Option<&Value>, ExecutorError> {
+        if self.peeked.is_none() && let Some(row) = self.rows.next() {
+            let row = row?;
+            let key = evaluate(&self.key, &row)?;
+            if !matches!(key, Value::Null) {
+                if let Some(last) = &self.last && compare(last, &key)? == Ordering::Greater {
+                    return Err(ExecutorError {
+                        message: format!("input of the merge join is not sorted on {}: {} came after {}", self.key, key, last)
+                    })
+                }
+                self.last = Some(key.clone());
+            }
+            self.peeked = Some((key, row));
+        }
+        Ok(self.peeked.as_ref().map(|(key, _)| key))
+    }
+
+    fn take(&mut self) -> Option<Vec<Value>> {
+        self.peeked.take().map(|(_, row)| row)
+    }
+}
+
+// joins two inputs sorted on a pair of keys by walking both at once, only the right rows of the current key are
+// held in memory; rows of an outer side without a partner come out padded with NULLs
+pub struct MergeJoinStream {
+    left: SortedInput,
+    right: SortedInput,
+    // over the joined row
+    condition: Option<ResolvedExpr>,
+    keep_left: bool,
+    keep_right: bool,
+    widths: (usize, usize),
+    pending: VecDeque<Vec<Value>>,
+    done: bool,
+    metrics: Rc<OperatorMetrics>
+}
+
+impl MergeJoinStream {
+    pub fn new(
+        (left, right): (RowIterator, RowIterator),
+        (left_key, right_key): (ResolvedExpr, ResolvedExpr),
+        kind: JoinKind,
+        condition: Option<ResolvedExpr>,
+        widths: (usize, usize),
+        metrics: Rc<OperatorMetrics>
+    ) -> Self {
+        MergeJoinStream {
+            left: SortedInput::new(left, left_key),
+            right: SortedInput::new(right, right_key),
+            condition,
+            keep_left: matches!(kind, JoinKind::Left | JoinKind::Full),
+            keep_right: matches!(kind, JoinKind::Right | JoinKind::Full),
+            widths,
+            pending: VecDeque::new(),
+            done: false,
+            metrics
+        }
+    }
+
+    fn joined(&self, left: Option<&[Value]>, right: Option<&[Value]>) -> Vec<Value> {
+        let mut row = Vec::with_capacity(self.widths.0 + self.widths.1);
+        match left {
+            Some(left) => row.extend_from_slice(left),
+            None => row.resize(self.widths.0, Value::Null)
+        }
+        match right {
+            Some(right) => row.extend_from_slice(right),
+            None => row.resize(self.widths.0 + self.widths.1, Value::Null)
+        }
+        row
+    }
+
+    fn unmatched(&mut self, side: JoinSide) {
+        let (row, keep) = match side {
+            JoinSide::Left => (self.left.take(), self.keep_left),
+            JoinSide::Right => (self.right.take(), self.keep_right)
+        };
+        if let Some(row) = row && keep {
+            let joined = match side {
+                JoinSide::Left => self.joined(Some(&row), None),
+                JoinSide::Right => self.joined(None, Some(&row))
+            };
+            self.pending.push_back(joined);
+        }
+    }
+
+    // moves past the next key of either input, false once both have ended
+    fn step(&mut self) -> Result<bool, ExecutorError> {
+        let left = self.left.peek()?.cloned();
+        let right = self.right.peek()?.cloned();
+        let (left, right) = match (left, right) {
+            (None, None) => return Ok(false),
+            (Some(_), None) | (Some(Value::Null), _) => {
+                self.unmatched(JoinSide::Left);
+                return Ok(true)
+            },
+            (None, Some(_)) | (_, Some(Value::Null)) => {
+                self.unmatched(JoinSide::Right);
+                return Ok(true)
+            },
+            (Some(left), Some(right)) => (left, right)
+        };
+        match compare(&left, &right)? {
+            Ordering::Less => self.unmatched(JoinSide::Left),
+            Ordering::Greater => self.unmatched(JoinSide::Right),
+            Ordering::Equal => self.merge_group(&right)?
+        }
+        Ok(true)
+    }
+
+    // pairs the left rows of the key with the right ones, which are buffered
+    fn merge_group(&mut self, key: &Value) -> Result<(), ExecutorError> {
+        let mut group = Vec::new();
+        while let Some(next) = self.right.peek()? && !matches!(next, Value::Null) && compare(next, key)? == Ordering::Equal {
+            let row = self.right.take().expect("peeked row is present");
+            self.metrics.grow_memory(row_size(&row));
+            group.push((row, false));
+        }
+        while let Some(next) = self.left.peek()? && !matches!(next, Value::Null) && compare(next, key)? == Ordering::Equal {
+            let row = self.left.take().expect("peeked row is present");
+            let mut found = false;
+            for (right, matched) in group.iter_mut() {
+                let joined = self.joined(Some(&row), Some(right));
+                let matches = match &self.condition {
+                    Some(condition) => is_true(&evaluate(condition, &joined)?),
+                    None => true
+                };
+                if matches {
+                    found = true;
+                    *matched = true;
+                    self.pending.push_back(joined);
+                }
+            }
+            if !found && self.keep_left {
+                let joined = self.joined(Some(&row), None);
+                self.pending.push_back(joined);
+            }
+        }
+        if self.keep_right {
+            for (right, _) in group.iter().filter(|(_, matched)| !matched) {
+                let joined = self.joined(None, Some(right));
+                self.pending.push_back(joined);
+            }
+        }
+        self.metrics.release_memory();
+        Ok(())
+    }
+}
+
+impl Iterator for MergeJoinStream {
+    type Item = Result<Vec<Value>, ExecutorError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(row) = self.pending.pop_front() {
+                return Some(Ok(row))
+            }
+            if self.done {
+                return None
+            }
+            let metrics = Rc::clone(&self.metrics);
+            match metrics.time_eval(|| self.step()) {
+                Ok(more) => self.done = !more,
+                Err(e) => {
+                    self.done = true;
+                    return Some(Err(e))
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,82 @@
+use std::cell::Cell;
+use std::time::{Duration, Instant};
+use rsql_parser::ast::constructs::Value;
+
+// counters of one operator, shared between its running iterator and the EXPLAIN ANALYZE report
+#[derive(Debug, Default)]
+pub struct OperatorMetrics {
+    rows: Cell<u64>,
+    bytes_read: Cell<u64>,
+    // reading records and converting their cells
+    parse_time: Cell<Duration>,
+    // evaluating expressions, aggregating and sorting
+    eval_time: Cell<Duration>,
+    memory: Cell<usize>,
+    peak_memory: Cell<usize>
+}
+
+impl OperatorMetrics {
+    pub fn add_row(&self) {
+        self.rows.set(self.rows.get() + 1);
+    }
+
+    pub fn set_bytes_read(&self, bytes: u64) {
+        self.bytes_read.set(bytes);
+    }
+
+    pub fn add_parse_time(&self, elapsed: Duration) {
+        self.parse_time.set(self.parse_time.get() + elapsed);
+    }
+
+    pub fn time_eval<T>(&self, work: impl FnOnce() -> T) -> T {
+        let started = Instant::now();
+        let result = work();
+        self.eval_time.set(self.eval_time.get() + started.elapsed());
+        result
+    }
+
+    // memory held by rows an operator buffers, such as the input of a sort
+    pub fn grow_memory(&self, bytes: usize) {
+        self.memory.set(self.memory.get() + bytes);
+        self.peak_memory.set(self.peak_memory.get().max(self.memory.get()));
+    }
+
+    pub fn release_memory(&self) {
+        self.memory.set(0);
+    }
+
+    pub fn rows(&self) -> u64 {
+        self.rows.get()
+    }
+
+    pub fn bytes_read(&self) -> u64 {
+        self.bytes_read.get()
+    }
+
+    pub fn parse_time(&self) -> Duration {
+        self.parse_time.get()
+    }
+
+    pub fn eval_time(&self) -> Duration {
+        self.eval_time.get()
+    }
+
+    pub fn peak_memory(&self) -> usize {
+        self.peak_memory.get()
+    }
+}
+
+// estimated heap and inline size of a row, used for the memory figures of EXPLAIN ANALYZE
+pub fn row_size(values: &[Value]) -> usize {
+    values.iter()
+        .map(|value| std::mem::size_of::<Value>() + match value {
+            Value::String(s) => s.capacity(),
+            Value::List(values) => row_size(values),
+            _ => 0
+        })
+        .sum()
+}
+
+pub fn format_duration(duration: Duration) -> String {
+    format!("{:.3}ms", duration.as_secs_f64() * 1000.0)
+}
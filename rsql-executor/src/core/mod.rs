@@ -1,3 +1,18 @@
 pub mod executor;
 pub mod dataframe;
-pub mod errors;
\ No newline at end of file
+pub mod errors;
+pub mod schema;
+pub mod plan;
+pub mod analyzer;
+pub mod evaluator;
+pub mod aggregate;
+pub mod sketch;
+pub mod join;
+pub mod sort;
+pub mod window;
+pub mod optimizer;
+pub mod physical;
+pub mod metrics;
+pub mod functions;
+pub mod settings;
+pub mod cast;
@@ -0,0 +1,359 @@
+use rsql_parser::ast::constructs::{BinaryOperator, JoinKind, UnaryOperator, Value};
+use crate::core::evaluator::evaluate;
+use crate::core::plan::{conjunction, side, JoinSide, LogicalPlan, ResolvedExpr};
+use crate::core::schema::DataType;
+
+// a rewrite of the logical plan which keeps the result of the query the same
+pub trait OptimizerRule {
+    fn name(&self) -> &'static str;
+    fn apply(&self, plan: LogicalPlan) -> LogicalPlan;
+}
+
+pub struct OptimizedPlan {
+    pub plan: LogicalPlan,
+    // names of the rules which changed the plan, in the order they ran
+    pub applied_rules: Vec<&'static str>
+}
+
+pub struct Optimizer {
+    rules: Vec<Box<dyn OptimizerRule>>
+}
+
+impl Default for Optimizer {
+    fn default() -> Self {
+        Optimizer {
+            rules: vec![
+                Box::new(ConstantFolding),
+                Box::new(SimplifyBooleans),
+                Box::new(PredicatePushdown),
+                Box::new(ProjectionPruning)
+            ]
+        }
+    }
+}
+
+impl Optimizer {
+    pub fn new(rules: Vec<Box<dyn OptimizerRule>>) -> Self {
+        Optimizer { rules }
+    }
+
+    pub fn optimize(&self, plan: LogicalPlan) -> OptimizedPlan {
+        let mut plan = plan;
+        let mut applied_rules = Vec::new();
+        for rule in &self.rules {
+            let optimized = rule.apply(plan.clone());
+            if optimized != plan {
+                applied_rules.push(rule.name());
+                plan = optimized;
+            }
+        }
+
+        OptimizedPlan { plan, applied_rules }
+    }
+}
+
+// evaluates operations on literals once, instead of once per row
+pub struct ConstantFolding;
+
+impl OptimizerRule for ConstantFolding {
+    fn name(&self) -> &'static str {
+        "constant_folding"
+    }
+
+    fn apply(&self, plan: LogicalPlan) -> LogicalPlan {
+        plan.transform_exprs(&fold)
+    }
+}
+
+fn fold(expr: ResolvedExpr) -> ResolvedExpr {
+    let constant = match &expr {
+        ResolvedExpr::BinaryOp { left, right, .. } => is_literal(left) && is_literal(right),
+        ResolvedExpr::UnaryOp { expr, .. } | ResolvedExpr::Cast { expr, .. } => is_literal(expr),
+        _ => false
+    };
+    // errors such as a division by zero are left to be raised when the query runs
+    match evaluate(&expr, &[]) {
+        Ok(value) if constant => ResolvedExpr::Literal(value),
+        _ => expr
+    }
+}
+
+fn is_literal(expr: &ResolvedExpr) -> bool {
+    matches!(expr, ResolvedExpr::Literal(_))
+}
+
+// removes boolean operations whose result is known from one operand, and pushes NOT into the negatable predicates
+pub struct SimplifyBooleans;
+
+impl OptimizerRule for SimplifyBooleans {
+    fn name(&self) -> &'static str {
+        "simplify_booleans"
+    }
+
+    fn apply(&self, plan: LogicalPlan) -> LogicalPlan {
+        remove_true_filters(plan.transform_exprs(&simplify))
+    }
+}
+
+fn simplify(expr: ResolvedExpr) -> ResolvedExpr {
+    match expr {
+        ResolvedExpr::BinaryOp { left, operator: BinaryOperator::And, right, data_type } => {
+            match (*left, *right) {
+                (ResolvedExpr::Literal(Value::Bool(false)), _) | (_, ResolvedExpr::Literal(Value::Bool(false))) => {
+                    ResolvedExpr::Literal(Value::Bool(false))
+                },
+                (ResolvedExpr::Literal(Value::Bool(true)), other) | (other, ResolvedExpr::Literal(Value::Bool(true))) => other,
+                (left, right) => ResolvedExpr::BinaryOp {
+                    left: Box::new(left), operator: BinaryOperator::And, right: Box::new(right), data_type
+                }
+            }
+        },
+        ResolvedExpr::BinaryOp { left, operator: BinaryOperator::Or, right, data_type } => {
+            match (*left, *right) {
+                (ResolvedExpr::Literal(Value::Bool(true)), _) | (_, ResolvedExpr::Literal(Value::Bool(true))) => {
+                    ResolvedExpr::Literal(Value::Bool(true))
+                },
+                (ResolvedExpr::Literal(Value::Bool(false)), other) | (other, ResolvedExpr::Literal(Value::Bool(false))) => other,
+                (left, right) => ResolvedExpr::BinaryOp {
+                    left: Box::new(left), operator: BinaryOperator::Or, right: Box::new(right), data_type
+                }
+            }
+        },
+        ResolvedExpr::UnaryOp { operator: UnaryOperator::Not, expr, data_type } => match *expr {
+            ResolvedExpr::UnaryOp { operator: UnaryOperator::Not, expr, .. } => *expr,
+            // NOT a < b is a >= b, which also holds for NULL operands
+            ResolvedExpr::BinaryOp { left, operator, right, data_type } if negate(&operator).is_some() => {
+                ResolvedExpr::BinaryOp { left, operator: negate(&operator).unwrap(), right, data_type }
+            },
+            ResolvedExpr::InList { expr, list, negated } => ResolvedExpr::InList { expr, list, negated: !negated },
+            ResolvedExpr::Between { expr, low, high, negated } => ResolvedExpr::Between { expr, low, high, negated: !negated },
+            ResolvedExpr::Like { expr, pattern, case_insensitive, negated } => {
+                ResolvedExpr::Like { expr, pattern, case_insensitive, negated: !negated }
+            },
+            ResolvedExpr::IsNull { expr, negated } => ResolvedExpr::IsNull { expr, negated: !negated },
+            ResolvedExpr::Regexp { expr, pattern, negated } => ResolvedExpr::Regexp { expr, pattern, negated: !negated },
+            expr => ResolvedExpr::UnaryOp { operator: UnaryOperator::Not, expr: Box::new(expr), data_type }
+        },
+        expr => expr
+    }
+}
+
+fn negate(operator: &BinaryOperator) -> Option<BinaryOperator> {
+    match operator {
+        BinaryOperator::Equals => Some(BinaryOperator::NotEquals),
+        BinaryOperator::NotEquals => Some(BinaryOperator::Equals),
+        BinaryOperator::GreaterThan => Some(BinaryOperator::LessThanOrEquals),
+        BinaryOperator::LessThanOrEquals => Some(BinaryOperator::GreaterThan),
+        BinaryOperator::LessThan => Some(BinaryOperator::GreaterThanOrEquals),
+        BinaryOperator::GreaterThanOrEquals => Some(BinaryOperator::LessThan),
+        _ => None
+    }
+}
+
+fn remove_true_filters(plan: LogicalPlan) -> LogicalPlan {
+    map_inputs(plan, &|plan| match plan {
+        LogicalPlan::Filter { input, predicate: ResolvedExpr::Literal(Value::Bool(true)) } => *input,
+        LogicalPlan::Scan { source, schema, projection, filter: Some(ResolvedExpr::Literal(Value::Bool(true))) } => {
+            LogicalPlan::Scan { source, schema, projection, filter: None }
+        },
+        plan => plan
+    })
+}
+
+// moves filters into the scan, so rows are dropped while they are read; predicates over one side of a join are
+// moved below it where that side is not padded with NULLs
+pub struct PredicatePushdown;
+
+impl OptimizerRule for PredicatePushdown {
+    fn name(&self) -> &'static str {
+        "predicate_pushdown"
+    }
+
+    fn apply(&self, plan: LogicalPlan) -> LogicalPlan {
+        map_inputs(plan, &|plan| match plan {
+            LogicalPlan::Filter { input, predicate } => push_filter(*input, predicate),
+            LogicalPlan::Join { left, right, kind, condition: Some(condition), schema } => {
+                // the condition only decides which rows of a side match, unless the side is kept without a match
+                let pushable = (
+                    matches!(kind, JoinKind::Inner | JoinKind::Right | JoinKind::Semi),
+                    matches!(kind, JoinKind::Inner | JoinKind::Left | JoinKind::Semi | JoinKind::Anti)
+                );
+                let (left, right, condition) = push_into_join(*left, *right, condition, pushable);
+                LogicalPlan::Join { left: Box::new(left), right: Box::new(right), kind, condition, schema }
+            },
+            plan => plan
+        })
+    }
+}
+
+fn push_filter(input: LogicalPlan, predicate: ResolvedExpr) -> LogicalPlan {
+    match input {
+        LogicalPlan::Scan { source, schema, projection, filter } => LogicalPlan::Scan {
+            source,
+            schema,
+            projection,
+            filter: Some(match filter {
+                Some(filter) => and(filter, predicate),
+                None => predicate
+            })
+        },
+        LogicalPlan::Join { left, right, kind, condition, schema } => {
+            // a side padded with NULLs gets those rows only in the join
+            let pushable = (
+                matches!(kind, JoinKind::Inner | JoinKind::Cross | JoinKind::Left | JoinKind::Semi | JoinKind::Anti),
+                matches!(kind, JoinKind::Inner | JoinKind::Cross | JoinKind::Right)
+            );
+            let (left, right, rest) = push_into_join(*left, *right, predicate, pushable);
+            let join = LogicalPlan::Join { left: Box::new(left), right: Box::new(right), kind, condition, schema };
+            match rest {
+                Some(predicate) => LogicalPlan::Filter { input: Box::new(join), predicate },
+                None => join
+            }
+        },
+        input => LogicalPlan::Filter { input: Box::new(input), predicate }
+    }
+}
+
+// moves the parts of the predicate which read one side only into that side where `pushable` allows it, the
+// rest of the predicate is returned
+fn push_into_join(
+    left: LogicalPlan,
+    right: LogicalPlan,
+    predicate: ResolvedExpr,
+    pushable: (bool, bool)
+) -> (LogicalPlan, LogicalPlan, Option<ResolvedExpr>) {
+    let width = left.schema().len();
+    let (mut to_left, mut to_right, mut rest) = (Vec::new(), Vec::new(), Vec::new());
+    for conjunct in predicate.conjuncts() {
+        match side(&conjunct, width) {
+            Some(JoinSide::Left) if pushable.0 => to_left.push(conjunct),
+            Some(JoinSide::Right) if pushable.1 => to_right.push(conjunct.shift_columns(width)),
+            _ => rest.push(conjunct)
+        }
+    }
+    let push = |input: LogicalPlan, predicates: Vec<ResolvedExpr>| match conjunction(predicates) {
+        Some(predicate) => push_filter(input, predicate),
+        None => input
+    };
+
+    (push(left, to_left), push(right, to_right), conjunction(rest))
+}
+
+fn and(left: ResolvedExpr, right: ResolvedExpr) -> ResolvedExpr {
+    ResolvedExpr::BinaryOp {
+        left: Box::new(left),
+        operator: BinaryOperator::And,
+        right: Box::new(right),
+        data_type: DataType::Bool
+    }
+}
+
+// limits the scan to the columns the query reads, the others are never converted
+pub struct ProjectionPruning;
+
+impl OptimizerRule for ProjectionPruning {
+    fn name(&self) -> &'static str {
+        "projection_pruning"
+    }
+
+    fn apply(&self, plan: LogicalPlan) -> LogicalPlan {
+        map_inputs(plan, &|plan| {
+            let mut columns = match &plan {
+                LogicalPlan::Project { input, exprs, .. } if is_scan(input) => {
+                    exprs.iter().flat_map(|expr| expr.columns()).collect::<Vec<usize>>()
+                },
+                LogicalPlan::Aggregate { input, group_by, aggregates, .. } if is_scan(input) => {
+                    group_by.iter().flat_map(|expr| expr.columns())
+                        .chain(aggregates.iter().flat_map(|call| call.columns()))
+                        .collect()
+                },
+                _ => return plan
+            };
+            columns.sort();
+            columns.dedup();
+            with_scan_projection(plan, columns)
+        })
+    }
+}
+
+fn is_scan(plan: &LogicalPlan) -> bool {
+    matches!(plan, LogicalPlan::Scan { .. })
+}
+
+fn with_scan_projection(plan: LogicalPlan, columns: Vec<usize>) -> LogicalPlan {
+    let prune = |input: Box<LogicalPlan>| match *input {
+        LogicalPlan::Scan { source, schema, projection, filter } => {
+            let projection = if columns.len() < schema.len() { Some(columns.clone()) } else { projection };
+            Box::new(LogicalPlan::Scan { source, schema, projection, filter })
+        },
+        input => Box::new(input)
+    };
+    match plan {
+        LogicalPlan::Project { input, exprs, schema } => LogicalPlan::Project { input: prune(input), exprs, schema },
+        LogicalPlan::Aggregate { input, group_by, grouping_sets, aggregates, schema } => {
+            LogicalPlan::Aggregate { input: prune(input), group_by, grouping_sets, aggregates, schema }
+        },
+        plan => plan
+    }
+}
+
+// rewrites the inputs of the node first and then the node itself
+fn map_inputs(plan: LogicalPlan, rewrite: &impl Fn(LogicalPlan) -> LogicalPlan) -> LogicalPlan {
+    let plan = match plan {
+        LogicalPlan::Scan { .. } => plan,
+        LogicalPlan::Filter { input, predicate } => {
+            LogicalPlan::Filter { input: Box::new(map_inputs(*input, rewrite)), predicate }
+        },
+        LogicalPlan::Project { input, exprs, schema } => {
+            LogicalPlan::Project { input: Box::new(map_inputs(*input, rewrite)), exprs, schema }
+        },
+        LogicalPlan::Aggregate { input, group_by, grouping_sets, aggregates, schema } => {
+            LogicalPlan::Aggregate { input: Box::new(map_inputs(*input, rewrite)), group_by, grouping_sets, aggregates, schema }
+        },
+        LogicalPlan::Sort { input, keys } => {
+            LogicalPlan::Sort { input: Box::new(map_inputs(*input, rewrite)), keys }
+        },
+        LogicalPlan::Distinct { input } => {
+            LogicalPlan::Distinct { input: Box::new(map_inputs(*input, rewrite)) }
+        },
+        LogicalPlan::SingleRow { input } => {
+            LogicalPlan::SingleRow { input: Box::new(map_inputs(*input, rewrite)) }
+        },
+        LogicalPlan::Join { left, right, kind, condition, schema } => LogicalPlan::Join {
+            left: Box::new(map_inputs(*left, rewrite)),
+            right: Box::new(map_inputs(*right, rewrite)),
+            kind,
+            condition,
+            schema
+        },
+        LogicalPlan::With { ctes, input } => LogicalPlan::With {
+            ctes: ctes.into_iter().map(|(name, plan)| (name, map_inputs(plan, rewrite))).collect(),
+            input: Box::new(map_inputs(*input, rewrite))
+        },
+        LogicalPlan::CteScan { .. } => plan,
+        LogicalPlan::RecursiveCte { name, anchor, step, distinct, schema } => LogicalPlan::RecursiveCte {
+            name,
+            anchor: Box::new(map_inputs(*anchor, rewrite)),
+            step: Box::new(map_inputs(*step, rewrite)),
+            distinct,
+            schema
+        },
+        LogicalPlan::SetOperation { left, right, operator, all, schema } => LogicalPlan::SetOperation {
+            left: Box::new(map_inputs(*left, rewrite)),
+            right: Box::new(map_inputs(*right, rewrite)),
+            operator,
+            all,
+            schema
+        },
+        LogicalPlan::Limit { input, count } => {
+            LogicalPlan::Limit { input: Box::new(map_inputs(*input, rewrite)), count }
+        },
+        LogicalPlan::Window { input, calls, schema } => {
+            LogicalPlan::Window { input: Box::new(map_inputs(*input, rewrite)), calls, schema }
+        },
+        LogicalPlan::Unpivot { input, columns, include_nulls, schema } => {
+            LogicalPlan::Unpivot { input: Box::new(map_inputs(*input, rewrite)), columns, include_nulls, schema }
+        }
+    };
+    rewrite(plan)
+}
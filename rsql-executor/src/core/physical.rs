@@ -0,0 +1,379 @@
+use std::fmt::Display;
+use rsql_parser::ast::constructs::{BinaryOperator, JoinKind, SetOperator};
+use crate::core::plan::{conjunction, describe_sets, join, side, AggregateCall, JoinSide, LogicalPlan, ResolvedExpr, SortKey, WindowCall};
+use crate::core::schema::DataType;
+use crate::core::settings::Settings;
+
+// how the logical plan is carried out, each node is an operator of the executor
+#[derive(Debug, Clone, PartialEq)]
+pub enum PhysicalPlan {
+    FileScan {
+        source: String,
+        projection: Option<Vec<usize>>,
+        filter: Option<ResolvedExpr>
+    },
+    Filter {
+        input: Box<PhysicalPlan>,
+        predicate: ResolvedExpr
+    },
+    Projection {
+        input: Box<PhysicalPlan>,
+        exprs: Vec<ResolvedExpr>
+    },
+    HashAggregate {
+        input: Box<PhysicalPlan>,
+        group_by: Vec<ResolvedExpr>,
+        grouping_sets: Vec<Vec<usize>>,
+        aggregates: Vec<AggregateCall>
+    },
+    // buffers its input in memory, beyond the memory budget in sorted runs on disk
+    Sort {
+        input: Box<PhysicalPlan>,
+        keys: Vec<SortKey>
+    },
+    // streams the first occurrence of each row, remembering the rows seen so far
+    HashDistinct {
+        input: Box<PhysicalPlan>
+    },
+    // passes on the only row of its input, fails on a second one
+    SingleRow {
+        input: Box<PhysicalPlan>
+    },
+    // buffers the build input in a hash table on its keys and streams the other input through it
+    HashJoin {
+        left: Box<PhysicalPlan>,
+        right: Box<PhysicalPlan>,
+        kind: JoinKind,
+        // equal keys, the left one over the left row and the right one over the right row
+        on: Vec<(ResolvedExpr, ResolvedExpr)>,
+        // the rest of the condition, over the joined row
+        filter: Option<ResolvedExpr>,
+        build: JoinSide,
+        // number of columns of the left and the right rows
+        widths: (usize, usize)
+    },
+    // walks both inputs in the order of their keys, which must be ascending, holding only the right rows of the
+    // current key
+    SortMergeJoin {
+        left: Box<PhysicalPlan>,
+        right: Box<PhysicalPlan>,
+        kind: JoinKind,
+        // the left key over the left row and the right key over the right row
+        on: (ResolvedExpr, ResolvedExpr),
+        filter: Option<ResolvedExpr>,
+        widths: (usize, usize)
+    },
+    // buffers the right input and compares each left row with all of its rows, for conditions without equal keys
+    NestedLoopJoin {
+        left: Box<PhysicalPlan>,
+        right: Box<PhysicalPlan>,
+        kind: JoinKind,
+        condition: Option<ResolvedExpr>,
+        widths: (usize, usize)
+    },
+    // collects the rows of each common table expression in memory before running the input
+    Materialize {
+        ctes: Vec<(String, PhysicalPlan)>,
+        input: Box<PhysicalPlan>
+    },
+    CteScan {
+        name: String
+    },
+    // runs the step over the rows it added last until it adds none, all rows are held in memory
+    RecursiveCte {
+        name: String,
+        anchor: Box<PhysicalPlan>,
+        step: Box<PhysicalPlan>,
+        distinct: bool
+    },
+    // UNION ALL streams the left rows and then the right ones, INTERSECT and EXCEPT count the right rows in a
+    // hash table and stream the left rows through it
+    SetOperation {
+        left: Box<PhysicalPlan>,
+        right: Box<PhysicalPlan>,
+        operator: SetOperator,
+        all: bool
+    },
+    // stops reading its input after `count` rows
+    Limit {
+        input: Box<PhysicalPlan>,
+        count: u64
+    },
+    // buffers its input in memory, splits it into the partitions of each call and sorts each of them before
+    // computing the value of every row
+    Window {
+        input: Box<PhysicalPlan>,
+        calls: Vec<WindowCall>
+    },
+    // streams a row for each unpivoted column of each input row, `names` are the values of the name column
+    Unpivot {
+        input: Box<PhysicalPlan>,
+        columns: Vec<usize>,
+        names: Vec<String>,
+        include_nulls: bool
+    }
+}
+
+impl PhysicalPlan {
+    pub fn from_logical(plan: &LogicalPlan, settings: &Settings) -> PhysicalPlan {
+        match plan {
+            LogicalPlan::Scan { source, projection, filter, .. } => PhysicalPlan::FileScan {
+                source: source.clone(),
+                projection: projection.clone(),
+                filter: filter.clone()
+            },
+            LogicalPlan::Filter { input, predicate } => PhysicalPlan::Filter {
+                input: Box::new(PhysicalPlan::from_logical(input, settings)),
+                predicate: predicate.clone()
+            },
+            LogicalPlan::Project { input, exprs, .. } => PhysicalPlan::Projection {
+                input: Box::new(PhysicalPlan::from_logical(input, settings)),
+                exprs: exprs.clone()
+            },
+            LogicalPlan::Aggregate { input, group_by, grouping_sets, aggregates, .. } => PhysicalPlan::HashAggregate {
+                input: Box::new(PhysicalPlan::from_logical(input, settings)),
+                group_by: group_by.clone(),
+                grouping_sets: grouping_sets.clone(),
+                aggregates: aggregates.clone()
+            },
+            LogicalPlan::Sort { input, keys } => PhysicalPlan::Sort {
+                input: Box::new(PhysicalPlan::from_logical(input, settings)),
+                keys: keys.clone()
+            },
+            LogicalPlan::Distinct { input } => PhysicalPlan::HashDistinct {
+                input: Box::new(PhysicalPlan::from_logical(input, settings))
+            },
+            LogicalPlan::SingleRow { input } => PhysicalPlan::SingleRow {
+                input: Box::new(PhysicalPlan::from_logical(input, settings))
+            },
+            LogicalPlan::With { ctes, input } => PhysicalPlan::Materialize {
+                ctes: ctes.iter().map(|(name, plan)| (name.clone(), PhysicalPlan::from_logical(plan, settings))).collect(),
+                input: Box::new(PhysicalPlan::from_logical(input, settings))
+            },
+            LogicalPlan::CteScan { name, .. } => PhysicalPlan::CteScan { name: name.clone() },
+            LogicalPlan::RecursiveCte { name, anchor, step, distinct, .. } => PhysicalPlan::RecursiveCte {
+                name: name.clone(),
+                anchor: Box::new(PhysicalPlan::from_logical(anchor, settings)),
+                step: Box::new(PhysicalPlan::from_logical(step, settings)),
+                distinct: *distinct
+            },
+            LogicalPlan::SetOperation { left, right, operator, all, .. } => {
+                let left = Box::new(PhysicalPlan::from_logical(left, settings));
+                let right = Box::new(PhysicalPlan::from_logical(right, settings));
+                // UNION is UNION ALL without the duplicate rows
+                if *operator == SetOperator::Union && !all {
+                    let input = Box::new(PhysicalPlan::SetOperation { left, right, operator: *operator, all: true });
+                    return PhysicalPlan::HashDistinct { input }
+                }
+                PhysicalPlan::SetOperation { left, right, operator: *operator, all: *all }
+            },
+            LogicalPlan::Limit { input, count } => PhysicalPlan::Limit {
+                input: Box::new(PhysicalPlan::from_logical(input, settings)),
+                count: *count
+            },
+            LogicalPlan::Window { input, calls, .. } => PhysicalPlan::Window {
+                input: Box::new(PhysicalPlan::from_logical(input, settings)),
+                calls: calls.clone()
+            },
+            LogicalPlan::Unpivot { input, columns, include_nulls, .. } => PhysicalPlan::Unpivot {
+                input: Box::new(PhysicalPlan::from_logical(input, settings)),
+                columns: columns.clone(),
+                names: columns.iter().map(|index| input.schema().fields[*index].name.clone()).collect(),
+                include_nulls: *include_nulls
+            },
+            LogicalPlan::Join { left, right, kind, condition, .. } => {
+                let widths = (left.schema().len(), right.schema().len());
+                let (on, filter) = equal_keys(condition.clone(), widths.0);
+                let (left_plan, right_plan) = (Box::new(PhysicalPlan::from_logical(left, settings)), Box::new(PhysicalPlan::from_logical(right, settings)));
+                if on.is_empty() {
+                    return PhysicalPlan::NestedLoopJoin { left: left_plan, right: right_plan, kind: *kind, condition: condition.clone(), widths }
+                }
+                // a semi or anti join streams the left rows through the right ones
+                if matches!(kind, JoinKind::Semi | JoinKind::Anti) {
+                    let on = on.into_iter().map(|(left, right)| (left, right.shift_columns(widths.0))).collect();
+                    return PhysicalPlan::HashJoin { left: left_plan, right: right_plan, kind: *kind, on, filter, build: JoinSide::Right, widths }
+                }
+                // inputs sorted on a pair of keys are merged, so are inputs too large to hash; an input which is not
+                // declared sorted or sorted by the plan is sorted first, a file in order in its sampled rows may not be
+                // in the rest of it. The size of a file is the estimate of its size in memory
+                let sorted = on.iter().position(|(left_key, right_key)| is_sorted_on(left, left_key, 0, true) && is_sorted_on(right, right_key, widths.0, true));
+                let too_large = settings.memory_budget
+                    .is_some_and(|budget| estimated_size(left).min(estimated_size(right)) > budget as u64);
+                if let Some(position) = sorted.or(too_large.then_some(0)) {
+                    let mut on = on;
+                    let (left_key, right_key) = on.remove(position);
+                    let right_key = right_key.shift_columns(widths.0);
+                    // the other equal keys are checked with the rest of the condition
+                    let rest = on.into_iter()
+                        .map(|(left, right)| ResolvedExpr::BinaryOp {
+                            left: Box::new(left),
+                            operator: BinaryOperator::Equals,
+                            right: Box::new(right),
+                            data_type: DataType::Bool
+                        })
+                        .chain(filter);
+                    return PhysicalPlan::SortMergeJoin {
+                        left: sorted_input(left_plan, left, &left_key),
+                        right: sorted_input(right_plan, right, &right_key),
+                        kind: *kind,
+                        on: (left_key, right_key),
+                        filter: conjunction(rest.collect()),
+                        widths
+                    }
+                }
+                // the smaller input is buffered
+                let build = if estimated_size(left) < estimated_size(right) { JoinSide::Left } else { JoinSide::Right };
+                let on = on.into_iter().map(|(left, right)| (left, right.shift_columns(widths.0))).collect();
+                PhysicalPlan::HashJoin { left: left_plan, right: right_plan, kind: *kind, on, filter, build, widths }
+            }
+        }
+    }
+
+    pub fn inputs(&self) -> Vec<&PhysicalPlan> {
+        match self {
+            PhysicalPlan::FileScan { .. } | PhysicalPlan::CteScan { .. } => Vec::new(),
+            PhysicalPlan::Filter { input, .. }
+            | PhysicalPlan::Projection { input, .. }
+            | PhysicalPlan::HashAggregate { input, .. }
+            | PhysicalPlan::Sort { input, .. }
+            | PhysicalPlan::HashDistinct { input }
+            | PhysicalPlan::SingleRow { input }
+            | PhysicalPlan::Limit { input, .. }
+            | PhysicalPlan::Window { input, .. }
+            | PhysicalPlan::Unpivot { input, .. } => vec![input],
+            PhysicalPlan::HashJoin { left, right, .. }
+            | PhysicalPlan::SetOperation { left, right, .. }
+            | PhysicalPlan::SortMergeJoin { left, right, .. }
+            | PhysicalPlan::NestedLoopJoin { left, right, .. } => vec![left, right],
+            PhysicalPlan::Materialize { ctes, input } => ctes.iter().map(|(_, plan)| plan).chain(std::iter::once(input.as_ref())).collect(),
+            PhysicalPlan::RecursiveCte { anchor, step, .. } => vec![anchor, step]
+        }
+    }
+
+    // one line description of the operator, without its inputs
+    pub fn describe(&self) -> String {
+        match self {
+            PhysicalPlan::FileScan { source, projection, filter } => {
+                let mut description = format!("FileScanExec: {}", source);
+                if let Some(projection) = projection {
+                    description.push_str(&format!(" projection={:?}", projection));
+                }
+                if let Some(filter) = filter {
+                    description.push_str(&format!(" filter={}", filter));
+                }
+                description
+            },
+            PhysicalPlan::Filter { predicate, .. } => format!("FilterExec: {}", predicate),
+            PhysicalPlan::Projection { exprs, .. } => format!("ProjectionExec: {}", join(exprs)),
+            PhysicalPlan::HashAggregate { group_by, grouping_sets, aggregates, .. } => {
+                format!("HashAggregateExec: group_by=[{}]{} aggregates=[{}]", join(group_by), describe_sets(group_by, grouping_sets), join(aggregates))
+            },
+            PhysicalPlan::Sort { keys, .. } => format!("SortExec: {}", join(keys)),
+            PhysicalPlan::HashDistinct { .. } => "HashDistinctExec".to_string(),
+            PhysicalPlan::SingleRow { .. } => "SingleRowExec".to_string(),
+            PhysicalPlan::HashJoin { kind, on, filter, build, .. } => {
+                let keys: Vec<String> = on.iter().map(|(left, right)| format!("{} = {}", left, right)).collect();
+                let mut description = format!("HashJoinExec: {} on=[{}] build={}", kind, keys.join(", "), build);
+                if let Some(filter) = filter {
+                    description.push_str(&format!(" filter={}", filter));
+                }
+                description
+            },
+            PhysicalPlan::SortMergeJoin { kind, on: (left, right), filter, .. } => {
+                let mut description = format!("SortMergeJoinExec: {} on=[{} = {}]", kind, left, right);
+                if let Some(filter) = filter {
+                    description.push_str(&format!(" filter={}", filter));
+                }
+                description
+            },
+            PhysicalPlan::NestedLoopJoin { kind, condition: Some(condition), .. } => {
+                format!("NestedLoopJoinExec: {} condition={}", kind, condition)
+            },
+            PhysicalPlan::NestedLoopJoin { kind, condition: None, .. } => format!("NestedLoopJoinExec: {}", kind),
+            PhysicalPlan::Materialize { ctes, .. } => {
+                format!("MaterializeExec: {}", ctes.iter().map(|(name, _)| name.as_str()).collect::<Vec<&str>>().join(", "))
+            },
+            PhysicalPlan::CteScan { name } => format!("CteScanExec: {}", name),
+            PhysicalPlan::RecursiveCte { name, distinct, .. } => {
+                format!("RecursiveCteExec: {} {}", name, if *distinct { "UNION" } else { "UNION ALL" })
+            },
+            PhysicalPlan::SetOperation { operator, all, .. } => {
+                format!("SetOperationExec: {}{}", operator, if *all { " ALL" } else { "" })
+            },
+            PhysicalPlan::Limit { count, .. } => format!("LimitExec: {}", count),
+            PhysicalPlan::Window { calls, .. } => format!("WindowExec: {}", join(calls)),
+            PhysicalPlan::Unpivot { names, include_nulls, .. } => {
+                format!("UnpivotExec: {}{}", names.join(", "), if *include_nulls { " INCLUDE NULLS" } else { "" })
+            }
+        }
+    }
+
+    // visits the operators depth first, together with their depth in the tree
+    pub fn walk<'a>(&'a self, depth: usize, visit: &mut impl FnMut(&'a PhysicalPlan, usize)) {
+        visit(self, depth);
+        for input in self.inputs() {
+            input.walk(depth + 1, visit);
+        }
+    }
+}
+
+// splits a join condition into equalities between an expression of each side and the remaining predicate, both
+// keys are bound to the joined row
+fn equal_keys(condition: Option<ResolvedExpr>, left_width: usize) -> (Vec<(ResolvedExpr, ResolvedExpr)>, Option<ResolvedExpr>) {
+    let mut keys = Vec::new();
+    let mut rest = Vec::new();
+    for conjunct in condition.map(ResolvedExpr::conjuncts).unwrap_or_default() {
+        match conjunct {
+            ResolvedExpr::BinaryOp { left, operator: BinaryOperator::Equals, right, .. } if hashable(&left, &right) => {
+                match (side(&left, left_width), side(&right, left_width)) {
+                    (Some(JoinSide::Left), Some(JoinSide::Right)) => keys.push((*left, *right)),
+                    (Some(JoinSide::Right), Some(JoinSide::Left)) => keys.push((*right, *left)),
+                    _ => rest.push(ResolvedExpr::BinaryOp { left, operator: BinaryOperator::Equals, right, data_type: DataType::Bool })
+                }
+            },
+            conjunct => rest.push(conjunct)
+        }
+    }
+
+    (keys, conjunction(rest))
+}
+
+// hashed keys only meet when their values are equal, integers and doubles hash alike but values of other
+// different types are left to the comparison, which reports them
+fn hashable(left: &ResolvedExpr, right: &ResolvedExpr) -> bool {
+    let numeric = |data_type: DataType| matches!(data_type, DataType::Int | DataType::Float);
+    left.data_type() == right.data_type() && left.data_type() != DataType::Any
+        || numeric(left.data_type()) && numeric(right.data_type())
+}
+
+// whether the rows of the input come in ascending order of the key, a column bound `offset` columns into the
+// joined row; with `sampled` it is enough for a file to be in that order in its sampled rows
+fn is_sorted_on(plan: &LogicalPlan, key: &ResolvedExpr, offset: usize, sampled: bool) -> bool {
+    matches!(key, ResolvedExpr::Column { index, .. } if plan.sorted_on(sampled).contains(&(index - offset)))
+}
+
+fn sorted_input(input: Box<PhysicalPlan>, plan: &LogicalPlan, key: &ResolvedExpr) -> Box<PhysicalPlan> {
+    if is_sorted_on(plan, key, 0, false) {
+        input
+    } else {
+        Box::new(PhysicalPlan::Sort { input, keys: vec![SortKey { expr: key.clone(), asc: true }] })
+    }
+}
+
+fn estimated_size(plan: &LogicalPlan) -> u64 {
+    match plan {
+        LogicalPlan::Scan { source, .. } => std::fs::metadata(source).map(|metadata| metadata.len()).unwrap_or(u64::MAX),
+        plan => plan.inputs().into_iter().map(estimated_size).fold(0, u64::saturating_add)
+    }
+}
+
+impl Display for PhysicalPlan {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut lines = Vec::new();
+        self.walk(0, &mut |plan, depth| lines.push(format!("{}{}", "  ".repeat(depth), plan.describe())));
+        for line in lines {
+            writeln!(f, "{}", line)?;
+        }
+        Ok(())
+    }
+}
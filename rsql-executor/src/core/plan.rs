@@ -0,0 +1,700 @@
+use std::fmt::Display;
+use regex::Regex;
+use rsql_parser::ast::constructs::{AggregateFunc, BinaryOperator, JoinKind, SetOperator, UnaryOperator, Value, WindowFrame, WindowFunc};
+use crate::core::schema::{DataType, Schema};
+
+// pattern compiled while the query is analyzed, so that it is not compiled again for every row
+#[derive(Debug, Clone)]
+pub struct CompiledRegex(pub Regex);
+
+impl PartialEq for CompiledRegex {
+    fn eq(&self, other: &Self) -> bool {
+        self.0.as_str() == other.0.as_str()
+    }
+}
+
+// expression whose columns are bound to positions in the input row and whose type is known
+#[derive(Debug, Clone, PartialEq)]
+pub enum ResolvedExpr {
+    Column {
+        index: usize,
+        name: String,
+        data_type: DataType
+    },
+    Literal(Value),
+    BinaryOp {
+        left: Box<ResolvedExpr>,
+        operator: BinaryOperator,
+        right: Box<ResolvedExpr>,
+        data_type: DataType
+    },
+    UnaryOp {
+        operator: UnaryOperator,
+        expr: Box<ResolvedExpr>,
+        data_type: DataType
+    },
+    InList {
+        expr: Box<ResolvedExpr>,
+        list: Vec<ResolvedExpr>,
+        negated: bool
+    },
+    Between {
+        expr: Box<ResolvedExpr>,
+        low: Box<ResolvedExpr>,
+        high: Box<ResolvedExpr>,
+        negated: bool
+    },
+    Like {
+        expr: Box<ResolvedExpr>,
+        pattern: Box<ResolvedExpr>,
+        case_insensitive: bool,
+        negated: bool
+    },
+    IsNull {
+        expr: Box<ResolvedExpr>,
+        negated: bool
+    },
+    // a string literal used as a regular expression, evaluates to the pattern text
+    Regex(CompiledRegex),
+    Regexp {
+        expr: Box<ResolvedExpr>,
+        pattern: Box<ResolvedExpr>,
+        negated: bool
+    },
+    Function {
+        name: String,
+        args: Vec<ResolvedExpr>,
+        data_type: DataType
+    },
+    Cast {
+        expr: Box<ResolvedExpr>,
+        data_type: DataType,
+        try_cast: bool
+    },
+    Case {
+        operand: Option<Box<ResolvedExpr>>,
+        branches: Vec<(ResolvedExpr, ResolvedExpr)>,
+        else_result: Option<Box<ResolvedExpr>>,
+        data_type: DataType
+    }
+}
+
+impl ResolvedExpr {
+    pub fn data_type(&self) -> DataType {
+        match self {
+            ResolvedExpr::Column { data_type, .. } => *data_type,
+            ResolvedExpr::Literal(value) => DataType::of(value),
+            ResolvedExpr::BinaryOp { data_type, .. } => *data_type,
+            ResolvedExpr::UnaryOp { data_type, .. } => *data_type,
+            ResolvedExpr::Function { data_type, .. }
+            | ResolvedExpr::Cast { data_type, .. }
+            | ResolvedExpr::Case { data_type, .. } => *data_type,
+            ResolvedExpr::Regex(_) => DataType::String,
+            ResolvedExpr::InList { .. }
+            | ResolvedExpr::Between { .. }
+            | ResolvedExpr::Like { .. }
+            | ResolvedExpr::IsNull { .. }
+            | ResolvedExpr::Regexp { .. } => DataType::Bool
+        }
+    }
+}
+
+impl ResolvedExpr {
+    pub fn children(&self) -> Vec<&ResolvedExpr> {
+        match self {
+            ResolvedExpr::Column { .. } | ResolvedExpr::Literal(_) | ResolvedExpr::Regex(_) => Vec::new(),
+            ResolvedExpr::BinaryOp { left, right, .. } => vec![left, right],
+            ResolvedExpr::UnaryOp { expr, .. } | ResolvedExpr::IsNull { expr, .. } | ResolvedExpr::Cast { expr, .. } => vec![expr],
+            ResolvedExpr::InList { expr, list, .. } => std::iter::once(expr.as_ref()).chain(list.iter()).collect(),
+            ResolvedExpr::Between { expr, low, high, .. } => vec![expr, low, high],
+            ResolvedExpr::Like { expr, pattern, .. } | ResolvedExpr::Regexp { expr, pattern, .. } => vec![expr, pattern],
+            ResolvedExpr::Function { args, .. } => args.iter().collect(),
+            ResolvedExpr::Case { operand, branches, else_result, .. } => operand.iter().map(|operand| operand.as_ref())
+                .chain(branches.iter().flat_map(|(when, then)| [when, then]))
+                .chain(else_result.iter().map(|result| result.as_ref()))
+                .collect()
+        }
+    }
+
+    // rebuilds the expression with each child replaced by `map` of it
+    pub fn map_children(self, map: &mut impl FnMut(ResolvedExpr) -> ResolvedExpr) -> ResolvedExpr {
+        let mut boxed = |expr: Box<ResolvedExpr>| Box::new(map(*expr));
+        match self {
+            ResolvedExpr::BinaryOp { left, operator, right, data_type } => {
+                let left = boxed(left);
+                ResolvedExpr::BinaryOp { left, operator, right: boxed(right), data_type }
+            },
+            ResolvedExpr::UnaryOp { operator, expr, data_type } => {
+                ResolvedExpr::UnaryOp { operator, expr: boxed(expr), data_type }
+            },
+            ResolvedExpr::InList { expr, list, negated } => {
+                let expr = boxed(expr);
+                ResolvedExpr::InList { expr, list: list.into_iter().map(|item| *boxed(Box::new(item))).collect(), negated }
+            },
+            ResolvedExpr::Between { expr, low, high, negated } => {
+                let expr = boxed(expr);
+                let low = boxed(low);
+                ResolvedExpr::Between { expr, low, high: boxed(high), negated }
+            },
+            ResolvedExpr::Like { expr, pattern, case_insensitive, negated } => {
+                let expr = boxed(expr);
+                ResolvedExpr::Like { expr, pattern: boxed(pattern), case_insensitive, negated }
+            },
+            ResolvedExpr::IsNull { expr, negated } => ResolvedExpr::IsNull { expr: boxed(expr), negated },
+            ResolvedExpr::Regexp { expr, pattern, negated } => {
+                let expr = boxed(expr);
+                ResolvedExpr::Regexp { expr, pattern: boxed(pattern), negated }
+            },
+            ResolvedExpr::Function { name, args, data_type } => ResolvedExpr::Function {
+                name,
+                args: args.into_iter().map(|arg| *boxed(Box::new(arg))).collect(),
+                data_type
+            },
+            ResolvedExpr::Cast { expr, data_type, try_cast } => ResolvedExpr::Cast { expr: boxed(expr), data_type, try_cast },
+            ResolvedExpr::Case { operand, branches, else_result, data_type } => {
+                let operand = operand.map(&mut boxed);
+                let branches = branches.into_iter().map(|(when, then)| (*boxed(Box::new(when)), *boxed(Box::new(then)))).collect();
+                ResolvedExpr::Case { operand, branches, else_result: else_result.map(boxed), data_type }
+            },
+            expr => expr
+        }
+    }
+
+    // rewrites the children first and then the expression itself
+    pub fn transform_up(self, rewrite: &impl Fn(ResolvedExpr) -> ResolvedExpr) -> ResolvedExpr {
+        let expr = self.map_children(&mut |child| child.transform_up(rewrite));
+        rewrite(expr)
+    }
+
+    // indices of the input columns the expression reads
+    pub fn columns(&self) -> Vec<usize> {
+        let mut columns = Vec::new();
+        self.collect_columns(&mut columns);
+        columns.sort();
+        columns.dedup();
+        columns
+    }
+
+    // the operands of a chain of ANDs, or the expression itself
+    pub fn conjuncts(self) -> Vec<ResolvedExpr> {
+        match self {
+            ResolvedExpr::BinaryOp { left, operator: BinaryOperator::And, right, .. } => {
+                let mut conjuncts = left.conjuncts();
+                conjuncts.extend(right.conjuncts());
+                conjuncts
+            },
+            expr => vec![expr]
+        }
+    }
+
+    // the expression over a row which lacks the first `offset` columns, the right row of a join
+    pub fn shift_columns(self, offset: usize) -> ResolvedExpr {
+        self.map_columns(&|index| index - offset)
+    }
+
+    // the expression over a row whose columns are arranged differently, `map` gives the new position of a column
+    pub fn map_columns(self, map: &impl Fn(usize) -> usize) -> ResolvedExpr {
+        self.transform_up(&|expr| match expr {
+            ResolvedExpr::Column { index, name, data_type } => ResolvedExpr::Column { index: map(index), name, data_type },
+            expr => expr
+        })
+    }
+
+    fn collect_columns(&self, columns: &mut Vec<usize>) {
+        if let ResolvedExpr::Column { index, .. } = self {
+            columns.push(*index);
+        }
+        for child in self.children() {
+            child.collect_columns(columns);
+        }
+    }
+}
+
+impl Display for ResolvedExpr {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ResolvedExpr::Column { index, name, .. } => write!(f, "{}#{}", name, index),
+            ResolvedExpr::Literal(value) => write!(f, "{}", value),
+            ResolvedExpr::BinaryOp { left, operator, right, .. } => write!(f, "({} {} {})", left, operator, right),
+            ResolvedExpr::UnaryOp { operator: UnaryOperator::Minus, expr, .. } => write!(f, "-{}", expr),
+            ResolvedExpr::UnaryOp { operator: UnaryOperator::Not, expr, .. } => write!(f, "NOT {}", expr),
+            ResolvedExpr::InList { expr, list, negated } => {
+                write!(f, "({} {}IN ({}))", expr, not(*negated), join(list))
+            },
+            ResolvedExpr::Between { expr, low, high, negated } => {
+                write!(f, "({} {}BETWEEN {} AND {})", expr, not(*negated), low, high)
+            },
+            ResolvedExpr::Like { expr, pattern, case_insensitive, negated } => {
+                write!(f, "({} {}{} {})", expr, not(*negated), if *case_insensitive { "ILIKE" } else { "LIKE" }, pattern)
+            },
+            ResolvedExpr::IsNull { expr, negated } => write!(f, "({} IS {}NULL)", expr, not(*negated)),
+            ResolvedExpr::Regex(regex) => write!(f, "{}", Value::String(regex.0.as_str().to_string())),
+            ResolvedExpr::Regexp { expr, pattern, negated } => write!(f, "({} {}REGEXP {})", expr, not(*negated), pattern),
+            ResolvedExpr::Function { name, args, .. } => write!(f, "{}({})", name, join(args)),
+            ResolvedExpr::Cast { expr, data_type, try_cast } => {
+                write!(f, "{}CAST({} AS {})", if *try_cast { "TRY_" } else { "" }, expr, data_type)
+            },
+            ResolvedExpr::Case { operand, branches, else_result, .. } => {
+                write!(f, "CASE")?;
+                if let Some(operand) = operand {
+                    write!(f, " {}", operand)?;
+                }
+                for (when, then) in branches {
+                    write!(f, " WHEN {} THEN {}", when, then)?;
+                }
+                if let Some(result) = else_result {
+                    write!(f, " ELSE {}", result)?;
+                }
+                write!(f, " END")
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct AggregateCall {
+    pub func: AggregateFunc,
+    // None for COUNT(*)
+    pub arg: Option<ResolvedExpr>,
+    pub distinct: bool,
+    pub data_type: DataType
+}
+
+impl Display for AggregateCall {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.arg {
+            Some(arg) => write!(f, "{}({}{}", self.func, if self.distinct { "DISTINCT " } else { "" }, arg)?,
+            None => write!(f, "{}(*", self.func)?
+        }
+        if let Some(parameter) = self.func.parameter() {
+            write!(f, ", {}", parameter)?;
+        }
+        write!(f, ")")
+    }
+}
+
+// a window function computed for each row over the rows of its partition, sorted on the keys; the frame is the
+// one written, or the default one
+#[derive(Debug, Clone, PartialEq)]
+pub struct WindowCall {
+    pub func: WindowFunc,
+    pub args: Vec<ResolvedExpr>,
+    pub partition_by: Vec<ResolvedExpr>,
+    pub order_by: Vec<SortKey>,
+    pub frame: WindowFrame,
+    pub data_type: DataType
+}
+
+impl Display for WindowCall {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.func {
+            WindowFunc::Aggregate(AggregateFunc::Count) if self.args.is_empty() => write!(f, "{}(*) OVER (", self.func)?,
+            WindowFunc::Aggregate(ref aggregate) if let Some(parameter) = aggregate.parameter() => {
+                write!(f, "{}({}, {}) OVER (", self.func, join(&self.args), parameter)?
+            },
+            _ => write!(f, "{}({}) OVER (", self.func, join(&self.args))?
+        }
+        if !self.partition_by.is_empty() {
+            write!(f, "PARTITION BY {} ", join(&self.partition_by))?;
+        }
+        if !self.order_by.is_empty() {
+            write!(f, "ORDER BY {} ", join(&self.order_by))?;
+        }
+        write!(f, "{})", self.frame)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SortKey {
+    pub expr: ResolvedExpr,
+    pub asc: bool
+}
+
+impl Display for SortKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} {}", self.expr, if self.asc { "ASC" } else { "DESC" })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum JoinSide {
+    Left,
+    Right
+}
+
+impl Display for JoinSide {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", if *self == JoinSide::Left { "left" } else { "right" })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum LogicalPlan {
+    // rows are read with every column, unless a projection lists the ones to convert; the filter is applied
+    // while reading, before the remaining columns are converted
+    Scan {
+        source: String,
+        schema: Schema,
+        projection: Option<Vec<usize>>,
+        filter: Option<ResolvedExpr>
+    },
+    Filter {
+        input: Box<LogicalPlan>,
+        predicate: ResolvedExpr
+    },
+    Project {
+        input: Box<LogicalPlan>,
+        exprs: Vec<ResolvedExpr>,
+        schema: Schema
+    },
+    // output rows hold the group by values followed by the aggregate results
+    Aggregate {
+        input: Box<LogicalPlan>,
+        group_by: Vec<ResolvedExpr>,
+        // the positions in group_by of each set the rows are grouped by, empty when they are grouped by all of
+        // group_by; with sets, the values outside the set of a row are NULL and the row ends with the position of
+        // its set
+        grouping_sets: Vec<Vec<usize>>,
+        aggregates: Vec<AggregateCall>,
+        schema: Schema
+    },
+    Sort {
+        input: Box<LogicalPlan>,
+        keys: Vec<SortKey>
+    },
+    // removes duplicate rows, SELECT DISTINCT
+    Distinct {
+        input: Box<LogicalPlan>
+    },
+    // the rows of a subquery used as a value, a second row is an error
+    SingleRow {
+        input: Box<LogicalPlan>
+    },
+    // output rows hold the columns of the left row followed by those of the right row, the condition is bound
+    // to such a row; semi and anti joins only output the left row
+    Join {
+        left: Box<LogicalPlan>,
+        right: Box<LogicalPlan>,
+        kind: JoinKind,
+        condition: Option<ResolvedExpr>,
+        schema: Schema
+    },
+    // the common table expressions read more than once are computed before the input and kept in memory,
+    // in order, so that one of them can read those before it
+    With {
+        ctes: Vec<(String, LogicalPlan)>,
+        input: Box<LogicalPlan>
+    },
+    // the rows of a common table expression computed by With or RecursiveCte
+    CteScan {
+        name: String,
+        schema: Schema
+    },
+    // the rows of the anchor followed by those the step computes from the rows added last, until it adds none;
+    // the step reads the rows added last as a CteScan of the name
+    RecursiveCte {
+        name: String,
+        anchor: Box<LogicalPlan>,
+        step: Box<LogicalPlan>,
+        // UNION rather than UNION ALL, rows already there are not added again
+        distinct: bool,
+        schema: Schema
+    },
+    // the rows of the left input combined with those of the right one, whose columns are named after the
+    // left ones; without ALL a row is returned once
+    SetOperation {
+        left: Box<LogicalPlan>,
+        right: Box<LogicalPlan>,
+        operator: SetOperator,
+        all: bool,
+        schema: Schema
+    },
+    // the first `count` rows of the input
+    Limit {
+        input: Box<LogicalPlan>,
+        count: u64
+    },
+    // output rows hold the columns of the input row followed by the value of each window function, in the order
+    // of the input
+    Window {
+        input: Box<LogicalPlan>,
+        calls: Vec<WindowCall>,
+        schema: Schema
+    },
+    // a row for each of `columns` of each input row, holding the other columns of the input followed by the name of
+    // the column and its value; rows whose value is NULL are left out unless include_nulls
+    Unpivot {
+        input: Box<LogicalPlan>,
+        columns: Vec<usize>,
+        include_nulls: bool,
+        schema: Schema
+    }
+}
+
+impl AggregateCall {
+    pub fn columns(&self) -> Vec<usize> {
+        self.arg.as_ref().map(|arg| arg.columns()).unwrap_or_default()
+    }
+}
+
+impl LogicalPlan {
+    // applies the rewrite to every expression of every node
+    pub fn transform_exprs(self, rewrite: &impl Fn(ResolvedExpr) -> ResolvedExpr) -> LogicalPlan {
+        match self {
+            LogicalPlan::Scan { source, schema, projection, filter } => LogicalPlan::Scan {
+                source,
+                schema,
+                projection,
+                filter: filter.map(|filter| filter.transform_up(rewrite))
+            },
+            LogicalPlan::Filter { input, predicate } => LogicalPlan::Filter {
+                input: Box::new(input.transform_exprs(rewrite)),
+                predicate: predicate.transform_up(rewrite)
+            },
+            LogicalPlan::Project { input, exprs, schema } => LogicalPlan::Project {
+                input: Box::new(input.transform_exprs(rewrite)),
+                exprs: exprs.into_iter().map(|expr| expr.transform_up(rewrite)).collect(),
+                schema
+            },
+            LogicalPlan::Aggregate { input, group_by, grouping_sets, aggregates, schema } => LogicalPlan::Aggregate {
+                input: Box::new(input.transform_exprs(rewrite)),
+                group_by: group_by.into_iter().map(|expr| expr.transform_up(rewrite)).collect(),
+                grouping_sets,
+                aggregates: aggregates.into_iter()
+                    .map(|call| AggregateCall { arg: call.arg.map(|arg| arg.transform_up(rewrite)), ..call })
+                    .collect(),
+                schema
+            },
+            LogicalPlan::Sort { input, keys } => LogicalPlan::Sort {
+                input: Box::new(input.transform_exprs(rewrite)),
+                keys: keys.into_iter()
+                    .map(|key| SortKey { expr: key.expr.transform_up(rewrite), asc: key.asc })
+                    .collect()
+            },
+            LogicalPlan::Distinct { input } => LogicalPlan::Distinct {
+                input: Box::new(input.transform_exprs(rewrite))
+            },
+            LogicalPlan::SingleRow { input } => LogicalPlan::SingleRow {
+                input: Box::new(input.transform_exprs(rewrite))
+            },
+            LogicalPlan::Join { left, right, kind, condition, schema } => LogicalPlan::Join {
+                left: Box::new(left.transform_exprs(rewrite)),
+                right: Box::new(right.transform_exprs(rewrite)),
+                kind,
+                condition: condition.map(|condition| condition.transform_up(rewrite)),
+                schema
+            },
+            LogicalPlan::With { ctes, input } => LogicalPlan::With {
+                ctes: ctes.into_iter().map(|(name, plan)| (name, plan.transform_exprs(rewrite))).collect(),
+                input: Box::new(input.transform_exprs(rewrite))
+            },
+            LogicalPlan::CteScan { .. } => self,
+            LogicalPlan::RecursiveCte { name, anchor, step, distinct, schema } => LogicalPlan::RecursiveCte {
+                name,
+                anchor: Box::new(anchor.transform_exprs(rewrite)),
+                step: Box::new(step.transform_exprs(rewrite)),
+                distinct,
+                schema
+            },
+            LogicalPlan::SetOperation { left, right, operator, all, schema } => LogicalPlan::SetOperation {
+                left: Box::new(left.transform_exprs(rewrite)),
+                right: Box::new(right.transform_exprs(rewrite)),
+                operator,
+                all,
+                schema
+            },
+            LogicalPlan::Limit { input, count } => LogicalPlan::Limit {
+                input: Box::new(input.transform_exprs(rewrite)),
+                count
+            },
+            LogicalPlan::Unpivot { input, columns, include_nulls, schema } => LogicalPlan::Unpivot {
+                input: Box::new(input.transform_exprs(rewrite)),
+                columns,
+                include_nulls,
+                schema
+            },
+            LogicalPlan::Window { input, calls, schema } => LogicalPlan::Window {
+                input: Box::new(input.transform_exprs(rewrite)),
+                calls: calls.into_iter()
+                    .map(|call| WindowCall {
+                        args: call.args.into_iter().map(|arg| arg.transform_up(rewrite)).collect(),
+                        partition_by: call.partition_by.into_iter().map(|expr| expr.transform_up(rewrite)).collect(),
+                        order_by: call.order_by.into_iter()
+                            .map(|key| SortKey { expr: key.expr.transform_up(rewrite), asc: key.asc })
+                            .collect(),
+                        ..call
+                    })
+                    .collect(),
+                schema
+            }
+        }
+    }
+
+    // the first input, the left one of a join
+    pub fn input(&self) -> Option<&LogicalPlan> {
+        self.inputs().into_iter().next()
+    }
+
+    pub fn inputs(&self) -> Vec<&LogicalPlan> {
+        match self {
+            LogicalPlan::Scan { .. } | LogicalPlan::CteScan { .. } => Vec::new(),
+            LogicalPlan::Filter { input, .. }
+            | LogicalPlan::Project { input, .. }
+            | LogicalPlan::Aggregate { input, .. }
+            | LogicalPlan::Sort { input, .. }
+            | LogicalPlan::Distinct { input }
+            | LogicalPlan::SingleRow { input }
+            | LogicalPlan::Limit { input, .. }
+            | LogicalPlan::Window { input, .. }
+            | LogicalPlan::Unpivot { input, .. } => vec![input],
+            LogicalPlan::Join { left, right, .. } | LogicalPlan::SetOperation { left, right, .. } => vec![left, right],
+            LogicalPlan::With { ctes, input } => ctes.iter().map(|(_, plan)| plan).chain(std::iter::once(input.as_ref())).collect(),
+            LogicalPlan::RecursiveCte { anchor, step, .. } => vec![anchor, step]
+        }
+    }
+
+    // one line description of the node, without its input
+    pub fn describe(&self) -> String {
+        match self {
+            LogicalPlan::Scan { source, projection, filter, .. } => {
+                let mut description = format!("Scan: {}", source);
+                if let Some(projection) = projection {
+                    description.push_str(&format!(" projection={:?}", projection));
+                }
+                if let Some(filter) = filter {
+                    description.push_str(&format!(" filter={}", filter));
+                }
+                description
+            },
+            LogicalPlan::Filter { predicate, .. } => format!("Filter: {}", predicate),
+            LogicalPlan::Project { exprs, .. } => format!("Project: {}", join(exprs)),
+            LogicalPlan::Aggregate { group_by, grouping_sets, aggregates, .. } => {
+                format!("Aggregate: group_by=[{}]{} aggregates=[{}]", join(group_by), describe_sets(group_by, grouping_sets), join(aggregates))
+            },
+            LogicalPlan::Sort { keys, .. } => format!("Sort: {}", join(keys)),
+            LogicalPlan::Distinct { .. } => "Distinct".to_string(),
+            LogicalPlan::SingleRow { .. } => "SingleRow".to_string(),
+            LogicalPlan::Join { kind, condition: Some(condition), .. } => format!("Join: {} {}", kind, condition),
+            LogicalPlan::Join { kind, condition: None, .. } => format!("Join: {}", kind),
+            LogicalPlan::With { ctes, .. } => {
+                format!("With: {}", ctes.iter().map(|(name, _)| name.as_str()).collect::<Vec<&str>>().join(", "))
+            },
+            LogicalPlan::CteScan { name, .. } => format!("CteScan: {}", name),
+            LogicalPlan::RecursiveCte { name, distinct, .. } => {
+                format!("RecursiveCte: {} {}", name, if *distinct { "UNION" } else { "UNION ALL" })
+            },
+            LogicalPlan::SetOperation { operator, all, .. } => {
+                format!("SetOperation: {}{}", operator, if *all { " ALL" } else { "" })
+            },
+            LogicalPlan::Limit { count, .. } => format!("Limit: {}", count),
+            LogicalPlan::Window { calls, .. } => format!("Window: {}", join(calls)),
+            LogicalPlan::Unpivot { input, columns, include_nulls, .. } => {
+                let names: Vec<&str> = columns.iter().map(|index| input.schema().fields[*index].name.as_str()).collect();
+                format!("Unpivot: {}{}", names.join(", "), if *include_nulls { " INCLUDE NULLS" } else { "" })
+            }
+        }
+    }
+
+    pub fn schema(&self) -> &Schema {
+        match self {
+            LogicalPlan::Scan { schema, .. } => schema,
+            LogicalPlan::Filter { input, .. } => input.schema(),
+            LogicalPlan::Project { schema, .. } => schema,
+            LogicalPlan::Aggregate { schema, .. } => schema,
+            LogicalPlan::Sort { input, .. } => input.schema(),
+            LogicalPlan::Distinct { input } | LogicalPlan::SingleRow { input } => input.schema(),
+            LogicalPlan::Join { schema, .. } => schema,
+            LogicalPlan::With { input, .. } => input.schema(),
+            LogicalPlan::CteScan { schema, .. }
+            | LogicalPlan::RecursiveCte { schema, .. }
+            | LogicalPlan::SetOperation { schema, .. }
+            | LogicalPlan::Window { schema, .. }
+            | LogicalPlan::Unpivot { schema, .. } => schema,
+            LogicalPlan::Limit { input, .. } => input.schema()
+        }
+    }
+
+    // columns of the output whose values, NULLs aside, never decrease from one row to the next; with `sampled` also
+    // those of files which were only seen in that order in the sampled rows
+    pub fn sorted_on(&self, sampled: bool) -> Vec<usize> {
+        match self {
+            LogicalPlan::Scan { schema, .. } if sampled => schema.sorted.iter().chain(&schema.sampled).copied().collect(),
+            LogicalPlan::Scan { schema, .. } => schema.sorted.clone(),
+            LogicalPlan::Filter { input, .. } | LogicalPlan::Distinct { input } | LogicalPlan::SingleRow { input } => input.sorted_on(sampled),
+            LogicalPlan::Project { input, exprs, .. } => {
+                let sorted = input.sorted_on(sampled);
+                exprs.iter()
+                    .enumerate()
+                    .filter(|(_, expr)| matches!(expr, ResolvedExpr::Column { index, .. } if sorted.contains(index)))
+                    .map(|(position, _)| position)
+                    .collect()
+            },
+            LogicalPlan::Sort { keys, .. } => match keys.first() {
+                Some(SortKey { expr: ResolvedExpr::Column { index, .. }, asc: true }) => vec![*index],
+                _ => Vec::new()
+            },
+            LogicalPlan::With { input, .. } | LogicalPlan::Limit { input, .. } | LogicalPlan::Window { input, .. } => input.sorted_on(sampled),
+            LogicalPlan::Aggregate { .. }
+            | LogicalPlan::Join { .. }
+            | LogicalPlan::CteScan { .. }
+            | LogicalPlan::RecursiveCte { .. }
+            | LogicalPlan::SetOperation { .. }
+            | LogicalPlan::Unpivot { .. } => Vec::new()
+        }
+    }
+}
+
+// the AND of the predicates, None when there are none
+pub fn conjunction(predicates: Vec<ResolvedExpr>) -> Option<ResolvedExpr> {
+    predicates.into_iter().reduce(|left, right| ResolvedExpr::BinaryOp {
+        left: Box::new(left),
+        operator: BinaryOperator::And,
+        right: Box::new(right),
+        data_type: DataType::Bool
+    })
+}
+
+// the side of the join whose columns the expression reads, None when it reads both or none
+pub fn side(expr: &ResolvedExpr, left_width: usize) -> Option<JoinSide> {
+    let columns = expr.columns();
+    if columns.is_empty() {
+        None
+    } else if columns.iter().all(|index| *index < left_width) {
+        Some(JoinSide::Left)
+    } else if columns.iter().all(|index| *index >= left_width) {
+        Some(JoinSide::Right)
+    } else {
+        None
+    }
+}
+
+fn not(negated: bool) -> &'static str {
+    if negated { "NOT " } else { "" }
+}
+
+pub(crate) fn join<T: Display>(items: &[T]) -> String {
+    items.iter().map(|item| item.to_string()).collect::<Vec<String>>().join(", ")
+}
+
+// the grouping sets of an aggregate, nothing when its rows are grouped by all of group_by
+pub(crate) fn describe_sets(group_by: &[ResolvedExpr], grouping_sets: &[Vec<usize>]) -> String {
+    if grouping_sets.is_empty() {
+        return String::new()
+    }
+    let sets: Vec<String> = grouping_sets.iter()
+        .map(|set| format!("({})", join(&set.iter().map(|index| &group_by[*index]).collect::<Vec<_>>())))
+        .collect();
+    format!(" grouping_sets=[{}]", sets.join(", "))
+}
+
+// indented tree, one node per line with its inputs below it
+impl Display for LogicalPlan {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut nodes = vec![(self, 0)];
+        while let Some((plan, depth)) = nodes.pop() {
+            writeln!(f, "{}{}", "  ".repeat(depth), plan.describe())?;
+            nodes.extend(plan.inputs().into_iter().rev().map(|input| (input, depth + 1)));
+        }
+        Ok(())
+    }
+}
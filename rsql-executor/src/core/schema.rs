@@ -0,0 +1,234 @@
+use std::fmt::Display;
+use rsql_parser::ast::constructs::{TypeName, Value};
+use rsql_parser::suggest::closest_match;
+use crate::core::errors::AnalyzerError;
+use crate::core::evaluator::compare;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DataType {
+    Bool,
+    Int,
+    Float,
+    String,
+    Date,
+    // the values of ARRAY_AGG, which can be of any type
+    List,
+    // type of the NULL literal and of columns whose sampled cells were all empty
+    Null,
+    // cells of the column were guessed as different types, checks are deferred to execution
+    Any
+}
+
+impl DataType {
+    pub fn of(value: &Value) -> DataType {
+        match value {
+            Value::Null => DataType::Null,
+            Value::Bool(_) => DataType::Bool,
+            Value::Int(_) => DataType::Int,
+            Value::Float(_) => DataType::Float,
+            Value::String(_) => DataType::String,
+            Value::Date(_) => DataType::Date,
+            Value::List(_) => DataType::List
+        }
+    }
+
+    pub fn is_numeric(&self) -> bool {
+        matches!(self, DataType::Int | DataType::Float | DataType::Null | DataType::Any)
+    }
+
+    pub fn is_boolean(&self) -> bool {
+        matches!(self, DataType::Bool | DataType::Null | DataType::Any)
+    }
+
+    // the type which can hold values of both types, None if they do not mix
+    pub fn unify(self, other: DataType) -> Option<DataType> {
+        match (self, other) {
+            (left, right) if left == right => Some(left),
+            (DataType::Null, other) | (other, DataType::Null) => Some(other),
+            (DataType::Any, _) | (_, DataType::Any) => Some(DataType::Any),
+            (DataType::Int, DataType::Float) | (DataType::Float, DataType::Int) => Some(DataType::Float),
+            _ => None
+        }
+    }
+
+    pub fn is_comparable_with(self, other: DataType) -> bool {
+        self.unify(other).is_some()
+    }
+}
+
+impl From<TypeName> for DataType {
+    fn from(type_name: TypeName) -> Self {
+        match type_name {
+            TypeName::Boolean => DataType::Bool,
+            TypeName::Integer => DataType::Int,
+            TypeName::Double => DataType::Float,
+            TypeName::Varchar => DataType::String,
+            TypeName::Date => DataType::Date
+        }
+    }
+}
+
+impl Display for DataType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let data_type = match self {
+            DataType::Bool => "BOOLEAN",
+            DataType::Int => "INTEGER",
+            DataType::Float => "DOUBLE",
+            DataType::String => "VARCHAR",
+            DataType::Date => "DATE",
+            DataType::List => "LIST",
+            DataType::Null => "NULL",
+            DataType::Any => "ANY"
+        };
+
+        write!(f, "{}", data_type)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Field {
+    pub name: String,
+    pub data_type: DataType,
+    // alias or file name of the table the column comes from, o in o.amount
+    pub qualifier: Option<String>
+}
+
+impl Field {
+    pub fn new(name: String, data_type: DataType) -> Self {
+        Field { name, data_type, qualifier: None }
+    }
+
+    pub fn qualified_name(&self) -> String {
+        match &self.qualifier {
+            Some(qualifier) => format!("{}.{}", qualifier, self.name),
+            None => self.name.clone()
+        }
+    }
+
+    fn is_named(&self, name: &str, exact: bool) -> bool {
+        let equal = |left: &str, right: &str| if exact { left == right } else { left.eq_ignore_ascii_case(right) };
+        equal(&self.name, name) || (self.qualifier.is_some() && equal(&self.qualified_name(), name))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Schema {
+    pub fields: Vec<Field>,
+    // columns whose values, NULLs aside, never decrease from one row of the file to the next, as declared
+    pub sorted: Vec<usize>,
+    // columns which are in that order in the sampled rows, which says nothing about the rest of the file
+    pub sampled: Vec<usize>
+}
+
+impl Schema {
+    pub fn new(fields: Vec<Field>) -> Self {
+        Schema { fields, sorted: Vec::new(), sampled: Vec::new() }
+    }
+
+    // column types are guessed from the header and the first rows of a file
+    pub fn infer(header: Vec<String>, sample: &[Vec<Value>]) -> Self {
+        let fields = header.into_iter()
+            .enumerate()
+            .map(|(index, name)| {
+                let data_type = sample.iter()
+                    .filter_map(|row| row.get(index))
+                    .map(DataType::of)
+                    .try_fold(DataType::Null, |inferred, data_type| inferred.unify(data_type))
+                    .unwrap_or(DataType::Any);
+                Field::new(name, data_type)
+            })
+            .collect();
+
+        Schema { fields, sorted: Vec::new(), sampled: sorted_columns(sample) }
+    }
+
+    pub fn names(&self) -> Vec<String> {
+        self.fields.iter().map(|field| field.name.clone()).collect()
+    }
+
+    pub fn len(&self) -> usize {
+        self.fields.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.fields.is_empty()
+    }
+
+    // every column of the schema is qualified as coming from the table
+    pub fn qualified(self, qualifier: &str) -> Self {
+        let fields = self.fields.into_iter()
+            .map(|field| Field { qualifier: Some(qualifier.to_string()), ..field })
+            .collect();
+        Schema { fields, sorted: self.sorted, sampled: self.sampled }
+    }
+
+    // the columns of a joined row, those of this schema followed by those of the other
+    pub fn join(&self, other: &Schema) -> Self {
+        Schema::new(self.fields.iter().chain(other.fields.iter()).cloned().collect())
+    }
+
+    // columns the name can refer to, exact matches first and else case insensitive ones; o.amount only matches the
+    // amount column of table o
+    fn matching(&self, name: &str) -> Vec<usize> {
+        let matching = |exact: bool| -> Vec<usize> {
+            self.fields.iter()
+                .enumerate()
+                .filter(|(_, field)| field.is_named(name, exact))
+                .map(|(index, _)| index)
+                .collect()
+        };
+        let exact = matching(true);
+        if exact.is_empty() { matching(false) } else { exact }
+    }
+
+    // exact match first, then case insensitive as long as that is not ambiguous; a file with a repeated header
+    // resolves to its first column of that name, the same name in two joined tables is ambiguous
+    pub fn index_of(&self, name: &str) -> Option<usize> {
+        match self.matching(name).as_slice() {
+            [index] => Some(*index),
+            [first, rest @ ..] if rest.iter().all(|index| self.fields[*index].qualified_name() == self.fields[*first].qualified_name()) => {
+                Some(*first)
+            },
+            _ => None
+        }
+    }
+
+    pub fn resolve(&self, name: &str) -> Result<usize, AnalyzerError> {
+        self.index_of(name).ok_or_else(|| {
+            // a qualified and an unqualified column of the same name are as ambiguous as two qualified ones
+            let mut candidates: Vec<String> = Vec::new();
+            for index in self.matching(name) {
+                let candidate = self.fields[index].qualified_name();
+                if !candidates.contains(&candidate) {
+                    candidates.push(candidate);
+                }
+            }
+            if candidates.len() > 1 {
+                return AnalyzerError {
+                    message: format!("column reference `{}` is ambiguous, it could be {}", name, candidates.join(" or "))
+                }
+            }
+            let names: Vec<&str> = self.fields.iter().map(|field| field.name.as_str()).collect();
+            let message = match closest_match(name, &names) {
+                Some(suggestion) => format!("column `{}` not found; did you mean `{}`?", name, suggestion),
+                None => format!("column `{}` not found", name)
+            };
+            AnalyzerError { message }
+        })
+    }
+}
+
+// columns whose sampled values are in ascending order, a guess about the whole file which is only trusted as far
+// as choosing to merge
+fn sorted_columns(sample: &[Vec<Value>]) -> Vec<usize> {
+    let width = sample.iter().map(|row| row.len()).max().unwrap_or(0);
+    (0..width)
+        .filter(|index| {
+            let values: Vec<&Value> = sample.iter()
+                .filter_map(|row| row.get(*index))
+                .filter(|value| !matches!(value, Value::Null))
+                .collect();
+            values.len() > 1 && values.windows(2).all(|pair| compare(pair[0], pair[1]).is_ok_and(|ordering| ordering.is_le()))
+        })
+        .collect()
+}
@@ -0,0 +1,20 @@
+use std::collections::HashMap;
+
+// what `/` returns when the divisor is zero
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DivisionByZero {
+    #[default]
+    Error,
+    Null
+}
+
+// options which change how a query is evaluated
+#[derive(Debug, Clone, Default)]
+pub struct Settings {
+    pub division_by_zero: DivisionByZero,
+    // bytes a sort may buffer before it writes sorted runs to disk, a join whose inputs are both larger sorts
+    // them instead of hashing one; None is no limit
+    pub memory_budget: Option<usize>,
+    // columns whose values never decrease from one row of the file to the next, by source
+    pub sorted_sources: HashMap<String, Vec<String>>
+}
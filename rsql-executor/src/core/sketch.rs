@@ -0,0 +1,259 @@
+use std::cmp::Reverse;
+use std::collections::{HashMap, HashSet};
+use std::f64::consts::PI;
+use std::hash::{DefaultHasher, Hash, Hasher};
+use rsql_parser::ast::constructs::Value;
+use crate::core::aggregate::KeyValue;
+
+// summaries of a fixed size, whatever the number of values, which give approximate answers; two summaries built
+// over different rows merge into the one of all their rows
+
+// bits of the hash which pick the register, 4096 registers give a standard error of about 1.6%
+const PRECISION: u32 = 12;
+// hashes kept before switching to the registers, which take as much memory
+const SPARSE: usize = (1 << PRECISION) / 8;
+
+// HyperLogLog: each register keeps the longest run of leading zeros among the hashes sent to it, the more distinct
+// values the longer the runs; the first hashes are kept as they are, which counts small sets exactly
+#[derive(Debug, Clone)]
+pub struct HyperLogLog {
+    hashes: Option<HashSet<u64>>,
+    registers: Vec<u8>
+}
+
+impl HyperLogLog {
+    pub fn new() -> Self {
+        HyperLogLog { hashes: Some(HashSet::new()), registers: Vec::new() }
+    }
+
+    pub fn insert(&mut self, key: &KeyValue) {
+        let mut hasher = DefaultHasher::new();
+        key.hash(&mut hasher);
+        self.add(hasher.finish());
+    }
+
+    fn add(&mut self, hash: u64) {
+        if let Some(hashes) = &mut self.hashes {
+            hashes.insert(hash);
+            if hashes.len() > SPARSE {
+                self.densify();
+            }
+            return
+        }
+        let index = (hash >> (64 - PRECISION)) as usize;
+        // the bit set past the remaining ones bounds the run
+        let rank = ((hash << PRECISION) | (1 << (PRECISION - 1))).leading_zeros() as u8 + 1;
+        self.registers[index] = self.registers[index].max(rank);
+    }
+
+    fn densify(&mut self) {
+        if let Some(hashes) = self.hashes.take() {
+            self.registers = vec![0; 1 << PRECISION];
+            for hash in hashes {
+                self.add(hash);
+            }
+        }
+    }
+
+    pub fn merge(&mut self, other: &HyperLogLog) {
+        match &other.hashes {
+            Some(hashes) => for hash in hashes {
+                self.add(*hash);
+            },
+            None => {
+                self.densify();
+                for (register, other) in self.registers.iter_mut().zip(&other.registers) {
+                    *register = (*register).max(*other);
+                }
+            }
+        }
+    }
+
+    // while registers are still empty, counting them is closer than the harmonic mean of the runs
+    pub fn estimate(&self) -> i64 {
+        if let Some(hashes) = &self.hashes {
+            return hashes.len() as i64
+        }
+        let m = self.registers.len() as f64;
+        let sum: f64 = self.registers.iter().map(|register| 2f64.powi(-(*register as i32))).sum();
+        let estimate = 0.7213 / (1.0 + 1.079 / m) * m * m / sum;
+        let empty = self.registers.iter().filter(|register| **register == 0).count();
+        let estimate = match estimate <= 2.5 * m && empty > 0 {
+            true => m * (m / empty as f64).ln(),
+            false => estimate
+        };
+        estimate.round() as i64
+    }
+}
+
+impl Default for HyperLogLog {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// larger compressions keep more centroids and give closer quantiles
+const COMPRESSION: f64 = 100.0;
+const BUFFERED: usize = 500;
+
+#[derive(Debug, Clone, Copy)]
+struct Centroid {
+    mean: f64,
+    weight: f64
+}
+
+// t-digest: the values are merged into centroids, small ones near the extremes and larger ones in the middle, so
+// that the tails keep their precision; new values are buffered and merged in batches
+#[derive(Debug, Clone)]
+pub struct TDigest {
+    centroids: Vec<Centroid>,
+    buffer: Vec<Centroid>,
+    count: f64,
+    min: f64,
+    max: f64
+}
+
+impl TDigest {
+    pub fn new() -> Self {
+        TDigest { centroids: Vec::new(), buffer: Vec::new(), count: 0.0, min: f64::INFINITY, max: f64::NEG_INFINITY }
+    }
+
+    pub fn insert(&mut self, value: f64) {
+        self.buffer.push(Centroid { mean: value, weight: 1.0 });
+        self.count += 1.0;
+        self.min = self.min.min(value);
+        self.max = self.max.max(value);
+        if self.buffer.len() >= BUFFERED {
+            self.compress();
+        }
+    }
+
+    pub fn merge(&mut self, other: &TDigest) {
+        self.buffer.extend(other.centroids.iter().chain(&other.buffer));
+        self.count += other.count;
+        self.min = self.min.min(other.min);
+        self.max = self.max.max(other.max);
+        self.compress();
+    }
+
+    // neighbouring centroids merge as long as they span one unit of the scale, which is steep near the extremes
+    fn compress(&mut self) {
+        let mut all: Vec<Centroid> = self.centroids.drain(..).chain(self.buffer.drain(..)).collect();
+        all.sort_by(|left, right| left.mean.total_cmp(&right.mean));
+        let scale = |quantile: f64| COMPRESSION / (2.0 * PI) * (2.0 * quantile - 1.0).clamp(-1.0, 1.0).asin();
+        let mut all = all.into_iter();
+        let Some(mut current) = all.next() else { return };
+        let mut before = 0.0;
+        for next in all {
+            let span = scale((before + current.weight + next.weight) / self.count) - scale(before / self.count);
+            if span <= 1.0 {
+                current.mean += (next.mean - current.mean) * next.weight / (current.weight + next.weight);
+                current.weight += next.weight;
+            } else {
+                before += current.weight;
+                self.centroids.push(current);
+                current = next;
+            }
+        }
+        self.centroids.push(current);
+    }
+
+    // interpolates between the centres of the centroids around the rank, and towards the smallest and largest
+    // values past the first and the last centre
+    pub fn quantile(&mut self, quantile: f64) -> Option<f64> {
+        if !self.buffer.is_empty() {
+            self.compress();
+        }
+        let first = self.centroids.first()?;
+        let last = self.centroids.last()?;
+        let rank = quantile * self.count;
+        if rank <= first.weight / 2.0 {
+            return Some(self.min + (first.mean - self.min) * rank / (first.weight / 2.0))
+        }
+        if rank >= self.count - last.weight / 2.0 {
+            let past = rank - (self.count - last.weight / 2.0);
+            return Some(last.mean + (self.max - last.mean) * past / (last.weight / 2.0))
+        }
+        let mut centre = first.weight / 2.0;
+        for pair in self.centroids.windows(2) {
+            let next = centre + (pair[0].weight + pair[1].weight) / 2.0;
+            if rank <= next {
+                return Some(pair[0].mean + (pair[1].mean - pair[0].mean) * (rank - centre) / (next - centre))
+            }
+            centre = next;
+        }
+        Some(last.mean)
+    }
+}
+
+impl Default for TDigest {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// space-saving: a fixed number of counters, a value without one takes over the smallest and counts on from it, so
+// counts are overestimated by the count of the value they replaced at most
+#[derive(Debug, Clone)]
+pub struct SpaceSaving {
+    capacity: usize,
+    positions: HashMap<KeyValue, usize>,
+    counters: Vec<(Value, u64)>
+}
+
+impl SpaceSaving {
+    pub fn new(capacity: usize) -> Self {
+        SpaceSaving { capacity: capacity.max(1), positions: HashMap::new(), counters: Vec::new() }
+    }
+
+    pub fn insert(&mut self, value: Value) {
+        let key = KeyValue::from(&value);
+        if let Some(position) = self.positions.get(&key) {
+            self.counters[*position].1 += 1;
+        } else if self.counters.len() < self.capacity {
+            self.positions.insert(key, self.counters.len());
+            self.counters.push((value, 1));
+        } else {
+            let (position, count) = self.smallest();
+            self.positions.remove(&KeyValue::from(&self.counters[position].0));
+            self.positions.insert(key, position);
+            self.counters[position] = (value, count + 1);
+        }
+    }
+
+    // a value missing from one side may have been counted up to that side's smallest counter there, once it is full
+    pub fn merge(&mut self, other: &SpaceSaving) {
+        let floor = |summary: &SpaceSaving| match summary.counters.len() < summary.capacity {
+            true => 0,
+            false => summary.smallest().1
+        };
+        let (own_floor, other_floor) = (floor(self), floor(other));
+        let mut counters: Vec<(Value, u64)> = self.counters.iter()
+            .map(|(value, count)| {
+                let other_count = other.positions.get(&KeyValue::from(value)).map(|position| other.counters[*position].1);
+                (value.clone(), count + other_count.unwrap_or(other_floor))
+            })
+            .collect();
+        counters.extend(other.counters.iter()
+            .filter(|(value, _)| !self.positions.contains_key(&KeyValue::from(value)))
+            .map(|(value, count)| (value.clone(), count + own_floor)));
+        counters.sort_by_key(|(_, count)| Reverse(*count));
+        counters.truncate(self.capacity);
+        self.positions = counters.iter().enumerate().map(|(position, (value, _))| (KeyValue::from(value), position)).collect();
+        self.counters = counters;
+    }
+
+    // the position and the count of the first smallest counter
+    fn smallest(&self) -> (usize, u64) {
+        self.counters.iter()
+            .enumerate()
+            .fold((0, u64::MAX), |smallest, (position, (_, count))| if *count < smallest.1 { (position, *count) } else { smallest })
+    }
+
+    // the k values counted most, the first counted first among those counted as often
+    pub fn top(&self, k: usize) -> Vec<Value> {
+        let mut counters: Vec<&(Value, u64)> = self.counters.iter().collect();
+        counters.sort_by_key(|(_, count)| Reverse(*count));
+        counters.into_iter().take(k).map(|(value, _)| value.clone()).collect()
+    }
+}
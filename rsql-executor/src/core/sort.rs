@@ -0,0 +1,338 @@
+use std::cell::RefCell;
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter, Read, Write};
+use std::path::PathBuf;
+use std::rc::Rc;
+use std::sync::atomic::{self, AtomicUsize};
+use chrono::{Datelike, NaiveDate};
+use rsql_parser::ast::constructs::Value;
+use crate::core::errors::ExecutorError;
+use crate::core::evaluator::{evaluate, sort_order};
+use crate::core::executor::RowIterator;
+use crate::core::metrics::row_size;
+use crate::core::plan::SortKey;
+
+// a row together with the values of its sort keys
+type Keyed = (Vec<Value>, Vec<Value>);
+
+type KeyedIterator = Box<dyn Iterator<Item=Result<Keyed, ExecutorError>>>;
+
+// numbers the files of the runs written by this process
+static NEXT_RUN: AtomicUsize = AtomicUsize::new(0);
+
+// runs read at once by a merge, more runs are first merged into fewer ones in passes
+const MERGE_FAN_IN: usize = 64;
+
+// sorts rows in memory as long as they fit the budget, past it the buffered rows are written to a temporary file
+// as a sorted run and the runs are merged once the input has ended
+pub struct Sorter {
+    keys: Vec<SortKey>,
+    budget: Option<usize>,
+    buffer: Vec<Keyed>,
+    memory: usize,
+    runs: Vec<Run>
+}
+
+impl Sorter {
+    pub fn new(keys: Vec<SortKey>, budget: Option<usize>) -> Self {
+        Sorter { keys, budget, buffer: Vec::new(), memory: 0, runs: Vec::new() }
+    }
+
+    pub fn push(&mut self, values: Vec<Value>) -> Result<(), ExecutorError> {
+        let key = self.keys.iter()
+            .map(|key| evaluate(&key.expr, &values))
+            .collect::<Result<Vec<Value>, ExecutorError>>()?;
+        self.memory += row_size(&key) + row_size(&values);
+        self.buffer.push((key, values));
+        if self.budget.is_some_and(|budget| self.memory > budget) {
+            self.spill()?;
+        }
+        Ok(())
+    }
+
+    // bytes of the rows held in memory, those written to runs are not counted
+    pub fn memory(&self) -> usize {
+        self.memory
+    }
+
+    pub fn runs(&self) -> usize {
+        self.runs.len()
+    }
+
+    pub fn finish(mut self) -> Result<RowIterator, ExecutorError> {
+        self.sort_buffer()?;
+        if self.runs.is_empty() {
+            return Ok(Box::new(self.buffer.into_iter().map(|(_, values)| Ok(values))))
+        }
+        // consecutive runs are merged into one, which keeps rows of equal keys in the order they came in
+        let mut runs = self.runs;
+        while runs.len() >= MERGE_FAN_IN {
+            let mut merged = Vec::new();
+            let mut pending = runs.into_iter();
+            loop {
+                let group: Vec<Run> = pending.by_ref().take(MERGE_FAN_IN).collect();
+                if group.is_empty() {
+                    break
+                }
+                let sources = group.into_iter().map(|run| Ok(Box::new(run.read()?) as KeyedIterator)).collect::<Result<_, ExecutorError>>()?;
+                merged.push(Run::write(Merge::new(self.keys.clone(), sources))?);
+            }
+            runs = merged;
+        }
+        // the rows still in memory take part in the merge as one more run, without being written
+        let mut sources: Vec<KeyedIterator> = Vec::new();
+        for run in runs {
+            sources.push(Box::new(run.read()?));
+        }
+        sources.push(Box::new(self.buffer.into_iter().map(Ok)));
+        Ok(Box::new(Merge::new(self.keys, sources).map(|row| row.map(|(_, values)| values))))
+    }
+
+    fn sort_buffer(&mut self) -> Result<(), ExecutorError> {
+        // values of one column can still be incomparable when its type is ANY, the first failure is kept
+        let mut failure = None;
+        self.buffer.sort_by(|(left, _), (right, _)| compare_keys(&self.keys, left, right).unwrap_or_else(|e| {
+            failure.get_or_insert(e);
+            Ordering::Equal
+        }));
+        failure.map_or(Ok(()), Err)
+    }
+
+    fn spill(&mut self) -> Result<(), ExecutorError> {
+        self.sort_buffer()?;
+        self.runs.push(Run::write(std::mem::take(&mut self.buffer).into_iter().map(Ok))?);
+        self.memory = 0;
+        Ok(())
+    }
+}
+
+pub(crate) fn compare_keys(keys: &[SortKey], left: &[Value], right: &[Value]) -> Result<Ordering, ExecutorError> {
+    for ((l, r), key) in left.iter().zip(right.iter()).zip(keys.iter()) {
+        match sort_order(l, r, key.asc)? {
+            Ordering::Equal => continue,
+            ordering => return Ok(ordering)
+        }
+    }
+    Ok(Ordering::Equal)
+}
+
+// yields the smallest of the next rows of the sorted sources, the earliest source first among equal keys
+struct Merge {
+    comparison: Rc<Comparison>,
+    sources: Vec<KeyedIterator>,
+    // the next row of each source which has one, filled on the first call
+    heads: BinaryHeap<Head>,
+    started: bool,
+    failed: bool
+}
+
+// the keys the heads of a merge are compared on, with the first comparison which failed
+struct Comparison {
+    keys: Vec<SortKey>,
+    failure: RefCell<Option<ExecutorError>>
+}
+
+struct Head {
+    key: Vec<Value>,
+    values: Vec<Value>,
+    source: usize,
+    comparison: Rc<Comparison>
+}
+
+impl Ord for Head {
+    // the heap yields its greatest element, which is the smallest key
+    fn cmp(&self, other: &Self) -> Ordering {
+        let ordering = compare_keys(&self.comparison.keys, &self.key, &other.key).unwrap_or_else(|e| {
+            self.comparison.failure.borrow_mut().get_or_insert(e);
+            Ordering::Equal
+        });
+        ordering.then(self.source.cmp(&other.source)).reverse()
+    }
+}
+
+impl PartialOrd for Head {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl PartialEq for Head {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Head {}
+
+impl Merge {
+    fn new(keys: Vec<SortKey>, sources: Vec<KeyedIterator>) -> Self {
+        let comparison = Rc::new(Comparison { keys, failure: RefCell::new(None) });
+        Merge { comparison, sources, heads: BinaryHeap::new(), started: false, failed: false }
+    }
+
+    fn advance(&mut self, source: usize) -> Result<(), ExecutorError> {
+        if let Some((key, values)) = self.sources[source].next().transpose()? {
+            self.heads.push(Head { key, values, source, comparison: Rc::clone(&self.comparison) });
+        }
+        Ok(())
+    }
+
+    fn next_row(&mut self) -> Result<Option<Keyed>, ExecutorError> {
+        if !self.started {
+            self.started = true;
+            for source in 0..self.sources.len() {
+                self.advance(source)?;
+            }
+        }
+        let Some(head) = self.heads.pop() else { return Ok(None) };
+        self.advance(head.source)?;
+        if let Some(e) = self.comparison.failure.take() {
+            return Err(e)
+        }
+        Ok(Some((head.key, head.values)))
+    }
+}
+
+impl Iterator for Merge {
+    type Item = Result<Keyed, ExecutorError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.failed {
+            return None
+        }
+        let row = self.next_row().transpose();
+        self.failed = matches!(row, Some(Err(_)));
+        row
+    }
+}
+
+// sorted rows written to a temporary file, which is removed once the run has been read or dropped
+struct Run {
+    file: SpillFile,
+    rows: usize
+}
+
+struct SpillFile {
+    path: PathBuf
+}
+
+impl Drop for SpillFile {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.path);
+    }
+}
+
+impl Run {
+    fn write(rows: impl Iterator<Item=Result<Keyed, ExecutorError>>) -> Result<Run, ExecutorError> {
+        let run = NEXT_RUN.fetch_add(1, atomic::Ordering::Relaxed);
+        let file = SpillFile { path: std::env::temp_dir().join(format!("rsql-sort-{}-{}.run", std::process::id(), run)) };
+        let mut out = BufWriter::new(File::create(&file.path).map_err(|e| spill_error(&file, e))?);
+        let mut count = 0;
+        for row in rows {
+            let (key, values) = row?;
+            write_values(&mut out, &key)
+                .and_then(|_| write_values(&mut out, &values))
+                .map_err(|e| spill_error(&file, e))?;
+            count += 1;
+        }
+        out.flush().map_err(|e| spill_error(&file, e))?;
+        Ok(Run { file, rows: count })
+    }
+
+    fn read(self) -> Result<RunReader, ExecutorError> {
+        let input = File::open(&self.file.path).map_err(|e| spill_error(&self.file, e))?;
+        Ok(RunReader { input: BufReader::new(input), remaining: self.rows, file: self.file })
+    }
+}
+
+struct RunReader {
+    input: BufReader<File>,
+    remaining: usize,
+    file: SpillFile
+}
+
+impl Iterator for RunReader {
+    type Item = Result<Keyed, ExecutorError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None
+        }
+        self.remaining -= 1;
+        let row = read_values(&mut self.input).and_then(|key| Ok((key, read_values(&mut self.input)?)));
+        Some(row.map_err(|e| {
+            self.remaining = 0;
+            spill_error(&self.file, e)
+        }))
+    }
+}
+
+fn spill_error(file: &SpillFile, e: io::Error) -> ExecutorError {
+    ExecutorError { message: format!("could not spill sorted rows to {}: {}", file.path.display(), e) }
+}
+
+// a count followed by the values, each a tag and its bytes in little endian
+fn write_values(out: &mut impl Write, values: &[Value]) -> io::Result<()> {
+    out.write_all(&(values.len() as u32).to_le_bytes())?;
+    for value in values {
+        match value {
+            Value::Null => out.write_all(&[0])?,
+            Value::Bool(b) => out.write_all(&[1, *b as u8])?,
+            Value::Int(i) => {
+                out.write_all(&[2])?;
+                out.write_all(&i.to_le_bytes())?;
+            },
+            Value::Float(f) => {
+                out.write_all(&[3])?;
+                out.write_all(&f.to_bits().to_le_bytes())?;
+            },
+            Value::String(s) => {
+                out.write_all(&[4])?;
+                out.write_all(&(s.len() as u64).to_le_bytes())?;
+                out.write_all(s.as_bytes())?;
+            },
+            Value::Date(d) => {
+                out.write_all(&[5])?;
+                out.write_all(&d.num_days_from_ce().to_le_bytes())?;
+            },
+            Value::List(values) => {
+                out.write_all(&[6])?;
+                write_values(out, values)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+fn read_values(input: &mut impl Read) -> io::Result<Vec<Value>> {
+    let count = u32::from_le_bytes(read_bytes(input)?);
+    let mut values = Vec::with_capacity(count as usize);
+    for _ in 0..count {
+        let [tag] = read_bytes(input)?;
+        values.push(match tag {
+            0 => Value::Null,
+            1 => Value::Bool(read_bytes::<1>(input)?[0] != 0),
+            2 => Value::Int(i64::from_le_bytes(read_bytes(input)?)),
+            3 => Value::Float(f64::from_bits(u64::from_le_bytes(read_bytes(input)?))),
+            4 => {
+                let mut bytes = vec![0; u64::from_le_bytes(read_bytes(input)?) as usize];
+                input.read_exact(&mut bytes)?;
+                Value::String(String::from_utf8(bytes).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?)
+            },
+            5 => NaiveDate::from_num_days_from_ce_opt(i32::from_le_bytes(read_bytes(input)?))
+                .map(Value::Date)
+                .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "date out of range"))?,
+            6 => Value::List(read_values(input)?),
+            tag => return Err(io::Error::new(io::ErrorKind::InvalidData, format!("unknown value tag {}", tag)))
+        });
+    }
+    Ok(values)
+}
+
+fn read_bytes<const N: usize>(input: &mut impl Read) -> io::Result<[u8; N]> {
+    let mut bytes = [0; N];
+    input.read_exact(&mut bytes)?;
+    Ok(bytes)
+}
@@ -0,0 +1,257 @@
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use chrono::Datelike;
+use rsql_parser::ast::constructs::{AggregateFunc, FrameBound, FrameUnits, Value, WindowFunc};
+use crate::core::aggregate::{Accumulator, GroupKey, KeyValue};
+use crate::core::errors::ExecutorError;
+use crate::core::evaluator::evaluate;
+use crate::core::plan::WindowCall;
+use crate::core::sort::compare_keys;
+
+// computes every window function over the rows and appends its value to each of them, the rows keep their order;
+// the calls only read the columns of the input
+pub fn evaluate_windows(mut rows: Vec<Vec<Value>>, calls: &[WindowCall]) -> Result<Vec<Vec<Value>>, ExecutorError> {
+    let mut columns = Vec::new();
+    for call in calls {
+        columns.push(evaluate_window(&rows, call)?);
+    }
+    for column in columns {
+        for (values, value) in rows.iter_mut().zip(column) {
+            values.push(value);
+        }
+    }
+    Ok(rows)
+}
+
+// splits the rows into partitions, sorts each of them and computes the value of each of its rows
+fn evaluate_window(rows: &[Vec<Value>], call: &WindowCall) -> Result<Vec<Value>, ExecutorError> {
+    let mut partitions: Vec<Vec<usize>> = Vec::new();
+    let mut positions: HashMap<GroupKey, usize> = HashMap::new();
+    for (index, values) in rows.iter().enumerate() {
+        let key = call.partition_by.iter()
+            .map(|expr| evaluate(expr, values).map(|value| KeyValue::from(&value)))
+            .collect::<Result<GroupKey, ExecutorError>>()?;
+        let position = *positions.entry(key).or_insert_with(|| {
+            partitions.push(Vec::new());
+            partitions.len() - 1
+        });
+        partitions[position].push(index);
+    }
+
+    let mut values = vec![Value::Null; rows.len()];
+    for indices in partitions {
+        let mut keyed = Vec::new();
+        for index in indices {
+            let keys = call.order_by.iter()
+                .map(|key| evaluate(&key.expr, &rows[index]))
+                .collect::<Result<Vec<Value>, ExecutorError>>()?;
+            keyed.push((keys, index));
+        }
+        // the sort is stable, peers keep the order of the input
+        let mut failure = None;
+        keyed.sort_by(|(left, _), (right, _)| compare_keys(&call.order_by, left, right).unwrap_or_else(|e| {
+            failure.get_or_insert(e);
+            Ordering::Equal
+        }));
+        if let Some(e) = failure {
+            return Err(e)
+        }
+
+        let (keys, indices): (Vec<Vec<Value>>, Vec<usize>) = keyed.into_iter().unzip();
+        let partition = Partition::new(indices.iter().map(|index| rows[*index].as_slice()).collect(), keys, call)?;
+        for (index, value) in indices.into_iter().zip(partition.values()?) {
+            values[index] = value;
+        }
+    }
+    Ok(values)
+}
+
+// the rows of one partition in the order of the window
+struct Partition<'a> {
+    rows: Vec<&'a [Value]>,
+    call: &'a WindowCall,
+    // first and past the last position of the rows sorted like the row at each position
+    peers: Vec<(usize, usize)>,
+    // the only sort key as a number, for RANGE offsets; NULL and values which are not numbers or dates are None
+    ordinals: Vec<Option<f64>>
+}
+
+impl<'a> Partition<'a> {
+    fn new(rows: Vec<&'a [Value]>, keys: Vec<Vec<Value>>, call: &'a WindowCall) -> Result<Self, ExecutorError> {
+        let mut peers = Vec::new();
+        let mut start = 0;
+        for position in 1..=keys.len() {
+            if position == keys.len() || compare_keys(&call.order_by, &keys[start], &keys[position])? != Ordering::Equal {
+                peers.extend(std::iter::repeat_n((start, position), position - start));
+                start = position;
+            }
+        }
+        let ordinals = keys.iter().map(|keys| keys.first().and_then(ordinal)).collect();
+
+        Ok(Partition { rows, call, peers, ordinals })
+    }
+
+    fn values(&self) -> Result<Vec<Value>, ExecutorError> {
+        let count = self.rows.len();
+        match &self.call.func {
+            WindowFunc::RowNumber => Ok((1..=count).map(|number| Value::Int(number as i64)).collect()),
+            WindowFunc::Rank => Ok(self.peers.iter().map(|(start, _)| Value::Int(*start as i64 + 1)).collect()),
+            WindowFunc::DenseRank => {
+                let mut rank = 0;
+                Ok(self.peers.iter()
+                    .enumerate()
+                    .map(|(position, (start, _))| {
+                        if position == *start {
+                            rank += 1;
+                        }
+                        Value::Int(rank)
+                    })
+                    .collect())
+            },
+            // the first `larger` buckets hold one row more than the others
+            WindowFunc::Ntile => {
+                let buckets = self.constant(0, 1)?.max(1);
+                let (size, larger) = (count / buckets, count % buckets);
+                Ok((0..count)
+                    .map(|position| {
+                        let bucket = match position < larger * (size + 1) {
+                            true => position / (size + 1),
+                            false => larger + (position - larger * (size + 1)) / size
+                        };
+                        Value::Int(bucket as i64 + 1)
+                    })
+                    .collect())
+            },
+            WindowFunc::Lag | WindowFunc::Lead => {
+                let offset = self.constant(1, 1)?;
+                (0..count)
+                    .map(|position| {
+                        let other = match self.call.func {
+                            WindowFunc::Lag => position.checked_sub(offset),
+                            _ => position.checked_add(offset).filter(|other| *other < count)
+                        };
+                        match (other, self.call.args.get(2)) {
+                            (Some(other), _) => self.arg(other),
+                            (None, Some(default)) => evaluate(default, self.rows[position]),
+                            (None, None) => Ok(Value::Null)
+                        }
+                    })
+                    .collect()
+            },
+            WindowFunc::FirstValue | WindowFunc::LastValue => {
+                (0..count)
+                    .map(|position| match self.frame(position) {
+                        (start, end) if start >= end => Ok(Value::Null),
+                        (start, _) if self.call.func == WindowFunc::FirstValue => self.arg(start),
+                        (_, end) => self.arg(end - 1)
+                    })
+                    .collect()
+            },
+            WindowFunc::Aggregate(func) => self.aggregate(func)
+        }
+    }
+
+    // a frame which starts with the partition only grows from one row to the next, its aggregate is carried over
+    // instead of computed again for each row
+    fn aggregate(&self, func: &AggregateFunc) -> Result<Vec<Value>, ExecutorError> {
+        let inputs = self.rows.iter()
+            .map(|values| self.call.args.first().map(|arg| evaluate(arg, values)).transpose())
+            .collect::<Result<Vec<Option<Value>>, ExecutorError>>()?;
+        let running = self.call.frame.start == FrameBound::UnboundedPreceding;
+        let mut accumulator = Accumulator::new(func);
+        let mut added = 0;
+        let mut values = Vec::new();
+        for position in 0..self.rows.len() {
+            let (start, end) = self.frame(position);
+            if !running {
+                accumulator = Accumulator::new(func);
+                added = start;
+            }
+            while added < end {
+                accumulator.update(inputs[added].clone())?;
+                added += 1;
+            }
+            values.push(accumulator.clone().finish());
+        }
+        Ok(values)
+    }
+
+    // the value of the first argument for the row at the position
+    fn arg(&self, position: usize) -> Result<Value, ExecutorError> {
+        match self.call.args.first() {
+            Some(arg) => evaluate(arg, self.rows[position]),
+            None => Ok(Value::Null)
+        }
+    }
+
+    // a constant argument such as the offset of LAG, which the analyzer checked to be a non-negative integer
+    fn constant(&self, index: usize, default: usize) -> Result<usize, ExecutorError> {
+        match self.call.args.get(index).map(|arg| evaluate(arg, &[])).transpose()? {
+            None => Ok(default),
+            Some(Value::Int(value)) if value >= 0 => Ok(value as usize),
+            Some(value) => Err(ExecutorError { message: format!("{} expects a non-negative integer, found {}", self.call.func, value) })
+        }
+    }
+
+    // the positions of the rows in the frame of the row at the position, from the start up to the end excluded
+    fn frame(&self, position: usize) -> (usize, usize) {
+        let count = self.rows.len();
+        let start = self.bound(position, &self.call.frame.start, false);
+        let end = self.bound(position, &self.call.frame.end, true);
+        (start.min(count), end.min(count))
+    }
+
+    // the first position of the frame, or the one past its last row for the end
+    fn bound(&self, position: usize, bound: &FrameBound, end: bool) -> usize {
+        let (start, past) = self.peers[position];
+        match (bound, self.call.frame.units) {
+            (FrameBound::UnboundedPreceding, _) => 0,
+            (FrameBound::UnboundedFollowing, _) => self.rows.len(),
+            (FrameBound::CurrentRow, FrameUnits::Rows) => position + end as usize,
+            (FrameBound::CurrentRow, FrameUnits::Range) => if end { past } else { start },
+            (FrameBound::Preceding(offset), FrameUnits::Rows) => (position + end as usize).saturating_sub(rows_offset(offset)),
+            (FrameBound::Following(offset), FrameUnits::Rows) => (position + end as usize).saturating_add(rows_offset(offset)),
+            (FrameBound::Preceding(offset) | FrameBound::Following(offset), FrameUnits::Range) => {
+                let preceding = matches!(bound, FrameBound::Preceding(_));
+                self.range_bound(position, offset, preceding, end)
+            }
+        }
+    }
+
+    // rows whose key is within the offset of that of the current row, in the direction of the sort; a NULL key
+    // is only within reach of the other NULLs, its peers
+    fn range_bound(&self, position: usize, offset: &Value, preceding: bool, end: bool) -> usize {
+        let (start, past) = self.peers[position];
+        let Some(current) = self.ordinals[position] else {
+            return if end { past } else { start }
+        };
+        let asc = self.call.order_by.first().is_none_or(|key| key.asc);
+        // NULLs sort last in ascending order and first in descending order
+        let nulls_first = !asc;
+        let directed = |value: f64| if asc { value } else { -value };
+        let offset = ordinal(offset).unwrap_or(0.0);
+        let target = directed(current) + if preceding { -offset } else { offset };
+        self.ordinals.partition_point(|key| match key {
+            None => nulls_first,
+            Some(key) if end => directed(*key) <= target,
+            Some(key) => directed(*key) < target
+        })
+    }
+}
+
+fn rows_offset(offset: &Value) -> usize {
+    match offset {
+        Value::Int(offset) => usize::try_from(*offset).unwrap_or(0),
+        _ => 0
+    }
+}
+
+// dates count in days
+fn ordinal(value: &Value) -> Option<f64> {
+    match value {
+        Value::Int(value) => Some(*value as f64),
+        Value::Float(value) => Some(*value),
+        Value::Date(date) => Some(date.num_days_from_ce() as f64),
+        _ => None
+    }
+}
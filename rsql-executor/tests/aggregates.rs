@@ -0,0 +1,202 @@
+use rsql_executor::core::aggregate::Accumulator;
+use rsql_parser::ast::constructs::{AggregateFunc, Value};
+
+mod common;
+
+use common::{error, rows, string};
+
+const EMPLOYEES: &str = "'tests/test_data/employees.csv'";
+
+#[test]
+fn variance_and_standard_deviation_of_samples_and_populations() {
+    let sql = format!(
+        "SELECT department, VARIANCE(salary), VAR_POP(salary), STDDEV_SAMP(salary), STDDEV_POP(salary) FROM {} \
+         GROUP BY department ORDER BY department;",
+        EMPLOYEES
+    );
+    assert_eq!(rows(&sql), vec![
+        vec![string("Engineering"), Value::Float(312500000.0), Value::Float(156250000.0), Value::Float(312500000f64.sqrt()), Value::Float(12500.0)],
+        // a single value has no sample variance
+        vec![string("Marketing"), Value::Null, Value::Float(0.0), Value::Null, Value::Float(0.0)],
+        vec![string("Sales"), Value::Float(12500000.0), Value::Float(6250000.0), Value::Float(12500000f64.sqrt()), Value::Float(2500.0)]
+    ]);
+}
+
+#[test]
+fn variance_keeps_its_precision_far_from_zero() {
+    // the sum of the squares of these values is too large for the deviations to survive in it
+    let sql = format!("SELECT VARIANCE(salary + 1000000000000) FROM {};", EMPLOYEES);
+    let Value::Float(variance) = rows(&sql).remove(0).remove(0) else { panic!("Expected a float") };
+    assert!((variance - 492500000.0).abs() < 1e-3, "variance was {}", variance);
+}
+
+#[test]
+fn median_and_percentiles() {
+    let sql = format!(
+        "SELECT MEDIAN(salary), PERCENTILE_CONT(salary, 0.25), PERCENTILE_CONT(salary, 0.9), PERCENTILE_DISC(salary, 0.5), \
+         PERCENTILE_DISC(name, 0) FROM {};",
+        EMPLOYEES
+    );
+    assert_eq!(rows(&sql), vec![vec![
+        Value::Float(80000.0), Value::Float(70000.0), Value::Float(110000.0), Value::Int(80000), string("Alice")
+    ]]);
+
+    let sql = format!(
+        "SELECT PERCENTILE_CONT(0.25) WITHIN GROUP (ORDER BY salary), PERCENTILE_DISC(0.5) WITHIN GROUP (ORDER BY salary ASC) FROM {};",
+        EMPLOYEES
+    );
+    assert_eq!(rows(&sql), vec![vec![Value::Float(70000.0), Value::Int(80000)]]);
+
+    let sql = format!("SELECT MEDIAN(salary) FROM {} WHERE age > 100;", EMPLOYEES);
+    assert_eq!(rows(&sql), vec![vec![Value::Null]]);
+}
+
+#[test]
+fn collection_aggregates() {
+    let sql = format!(
+        "SELECT department, STRING_AGG(name, ', '), ARRAY_AGG(salary), MODE(active) FROM {} GROUP BY department ORDER BY department;",
+        EMPLOYEES
+    );
+    assert_eq!(rows(&sql), vec![
+        // ARRAY_AGG keeps the NULLs, which the other aggregates skip
+        vec![
+            string("Engineering"), string("Alice, Bob, Frank"),
+            Value::List(vec![Value::Int(120000), Value::Int(95000), Value::Null]), Value::Bool(true)
+        ],
+        vec![string("Marketing"), string("Eve"), Value::List(vec![Value::Int(80000)]), Value::Bool(true)],
+        // ties go to the value seen first
+        vec![string("Sales"), string("Carol, Dave"), Value::List(vec![Value::Int(70000), Value::Int(65000)]), Value::Bool(false)]
+    ]);
+
+    let sql = format!("SELECT MODE(department), STRING_AGG(DISTINCT department, '|') FROM {};", EMPLOYEES);
+    assert_eq!(rows(&sql), vec![vec![string("Engineering"), string("Engineering|Sales|Marketing")]]);
+}
+
+#[test]
+fn boolean_aggregates_and_count_if() {
+    let sql = format!(
+        "SELECT department, BOOL_AND(active), BOOL_OR(active), COUNT_IF(age > 35) FROM {} GROUP BY department ORDER BY department;",
+        EMPLOYEES
+    );
+    assert_eq!(rows(&sql), vec![
+        vec![string("Engineering"), Value::Bool(false), Value::Bool(true), Value::Int(1)],
+        vec![string("Marketing"), Value::Bool(true), Value::Bool(true), Value::Int(0)],
+        vec![string("Sales"), Value::Bool(false), Value::Bool(true), Value::Int(2)]
+    ]);
+
+    let sql = format!("SELECT COUNT_IF(active) FROM {} WHERE age > 100;", EMPLOYEES);
+    assert_eq!(rows(&sql), vec![vec![Value::Int(0)]]);
+}
+
+#[test]
+fn new_aggregates_work_over_windows() {
+    let sql = format!(
+        "SELECT name, STRING_AGG(name, '+') OVER (PARTITION BY department ORDER BY age) FROM {} WHERE department = 'Sales' ORDER BY name;",
+        EMPLOYEES
+    );
+    assert_eq!(rows(&sql), vec![
+        vec![string("Carol"), string("Dave+Carol")],
+        vec![string("Dave"), string("Dave")]
+    ]);
+}
+
+#[test]
+fn arguments_of_the_wrong_type_are_rejected() {
+    assert_eq!(error(&format!("SELECT STDDEV(name) FROM {};", EMPLOYEES)), "STDDEV expects a numeric argument, found VARCHAR");
+    assert_eq!(error(&format!("SELECT BOOL_AND(salary) FROM {};", EMPLOYEES)), "BOOL_AND expects a boolean argument, found INTEGER");
+    assert_eq!(error(&format!("SELECT STRING_AGG(age, ',') FROM {};", EMPLOYEES)), "STRING_AGG expects a string argument, found INTEGER");
+}
+
+// the numbers 1 to 10000, from the cross join of 1 to 100 with itself
+const NUMBERS: &str = "WITH RECURSIVE n(x) AS (SELECT id FROM 'tests/test_data/org.csv' WHERE id = 1 UNION ALL SELECT x + 1 FROM n WHERE x < 100) \
+                       SELECT (a.x - 1) * 100 + b.x AS number FROM n AS a CROSS JOIN n AS b";
+
+fn float(sql: &str) -> f64 {
+    match rows(sql).remove(0).remove(0) {
+        Value::Float(value) => value,
+        Value::Int(value) => value as f64,
+        other => panic!("Expected a number, got {:?}", other)
+    }
+}
+
+#[test]
+fn approximate_count_distinct() {
+    let sql = format!("SELECT APPROX_COUNT_DISTINCT(department), APPROX_COUNT_DISTINCT(salary) FROM {};", EMPLOYEES);
+    assert_eq!(rows(&sql), vec![vec![Value::Int(3), Value::Int(5)]]);
+
+    let estimate = float(&format!("SELECT APPROX_COUNT_DISTINCT(number) FROM ({}) AS numbers;", NUMBERS));
+    assert!((estimate - 10000.0).abs() < 500.0, "estimate was {}", estimate);
+    // 1 and 1.0 are the same value
+    let estimate = float(&format!("SELECT APPROX_COUNT_DISTINCT(number % 100 * 1.0) FROM ({}) AS numbers;", NUMBERS));
+    assert_eq!(estimate, 100.0);
+}
+
+#[test]
+fn approximate_percentiles() {
+    let sql = format!("SELECT APPROX_PERCENTILE(salary, 0.5), APPROX_PERCENTILE(salary, 0) FROM {};", EMPLOYEES);
+    assert_eq!(rows(&sql), vec![vec![Value::Float(80000.0), Value::Float(65000.0)]]);
+
+    for (fraction, exact) in [(0.5, 5000.0), (0.99, 9900.0), (0.001, 10.0)] {
+        let estimate = float(&format!("SELECT APPROX_PERCENTILE(number, {}) FROM ({}) AS numbers;", fraction, NUMBERS));
+        assert!((estimate - exact).abs() < 50.0, "estimate of {} was {}", fraction, estimate);
+    }
+}
+
+#[test]
+fn approximate_top_k() {
+    let sql = format!("SELECT APPROX_TOP_K(department, 2) FROM {};", EMPLOYEES);
+    assert_eq!(rows(&sql), vec![vec![Value::List(vec![string("Engineering"), string("Sales")])]]);
+
+    // number % 1000 / 10 leaves 100 values seen 100 times each, the 3 multiples of 400 are seen 10 times more
+    let sql = format!(
+        "SELECT APPROX_TOP_K(CASE WHEN number % 400 = 0 THEN number ELSE number % 1000 / 10 + 20000 END, 3) FROM ({}) AS numbers;",
+        NUMBERS
+    );
+    let Value::List(mut top) = rows(&sql).remove(0).remove(0) else { panic!("Expected a list") };
+    top.sort_by_key(|value| match value { Value::Int(value) => *value, _ => 0 });
+    assert_eq!(top.len(), 3);
+    assert!(top.iter().all(|value| matches!(value, Value::Int(value) if *value >= 20000)));
+}
+
+#[test]
+fn states_merge_into_the_state_of_all_the_rows() {
+    let funcs = [
+        AggregateFunc::Sum, AggregateFunc::Avg, AggregateFunc::Variance, AggregateFunc::Median, AggregateFunc::Mode,
+        AggregateFunc::StringAgg(",".to_string()), AggregateFunc::ApproxCountDistinct, AggregateFunc::ApproxPercentile(0.9),
+        AggregateFunc::ApproxTopK(2)
+    ];
+    // a third of the values are 0 or 1, the others spread over enough values for the count of distinct values to
+    // leave its exact phase
+    let values: Vec<Value> = (0..2000)
+        .map(|i| Value::Int(if i % 3 == 0 { ((i / 3) % 10 >= 6) as i64 } else { i * 7919 % 1501 }))
+        .collect();
+    for func in funcs {
+        let values: Vec<Value> = match func {
+            AggregateFunc::StringAgg(_) => values.iter().map(|value| Value::String(value.to_string())).collect(),
+            _ => values.clone()
+        };
+        let mut whole = Accumulator::new(&func);
+        let (mut left, mut right) = (Accumulator::new(&func), Accumulator::new(&func));
+        for (position, value) in values.into_iter().enumerate() {
+            whole.update(Some(value.clone())).unwrap();
+            match position < 700 {
+                true => left.update(Some(value)).unwrap(),
+                false => right.update(Some(value)).unwrap()
+            }
+        }
+        left.merge(right).unwrap();
+        // the centroids of a t-digest depend on the order they were merged in
+        let tolerance = if matches!(func, AggregateFunc::ApproxPercentile(_)) { 1e-2 } else { 1e-9 };
+        match (whole.finish(), left.finish()) {
+            (Value::Float(whole), Value::Float(merged)) => {
+                assert!((whole - merged).abs() < tolerance * whole.abs(), "{} gave {} and {}", func, whole, merged)
+            },
+            (whole, merged) => assert_eq!(whole, merged, "{}", func)
+        }
+    }
+
+    assert_eq!(
+        Accumulator::new(&AggregateFunc::Sum).merge(Accumulator::new(&AggregateFunc::Max)).unwrap_err().message,
+        "can not merge the states of different aggregates"
+    );
+}
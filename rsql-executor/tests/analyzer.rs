@@ -0,0 +1,157 @@
+use rcsv_parser::parser::entities::csv_parser::CsvParser;
+use rsql_executor::core::errors::ExecutorError;
+use rsql_executor::core::executor::Executor;
+use rsql_executor::core::plan::{LogicalPlan, ResolvedExpr};
+use rsql_executor::core::schema::DataType;
+use rsql_parser::ast::constructs::Statement;
+use rsql_parser::lexer::tokenizer::tokenize;
+use rsql_parser::parser::parser::Parser;
+
+fn plan(sql: &str) -> Result<LogicalPlan, ExecutorError> {
+    let mut executor = Executor::new(Box::from(CsvParser{}));
+    let Statement::Select(select) = Parser::new(tokenize(sql)).parse().unwrap() else { panic!("Expected select statement") };
+    executor.plan(&select)
+}
+
+fn error(sql: &str) -> String {
+    match plan(sql) {
+        Ok(plan) => panic!("Expected an error, got {:?}", plan),
+        Err(e) => e.message
+    }
+}
+
+#[test]
+fn infers_column_types_from_sample() {
+    let mut executor = Executor::new(Box::from(CsvParser{}));
+    let schema = executor.read_schema("tests/test_data/employees.csv").unwrap();
+    let types: Vec<DataType> = schema.fields.iter().map(|field| field.data_type).collect();
+    assert_eq!(types, vec![DataType::String, DataType::String, DataType::Int, DataType::Int, DataType::Bool]);
+}
+
+#[test]
+fn binds_columns_to_indices() {
+    let plan = plan("SELECT salary, name FROM 'tests/test_data/employees.csv' WHERE age > 30;").unwrap();
+    let LogicalPlan::Project { input, exprs, schema } = plan else { panic!("Expected a projection") };
+    assert_eq!(exprs[0], ResolvedExpr::Column { index: 2, name: "salary".to_string(), data_type: DataType::Int });
+    assert_eq!(exprs[1], ResolvedExpr::Column { index: 0, name: "name".to_string(), data_type: DataType::String });
+    assert_eq!(schema.names(), vec!["salary".to_string(), "name".to_string()]);
+    assert!(matches!(*input, LogicalPlan::Filter { .. }));
+}
+
+#[test]
+fn reports_unknown_column_with_suggestion() {
+    assert_eq!(
+        error("SELECT salry FROM 'tests/test_data/employees.csv';"),
+        "column `salry` not found; did you mean `salary`?"
+    );
+    assert_eq!(
+        error("SELECT name FROM 'tests/test_data/employees.csv' WHERE xyz = 1;"),
+        "column `xyz` not found"
+    );
+}
+
+#[test]
+fn rejects_type_mismatches() {
+    assert_eq!(
+        error("SELECT name FROM 'tests/test_data/employees.csv' WHERE name > 10;"),
+        "cannot compare VARCHAR with INTEGER using >"
+    );
+    assert_eq!(
+        error("SELECT name + 1 FROM 'tests/test_data/employees.csv';"),
+        "operator + can not be applied to VARCHAR and INTEGER"
+    );
+    assert_eq!(
+        error("SELECT name FROM 'tests/test_data/employees.csv' WHERE salary;"),
+        "WHERE condition must be BOOLEAN, found INTEGER"
+    );
+    assert_eq!(
+        error("SELECT SUM(name) FROM 'tests/test_data/employees.csv';"),
+        "SUM expects a numeric argument, found VARCHAR"
+    );
+}
+
+#[test]
+fn validates_aggregate_usage() {
+    assert_eq!(
+        error("SELECT name, COUNT(*) FROM 'tests/test_data/employees.csv';"),
+        "column `name` must appear in the GROUP BY clause or be used in an aggregate function"
+    );
+    assert_eq!(
+        error("SELECT name FROM 'tests/test_data/employees.csv' WHERE SUM(salary) > 10;"),
+        "aggregate function SUM is not allowed in WHERE"
+    );
+    assert_eq!(
+        error("SELECT SUM(MAX(salary)) FROM 'tests/test_data/employees.csv';"),
+        "aggregate function calls can not be nested: SUM(MAX(salary))"
+    );
+    assert!(plan("SELECT department, SUM(salary) / COUNT(*) FROM 'tests/test_data/employees.csv' GROUP BY department;").is_ok());
+}
+
+#[test]
+fn checks_predicate_operands() {
+    assert_eq!(
+        error("SELECT name FROM 'tests/test_data/employees.csv' WHERE age IN (1, 'two');"),
+        "cannot compare INTEGER with VARCHAR using IN"
+    );
+    assert_eq!(
+        error("SELECT name FROM 'tests/test_data/employees.csv' WHERE age LIKE '3%';"),
+        "LIKE expects VARCHAR operands, found INTEGER"
+    );
+    assert!(plan("SELECT name FROM 'tests/test_data/employees.csv' WHERE salary IS NOT NULL AND age BETWEEN 20 AND 30.5;").is_ok());
+}
+
+#[test]
+fn checks_regular_expressions_and_function_calls() {
+    assert!(error("SELECT name FROM 'tests/test_data/employees.csv' WHERE name REGEXP '(a';")
+        .starts_with("invalid regular expression '(a'"));
+    assert_eq!(
+        error("SELECT name FROM 'tests/test_data/employees.csv' WHERE age RLIKE '4.';"),
+        "REGEXP expects VARCHAR operands, found INTEGER"
+    );
+    assert_eq!(
+        error("SELECT regexp_extrat(name, 'a') FROM 'tests/test_data/employees.csv';"),
+        "unknown function `regexp_extrat`, did you mean REGEXP_EXTRACT?"
+    );
+    assert_eq!(
+        error("SELECT regexp_replace(name, 'a') FROM 'tests/test_data/employees.csv';"),
+        "REGEXP_REPLACE expects 3 arguments, found 2"
+    );
+    assert_eq!(
+        error("SELECT regexp_extract(name, 'a', 'b') FROM 'tests/test_data/employees.csv';"),
+        "argument 3 of REGEXP_EXTRACT must be INTEGER, found VARCHAR"
+    );
+}
+
+#[test]
+fn checks_conditional_expressions() {
+    assert_eq!(
+        error("SELECT CASE WHEN age > 30 THEN name ELSE age END FROM 'tests/test_data/employees.csv';"),
+        "CASE results must have a common type, found VARCHAR and INTEGER"
+    );
+    assert_eq!(
+        error("SELECT CASE WHEN age THEN 1 END FROM 'tests/test_data/employees.csv';"),
+        "CASE WHEN condition must be BOOLEAN, found INTEGER"
+    );
+    assert_eq!(
+        error("SELECT CASE name WHEN 1 THEN 1 END FROM 'tests/test_data/employees.csv';"),
+        "cannot compare VARCHAR with INTEGER using CASE"
+    );
+    assert_eq!(
+        error("SELECT IF(active, 1) FROM 'tests/test_data/employees.csv';"),
+        "IF expects 3 arguments, found 2"
+    );
+    assert_eq!(
+        error("SELECT COALESCE(salary, name) FROM 'tests/test_data/employees.csv';"),
+        "arguments of COALESCE must have a common type, found INTEGER and VARCHAR"
+    );
+}
+
+#[test]
+fn rejects_casts_which_can_never_succeed() {
+    assert_eq!(
+        error("SELECT CAST(order_date AS INTEGER) FROM 'tests/test_data/orders.csv';"),
+        "cannot cast DATE to INTEGER"
+    );
+    let plan = plan("SELECT TRY_CAST(customer AS DATE) FROM 'tests/test_data/orders.csv';").unwrap();
+    assert_eq!(plan.schema().fields[0].data_type, DataType::Date);
+}
@@ -0,0 +1,35 @@
+use chrono::NaiveDate;
+use rsql_executor::core::cast::{can_cast, cast};
+use rsql_executor::core::schema::DataType;
+use rsql_parser::ast::constructs::Value;
+
+fn string(value: &str) -> Value {
+    Value::String(value.to_string())
+}
+
+#[test]
+fn converts_between_every_type() {
+    let date = Value::Date(NaiveDate::from_ymd_opt(2024, 2, 29).unwrap());
+    assert_eq!(cast(string(" 42 "), DataType::Int).unwrap(), Value::Int(42));
+    assert_eq!(cast(Value::Float(2.5), DataType::Int).unwrap(), Value::Int(3));
+    assert_eq!(cast(Value::Float(-2.5), DataType::Int).unwrap(), Value::Int(-3));
+    assert_eq!(cast(Value::Bool(true), DataType::Int).unwrap(), Value::Int(1));
+    assert_eq!(cast(string("1e3"), DataType::Float).unwrap(), Value::Float(1000.0));
+    assert_eq!(cast(Value::Int(7), DataType::Float).unwrap(), Value::Float(7.0));
+    assert_eq!(cast(string("Yes"), DataType::Bool).unwrap(), Value::Bool(true));
+    assert_eq!(cast(Value::Int(0), DataType::Bool).unwrap(), Value::Bool(false));
+    assert_eq!(cast(string("2024-02-29"), DataType::Date).unwrap(), date.clone());
+    assert_eq!(cast(date, DataType::String).unwrap(), string("2024-02-29"));
+    assert_eq!(cast(Value::Float(1.5), DataType::String).unwrap(), string("1.5"));
+    assert_eq!(cast(Value::Null, DataType::Date).unwrap(), Value::Null);
+}
+
+#[test]
+fn reports_values_which_do_not_convert() {
+    assert_eq!(cast(string("12a"), DataType::Int).unwrap_err().message, "cannot cast '12a' to INTEGER");
+    assert_eq!(cast(Value::Float(1e30), DataType::Int).unwrap_err().message, "cannot cast 1000000000000000000000000000000 to INTEGER");
+    assert_eq!(cast(string("2024-02-30"), DataType::Date).unwrap_err().message, "cannot cast '2024-02-30' to DATE");
+    assert_eq!(cast(string("maybe"), DataType::Bool).unwrap_err().message, "cannot cast 'maybe' to BOOLEAN");
+    assert!(!can_cast(DataType::Date, DataType::Int));
+    assert!(can_cast(DataType::String, DataType::Date));
+}
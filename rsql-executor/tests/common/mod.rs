@@ -0,0 +1,47 @@
+// helpers shared by the integration tests, each test file uses some of them
+#![allow(dead_code)]
+
+use rcsv_parser::parser::entities::csv_parser::CsvParser;
+use rsql_executor::core::dataframe::DataFrame;
+use rsql_executor::core::errors::ExecutorError;
+use rsql_executor::core::executor::Executor;
+use rsql_executor::core::settings::Settings;
+use rsql_parser::ast::constructs::Value;
+use rsql_parser::lexer::tokenizer::tokenize;
+use rsql_parser::parser::parser::Parser;
+
+pub fn run(sql: &str) -> Result<DataFrame, ExecutorError> {
+    run_with(sql, Settings::default())
+}
+
+pub fn run_with(sql: &str, settings: Settings) -> Result<DataFrame, ExecutorError> {
+    let mut executor = Executor::new(Box::from(CsvParser{}));
+    executor.settings = settings;
+    executor.execute(Parser::new(tokenize(sql)).parse().unwrap())
+}
+
+pub fn rows(sql: &str) -> Vec<Vec<Value>> {
+    rows_with(sql, Settings::default())
+}
+
+pub fn rows_with(sql: &str, settings: Settings) -> Vec<Vec<Value>> {
+    match run_with(sql, settings) {
+        Ok(data_frame) => data_frame.rows.into_iter().map(|row| row.values).collect(),
+        Err(e) => panic!("Expected valid result, got error {}", e.message)
+    }
+}
+
+pub fn error(sql: &str) -> String {
+    error_with(sql, Settings::default())
+}
+
+pub fn error_with(sql: &str, settings: Settings) -> String {
+    match run_with(sql, settings) {
+        Ok(_) => panic!("Expected an error"),
+        Err(e) => e.message
+    }
+}
+
+pub fn string(value: &str) -> Value {
+    Value::String(value.to_string())
+}
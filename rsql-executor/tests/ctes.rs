@@ -0,0 +1,95 @@
+use rsql_parser::ast::constructs::Value;
+
+mod common;
+
+use common::{error, rows, string};
+
+fn plan(sql: &str) -> Vec<String> {
+    rows(&format!("EXPLAIN {}", sql)).into_iter()
+        .map(|mut row| match row.remove(0) {
+            Value::String(line) => line.trim().to_string(),
+            other => panic!("Expected plan line, got {:?}", other)
+        })
+        .collect()
+}
+
+const TOTALS: &str = "WITH big AS (SELECT customer, amount FROM 'tests/test_data/orders.csv' WHERE amount > 60), \
+    totals AS (SELECT customer, SUM(amount) AS total FROM big GROUP BY customer)";
+
+const CHAIN: &str = "WITH RECURSIVE chain(id, name, level) AS (\
+    SELECT id, name, 0 FROM 'tests/test_data/org.csv' WHERE manager_id IS NULL \
+    UNION ALL \
+    SELECT o.id, o.name, c.level + 1 FROM 'tests/test_data/org.csv' AS o JOIN chain AS c ON o.manager_id = c.id)";
+
+#[test]
+fn ctes_read_the_ones_before_them() {
+    let sql = format!("{} SELECT customer, total FROM totals ORDER BY customer;", TOTALS);
+    assert_eq!(rows(&sql), vec![
+        vec![string("acme"), Value::Int(320)],
+        vec![string("globex"), Value::Int(155)]
+    ]);
+}
+
+#[test]
+fn cte_read_once_is_inlined_and_one_read_twice_is_materialized() {
+    let once = plan(&format!("{} SELECT customer FROM totals;", TOTALS));
+    assert!(!once.iter().any(|line| line.starts_with("MaterializeExec") || line.starts_with("CteScanExec")));
+
+    let twice = format!(
+        "{} SELECT a.customer, b.customer FROM totals AS a JOIN totals AS b ON a.total < b.total;", TOTALS
+    );
+    let lines = plan(&twice);
+    assert!(lines.contains(&"MaterializeExec: totals".to_string()));
+    assert_eq!(lines.iter().filter(|line| *line == "CteScanExec: totals").count(), 2);
+    assert_eq!(rows(&twice), vec![vec![string("globex"), string("acme")]]);
+}
+
+#[test]
+fn cte_columns_can_be_renamed() {
+    let sql = "WITH t(who, n) AS (SELECT customer, COUNT(*) FROM 'tests/test_data/orders.csv' GROUP BY customer) \
+               SELECT who, n FROM t WHERE n > 1 ORDER BY who;";
+    assert_eq!(rows(sql), vec![
+        vec![string("acme"), Value::Int(3)],
+        vec![string("globex"), Value::Int(2)]
+    ]);
+    assert_eq!(
+        error("WITH t(who) AS (SELECT customer, amount FROM 'tests/test_data/orders.csv') SELECT who FROM t;"),
+        "common table expression `t` names 1 columns but its query returns 2"
+    );
+}
+
+#[test]
+fn recursive_cte_walks_a_hierarchy() {
+    let sql = format!("{} SELECT name, level FROM chain ORDER BY level, name;", CHAIN);
+    assert_eq!(rows(&sql), vec![
+        vec![string("Ada"), Value::Int(0)],
+        vec![string("Barbara"), Value::Int(0)],
+        vec![string("Grace"), Value::Int(1)],
+        vec![string("Linus"), Value::Int(1)],
+        vec![string("Ken"), Value::Int(2)],
+        vec![string("Dennis"), Value::Int(3)]
+    ]);
+    assert!(plan(&format!("{} SELECT name FROM chain;", CHAIN)).contains(&"RecursiveCteExec: chain UNION ALL".to_string()));
+}
+
+#[test]
+fn recursive_cte_stops_once_union_adds_no_new_rows() {
+    let start = "SELECT id FROM 'tests/test_data/org.csv' WHERE id = 1";
+    let sql = format!("WITH RECURSIVE n(x) AS ({} UNION SELECT x FROM n) SELECT x FROM n;", start);
+    assert_eq!(rows(&sql), vec![vec![Value::Int(1)]]);
+
+    let sql = format!("WITH RECURSIVE n(x) AS ({} UNION ALL SELECT x + 1 FROM n) SELECT x FROM n;", start);
+    assert_eq!(error(&sql), "recursive common table expression `n` still adds rows after 1000 steps");
+}
+
+#[test]
+fn recursive_cte_parts_must_match() {
+    assert_eq!(
+        error("WITH RECURSIVE n(x) AS (SELECT id FROM 'tests/test_data/org.csv' UNION ALL SELECT x, x FROM n) SELECT x FROM n;"),
+        "recursive common table expression `n` returns 1 columns in its first part and 2 in its recursive part"
+    );
+    assert_eq!(
+        error("WITH RECURSIVE n(x) AS (SELECT id FROM 'tests/test_data/org.csv' UNION SELECT x FROM n LIMIT 3) SELECT x FROM n;"),
+        "recursive common table expression `n` can not have LIMIT"
+    );
+}
@@ -0,0 +1,20 @@
+use rsql_executor::core::evaluator::like;
+
+#[test]
+fn like_wildcards() {
+    assert!(like("Sheila", "Sh%"));
+    assert!(like("Sheila", "%ei%a"));
+    assert!(like("Sheila", "S_e_la"));
+    assert!(like("", "%"));
+    assert!(!like("Sheila", "Sh"));
+    assert!(!like("Sheila", "_h"));
+    assert!(like("aaab", "%a%ab"));
+}
+
+#[test]
+fn like_escapes() {
+    assert!(like("100%", "100\\%"));
+    assert!(!like("1000", "100\\%"));
+    assert!(like("a_b", "a\\_b"));
+    assert!(!like("axb", "a\\_b"));
+}
@@ -6,18 +6,20 @@ use rsql_parser::ast::constructs::{BinaryOperator, Expr, FromClause, SelectItem,
 #[test]
 fn fetch_all_data() {
     let csv_parser = CsvParser{};
-    let mut executor = Executor {
-        parser: Box::from(csv_parser)
-    };
+    let mut executor = Executor::new(Box::from(csv_parser));
     let select_statement = Statement::Select(SelectStatement {
-        columns: vec![SelectItem::Column("First Name".to_string()),
-        ],
-        from: Some(FromClause {
+        with: None,
+        distinct: false,
+        columns: vec![SelectItem::Wildcard],
+        from: Some(FromClause::File {
             source: "tests/test_data/test-data.csv".to_string(),
+            alias: None,
         }),
         where_clause: None,
         group_by: None,
+        set_operations: Vec::new(),
         order_by: None,
+        limit: None,
     });
     let response  = executor.execute(select_statement);
     match response {
@@ -43,46 +45,34 @@ fn fetch_all_data() {
 #[test]
 fn test_where() {
     let csv_parser = CsvParser{};
-    let mut executor = Executor {
-        parser: Box::from(csv_parser)
-    };
+    let mut executor = Executor::new(Box::from(csv_parser));
     let where_clause = Expr::BinaryOp {
         left: Box::from(Expr::Column("Gender".to_string())),
         operator: BinaryOperator::Equals,
         right: Box::from(Expr::Literal(Value::String("Female".to_string()))),
     };
     let select_statement = Statement::Select(SelectStatement {
+        with: None,
+        distinct: false,
         columns: vec![SelectItem::Column("First Name".to_string()),
         ],
-        from: Some(FromClause {
+        from: Some(FromClause::File {
             source: "tests/test_data/test-data-where.csv".to_string(),
+            alias: None,
         }),
         where_clause: Some(where_clause),
         group_by: None,
+        set_operations: Vec::new(),
         order_by: None,
+        limit: None,
     });
     let response  = executor.execute(select_statement);
     match response {
         Ok(DataFrame { columns, rows }) => {
-            assert_eq!(columns.len(), 3);
-            assert_eq!(columns[0], "First Name".to_string());
-            assert_eq!(columns[1], "Last Name".to_string());
-            assert_eq!(columns[2], "Gender".to_string());
-            assert_eq!(rows.len(), 4);
-            assert_eq!(rows[0].values.len(), 3);
-            assert_eq!(rows[0].values[0], Value::String("Sheila".to_string()));
-            assert_eq!(rows[0].values[1], Value::String("Mahoney".to_string()));
-            assert_eq!(rows[0].values[2], Value::String("Female".to_string()));
-            assert_eq!(rows[1].values.len(), 3);
-            assert_eq!(rows[1].values[0], Value::String("Ram".to_string()));
-            assert_eq!(rows[1].values[1], Value::String("Sharma".to_string()));
-            assert_eq!(rows[1].values[2], Value::String("Male".to_string()));
-            assert_eq!(rows[2].values[0], Value::String("Shyam".to_string()));
-            assert_eq!(rows[2].values[1], Value::String("Singh".to_string()));
-            assert_eq!(rows[2].values[2], Value::String("Male".to_string()));
-            assert_eq!(rows[3].values[0], Value::String("Julie".to_string()));
-            assert_eq!(rows[3].values[1], Value::String("Dsouza".to_string()));
-            assert_eq!(rows[3].values[2], Value::String("Female".to_string()));
+            assert_eq!(columns, vec!["First Name".to_string()]);
+            assert_eq!(rows.len(), 2);
+            assert_eq!(rows[0].values, vec![Value::String("Sheila".to_string())]);
+            assert_eq!(rows[1].values, vec![Value::String("Julie".to_string())]);
         },
         Err(_) => panic!("Expected valid result, got error")
     }
@@ -0,0 +1,56 @@
+use rcsv_parser::parser::entities::csv_parser::CsvParser;
+use rsql_executor::core::executor::Executor;
+use rsql_parser::ast::constructs::Value;
+use rsql_parser::lexer::tokenizer::tokenize;
+use rsql_parser::parser::parser::Parser;
+
+fn explain(sql: &str) -> Vec<String> {
+    let mut executor = Executor::new(Box::from(CsvParser{}));
+    let statement = Parser::new(tokenize(sql)).parse().unwrap();
+    let result = match executor.execute(statement) {
+        Ok(data_frame) => data_frame,
+        Err(e) => panic!("Expected valid result, got error {}", e.message)
+    };
+    assert_eq!(result.columns, vec!["plan".to_string()]);
+    result.rows.into_iter()
+        .map(|row| match &row.values[0] {
+            Value::String(line) => line.clone(),
+            other => panic!("Expected plan line, got {:?}", other)
+        })
+        .collect()
+}
+
+#[test]
+fn explain_prints_logical_and_physical_plans() {
+    let lines = explain("EXPLAIN SELECT department, COUNT(*) FROM 'tests/test_data/employees.csv' WHERE age > 30 GROUP BY department;");
+    assert_eq!(lines, vec![
+        "Logical plan:",
+        "  Project: department#0, COUNT(*)#1",
+        "    Aggregate: group_by=[department#1] aggregates=[COUNT(*)]",
+        "      Scan: tests/test_data/employees.csv projection=[1] filter=(age#3 > 30)",
+        "Optimizer rules: predicate_pushdown, projection_pruning",
+        "Physical plan:",
+        "  ProjectionExec: department#0, COUNT(*)#1",
+        "    HashAggregateExec: group_by=[department#1] aggregates=[COUNT(*)]",
+        "      FileScanExec: tests/test_data/employees.csv projection=[1] filter=(age#3 > 30)",
+    ]);
+}
+
+#[test]
+fn explain_analyze_reports_operator_metrics() {
+    let lines = explain("EXPLAIN ANALYZE SELECT name FROM 'tests/test_data/employees.csv' WHERE age > 30 ORDER BY name;");
+    let physical = lines.iter().position(|line| line == "Physical plan:").unwrap();
+
+    assert!(lines[physical + 1].starts_with("  SortExec: name#0 ASC (rows=5, eval="));
+    assert!(lines[physical + 1].contains("memory="));
+    assert!(lines[physical + 2].starts_with("    ProjectionExec: name#0 (rows=5, eval="));
+    // every data line of the file is read, rows failing the filter included
+    assert!(lines[physical + 3].starts_with(
+        "      FileScanExec: tests/test_data/employees.csv projection=[0] filter=(age#3 > 30) (rows=5, bytes=171, parse="
+    ));
+    assert_eq!(lines[physical + 4], "Rows: 5");
+    assert_eq!(lines[physical + 5], "Bytes read: 171");
+    for (offset, label) in ["Parse time: ", "Evaluation time: ", "Total time: ", "Peak memory: "].iter().enumerate() {
+        assert!(lines[physical + 6 + offset].starts_with(label), "{} missing", label);
+    }
+}
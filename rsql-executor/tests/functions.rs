@@ -0,0 +1,159 @@
+use rsql_executor::core::evaluator::evaluate;
+use rsql_executor::core::functions::resolve;
+use rsql_executor::core::plan::ResolvedExpr;
+use rsql_parser::ast::constructs::Value;
+
+fn call(name: &str, args: Vec<Value>) -> Value {
+    let expr = resolve(name, args.into_iter().map(ResolvedExpr::Literal).collect()).unwrap();
+    evaluate(&expr, &[]).unwrap()
+}
+
+fn string(value: &str) -> Value {
+    Value::String(value.to_string())
+}
+
+#[test]
+fn case_trim_and_length() {
+    assert_eq!(call("upper", vec![string("straße")]), string("STRASSE"));
+    assert_eq!(call("LOWER", vec![string("ÀB")]), string("àb"));
+    assert_eq!(call("trim", vec![string("  a b \t")]), string("a b"));
+    assert_eq!(call("ltrim", vec![string("xxaxx"), string("x")]), string("axx"));
+    assert_eq!(call("rtrim", vec![string("a.,;"), string(";,.")]), string("a"));
+    assert_eq!(call("length", vec![string("héllo")]), Value::Int(5));
+    assert_eq!(call("length", vec![Value::Null]), Value::Null);
+}
+
+#[test]
+fn substring_and_split_part() {
+    assert_eq!(call("substring", vec![string("héllo"), Value::Int(2), Value::Int(3)]), string("éll"));
+    assert_eq!(call("substring", vec![string("hello"), Value::Int(3)]), string("llo"));
+    assert_eq!(call("substring", vec![string("hello"), Value::Int(0), Value::Int(3)]), string("he"));
+    assert_eq!(call("substring", vec![string("hello"), Value::Int(9)]), string(""));
+
+    assert_eq!(call("split_part", vec![string("a,b,,c"), string(","), Value::Int(2)]), string("b"));
+    assert_eq!(call("split_part", vec![string("a,b,,c"), string(","), Value::Int(-1)]), string("c"));
+    assert_eq!(call("split_part", vec![string("a,b,,c"), string(","), Value::Int(3)]), string(""));
+    assert_eq!(call("split_part", vec![string("a,b"), string(","), Value::Int(5)]), string(""));
+}
+
+#[test]
+fn replace_concat_and_padding() {
+    assert_eq!(call("replace", vec![string("a-b-c"), string("-"), string("+")]), string("a+b+c"));
+    assert_eq!(call("concat", vec![string("a"), Value::Null, Value::Int(1), Value::Bool(true)]), string("a1TRUE"));
+    assert_eq!(call("lpad", vec![string("7"), Value::Int(3), string("0")]), string("007"));
+    assert_eq!(call("rpad", vec![string("ab"), Value::Int(5), string("xy")]), string("abxyx"));
+    assert_eq!(call("lpad", vec![string("abcdef"), Value::Int(3)]), string("abc"));
+    assert_eq!(call("rpad", vec![string("ab"), Value::Int(4)]), string("ab  "));
+
+    let expr = resolve("lpad", vec![ResolvedExpr::Literal(string("abc")), ResolvedExpr::Literal(Value::Int(100_000_000_000)), ResolvedExpr::Literal(string("x"))]).unwrap();
+    assert_eq!(evaluate(&expr, &[]).unwrap_err().message, "LPAD length too large, at most 10000000 characters");
+}
+
+#[test]
+fn prefix_and_position() {
+    assert_eq!(call("starts_with", vec![string("Engineering"), string("Eng")]), Value::Bool(true));
+    assert_eq!(call("starts_with", vec![string("Sales"), string("Eng")]), Value::Bool(false));
+    assert_eq!(call("position", vec![string("ll"), string("héllo")]), Value::Int(3));
+    assert_eq!(call("position", vec![string("z"), string("hello")]), Value::Int(0));
+}
+
+#[test]
+fn checks_arguments() {
+    let error = |name: &str, args: Vec<Value>| resolve(name, args.into_iter().map(ResolvedExpr::Literal).collect()).unwrap_err().message;
+    assert_eq!(error("upper", vec![]), "UPPER expects 1 argument, found 0");
+    assert_eq!(error("substring", vec![string("a")]), "SUBSTRING expects 2 to 3 arguments, found 1");
+    assert_eq!(error("concat", vec![]), "CONCAT expects at least 1 argument, found 0");
+    assert_eq!(error("length", vec![Value::Int(1)]), "argument 1 of LENGTH must be VARCHAR, found INTEGER");
+    assert_eq!(error("lenght", vec![string("a")]), "unknown function `lenght`, did you mean LENGTH?");
+    // the targets of the division by zero rewrite are not callable
+    assert_eq!(error("safe_divide", vec![Value::Int(1), Value::Int(0)]), "unknown function `safe_divide`");
+    assert_eq!(error("safe_modul", vec![Value::Int(1), Value::Int(0)]), "unknown function `safe_modul`");
+}
+
+fn date(year: i32, month: u32, day: u32) -> Value {
+    Value::Date(chrono::NaiveDate::from_ymd_opt(year, month, day).unwrap())
+}
+
+#[test]
+fn truncates_and_extracts_date_parts() {
+    assert_eq!(call("date_trunc", vec![string("quarter"), date(2024, 8, 17)]), date(2024, 7, 1));
+    assert_eq!(call("date_trunc", vec![string("WEEK"), date(2024, 8, 17)]), date(2024, 8, 12));
+    assert_eq!(call("date_trunc", vec![string("year"), string("2024-08-17")]), date(2024, 1, 1));
+    assert_eq!(call("extract", vec![string("DOW"), date(2024, 8, 18)]), Value::Int(0));
+    assert_eq!(call("extract", vec![string("DOY"), date(2024, 12, 31)]), Value::Int(366));
+    assert_eq!(call("extract", vec![string("QUARTER"), date(2024, 12, 31)]), Value::Int(4));
+}
+
+#[test]
+fn date_arithmetic() {
+    assert_eq!(call("date_add", vec![string("month"), Value::Int(1), date(2024, 1, 31)]), date(2024, 2, 29));
+    assert_eq!(call("date_add", vec![string("year"), Value::Int(-1), date(2024, 2, 29)]), date(2023, 2, 28));
+    assert_eq!(call("date_add", vec![string("week"), Value::Int(2), date(2024, 12, 25)]), date(2025, 1, 8));
+    assert_eq!(call("date_diff", vec![string("month"), date(2024, 1, 31), date(2024, 2, 1)]), Value::Int(1));
+    assert_eq!(call("date_diff", vec![string("year"), date(2024, 12, 31), date(2023, 1, 1)]), Value::Int(-1));
+    assert_eq!(call("date_diff", vec![string("day"), date(2024, 2, 1), date(2024, 3, 1)]), Value::Int(29));
+}
+
+#[test]
+fn parses_and_formats_dates() {
+    assert_eq!(call("to_date", vec![string("31/01/2024"), string("%d/%m/%Y")]), date(2024, 1, 31));
+    assert_eq!(call("to_date", vec![string("2024-01-31")]), date(2024, 1, 31));
+    assert_eq!(call("strftime", vec![date(2024, 1, 5), string("%Y %b %e")]), string("2024 Jan  5"));
+
+    let failure = |name: &str, args: Vec<Value>| {
+        let expr = resolve(name, args.into_iter().map(ResolvedExpr::Literal).collect()).unwrap();
+        evaluate(&expr, &[]).unwrap_err().message
+    };
+    assert!(failure("to_date", vec![string("2024-13-01")]).starts_with("'2024-13-01' does not match the date format '%Y-%m-%d'"));
+    assert_eq!(failure("strftime", vec![date(2024, 1, 5), string("%H:%M")]), "'%H:%M' is not a valid date format");
+    assert_eq!(
+        failure("date_trunc", vec![string("hour"), date(2024, 1, 5)]),
+        "DATE_TRUNC unit must be one of year, quarter, month, week or day, found 'hour'"
+    );
+}
+
+#[test]
+fn math_functions_keep_integer_results() {
+    assert_eq!(call("abs", vec![Value::Int(-3)]), Value::Int(3));
+    assert_eq!(call("abs", vec![Value::Float(-2.5)]), Value::Float(2.5));
+    assert_eq!(call("round", vec![Value::Float(2.345), Value::Int(2)]), Value::Float(2.35));
+    assert_eq!(call("round", vec![Value::Float(-2.5)]), Value::Float(-3.0));
+    assert_eq!(call("round", vec![Value::Int(1250), Value::Int(-2)]), Value::Int(1300));
+    assert_eq!(call("round", vec![Value::Int(-15), Value::Int(-1)]), Value::Int(-20));
+    assert_eq!(call("floor", vec![Value::Float(-1.5)]), Value::Float(-2.0));
+    assert_eq!(call("ceil", vec![Value::Int(7)]), Value::Int(7));
+    assert_eq!(call("mod", vec![Value::Int(-7), Value::Int(3)]), Value::Int(-1));
+    assert_eq!(call("mod", vec![Value::Float(7.5), Value::Int(2)]), Value::Float(1.5));
+}
+
+#[test]
+fn powers_roots_and_logarithms() {
+    assert_eq!(call("power", vec![Value::Int(2), Value::Int(10)]), Value::Float(1024.0));
+    assert_eq!(call("sqrt", vec![Value::Int(16)]), Value::Float(4.0));
+    assert_eq!(call("ln", vec![Value::Int(1)]), Value::Float(0.0));
+    assert_eq!(call("log", vec![Value::Int(1000)]), Value::Float(3.0));
+    assert_eq!(call("log", vec![Value::Int(2), Value::Int(8)]), Value::Float(3.0));
+
+    let failure = |name: &str, args: Vec<Value>| {
+        let expr = resolve(name, args.into_iter().map(ResolvedExpr::Literal).collect()).unwrap();
+        evaluate(&expr, &[]).unwrap_err().message
+    };
+    assert_eq!(failure("sqrt", vec![Value::Int(-1)]), "cannot take the square root of a negative number");
+    assert_eq!(failure("ln", vec![Value::Int(0)]), "cannot take the logarithm of zero or a negative number");
+    assert_eq!(failure("mod", vec![Value::Int(1), Value::Int(0)]), "division by zero");
+}
+
+#[test]
+fn greatest_and_least_skip_nulls() {
+    assert_eq!(call("greatest", vec![Value::Int(3), Value::Null, Value::Float(2.5)]), Value::Float(3.0));
+    assert_eq!(call("least", vec![string("b"), string("a")]), string("a"));
+    assert_eq!(call("least", vec![Value::Null, Value::Null]), Value::Null);
+    assert_eq!(call("least", vec![Value::Int(1), Value::Float(2.5)]), Value::Float(1.0));
+    assert_eq!(call("coalesce", vec![Value::Null, Value::Int(1), Value::Float(2.5)]), Value::Float(1.0));
+    assert_eq!(call("greatest", vec![Value::Int(1), Value::Int(2)]), Value::Int(2));
+
+    let args = vec![ResolvedExpr::Literal(Value::Int(1)), ResolvedExpr::Literal(string("a"))];
+    assert_eq!(resolve("greatest", args).unwrap_err().message, "arguments of GREATEST must have a common type, found INTEGER and VARCHAR");
+    let expr = resolve("abs", vec![ResolvedExpr::Literal(Value::Int(-1))]).unwrap();
+    assert_eq!(expr.data_type(), rsql_executor::core::schema::DataType::Int);
+}
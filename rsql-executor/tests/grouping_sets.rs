@@ -0,0 +1,125 @@
+use rsql_parser::ast::constructs::Value;
+
+mod common;
+
+use common::{error, rows, string};
+
+const EMPLOYEES: &str = "'tests/test_data/employees.csv'";
+
+#[test]
+fn rollup_adds_subtotals_and_a_grand_total() {
+    let sql = format!(
+        "SELECT department, active, COUNT(*) FROM {} GROUP BY ROLLUP(department, active) ORDER BY department, active;",
+        EMPLOYEES
+    );
+    assert_eq!(rows(&sql), vec![
+        vec![string("Engineering"), Value::Bool(false), Value::Int(1)],
+        vec![string("Engineering"), Value::Bool(true), Value::Int(2)],
+        vec![string("Engineering"), Value::Null, Value::Int(3)],
+        vec![string("Marketing"), Value::Bool(true), Value::Int(1)],
+        vec![string("Marketing"), Value::Null, Value::Int(1)],
+        vec![string("Sales"), Value::Bool(false), Value::Int(1)],
+        vec![string("Sales"), Value::Bool(true), Value::Int(1)],
+        vec![string("Sales"), Value::Null, Value::Int(2)],
+        vec![Value::Null, Value::Null, Value::Int(6)]
+    ]);
+}
+
+#[test]
+fn cube_groups_by_every_subset() {
+    let sql = format!(
+        "SELECT department, active, COUNT(*), GROUPING(department, active) AS level FROM {} \
+         GROUP BY CUBE(department, active) ORDER BY level, department, active;",
+        EMPLOYEES
+    );
+    let rows = rows(&sql);
+    let levels: Vec<Value> = rows.iter().map(|row| row[3].clone()).collect();
+    assert_eq!(levels, [0, 0, 0, 0, 0, 1, 1, 1, 2, 2, 3].map(Value::Int).to_vec());
+    assert_eq!(rows[8..].to_vec(), vec![
+        vec![Value::Null, Value::Bool(false), Value::Int(2), Value::Int(2)],
+        vec![Value::Null, Value::Bool(true), Value::Int(4), Value::Int(2)],
+        vec![Value::Null, Value::Null, Value::Int(6), Value::Int(3)]
+    ]);
+}
+
+#[test]
+fn grouping_sets_are_aggregated_in_one_pass() {
+    let sql = format!(
+        "SELECT department, active, SUM(salary) FROM {} GROUP BY GROUPING SETS ((department), (active), ()) \
+         ORDER BY GROUPING(department), GROUPING(active), department, active;",
+        EMPLOYEES
+    );
+    assert_eq!(rows(&sql), vec![
+        vec![string("Engineering"), Value::Null, Value::Int(215000)],
+        vec![string("Marketing"), Value::Null, Value::Int(80000)],
+        vec![string("Sales"), Value::Null, Value::Int(135000)],
+        vec![Value::Null, Value::Bool(false), Value::Int(70000)],
+        vec![Value::Null, Value::Bool(true), Value::Int(360000)],
+        vec![Value::Null, Value::Null, Value::Int(430000)]
+    ]);
+
+    // a plain expression is part of every set
+    let sql = format!(
+        "SELECT department, active, COUNT(*) FROM {} WHERE department = 'Sales' \
+         GROUP BY department, ROLLUP(active) ORDER BY active;",
+        EMPLOYEES
+    );
+    assert_eq!(rows(&sql), vec![
+        vec![string("Sales"), Value::Bool(false), Value::Int(1)],
+        vec![string("Sales"), Value::Bool(true), Value::Int(1)],
+        vec![string("Sales"), Value::Null, Value::Int(2)]
+    ]);
+}
+
+#[test]
+fn grouping_tells_subtotals_from_null_groups() {
+    let sql = format!(
+        "SELECT CASE WHEN GROUPING(salary) = 1 THEN 'total' ELSE 'group' END, salary, COUNT(*) FROM {} \
+         WHERE department = 'Engineering' GROUP BY ROLLUP(salary) ORDER BY GROUPING(salary), salary;",
+        EMPLOYEES
+    );
+    assert_eq!(rows(&sql), vec![
+        vec![string("group"), Value::Int(95000), Value::Int(1)],
+        vec![string("group"), Value::Int(120000), Value::Int(1)],
+        vec![string("group"), Value::Null, Value::Int(1)],
+        vec![string("total"), Value::Null, Value::Int(3)]
+    ]);
+
+    // without grouping sets every row is grouped by all the expressions
+    let sql = format!("SELECT department, GROUPING(department) FROM {} GROUP BY department ORDER BY department;", EMPLOYEES);
+    assert!(rows(&sql).iter().all(|row| row[1] == Value::Int(0)));
+}
+
+#[test]
+fn empty_grouping_sets_return_a_row_without_input() {
+    let sql = format!("SELECT department, COUNT(*) FROM {} WHERE age > 100 GROUP BY ROLLUP(department);", EMPLOYEES);
+    assert_eq!(rows(&sql), vec![vec![Value::Null, Value::Int(0)]]);
+}
+
+#[test]
+fn explain_shows_the_grouping_sets() {
+    let sql = format!("EXPLAIN SELECT department, active, COUNT(*) FROM {} GROUP BY ROLLUP(department, active);", EMPLOYEES);
+    let lines: Vec<Value> = rows(&sql).into_iter().map(|mut row| row.remove(0)).collect();
+    assert!(lines.iter().any(|line| matches!(line, Value::String(line)
+        if line.contains("grouping_sets=[(department#1, active#4), (department#1), ()]"))));
+}
+
+#[test]
+fn invalid_grouping_is_rejected() {
+    assert_eq!(
+        error(&format!("SELECT department, GROUPING(age) FROM {} GROUP BY ROLLUP(department);", EMPLOYEES)),
+        "arguments of GROUPING must be grouping expressions, found age"
+    );
+    assert_eq!(
+        error(&format!("SELECT name FROM {} WHERE GROUPING(name) = 0;", EMPLOYEES)),
+        "GROUPING is only allowed over the groups of a query, not in WHERE"
+    );
+    assert_eq!(
+        error(&format!(
+            "SELECT COUNT(*) FROM {} GROUP BY CUBE(name, department, salary, age, active), \
+             CUBE(name, department, salary, age, active), CUBE(name, age, active);",
+            EMPLOYEES
+        )),
+        "GROUP BY can not expand to more than 4096 grouping sets"
+    );
+}
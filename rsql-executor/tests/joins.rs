@@ -0,0 +1,212 @@
+use rsql_executor::core::settings::Settings;
+use rsql_parser::ast::constructs::Value;
+
+mod common;
+
+use common::{error, error_with, rows, rows_with, run, string};
+
+fn plan(sql: &str) -> Vec<String> {
+    plan_with(sql, Settings::default())
+}
+
+fn plan_with(sql: &str, settings: Settings) -> Vec<String> {
+    rows_with(sql, settings).into_iter()
+        .map(|mut row| match row.remove(0) {
+            Value::String(line) => line,
+            other => panic!("Expected plan line, got {:?}", other)
+        })
+        .collect()
+}
+
+fn with_budget(bytes: usize) -> Settings {
+    Settings { memory_budget: Some(bytes), ..Settings::default() }
+}
+
+const ORDERS_AND_CUSTOMERS: &str = "'tests/test_data/orders.csv' AS o {} 'tests/test_data/customers.csv' AS c";
+
+fn join(kind: &str) -> String {
+    ORDERS_AND_CUSTOMERS.replace("{}", kind)
+}
+
+#[test]
+fn inner_join_matches_rows_on_qualified_columns() {
+    let sql = format!("SELECT o.order_id, c.city FROM {} ON o.customer = c.customer ORDER BY o.order_id;", join("JOIN"));
+    assert_eq!(rows(&sql), vec![
+        vec![Value::Int(1), string("Berlin")],
+        vec![Value::Int(2), string("Paris")],
+        vec![Value::Int(3), string("Berlin")],
+        vec![Value::Int(5), string("Paris")],
+        vec![Value::Int(6), string("Berlin")]
+    ]);
+}
+
+#[test]
+fn outer_joins_pad_rows_without_a_match() {
+    let left = format!("SELECT o.order_id, c.city FROM {} ON o.customer = c.customer WHERE o.order_id = 4;", join("LEFT JOIN"));
+    assert_eq!(rows(&left), vec![vec![Value::Int(4), Value::Null]]);
+
+    let right = format!("SELECT c.customer, o.order_id FROM {} ON o.customer = c.customer WHERE o.order_id IS NULL;", join("RIGHT OUTER JOIN"));
+    assert_eq!(rows(&right), vec![vec![string("hooli"), Value::Null]]);
+
+    let full = format!(
+        "SELECT o.order_id, c.customer FROM {} ON o.customer = c.customer WHERE o.order_id IS NULL OR c.customer IS NULL;",
+        join("FULL OUTER JOIN")
+    );
+    assert_eq!(rows(&full), vec![
+        vec![Value::Int(4), Value::Null],
+        vec![Value::Null, string("hooli")]
+    ]);
+}
+
+#[test]
+fn cross_join_pairs_every_row() {
+    let sql = format!("SELECT COUNT(*) FROM {};", join("CROSS JOIN"));
+    assert_eq!(rows(&sql), vec![vec![Value::Int(18)]]);
+}
+
+#[test]
+fn conditions_beyond_equal_keys() {
+    // an equality plus a residual filter
+    let sql = format!("SELECT o.order_id FROM {} ON o.customer = c.customer AND o.amount > 100;", join("JOIN"));
+    assert_eq!(rows(&sql), vec![vec![Value::Int(1)], vec![Value::Int(3)]]);
+
+    // without an equality the rows are compared in a nested loop
+    let sql = format!("SELECT c.customer, COUNT(*) FROM {} ON o.order_id < c.since - 2018 GROUP BY c.customer;", join("JOIN"));
+    assert_eq!(rows(&sql), vec![vec![string("globex"), Value::Int(2)], vec![string("hooli"), Value::Int(4)]]);
+
+    let sql = format!("SELECT c.customer, o.order_id FROM {} ON o.order_id < c.since - 2018 AND c.customer = 'acme';", join("LEFT JOIN"));
+    assert_eq!(rows(&sql).len(), 6);
+}
+
+#[test]
+fn aggregates_over_joined_rows() {
+    let sql = format!("SELECT c.city, SUM(o.amount) FROM {} ON o.customer = c.customer GROUP BY c.city ORDER BY c.city;", join("JOIN"));
+    assert_eq!(rows(&sql), vec![
+        vec![string("Berlin"), Value::Int(320)],
+        vec![string("Paris"), Value::Int(155)]
+    ]);
+}
+
+#[test]
+fn wildcard_keeps_columns_of_both_tables() {
+    let sql = format!("SELECT * FROM {} ON o.customer = c.customer WHERE o.order_id = 2;", join("JOIN"));
+    let result = run(&sql).unwrap();
+    assert_eq!(result.columns, vec!["order_id", "customer", "order_date", "amount", "customer", "city", "since"]);
+    assert_eq!(result.rows[0].values[4], string("globex"));
+}
+
+#[test]
+fn table_names_qualify_columns_without_an_alias() {
+    let sql = "SELECT orders.order_id FROM 'tests/test_data/orders.csv' JOIN 'tests/test_data/customers.csv' \
+               ON orders.customer = customers.customer WHERE customers.city = 'Paris';";
+    assert_eq!(rows(sql), vec![vec![Value::Int(2)], vec![Value::Int(5)]]);
+}
+
+#[test]
+fn reports_ambiguous_and_repeated_names() {
+    let sql = format!("SELECT customer FROM {} ON o.customer = c.customer;", join("JOIN"));
+    assert_eq!(error(&sql), "column reference `customer` is ambiguous, it could be o.customer or c.customer");
+    // the columns made by UNPIVOT have no table name
+    let sql = "SELECT amount FROM 'tests/test_data/monthly.csv' UNPIVOT (amount FOR month IN (jan)) \
+               JOIN 'tests/test_data/orders.csv' AS o ON o.amount = 10;";
+    assert_eq!(error(sql), "column reference `amount` is ambiguous, it could be amount or o.amount");
+
+    let sql = "SELECT * FROM 'tests/test_data/orders.csv' JOIN 'tests/test_data/orders.csv' ON order_id = order_id;";
+    assert_eq!(error(sql), "table name `orders` is used more than once, give each of them an alias");
+
+    let sql = format!("SELECT * FROM {} ON o.amount;", join("JOIN"));
+    assert_eq!(error(&sql), "JOIN condition must be BOOLEAN, found INTEGER");
+}
+
+#[test]
+fn hash_join_builds_on_the_smaller_input() {
+    let lines = plan(&format!(
+        "EXPLAIN SELECT o.order_id FROM {} ON o.customer = c.customer AND c.city <> 'Paris' WHERE o.amount > 60;",
+        join("JOIN")
+    ));
+    assert_eq!(lines[1..5], [
+        "  Project: order_id#0",
+        "    Join: INNER (customer#1 = customer#4)",
+        "      Scan: tests/test_data/orders.csv filter=(amount#3 > 60)",
+        "      Scan: tests/test_data/customers.csv filter=(city#1 != 'Paris')"
+    ]);
+    assert!(lines.contains(&"    HashJoinExec: INNER on=[customer#1 = customer#0] build=right".to_string()));
+
+    let lines = plan(&format!("EXPLAIN SELECT o.order_id FROM {} ON o.order_id < c.since;", join("LEFT JOIN")));
+    assert!(lines.contains(&"    NestedLoopJoinExec: LEFT condition=(order_id#0 < since#6)".to_string()));
+}
+
+const CUSTOMERS_AND_PAYMENTS: &str = "'tests/test_data/customers.csv' AS c {} 'tests/test_data/payments.csv' AS p ON c.customer = p.customer";
+
+#[test]
+fn inputs_sorted_on_the_key_are_merged() {
+    let sql = format!("SELECT c.customer, c.city, p.paid FROM {};", CUSTOMERS_AND_PAYMENTS.replace("{}", "FULL JOIN"));
+    assert_eq!(rows(&sql), vec![
+        vec![string("acme"), string("Berlin"), Value::Int(100)],
+        vec![string("acme"), string("Berlin"), Value::Int(40)],
+        vec![string("globex"), string("Paris"), Value::Int(30)],
+        vec![string("hooli"), string("Lisbon"), Value::Null],
+        vec![Value::Null, Value::Null, Value::Int(10)],
+        vec![Value::Null, Value::Null, Value::Int(5)]
+    ]);
+
+    let sql = format!("SELECT p.paid FROM {} AND p.paid > 50;", CUSTOMERS_AND_PAYMENTS.replace("{}", "JOIN"));
+    assert_eq!(rows(&sql), vec![vec![Value::Int(100)]]);
+
+    let lines = plan(&format!("EXPLAIN SELECT c.city FROM {};", CUSTOMERS_AND_PAYMENTS.replace("{}", "LEFT JOIN")));
+    assert!(lines.contains(&"    SortMergeJoinExec: LEFT on=[customer#0 = customer#0]".to_string()));
+}
+
+#[test]
+fn inputs_beyond_the_memory_budget_are_sorted_and_merged() {
+    let sql = format!("SELECT o.order_id, c.city FROM {} ON o.customer = c.customer ORDER BY o.order_id;", join("JOIN"));
+    assert_eq!(rows_with(&sql, with_budget(64)), rows(&sql));
+
+    let mut settings = with_budget(64);
+    settings.sorted_sources.insert("tests/test_data/customers.csv".to_string(), vec!["customer".to_string()]);
+    let lines = plan_with(&format!("EXPLAIN SELECT o.order_id FROM {} ON o.customer = c.customer;", join("LEFT JOIN")), settings);
+    let merge = lines.iter().position(|line| line == "    SortMergeJoinExec: LEFT on=[customer#1 = customer#0]").unwrap();
+    // customers are declared in order, only the orders are sorted
+    assert_eq!(lines[merge + 1..merge + 4], [
+        "      SortExec: customer#1 ASC",
+        "        FileScanExec: tests/test_data/orders.csv",
+        "      FileScanExec: tests/test_data/customers.csv"
+    ]);
+}
+
+#[test]
+fn inputs_sorted_in_the_sample_are_sorted_before_merging() {
+    // the keys are in order for more rows than the schema is guessed from, the last one is not
+    let sql = "SELECT a.key, b.label FROM 'tests/test_data/late_unsorted.csv' AS a \
+               JOIN 'tests/test_data/late_unsorted.csv' AS b ON a.key = b.key;";
+    let joined = rows(sql);
+    assert_eq!(joined.len(), 152);
+    assert_eq!(joined.iter().filter(|row| row[0] == Value::Int(3)).count(), 4);
+
+    let lines = plan(&format!("EXPLAIN {}", sql));
+    let merge = lines.iter().position(|line| line.trim_start().starts_with("SortMergeJoinExec")).unwrap();
+    assert_eq!(lines[merge + 1].trim_start(), "SortExec: key#0 ASC");
+}
+
+#[test]
+fn sorts_beyond_the_memory_budget_spill_to_disk() {
+    let sql = "SELECT name FROM 'tests/test_data/employees.csv' ORDER BY department DESC, salary;";
+    assert_eq!(rows_with(sql, with_budget(1)), rows(sql));
+    assert_eq!(rows_with(sql, with_budget(1))[0], vec![string("Dave")]);
+
+    // a run for each of the 150 rows is more than one merge reads at once, rows of equal keys keep their order
+    let sql = "SELECT key, label FROM 'tests/test_data/late_unsorted.csv' ORDER BY key % 7 DESC;";
+    assert_eq!(rows_with(sql, with_budget(1)), rows(sql));
+    assert_eq!(rows_with(sql, with_budget(1))[..2], [vec![Value::Int(6), string("row6")], vec![Value::Int(13), string("row13")]]);
+}
+
+#[test]
+fn declared_order_is_checked_while_merging() {
+    let mut settings = Settings::default();
+    settings.sorted_sources.insert("tests/test_data/orders.csv".to_string(), vec!["amount".to_string()]);
+    let sql = format!("SELECT o.order_id FROM {} ON o.amount = c.since;", join("JOIN"));
+    assert_eq!(error_with(&sql, settings.clone()), "input of the merge join is not sorted on amount#3: 80 came after 120");
+
+    settings.sorted_sources.insert("tests/test_data/orders.csv".to_string(), vec!["total".to_string()]);
+    assert_eq!(error_with(&sql, settings), "column `total` not found, it is declared sorted in `tests/test_data/orders.csv`");
+}
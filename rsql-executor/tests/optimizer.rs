@@ -0,0 +1,59 @@
+use rcsv_parser::parser::entities::csv_parser::CsvParser;
+use rsql_executor::core::executor::Executor;
+use rsql_executor::core::optimizer::OptimizedPlan;
+use rsql_executor::core::plan::{LogicalPlan, ResolvedExpr};
+use rsql_parser::ast::constructs::Statement;
+use rsql_parser::lexer::tokenizer::tokenize;
+use rsql_parser::parser::parser::Parser;
+
+fn optimize(sql: &str) -> OptimizedPlan {
+    let mut executor = Executor::new(Box::from(CsvParser{}));
+    let Statement::Select(select) = Parser::new(tokenize(sql)).parse().unwrap() else { panic!("Expected select statement") };
+    executor.optimized_plan(&select).unwrap()
+}
+
+fn scan(plan: &LogicalPlan) -> &LogicalPlan {
+    match plan.input() {
+        Some(input) => scan(input),
+        None => plan
+    }
+}
+
+#[test]
+fn pushes_predicate_and_projection_into_scan() {
+    let optimized = optimize("SELECT name FROM 'tests/test_data/employees.csv' WHERE age > 30;");
+    assert_eq!(optimized.applied_rules, vec!["predicate_pushdown", "projection_pruning"]);
+    assert_eq!(
+        optimized.plan.to_string(),
+        "Project: name#0\n  Scan: tests/test_data/employees.csv projection=[0] filter=(age#3 > 30)\n"
+    );
+}
+
+#[test]
+fn folds_constants_and_simplifies_booleans() {
+    let optimized = optimize(
+        "SELECT salary * (2 + 10) FROM 'tests/test_data/employees.csv' WHERE 1 = 1 AND active = true;"
+    );
+    assert_eq!(
+        optimized.applied_rules,
+        vec!["constant_folding", "simplify_booleans", "predicate_pushdown", "projection_pruning"]
+    );
+    let LogicalPlan::Scan { projection, filter, .. } = scan(&optimized.plan) else { panic!("Expected a scan") };
+    assert_eq!(projection, &Some(vec![2]));
+    assert_eq!(filter.as_ref().unwrap().to_string(), "(active#4 = TRUE)");
+    assert_eq!(optimized.plan.describe(), "Project: (salary#2 * 12)");
+}
+
+#[test]
+fn removes_filters_which_always_hold() {
+    let optimized = optimize("SELECT * FROM 'tests/test_data/employees.csv' WHERE age > 1 OR 2 > 1;");
+    assert_eq!(optimized.applied_rules, vec!["constant_folding", "simplify_booleans"]);
+    assert_eq!(optimized.plan.to_string(), "Project: name#0, department#1, salary#2, age#3, active#4\n  Scan: tests/test_data/employees.csv\n");
+}
+
+#[test]
+fn leaves_failing_constants_to_execution() {
+    let optimized = optimize("SELECT 1 / 0 FROM 'tests/test_data/employees.csv';");
+    let LogicalPlan::Project { exprs, .. } = &optimized.plan else { panic!("Expected a projection") };
+    assert!(matches!(exprs[0], ResolvedExpr::BinaryOp { .. }));
+}
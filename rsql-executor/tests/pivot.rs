@@ -0,0 +1,88 @@
+use rsql_parser::ast::constructs::Value;
+
+mod common;
+
+use common::{error, rows, string};
+
+const MONTHLY: &str = "'tests/test_data/monthly.csv'";
+const EMPLOYEES: &str = "'tests/test_data/employees.csv'";
+
+#[test]
+fn unpivot_turns_columns_into_rows() {
+    let sql = format!("SELECT * FROM {} UNPIVOT (amount FOR month IN (jan, feb, mar));", MONTHLY);
+    assert_eq!(rows(&sql), vec![
+        vec![string("north"), string("jan"), Value::Int(10)],
+        vec![string("north"), string("feb"), Value::Int(20)],
+        vec![string("south"), string("jan"), Value::Int(5)],
+        vec![string("south"), string("mar"), Value::Int(7)],
+        vec![string("east"), string("jan"), Value::Int(3)],
+        vec![string("east"), string("feb"), Value::Int(4)],
+        vec![string("east"), string("mar"), Value::Int(5)]
+    ]);
+
+    let sql = format!(
+        "SELECT u.month, u.amount FROM {} UNPIVOT INCLUDE NULLS (amount FOR month IN (mar)) AS u WHERE u.region <> 'east';",
+        MONTHLY
+    );
+    assert_eq!(rows(&sql), vec![vec![string("mar"), Value::Null], vec![string("mar"), Value::Int(7)]]);
+}
+
+#[test]
+fn pivot_turns_rows_into_columns() {
+    let sql = format!(
+        "SELECT * FROM (SELECT department, active FROM {}) AS e \
+         PIVOT (COUNT(*) FOR department IN ('Engineering' AS engineering, 'Sales', 'Marketing')) ORDER BY active;",
+        EMPLOYEES
+    );
+    assert_eq!(rows(&sql), vec![
+        vec![Value::Bool(false), Value::Int(1), Value::Int(1), Value::Int(0)],
+        vec![Value::Bool(true), Value::Int(2), Value::Int(1), Value::Int(1)]
+    ]);
+
+    // the columns are named after the values, or their alias
+    let sql = format!(
+        "SELECT p.Sales, engineering FROM (SELECT department, salary FROM {}) AS e \
+         PIVOT (SUM(salary) FOR department IN ('Engineering' AS engineering, 'Sales')) AS p;",
+        EMPLOYEES
+    );
+    assert_eq!(rows(&sql), vec![vec![Value::Int(135000), Value::Int(215000)]]);
+}
+
+#[test]
+fn pivot_undoes_unpivot() {
+    let sql = format!(
+        "SELECT region, jan, feb, mar FROM {} UNPIVOT (amount FOR month IN (jan, feb, mar)) \
+         PIVOT (SUM(amount) FOR month IN ('jan', 'feb', 'mar')) ORDER BY region;",
+        MONTHLY
+    );
+    assert_eq!(rows(&sql), vec![
+        vec![string("east"), Value::Int(3), Value::Int(4), Value::Int(5)],
+        vec![string("north"), Value::Int(10), Value::Int(20), Value::Null],
+        vec![string("south"), Value::Int(5), Value::Null, Value::Int(7)]
+    ]);
+
+    let lines: Vec<Value> = rows(&format!("EXPLAIN {}", sql)).into_iter().map(|mut row| row.remove(0)).collect();
+    assert!(lines.iter().any(|line| matches!(line, Value::String(line) if line.trim() == "UnpivotExec: jan, feb, mar")));
+    assert!(lines.iter().any(|line| matches!(line, Value::String(line)
+        if line.contains("SUM(CASE WHEN (month#1 = 'jan') THEN amount#2 END)"))));
+}
+
+#[test]
+fn invalid_pivots_are_rejected() {
+    assert_eq!(
+        error(&format!("SELECT * FROM {} UNPIVOT (amount FOR month IN (jan, region));", MONTHLY)),
+        "the columns of UNPIVOT must have a common type, found INTEGER and VARCHAR"
+    );
+    assert_eq!(
+        error(&format!("SELECT * FROM {} UNPIVOT (amount FOR month IN (jan, JAN));", MONTHLY)),
+        "column `JAN` is unpivoted more than once"
+    );
+    assert_eq!(
+        error(&format!("SELECT * FROM {} PIVOT (SUM(salary) FOR department IN ('Sales', 'Sales'));", EMPLOYEES)),
+        "PIVOT returns more than one column named `Sales`"
+    );
+    assert_eq!(
+        error(&format!("SELECT * FROM {} PIVOT (SUM(salary) FOR department IN (1));", EMPLOYEES)),
+        error(&format!("SELECT * FROM {} WHERE department = 1;", EMPLOYEES))
+    );
+}
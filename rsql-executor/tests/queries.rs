@@ -0,0 +1,291 @@
+use rcsv_parser::parser::entities::csv_parser::CsvParser;
+use rsql_executor::core::dataframe::DataFrame;
+use rsql_executor::core::executor::Executor;
+use rsql_executor::core::settings::DivisionByZero;
+use rsql_parser::ast::constructs::Value;
+use rsql_parser::lexer::tokenizer::tokenize;
+use rsql_parser::parser::parser::Parser;
+
+fn query(sql: &str) -> DataFrame {
+    let mut executor = Executor::new(Box::from(CsvParser{}));
+    let statement = Parser::new(tokenize(sql)).parse().unwrap();
+    match executor.execute(statement) {
+        Ok(data_frame) => data_frame,
+        Err(e) => panic!("Expected valid result, got error {}", e.message)
+    }
+}
+
+fn rows(data_frame: &DataFrame) -> Vec<Vec<Value>> {
+    data_frame.rows.iter().map(|row| row.values.clone()).collect()
+}
+
+fn string(value: &str) -> Value {
+    Value::String(value.to_string())
+}
+
+#[test]
+fn projects_computed_expressions() {
+    let result = query("SELECT name, salary / 1000 AS k FROM 'tests/test_data/employees.csv' WHERE age < 30;");
+    assert_eq!(result.columns, vec!["name".to_string(), "k".to_string()]);
+    assert_eq!(rows(&result), vec![vec![string("Bob"), Value::Int(95)]]);
+}
+
+#[test]
+fn null_comparisons_filter_rows_out() {
+    let result = query("SELECT name FROM 'tests/test_data/employees.csv' WHERE salary < 100000 OR active = false;");
+    let names: Vec<Value> = rows(&result).into_iter().map(|mut row| row.remove(0)).collect();
+    assert_eq!(names, vec![string("Bob"), string("Carol"), string("Dave"), string("Eve"), string("Frank")]);
+}
+
+#[test]
+fn groups_and_orders_by_aggregate() {
+    let result = query(
+        "SELECT department, COUNT(*), SUM(salary) AS total FROM 'tests/test_data/employees.csv' \
+         GROUP BY department ORDER BY total DESC;"
+    );
+    assert_eq!(result.columns, vec!["department".to_string(), "COUNT(*)".to_string(), "total".to_string()]);
+    assert_eq!(rows(&result), vec![
+        vec![string("Engineering"), Value::Int(3), Value::Int(215000)],
+        vec![string("Sales"), Value::Int(2), Value::Int(135000)],
+        vec![string("Marketing"), Value::Int(1), Value::Int(80000)],
+    ]);
+}
+
+#[test]
+fn aggregates_without_group_by_return_one_row() {
+    let result = query("SELECT COUNT(salary), AVG(age), MAX(name) FROM 'tests/test_data/employees.csv' WHERE age > 40;");
+    assert_eq!(rows(&result), vec![vec![Value::Int(1), Value::Float(48.5), string("Frank")]]);
+
+    let empty = query("SELECT COUNT(*), SUM(salary) FROM 'tests/test_data/employees.csv' WHERE age > 100;");
+    assert_eq!(rows(&empty), vec![vec![Value::Int(0), Value::Null]]);
+}
+
+#[test]
+fn orders_by_hidden_expression() {
+    let result = query("SELECT name FROM 'tests/test_data/employees.csv' ORDER BY age * -1;");
+    let names: Vec<Value> = rows(&result).into_iter().map(|mut row| row.remove(0)).collect();
+    assert_eq!(result.columns, vec!["name".to_string()]);
+    assert_eq!(names, vec![string("Frank"), string("Carol"), string("Dave"), string("Alice"), string("Eve"), string("Bob")]);
+}
+
+#[test]
+fn pushed_down_filter_reports_evaluation_errors() {
+    let mut executor = Executor::new(Box::from(CsvParser{}));
+    let statement = Parser::new(tokenize("SELECT name FROM 'tests/test_data/employees.csv' WHERE age / 0 > 1;")).parse().unwrap();
+    match executor.execute(statement) {
+        Ok(_) => panic!("Expected division by zero"),
+        Err(e) => assert_eq!(e.message, "division by zero")
+    }
+}
+
+#[test]
+fn select_distinct_treats_equal_numbers_and_nulls_as_duplicates() {
+    let result = query("SELECT DISTINCT score FROM 'tests/test_data/scores.csv';");
+    assert_eq!(rows(&result), vec![
+        vec![Value::Int(1)],
+        vec![Value::Null],
+        vec![Value::Int(2)],
+        vec![Value::Float(2.5)],
+    ]);
+
+    let result = query("SELECT DISTINCT player FROM 'tests/test_data/scores.csv' ORDER BY player DESC;");
+    let players: Vec<Value> = rows(&result).into_iter().map(|mut row| row.remove(0)).collect();
+    assert_eq!(players, vec![string("eve"), string("dan"), string("cid"), string("bob"), string("ann")]);
+}
+
+#[test]
+fn distinct_aggregates_skip_repeated_values() {
+    let result = query(
+        "SELECT COUNT(DISTINCT score), SUM(DISTINCT score), COUNT(score), COUNT(DISTINCT player) \
+         FROM 'tests/test_data/scores.csv';"
+    );
+    assert_eq!(result.columns[0], "COUNT(DISTINCT score)");
+    assert_eq!(rows(&result), vec![vec![Value::Int(3), Value::Float(5.5), Value::Int(5), Value::Int(5)]]);
+
+    let result = query("SELECT player, COUNT(DISTINCT score) FROM 'tests/test_data/scores.csv' GROUP BY player ORDER BY player;");
+    assert_eq!(rows(&result)[1], vec![string("bob"), Value::Int(1)]);
+}
+
+#[test]
+fn distinct_order_by_must_use_selected_columns() {
+    let mut executor = Executor::new(Box::from(CsvParser{}));
+    let statement = Parser::new(tokenize("SELECT DISTINCT player FROM 'tests/test_data/scores.csv' ORDER BY score;")).parse().unwrap();
+    match executor.execute(statement) {
+        Ok(_) => panic!("Expected an error"),
+        Err(e) => assert_eq!(e.message, "for SELECT DISTINCT, ORDER BY expressions must appear in the select list")
+    }
+}
+
+fn names(data_frame: &DataFrame) -> Vec<Value> {
+    rows(data_frame).into_iter().map(|mut row| row.remove(0)).collect()
+}
+
+#[test]
+fn in_between_and_null_predicates() {
+    let result = query("SELECT name FROM 'tests/test_data/employees.csv' WHERE department IN ('Sales', 'Marketing');");
+    assert_eq!(names(&result), vec![string("Carol"), string("Dave"), string("Eve")]);
+
+    let result = query("SELECT name FROM 'tests/test_data/employees.csv' WHERE age NOT BETWEEN 30 AND 45;");
+    assert_eq!(names(&result), vec![string("Bob"), string("Frank")]);
+
+    let result = query("SELECT name FROM 'tests/test_data/employees.csv' WHERE salary IS NULL OR NOT active;");
+    assert_eq!(names(&result), vec![string("Carol"), string("Frank")]);
+
+    // a NULL in the list makes a failed match unknown, so NOT IN keeps no rows
+    let result = query("SELECT name FROM 'tests/test_data/employees.csv' WHERE age NOT IN (28, NULL);");
+    assert!(result.rows.is_empty());
+}
+
+#[test]
+fn like_and_ilike_patterns() {
+    let result = query("SELECT name FROM 'tests/test_data/employees.csv' WHERE name LIKE '_a%';");
+    assert_eq!(names(&result), vec![string("Carol"), string("Dave")]);
+
+    let result = query("SELECT name FROM 'tests/test_data/employees.csv' WHERE department ILIKE '%ING' AND name NOT LIKE 'A%';");
+    assert_eq!(names(&result), vec![string("Bob"), string("Eve"), string("Frank")]);
+}
+
+#[test]
+fn regular_expression_predicates_and_functions() {
+    let result = query("SELECT name FROM 'tests/test_data/employees.csv' WHERE name REGEXP '^[A-D]' AND department NOT RLIKE 'Eng';");
+    assert_eq!(names(&result), vec![string("Carol"), string("Dave")]);
+
+    let result = query(
+        "SELECT regexp_extract(name, '([aeiou])([a-z])', 2), regexp_extract(name, 'z'), regexp_replace(department, '[aeiou]', '') \
+         FROM 'tests/test_data/employees.csv' WHERE age < 30;"
+    );
+    assert_eq!(rows(&result), vec![vec![string("b"), Value::Null, string("Engnrng")]]);
+
+    let mut executor = Executor::new(Box::from(CsvParser{}));
+    let statement = Parser::new(tokenize("SELECT regexp_extract(name, 'a', 1) FROM 'tests/test_data/employees.csv';")).parse().unwrap();
+    match executor.execute(statement) {
+        Ok(_) => panic!("Expected an error"),
+        Err(e) => assert_eq!(e.message, "REGEXP_EXTRACT group 1 is out of range, the pattern has 0 groups")
+    }
+}
+
+#[test]
+fn string_functions_in_select_and_where() {
+    let result = query(
+        "SELECT upper(name) AS upper_name, lpad(CONCAT(age, ''), 3, '0'), substring(department, 1, 3) \
+         FROM 'tests/test_data/employees.csv' WHERE starts_with(department, 'Eng') AND length(name) > 3 ORDER BY upper_name;"
+    );
+    assert_eq!(result.columns[0], "upper_name");
+    assert_eq!(rows(&result), vec![
+        vec![string("ALICE"), string("034"), string("Eng")],
+        vec![string("FRANK"), string("052"), string("Eng")]
+    ]);
+
+    let result = query("SELECT POSITION('a' IN lower(name)) FROM 'tests/test_data/employees.csv' WHERE age > 40;");
+    assert_eq!(result.columns[0], "POSITION('a' IN lower(name))");
+    assert_eq!(rows(&result), vec![vec![Value::Int(2)], vec![Value::Int(3)]]);
+}
+
+#[test]
+fn monthly_roll_up_with_date_functions() {
+    let result = query(
+        "SELECT DATE_TRUNC('month', order_date), COUNT(*), SUM(amount) FROM 'tests/test_data/orders.csv' \
+         WHERE order_date < DATE '2024-04-01' GROUP BY DATE_TRUNC('month', order_date) ORDER BY DATE_TRUNC('month', order_date);"
+    );
+    assert_eq!(result.columns[0], "DATE_TRUNC('month', order_date)");
+    let month = |month: u32| Value::Date(chrono::NaiveDate::from_ymd_opt(2024, month, 1).unwrap());
+    assert_eq!(rows(&result), vec![
+        vec![month(1), Value::Int(2), Value::Int(200)],
+        vec![month(2), Value::Int(2), Value::Int(250)],
+        vec![month(3), Value::Int(1), Value::Int(75)]
+    ]);
+
+    let result = query(
+        "SELECT order_id, STRFTIME(DATE_ADD('month', 1, order_date), '%d/%m/%Y'), DATE_DIFF('day', '2024-01-01', order_date) \
+         FROM 'tests/test_data/orders.csv' WHERE EXTRACT(MONTH FROM order_date) = 1 AND order_date <= CURRENT_DATE;"
+    );
+    assert_eq!(result.columns[2], "DATE_DIFF('day', '2024-01-01', order_date)");
+    assert_eq!(rows(&result), vec![
+        vec![Value::Int(1), string("05/02/2024"), Value::Int(4)],
+        vec![Value::Int(2), string("20/02/2024"), Value::Int(19)]
+    ]);
+}
+
+#[test]
+fn division_by_zero_follows_the_setting() {
+    let sql = "SELECT name, 7 / (age - 28), salary / 1000 FROM 'tests/test_data/employees.csv' WHERE age < 32;";
+    let mut executor = Executor::new(Box::from(CsvParser{}));
+    match executor.execute(Parser::new(tokenize(sql)).parse().unwrap()) {
+        Ok(_) => panic!("Expected an error"),
+        Err(e) => assert_eq!(e.message, "division by zero")
+    }
+
+    executor.settings.division_by_zero = DivisionByZero::Null;
+    let result = executor.execute(Parser::new(tokenize(sql)).parse().unwrap()).unwrap();
+    assert_eq!(rows(&result), vec![
+        vec![string("Bob"), Value::Null, Value::Int(95)],
+        vec![string("Eve"), Value::Int(2), Value::Int(80)]
+    ]);
+}
+
+#[test]
+fn modulo_by_zero_follows_the_setting() {
+    for sql in [
+        "SELECT name, 7 % (age - 28) FROM 'tests/test_data/employees.csv' WHERE age < 32;",
+        "SELECT name, MOD(7, age - 28) FROM 'tests/test_data/employees.csv' WHERE age < 32;"
+    ] {
+        let mut executor = Executor::new(Box::from(CsvParser{}));
+        match executor.execute(Parser::new(tokenize(sql)).parse().unwrap()) {
+            Ok(_) => panic!("Expected an error"),
+            Err(e) => assert_eq!(e.message, "division by zero")
+        }
+
+        executor.settings.division_by_zero = DivisionByZero::Null;
+        let result = executor.execute(Parser::new(tokenize(sql)).parse().unwrap()).unwrap();
+        assert_eq!(rows(&result), vec![
+            vec![string("Bob"), Value::Null],
+            vec![string("Eve"), Value::Int(1)]
+        ]);
+    }
+}
+
+#[test]
+fn case_expressions_and_null_handling_functions() {
+    let result = query(
+        "SELECT name, CASE WHEN age < 30 THEN 'junior' WHEN age < 40 THEN 'mid' ELSE 'senior' END AS band, \
+         CASE department WHEN 'Sales' THEN 1.5 WHEN 'Marketing' THEN 2 END AS factor, COALESCE(salary, 0), \
+         NULLIF(department, 'Engineering'), IF(active, 'yes', 'no') \
+         FROM 'tests/test_data/employees.csv' WHERE age > 38 OR name = 'Eve';"
+    );
+    assert_eq!(result.columns[1..3], ["band".to_string(), "factor".to_string()]);
+    assert_eq!(rows(&result), vec![
+        vec![string("Carol"), string("senior"), Value::Float(1.5), Value::Int(70000), string("Sales"), string("no")],
+        vec![string("Dave"), string("mid"), Value::Float(1.5), Value::Int(65000), string("Sales"), string("yes")],
+        vec![string("Eve"), string("mid"), Value::Float(2.0), Value::Int(80000), string("Marketing"), string("yes")],
+        vec![string("Frank"), string("senior"), Value::Null, Value::Int(0), Value::Null, string("no")]
+    ]);
+
+    let result = query(
+        "SELECT department, SUM(CASE WHEN active THEN 1 ELSE 0 END) FROM 'tests/test_data/employees.csv' \
+         GROUP BY department ORDER BY department;"
+    );
+    assert_eq!(rows(&result), vec![
+        vec![string("Engineering"), Value::Int(2)],
+        vec![string("Marketing"), Value::Int(1)],
+        vec![string("Sales"), Value::Int(1)]
+    ]);
+}
+
+#[test]
+fn cast_and_try_cast_convert_guessed_types() {
+    let result = query(
+        "SELECT CAST(age AS VARCHAR) || '!', salary::DOUBLE / 1000, TRY_CAST(name AS INTEGER), CAST(active AS INT) \
+         FROM 'tests/test_data/employees.csv' WHERE CAST(age AS VARCHAR) LIKE '3%' AND age::DOUBLE > 32.5;"
+    );
+    assert_eq!(rows(&result), vec![
+        vec![string("34!"), Value::Float(120.0), Value::Null, Value::Int(1)],
+        vec![string("39!"), Value::Float(65.0), Value::Null, Value::Int(1)]
+    ]);
+
+    let mut executor = Executor::new(Box::from(CsvParser{}));
+    let statement = Parser::new(tokenize("SELECT CAST(name AS INTEGER) FROM 'tests/test_data/employees.csv';")).parse().unwrap();
+    match executor.execute(statement) {
+        Ok(_) => panic!("Expected an error"),
+        Err(e) => assert_eq!(e.message, "cannot cast 'Alice' to INTEGER")
+    }
+}
@@ -0,0 +1,106 @@
+use rsql_parser::ast::constructs::Value;
+
+mod common;
+
+use common::{error, rows, run};
+
+fn plan(sql: &str) -> Vec<String> {
+    rows(&format!("EXPLAIN {}", sql)).into_iter()
+        .map(|mut row| match row.remove(0) {
+            Value::String(line) => line.trim().to_string(),
+            other => panic!("Expected plan line, got {:?}", other)
+        })
+        .collect()
+}
+
+fn strings(values: &[&str]) -> Vec<Vec<Value>> {
+    values.iter().map(|value| vec![Value::String(value.to_string())]).collect()
+}
+
+const JAN: &str = "'tests/test_data/jan.csv'";
+const FEB: &str = "'tests/test_data/feb.csv'";
+const BLOCKED: &str = "'tests/test_data/blocked.csv'";
+
+#[test]
+fn union_all_keeps_every_row_under_the_names_of_the_left_columns() {
+    let sql = format!(
+        "SELECT customer, amount FROM {} UNION ALL SELECT customer, total FROM {} ORDER BY amount DESC, customer;",
+        JAN, FEB
+    );
+    let data_frame = run(&sql).unwrap();
+    assert_eq!(data_frame.columns, vec!["customer".to_string(), "amount".to_string()]);
+    let amounts: Vec<Value> = data_frame.rows.into_iter().map(|mut row| row.values.remove(1)).collect();
+    assert_eq!(amounts.len(), 8);
+    assert_eq!(amounts[0], Value::Int(200));
+    assert_eq!(amounts[7], Value::Float(45.5));
+}
+
+#[test]
+fn union_returns_each_row_once() {
+    let sql = format!("SELECT customer FROM {} UNION SELECT customer FROM {} ORDER BY customer;", JAN, FEB);
+    assert_eq!(rows(&sql), strings(&["acme", "globex", "initech", "umbrella"]));
+
+    let sql = format!("SELECT COUNT(*) FROM (SELECT customer, amount FROM {} UNION SELECT customer, total FROM {}) AS months;", JAN, FEB);
+    assert_eq!(rows(&sql), vec![vec![Value::Int(5)]]);
+}
+
+#[test]
+fn intersect_and_except_compare_whole_rows() {
+    let sql = format!("SELECT customer FROM {} INTERSECT SELECT customer FROM {} ORDER BY customer;", JAN, FEB);
+    assert_eq!(rows(&sql), strings(&["acme", "initech"]));
+
+    let sql = format!("SELECT customer, amount FROM {} INTERSECT SELECT customer, total FROM {};", JAN, FEB);
+    assert_eq!(rows(&sql), vec![vec![Value::String("acme".to_string()), Value::Int(120)]]);
+
+    let sql = format!("SELECT customer FROM {} EXCEPT SELECT customer FROM {};", JAN, FEB);
+    assert_eq!(rows(&sql), strings(&["globex"]));
+}
+
+#[test]
+fn intersect_all_and_except_all_count_duplicate_rows() {
+    let sql = format!("SELECT customer FROM {} INTERSECT ALL SELECT customer FROM {} ORDER BY customer;", JAN, FEB);
+    assert_eq!(rows(&sql), strings(&["acme", "acme", "initech"]));
+
+    let sql = format!("SELECT customer FROM {} EXCEPT ALL SELECT customer FROM {} WHERE total > 100 ORDER BY customer;", JAN, FEB);
+    assert_eq!(rows(&sql), strings(&["globex", "initech"]));
+}
+
+#[test]
+fn intersect_binds_tighter_than_union() {
+    let sql = format!(
+        "SELECT customer FROM {} UNION SELECT customer FROM {} INTERSECT SELECT customer FROM {} ORDER BY customer;",
+        JAN, FEB, BLOCKED
+    );
+    assert_eq!(rows(&sql), strings(&["acme", "globex", "initech"]));
+}
+
+#[test]
+fn order_by_and_limit_apply_to_the_combined_rows() {
+    let sql = format!(
+        "SELECT customer, amount FROM {} UNION SELECT customer, total FROM {} ORDER BY amount DESC LIMIT 2;",
+        JAN, FEB
+    );
+    assert_eq!(rows(&sql), vec![
+        vec![Value::String("umbrella".to_string()), Value::Int(200)],
+        vec![Value::String("acme".to_string()), Value::Int(120)]
+    ]);
+
+    let sql = format!("SELECT customer FROM {} ORDER BY amount LIMIT 1;", JAN);
+    assert_eq!(rows(&sql), strings(&["initech"]));
+
+    let lines = plan(&format!("SELECT customer FROM {} UNION SELECT customer FROM {} LIMIT 3;", JAN, FEB));
+    let physical = lines.iter().position(|line| line == "LimitExec: 3").expect("Expected a limit operator");
+    assert_eq!(&lines[physical..physical + 3], ["LimitExec: 3", "HashDistinctExec", "SetOperationExec: UNION ALL"]);
+}
+
+#[test]
+fn both_sides_must_return_compatible_columns() {
+    assert_eq!(
+        error(&format!("SELECT customer, amount FROM {} UNION SELECT customer FROM {};", JAN, FEB)),
+        "each side of UNION must return the same number of columns, found 2 and 1"
+    );
+    assert_eq!(
+        error(&format!("SELECT amount FROM {} EXCEPT SELECT customer FROM {};", JAN, FEB)),
+        "column 1 of EXCEPT is INTEGER on the left and VARCHAR on the right"
+    );
+}
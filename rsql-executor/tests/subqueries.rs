@@ -0,0 +1,126 @@
+use rsql_parser::ast::constructs::Value;
+
+mod common;
+
+use common::{error, rows, run, string};
+
+fn ints(values: &[i64]) -> Vec<Vec<Value>> {
+    values.iter().map(|value| vec![Value::Int(*value)]).collect()
+}
+
+const ORDERS: &str = "'tests/test_data/orders.csv'";
+const CUSTOMERS: &str = "'tests/test_data/customers.csv'";
+const BLOCKED: &str = "'tests/test_data/blocked.csv'";
+
+#[test]
+fn in_subquery_keeps_rows_whose_value_it_returns() {
+    let sql = format!("SELECT order_id FROM {} WHERE customer IN (SELECT customer FROM {}) ORDER BY order_id;", ORDERS, BLOCKED);
+    assert_eq!(rows(&sql), ints(&[4]));
+}
+
+#[test]
+fn not_in_subquery_drops_every_row_once_the_subquery_returns_null() {
+    let sql = format!("SELECT order_id FROM {} WHERE customer NOT IN (SELECT customer FROM {}) ORDER BY order_id;", ORDERS, BLOCKED);
+    assert_eq!(rows(&sql), ints(&[]));
+
+    let sql = format!(
+        "SELECT order_id FROM {} WHERE NOT customer IN (SELECT customer FROM {} WHERE reason = 'fraud') ORDER BY order_id;",
+        ORDERS, BLOCKED
+    );
+    assert_eq!(rows(&sql), ints(&[1, 2, 3, 5, 6]));
+}
+
+#[test]
+fn correlated_exists_becomes_a_semi_join() {
+    let sql = format!(
+        "SELECT customer FROM {} AS c WHERE EXISTS (SELECT * FROM {} AS o WHERE o.customer = c.customer AND amount > 100) ORDER BY customer;",
+        CUSTOMERS, ORDERS
+    );
+    assert_eq!(rows(&sql), vec![vec![string("acme")]]);
+
+    let sql = format!(
+        "SELECT customer FROM {} AS c WHERE NOT EXISTS (SELECT * FROM {} AS o WHERE o.customer = c.customer) ORDER BY customer;",
+        CUSTOMERS, ORDERS
+    );
+    assert_eq!(rows(&sql), vec![vec![string("hooli")]]);
+
+    let explained = rows(&format!(
+        "EXPLAIN SELECT customer FROM {} AS c WHERE EXISTS (SELECT * FROM {} AS o WHERE o.customer = c.customer);",
+        CUSTOMERS, ORDERS
+    ));
+    assert!(explained.iter().any(|row| matches!(&row[0], Value::String(line) if line.contains("HashJoinExec: SEMI"))));
+}
+
+#[test]
+fn scalar_subquery_is_compared_as_a_value() {
+    let sql = format!("SELECT order_id FROM {} WHERE amount > (SELECT AVG(amount) FROM {}) ORDER BY order_id;", ORDERS, ORDERS);
+    assert_eq!(rows(&sql), ints(&[1, 3]));
+}
+
+#[test]
+fn scalar_subquery_may_return_a_column_of_the_same_name() {
+    let sql = format!("SELECT order_id FROM {} WHERE amount > (SELECT amount FROM {} WHERE order_id = 2) ORDER BY order_id;", ORDERS, ORDERS);
+    assert_eq!(rows(&sql), ints(&[1, 3]));
+    let sql = "SELECT name FROM 'tests/test_data/employees.csv' \
+               WHERE age = (SELECT age FROM 'tests/test_data/employees.csv' WHERE name = 'Bob');";
+    assert_eq!(rows(sql), vec![vec![string("Bob")]]);
+    let sql = format!(
+        "SELECT customer FROM {} AS c WHERE (SELECT SUM(amount) FROM {} AS o WHERE o.customer = c.customer) > 200 ORDER BY customer;",
+        CUSTOMERS, ORDERS
+    );
+    assert_eq!(rows(&sql), vec![vec![string("acme")]]);
+}
+
+#[test]
+fn correlated_scalar_subquery_counts_zero_for_rows_without_a_match() {
+    let sql = format!(
+        "SELECT customer, (SELECT COUNT(*) FROM {} AS o WHERE o.customer = c.customer) AS orders, \
+         (SELECT SUM(amount) FROM {} AS o WHERE o.customer = c.customer) AS total FROM {} AS c ORDER BY customer;",
+        ORDERS, ORDERS, CUSTOMERS
+    );
+    assert_eq!(rows(&sql), vec![
+        vec![string("acme"), Value::Int(3), Value::Int(320)],
+        vec![string("globex"), Value::Int(2), Value::Int(155)],
+        vec![string("hooli"), Value::Int(0), Value::Null]
+    ]);
+}
+
+#[test]
+fn wildcard_leaves_out_the_values_of_subqueries() {
+    let data_frame = run(&format!("SELECT *, (SELECT COUNT(*) FROM {}) AS blocked FROM {};", BLOCKED, CUSTOMERS)).unwrap();
+    assert_eq!(data_frame.columns, vec!["customer", "city", "since", "blocked"]);
+    assert_eq!(data_frame.rows[0].values[3], Value::Int(2));
+}
+
+#[test]
+fn subquery_in_from_is_qualified_by_its_alias() {
+    let sql = format!(
+        "SELECT t.customer, t.total FROM (SELECT customer, SUM(amount) AS total FROM {} GROUP BY customer) AS t \
+         WHERE t.total > 100 ORDER BY t.customer;",
+        ORDERS
+    );
+    assert_eq!(rows(&sql), vec![
+        vec![string("acme"), Value::Int(320)],
+        vec![string("globex"), Value::Int(155)]
+    ]);
+}
+
+#[test]
+fn subqueries_report_misuse() {
+    assert_eq!(
+        error(&format!("SELECT (SELECT customer FROM {}) FROM {};", CUSTOMERS, ORDERS)),
+        "subquery used as a value returned more than one row"
+    );
+    assert_eq!(
+        error(&format!("SELECT order_id FROM {} WHERE customer IN (SELECT customer, city FROM {});", ORDERS, CUSTOMERS)),
+        "subquery must return only one column, found 2"
+    );
+    assert_eq!(
+        error(&format!("SELECT EXISTS (SELECT * FROM {}) FROM {};", CUSTOMERS, ORDERS)),
+        "EXISTS and IN subqueries are only supported as conditions of WHERE, combined with AND"
+    );
+    assert_eq!(
+        error(&format!("SELECT order_id FROM {} WHERE amount > 10 OR customer IN (SELECT customer FROM {});", ORDERS, BLOCKED)),
+        "EXISTS and IN subqueries are only supported as conditions of WHERE, combined with AND"
+    );
+}
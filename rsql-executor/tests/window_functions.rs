@@ -0,0 +1,156 @@
+use rsql_parser::ast::constructs::Value;
+
+mod common;
+
+use common::{error, rows};
+
+fn column(sql: &str, index: usize) -> Vec<Value> {
+    rows(sql).into_iter().map(|mut row| row.remove(index)).collect()
+}
+
+fn ints(values: &[i64]) -> Vec<Value> {
+    values.iter().map(|value| Value::Int(*value)).collect()
+}
+
+const ORDERS: &str = "'tests/test_data/orders.csv'";
+const SCORES: &str = "'tests/test_data/scores.csv'";
+
+#[test]
+fn row_number_counts_the_rows_of_each_partition() {
+    let sql = format!(
+        "SELECT order_id, ROW_NUMBER() OVER (PARTITION BY customer ORDER BY order_date) FROM {} ORDER BY order_id;",
+        ORDERS
+    );
+    assert_eq!(column(&sql, 1), ints(&[1, 1, 2, 1, 2, 3]));
+}
+
+#[test]
+fn rank_and_dense_rank_share_the_rank_of_peers() {
+    let sql = format!(
+        "SELECT score, RANK() OVER (ORDER BY score DESC), DENSE_RANK() OVER (ORDER BY score DESC) FROM {} ORDER BY score DESC;",
+        SCORES
+    );
+    let rows = rows(&sql);
+    let ranks: Vec<Value> = rows.iter().map(|row| row[1].clone()).collect();
+    let dense: Vec<Value> = rows.iter().map(|row| row[2].clone()).collect();
+    // NULLs come first in descending order and are peers of each other
+    assert_eq!(ranks, ints(&[1, 1, 3, 4, 5, 5, 5]));
+    assert_eq!(dense, ints(&[1, 1, 2, 3, 4, 4, 4]));
+}
+
+#[test]
+fn lag_and_lead_read_other_rows_of_the_partition() {
+    let sql = format!(
+        "SELECT order_id, LAG(amount) OVER (ORDER BY order_id), LEAD(amount, 2, 0) OVER (ORDER BY order_id) FROM {} ORDER BY order_id;",
+        ORDERS
+    );
+    let rows = rows(&sql);
+    let lag: Vec<Value> = rows.iter().map(|row| row[1].clone()).collect();
+    let lead: Vec<Value> = rows.iter().map(|row| row[2].clone()).collect();
+    assert_eq!(lag, vec![Value::Null, Value::Int(120), Value::Int(80), Value::Int(200), Value::Int(50), Value::Int(75)]);
+    assert_eq!(lead, vec![Value::Int(200), Value::Int(50), Value::Int(75), Value::Null, Value::Int(0), Value::Int(0)]);
+}
+
+#[test]
+fn first_and_last_value_follow_the_frame() {
+    let sql = format!(
+        "SELECT order_id, FIRST_VALUE(order_id) OVER (PARTITION BY customer ORDER BY order_date), \
+         LAST_VALUE(order_id) OVER (PARTITION BY customer ORDER BY order_date), \
+         LAST_VALUE(order_id) OVER (PARTITION BY customer ORDER BY order_date ROWS BETWEEN UNBOUNDED PRECEDING AND UNBOUNDED FOLLOWING) \
+         FROM {} ORDER BY order_id;",
+        ORDERS
+    );
+    let rows = rows(&sql);
+    let first: Vec<Value> = rows.iter().map(|row| row[1].clone()).collect();
+    let last_so_far: Vec<Value> = rows.iter().map(|row| row[2].clone()).collect();
+    let last: Vec<Value> = rows.iter().map(|row| row[3].clone()).collect();
+    assert_eq!(first, ints(&[1, 2, 1, 4, 2, 1]));
+    assert_eq!(last_so_far, ints(&[1, 2, 3, 4, 5, 6]));
+    assert_eq!(last, ints(&[6, 5, 6, 4, 5, 6]));
+}
+
+#[test]
+fn ntile_gives_the_first_buckets_the_extra_rows() {
+    let sql = format!("SELECT order_id, NTILE(4) OVER (ORDER BY order_id) FROM {} ORDER BY order_id;", ORDERS);
+    assert_eq!(column(&sql, 1), ints(&[1, 1, 2, 2, 3, 4]));
+}
+
+#[test]
+fn aggregates_over_the_default_frame_run_up_to_the_current_peers() {
+    let sql = format!(
+        "SELECT order_id, SUM(amount) OVER (PARTITION BY customer ORDER BY order_date), COUNT(*) OVER () FROM {} ORDER BY order_id;",
+        ORDERS
+    );
+    let rows = rows(&sql);
+    let running: Vec<Value> = rows.iter().map(|row| row[1].clone()).collect();
+    let counts: Vec<Value> = rows.iter().map(|row| row[2].clone()).collect();
+    assert_eq!(running, ints(&[120, 80, 320, 50, 155, 320]));
+    assert_eq!(counts, ints(&[6, 6, 6, 6, 6, 6]));
+}
+
+#[test]
+fn rows_and_range_frames_bound_the_aggregated_rows() {
+    let sql = format!(
+        "SELECT order_id, SUM(amount) OVER (ORDER BY order_id ROWS BETWEEN 1 PRECEDING AND 1 FOLLOWING) FROM {} ORDER BY order_id;",
+        ORDERS
+    );
+    assert_eq!(column(&sql, 1), ints(&[200, 400, 330, 325, 125, 75]));
+
+    // the orders of the 30 days up to each one
+    let sql = format!(
+        "SELECT order_id, COUNT(*) OVER (ORDER BY order_date RANGE BETWEEN 30 PRECEDING AND CURRENT ROW) FROM {} ORDER BY order_id;",
+        ORDERS
+    );
+    assert_eq!(column(&sql, 1), ints(&[1, 2, 3, 2, 2, 2]));
+
+    let sql = format!(
+        "SELECT order_id, MAX(order_id) OVER (ORDER BY amount DESC RANGE BETWEEN CURRENT ROW AND 50 FOLLOWING) FROM {} ORDER BY order_id;",
+        ORDERS
+    );
+    assert_eq!(column(&sql, 1), ints(&[5, 5, 3, 4, 5, 6]));
+}
+
+#[test]
+fn window_functions_apply_to_the_groups() {
+    let sql = format!(
+        "SELECT customer, SUM(amount), RANK() OVER (ORDER BY SUM(amount) DESC) AS place FROM {} GROUP BY customer ORDER BY place;",
+        ORDERS
+    );
+    assert_eq!(rows(&sql), vec![
+        vec![Value::String("acme".to_string()), Value::Int(320), Value::Int(1)],
+        vec![Value::String("globex".to_string()), Value::Int(155), Value::Int(2)],
+        vec![Value::String("initech".to_string()), Value::Int(50), Value::Int(3)]
+    ]);
+
+    let lines = column(&format!("EXPLAIN SELECT customer, RANK() OVER (ORDER BY customer) FROM {};", ORDERS), 0);
+    assert!(lines.iter().any(|line| matches!(line, Value::String(line) if line.trim().starts_with("WindowExec: RANK() OVER"))));
+}
+
+#[test]
+fn invalid_window_functions_are_rejected() {
+    assert_eq!(
+        error(&format!("SELECT order_id FROM {} WHERE ROW_NUMBER() OVER () > 1;", ORDERS)),
+        "window functions are not allowed in WHERE"
+    );
+    assert_eq!(
+        error(&format!("SELECT NTILE(0) OVER () FROM {};", ORDERS)),
+        "NTILE expects a positive integer constant, found 0"
+    );
+    assert_eq!(
+        error(&format!("SELECT LAG(amount, 1, 'none') OVER () FROM {};", ORDERS)),
+        "the default of LAG must have the type of its value, found INTEGER and VARCHAR"
+    );
+    assert_eq!(
+        error(&format!("SELECT SUM(amount) OVER (ORDER BY order_id ROWS BETWEEN CURRENT ROW AND 1 PRECEDING) FROM {};", ORDERS)),
+        "a window frame can not start after its end, found ROWS BETWEEN CURRENT ROW AND 1 PRECEDING"
+    );
+    assert_eq!(
+        error(&format!("SELECT SUM(amount) OVER (ORDER BY customer RANGE 1 PRECEDING) FROM {};", ORDERS)),
+        "RANGE offset 1 does not apply to an ORDER BY expression of type VARCHAR, it needs a non-negative number \
+         for numbers and a number of days for dates"
+    );
+    assert_eq!(
+        error(&format!("SELECT SUM(ROW_NUMBER() OVER ()) OVER () FROM {};", ORDERS)),
+        "window function calls can not be nested: SUM(ROW_NUMBER() OVER ()) OVER ()"
+    );
+}
@@ -1,15 +1,73 @@
+use std::fmt::Display;
+
+// a statement is parsed and run one at a time, boxing the select would only add an indirection
+#[allow(clippy::large_enum_variant)]
 #[derive(Debug, PartialEq, Clone)]
 pub enum Statement {
-    Select(SelectStatement)
+    Select(SelectStatement),
+    // EXPLAIN shows the plan of the statement, EXPLAIN ANALYZE also runs it and reports what each operator did
+    Explain {
+        analyze: bool,
+        statement: Box<Statement>
+    }
 }
 
 #[derive(Debug, PartialEq, Clone)]
 pub struct SelectStatement {
+    pub with: Option<With>,
+    pub distinct: bool,
     pub columns: Vec<SelectItem>,
     pub from: Option<FromClause>,
     pub where_clause: Option<Expr>,
-    pub group_by: Option<Vec<Expr>>,
-    pub order_by: Option<Vec<OrderByItem>>
+    pub group_by: Option<Vec<GroupByItem>>,
+    // the selects whose rows are combined with those of this one, from left to right; ORDER BY sorts the
+    // combined rows and the selects combined have none of their own
+    pub set_operations: Vec<SetOperation>,
+    pub order_by: Option<Vec<OrderByItem>>,
+    // LIMIT n keeps the first n rows
+    pub limit: Option<u64>
+}
+
+// WITH [RECURSIVE] a AS (SELECT ...), b AS (SELECT ...)
+#[derive(Debug, PartialEq, Clone)]
+pub struct With {
+    pub recursive: bool,
+    pub ctes: Vec<Cte>
+}
+
+// a common table expression, name (columns) AS (query); the columns rename those of the query when given
+#[derive(Debug, PartialEq, Clone)]
+pub struct Cte {
+    pub name: String,
+    pub columns: Vec<String>,
+    pub query: Box<SelectStatement>
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct SetOperation {
+    pub operator: SetOperator,
+    // UNION ALL keeps duplicate rows, INTERSECT ALL and EXCEPT ALL count them
+    pub all: bool,
+    pub query: Box<SelectStatement>
+}
+
+#[derive(Debug, PartialEq, Clone, Copy, Eq)]
+pub enum SetOperator {
+    Union,
+    Intersect,
+    Except
+}
+
+impl Display for SetOperator {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let operator = match self {
+            SetOperator::Union => "UNION",
+            SetOperator::Intersect => "INTERSECT",
+            SetOperator::Except => "EXCEPT"
+        };
+
+        write!(f, "{}", operator)
+    }
 }
 
 #[derive(Debug, PartialEq, Clone)]
@@ -26,7 +84,53 @@ pub enum SelectItem {
         func: AggregateFunc,
         // If its a Count aggregate function, then None means it's a case of Count(*)
         // using box type so that size of enum is small
-        expr: Option<Box<Expr>>
+        expr: Option<Box<Expr>>,
+        // COUNT(DISTINCT col) aggregates each distinct value once
+        distinct: bool
+    },
+    // anything else, such as salary * 12 AS yearly
+    Expr {
+        expr: Expr,
+        alias: Option<String>
+    }
+}
+
+// an item of GROUP BY; ROLLUP, CUBE and GROUPING SETS each stand for several sets of expressions, the rows are
+// grouped by every combination of one set from each item
+#[derive(Debug, PartialEq, Clone)]
+pub enum GroupByItem {
+    Expr(Expr),
+    // ROLLUP(a, b) groups by (a, b), (a) and ()
+    Rollup(Vec<Expr>),
+    // CUBE(a, b) groups by (a, b), (a), (b) and ()
+    Cube(Vec<Expr>),
+    GroupingSets(Vec<Vec<Expr>>)
+}
+
+impl GroupByItem {
+    pub fn exprs(&self) -> Vec<&Expr> {
+        match self {
+            GroupByItem::Expr(expr) => vec![expr],
+            GroupByItem::Rollup(exprs) | GroupByItem::Cube(exprs) => exprs.iter().collect(),
+            GroupByItem::GroupingSets(sets) => sets.iter().flatten().collect()
+        }
+    }
+
+    // the sets of expressions the item groups by, from the largest
+    pub fn sets(&self) -> Vec<Vec<Expr>> {
+        match self {
+            GroupByItem::Expr(expr) => vec![vec![expr.clone()]],
+            GroupByItem::Rollup(exprs) => (0..=exprs.len()).rev().map(|length| exprs[..length].to_vec()).collect(),
+            // the bits of the mask pick the expressions, the first one being the highest bit
+            GroupByItem::Cube(exprs) => (0..1usize << exprs.len()).rev()
+                .map(|mask| exprs.iter()
+                    .enumerate()
+                    .filter(|(position, _)| mask & (1 << (exprs.len() - 1 - position)) != 0)
+                    .map(|(_, expr)| expr.clone())
+                    .collect())
+                .collect(),
+            GroupByItem::GroupingSets(sets) => sets.clone()
+        }
     }
 }
 
@@ -36,12 +140,152 @@ pub enum AggregateFunc {
     Avg,
     Count,
     Min,
-    Max
+    Max,
+    // sample standard deviation and variance, STDDEV_SAMP and VAR_SAMP are the same
+    Stddev,
+    StddevPop,
+    Variance,
+    VarPop,
+    Median,
+    // PERCENTILE_CONT(expr, 0.9) interpolates between the two closest values, PERCENTILE_DISC(expr, 0.9) is the
+    // first value at or past the fraction; PERCENTILE_CONT(0.9) WITHIN GROUP (ORDER BY expr) parses to the same
+    PercentileCont(f64),
+    PercentileDisc(f64),
+    // the most frequent value, the first one seen among those as frequent
+    Mode,
+    // STRING_AGG(expr, ', ') joins the values with the separator
+    StringAgg(String),
+    ArrayAgg,
+    BoolAnd,
+    BoolOr,
+    // COUNT_IF(condition) counts the rows where the condition is true
+    CountIf,
+    // estimates which keep a summary of a fixed size instead of the values: APPROX_PERCENTILE(expr, 0.99) and
+    // APPROX_TOP_K(expr, k), the k most frequent values
+    ApproxCountDistinct,
+    ApproxPercentile(f64),
+    ApproxTopK(u64)
 }
 
-#[derive(Debug, PartialEq, Clone, Eq)]
-pub struct FromClause {
-    pub source: String
+// function computed for each row over the rows of its window rather than over a group
+#[derive(Debug, PartialEq, Clone)]
+pub enum WindowFunc {
+    RowNumber,
+    Rank,
+    DenseRank,
+    // NTILE(n) deals the rows of the partition into n buckets whose sizes differ by one at most
+    Ntile,
+    // LAG(expr [, offset [, default]]) is the value `offset` rows before the row, LEAD the one after it
+    Lag,
+    Lead,
+    FirstValue,
+    LastValue,
+    // SUM(amount) OVER (...), the aggregate over the frame of the row
+    Aggregate(AggregateFunc)
+}
+
+// OVER (PARTITION BY ... ORDER BY ... frame)
+#[derive(Debug, PartialEq, Clone)]
+pub struct WindowSpec {
+    pub partition_by: Vec<Expr>,
+    pub order_by: Vec<OrderByItem>,
+    // without one the frame runs from the start of the partition to the last row sorted like the current one,
+    // or over the whole partition when there is no ORDER BY
+    pub frame: Option<WindowFrame>
+}
+
+// the rows of the partition an aggregate, FIRST_VALUE or LAST_VALUE reads, BETWEEN start AND end
+#[derive(Debug, PartialEq, Clone)]
+pub struct WindowFrame {
+    pub units: FrameUnits,
+    pub start: FrameBound,
+    pub end: FrameBound
+}
+
+// ROWS counts rows from the current one, RANGE compares the ORDER BY value with that of the current row
+#[derive(Debug, PartialEq, Clone, Copy, Eq)]
+pub enum FrameUnits {
+    Rows,
+    Range
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum FrameBound {
+    UnboundedPreceding,
+    Preceding(Value),
+    CurrentRow,
+    Following(Value),
+    UnboundedFollowing
+}
+
+// a table expression, what FROM reads its rows from
+#[derive(Debug, PartialEq, Clone)]
+pub enum FromClause {
+    // 'orders.csv' AS o, the columns are then qualified as o.amount
+    File {
+        source: String,
+        alias: Option<String>
+    },
+    // (SELECT ...) AS t
+    Subquery {
+        query: Box<SelectStatement>,
+        alias: String
+    },
+    // left JOIN right ON condition, the condition is None for CROSS JOIN
+    Join {
+        left: Box<FromClause>,
+        kind: JoinKind,
+        right: Box<FromClause>,
+        condition: Option<Expr>
+    },
+    // input PIVOT (SUM(amount) FOR month IN ('jan', 'feb' AS february)) AS p, a row for each combination of the
+    // other columns, with a column for each value holding the aggregate of the rows where month has that value
+    Pivot {
+        input: Box<FromClause>,
+        aggregate: Expr,
+        column: String,
+        values: Vec<(Expr, Option<String>)>,
+        alias: Option<String>
+    },
+    // input UNPIVOT (amount FOR month IN (jan, feb)) AS u, a row for each listed column of each row, with the name of
+    // the column in month and its value in amount; rows whose value is NULL are left out, unless INCLUDE NULLS
+    Unpivot {
+        input: Box<FromClause>,
+        value: String,
+        name: String,
+        columns: Vec<String>,
+        include_nulls: bool,
+        alias: Option<String>
+    }
+}
+
+#[derive(Debug, PartialEq, Clone, Copy, Eq)]
+pub enum JoinKind {
+    Inner,
+    Left,
+    Right,
+    Full,
+    Cross,
+    // rows of the left side with a match, or without one for Anti; written as EXISTS and IN subqueries rather
+    // than as joins
+    Semi,
+    Anti
+}
+
+impl Display for JoinKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let kind = match self {
+            JoinKind::Inner => "INNER",
+            JoinKind::Left => "LEFT",
+            JoinKind::Right => "RIGHT",
+            JoinKind::Full => "FULL",
+            JoinKind::Cross => "CROSS",
+            JoinKind::Semi => "SEMI",
+            JoinKind::Anti => "ANTI"
+        };
+
+        write!(f, "{}", kind)
+    }
 }
 
 #[derive(Debug, PartialEq, Clone)]
@@ -56,6 +300,77 @@ pub enum Expr {
     UnaryOp {
         operator: UnaryOperator,
         expr: Box<Expr>,
+    },
+    // aggregate used inside a larger expression, e.g. SUM(salary) / COUNT(*)
+    Aggregate {
+        func: AggregateFunc,
+        expr: Option<Box<Expr>>,
+        distinct: bool
+    },
+    // status [NOT] IN ('active', 'pending')
+    InList {
+        expr: Box<Expr>,
+        list: Vec<Expr>,
+        negated: bool
+    },
+    // age [NOT] BETWEEN 18 AND 65, both bounds included
+    Between {
+        expr: Box<Expr>,
+        low: Box<Expr>,
+        high: Box<Expr>,
+        negated: bool
+    },
+    // name [NOT] LIKE 'Sh%', ILIKE ignores case
+    Like {
+        expr: Box<Expr>,
+        pattern: Box<Expr>,
+        case_insensitive: bool,
+        negated: bool
+    },
+    // email IS [NOT] NULL
+    IsNull {
+        expr: Box<Expr>,
+        negated: bool
+    },
+    // email [NOT] REGEXP '^[a-z]+@', RLIKE is the same
+    Regexp {
+        expr: Box<Expr>,
+        pattern: Box<Expr>,
+        negated: bool
+    },
+    // call of a scalar function, REGEXP_REPLACE(phone, '[^0-9]', '')
+    Function {
+        name: String,
+        args: Vec<Expr>
+    },
+    // CAST(expr AS type), expr::type is the same, TRY_CAST gives NULL instead of an error for values which do not convert
+    Cast {
+        expr: Box<Expr>,
+        type_name: TypeName,
+        try_cast: bool
+    },
+    // CASE [operand] WHEN condition THEN result ... [ELSE result] END, with an operand each WHEN is a value
+    // compared to it
+    Case {
+        operand: Option<Box<Expr>>,
+        branches: Vec<(Expr, Expr)>,
+        else_result: Option<Box<Expr>>
+    },
+    // (SELECT MAX(amount) FROM 'orders.csv'), the single value of its single row, NULL without a row
+    Subquery(Box<SelectStatement>),
+    // EXISTS (SELECT ...), whether the query returns a row
+    Exists(Box<SelectStatement>),
+    // id [NOT] IN (SELECT id FROM 'blocked.csv')
+    InSubquery {
+        expr: Box<Expr>,
+        query: Box<SelectStatement>,
+        negated: bool
+    },
+    // RANK() OVER (PARTITION BY region ORDER BY amount DESC), COUNT(*) has no arguments
+    Window {
+        func: WindowFunc,
+        args: Vec<Expr>,
+        over: Box<WindowSpec>
     }
 }
 
@@ -78,6 +393,10 @@ pub enum BinaryOperator {
     Subtract,
     Multiply,
     Divide,
+    Modulo,
+
+    // String
+    Concat,
 }
 
 #[derive(Debug, PartialEq, Clone, Eq)]
@@ -86,6 +405,30 @@ pub enum UnaryOperator {
     Minus,
 }
 
+// type a value can be cast to, every spelling the parser accepts maps to one of these
+#[derive(Debug, PartialEq, Clone, Copy, Eq)]
+pub enum TypeName {
+    Boolean,
+    Integer,
+    Double,
+    Varchar,
+    Date
+}
+
+impl Display for TypeName {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let type_name = match self {
+            TypeName::Boolean => "BOOLEAN",
+            TypeName::Integer => "INTEGER",
+            TypeName::Double => "DOUBLE",
+            TypeName::Varchar => "VARCHAR",
+            TypeName::Date => "DATE"
+        };
+
+        write!(f, "{}", type_name)
+    }
+}
+
 #[derive(Debug, PartialEq, Clone)]
 pub enum Value {
     Null,
@@ -94,4 +437,468 @@ pub enum Value {
     Float(f64),
     String(String),
     Date(chrono::NaiveDate),
-}
\ No newline at end of file
+    // the values collected by ARRAY_AGG
+    List(Vec<Value>),
+}
+
+impl Expr {
+    // the expressions this one is computed from, in the order they are written
+    pub fn children(&self) -> Vec<&Expr> {
+        match self {
+            Expr::Column(_) | Expr::Literal(_) | Expr::Subquery(_) | Expr::Exists(_) => Vec::new(),
+            Expr::BinaryOp { left, right, .. } => vec![left, right],
+            Expr::UnaryOp { expr, .. } => vec![expr],
+            Expr::Aggregate { expr, .. } => expr.iter().map(|expr| expr.as_ref()).collect(),
+            Expr::InList { expr, list, .. } => std::iter::once(expr.as_ref()).chain(list.iter()).collect(),
+            Expr::Between { expr, low, high, .. } => vec![expr, low, high],
+            Expr::Like { expr, pattern, .. } => vec![expr, pattern],
+            Expr::IsNull { expr, .. } => vec![expr],
+            Expr::Regexp { expr, pattern, .. } => vec![expr, pattern],
+            Expr::Function { args, .. } => args.iter().collect(),
+            Expr::Cast { expr, .. } => vec![expr],
+            Expr::Case { operand, branches, else_result } => operand.iter().map(|operand| operand.as_ref())
+                .chain(branches.iter().flat_map(|(when, then)| [when, then]))
+                .chain(else_result.iter().map(|result| result.as_ref()))
+                .collect(),
+            Expr::InSubquery { expr, .. } => vec![expr],
+            Expr::Window { args, over, .. } => args.iter()
+                .chain(over.partition_by.iter())
+                .chain(over.order_by.iter().map(|item| &item.expr))
+                .collect()
+        }
+    }
+
+    // the queries nested in the expression, those in its subqueries excluded
+    pub fn subqueries(&self) -> Vec<&SelectStatement> {
+        match self {
+            Expr::Subquery(query) | Expr::Exists(query) => vec![query],
+            Expr::InSubquery { expr, query, .. } => expr.subqueries().into_iter().chain(std::iter::once(query.as_ref())).collect(),
+            expr => expr.children().into_iter().flat_map(Expr::subqueries).collect()
+        }
+    }
+}
+
+impl SelectStatement {
+    // the expressions written in the statement, those of its subqueries excluded
+    pub fn exprs(&self) -> Vec<&Expr> {
+        let mut exprs = Vec::new();
+        for item in &self.columns {
+            match item {
+                SelectItem::Aggregate { expr: Some(expr), .. } => exprs.push(expr.as_ref()),
+                SelectItem::Expr { expr, .. } => exprs.push(expr),
+                _ => {}
+            }
+        }
+        if let Some(from) = &self.from {
+            from.exprs(&mut exprs);
+        }
+        exprs.extend(self.where_clause.iter());
+        exprs.extend(self.group_by.iter().flatten().flat_map(|item| item.exprs()));
+        exprs.extend(self.order_by.iter().flatten().map(|item| &item.expr));
+        exprs
+    }
+
+    // tables the statement reads, those read by its subqueries included; a table is a file or a common table
+    // expression of an enclosing WITH, those of its own WITH are left out
+    pub fn sources(&self) -> Vec<&str> {
+        let mut sources = Vec::new();
+        if let Some(from) = &self.from {
+            from.sources(&mut sources);
+        }
+        for expr in self.exprs() {
+            for query in expr.subqueries() {
+                sources.extend(query.sources());
+            }
+        }
+        for operation in &self.set_operations {
+            sources.extend(operation.query.sources());
+        }
+        if let Some(with) = &self.with {
+            for cte in &with.ctes {
+                sources.extend(cte.query.sources());
+            }
+            sources.retain(|source| !with.ctes.iter().any(|cte| cte.name.eq_ignore_ascii_case(source)));
+        }
+        sources
+    }
+}
+
+impl FromClause {
+    fn exprs<'a>(&'a self, exprs: &mut Vec<&'a Expr>) {
+        match self {
+            FromClause::Join { left, right, condition, .. } => {
+                left.exprs(exprs);
+                right.exprs(exprs);
+                exprs.extend(condition.iter());
+            },
+            FromClause::Pivot { input, aggregate, values, .. } => {
+                input.exprs(exprs);
+                exprs.push(aggregate);
+                exprs.extend(values.iter().map(|(value, _)| value));
+            },
+            FromClause::Unpivot { input, .. } => input.exprs(exprs),
+            FromClause::File { .. } | FromClause::Subquery { .. } => {}
+        }
+    }
+
+    fn sources<'a>(&'a self, sources: &mut Vec<&'a str>) {
+        match self {
+            FromClause::File { source, .. } => sources.push(source),
+            FromClause::Subquery { query, .. } => sources.extend(query.sources()),
+            FromClause::Join { left, right, .. } => {
+                left.sources(sources);
+                right.sources(sources);
+            },
+            FromClause::Pivot { input, .. } | FromClause::Unpivot { input, .. } => input.sources(sources)
+        }
+    }
+}
+
+impl Display for GroupByItem {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let list = |exprs: &[Expr]| exprs.iter().map(|expr| expr.to_string()).collect::<Vec<String>>().join(", ");
+        match self {
+            GroupByItem::Expr(expr) => write!(f, "{}", expr),
+            GroupByItem::Rollup(exprs) => write!(f, "ROLLUP({})", list(exprs)),
+            GroupByItem::Cube(exprs) => write!(f, "CUBE({})", list(exprs)),
+            GroupByItem::GroupingSets(sets) => {
+                let sets: Vec<String> = sets.iter().map(|set| format!("({})", list(set))).collect();
+                write!(f, "GROUPING SETS ({})", sets.join(", "))
+            }
+        }
+    }
+}
+
+impl Display for AggregateFunc {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let func = match self {
+            AggregateFunc::Sum => "SUM",
+            AggregateFunc::Avg => "AVG",
+            AggregateFunc::Count => "COUNT",
+            AggregateFunc::Min => "MIN",
+            AggregateFunc::Max => "MAX",
+            AggregateFunc::Stddev => "STDDEV",
+            AggregateFunc::StddevPop => "STDDEV_POP",
+            AggregateFunc::Variance => "VARIANCE",
+            AggregateFunc::VarPop => "VAR_POP",
+            AggregateFunc::Median => "MEDIAN",
+            AggregateFunc::PercentileCont(_) => "PERCENTILE_CONT",
+            AggregateFunc::PercentileDisc(_) => "PERCENTILE_DISC",
+            AggregateFunc::Mode => "MODE",
+            AggregateFunc::StringAgg(_) => "STRING_AGG",
+            AggregateFunc::ArrayAgg => "ARRAY_AGG",
+            AggregateFunc::BoolAnd => "BOOL_AND",
+            AggregateFunc::BoolOr => "BOOL_OR",
+            AggregateFunc::CountIf => "COUNT_IF",
+            AggregateFunc::ApproxCountDistinct => "APPROX_COUNT_DISTINCT",
+            AggregateFunc::ApproxPercentile(_) => "APPROX_PERCENTILE",
+            AggregateFunc::ApproxTopK(_) => "APPROX_TOP_K"
+        };
+
+        write!(f, "{}", func)
+    }
+}
+
+impl AggregateFunc {
+    // the constant written after the aggregated expression, the separator of STRING_AGG, the fraction of a
+    // percentile or the k of APPROX_TOP_K
+    pub fn parameter(&self) -> Option<Value> {
+        match self {
+            AggregateFunc::PercentileCont(fraction) | AggregateFunc::PercentileDisc(fraction)
+            | AggregateFunc::ApproxPercentile(fraction) => Some(Value::Float(*fraction)),
+            AggregateFunc::ApproxTopK(k) => Some(Value::Int(*k as i64)),
+            AggregateFunc::StringAgg(separator) => Some(Value::String(separator.clone())),
+            _ => None
+        }
+    }
+}
+
+impl Display for BinaryOperator {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let operator = match self {
+            BinaryOperator::Equals => "=",
+            BinaryOperator::NotEquals => "!=",
+            BinaryOperator::GreaterThan => ">",
+            BinaryOperator::LessThan => "<",
+            BinaryOperator::GreaterThanOrEquals => ">=",
+            BinaryOperator::LessThanOrEquals => "<=",
+            BinaryOperator::And => "AND",
+            BinaryOperator::Or => "OR",
+            BinaryOperator::Add => "+",
+            BinaryOperator::Subtract => "-",
+            BinaryOperator::Multiply => "*",
+            BinaryOperator::Divide => "/",
+            BinaryOperator::Modulo => "%",
+            BinaryOperator::Concat => "||"
+        };
+
+        write!(f, "{}", operator)
+    }
+}
+
+impl Display for Value {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Value::Null => write!(f, "NULL"),
+            Value::Bool(b) => write!(f, "{}", if *b { "TRUE" } else { "FALSE" }),
+            Value::Int(i) => write!(f, "{}", i),
+            Value::Float(fl) => write!(f, "{}", fl),
+            Value::String(s) => write!(f, "'{}'", s.replace('\'', "''")),
+            Value::Date(d) => write!(f, "DATE '{}'", d),
+            Value::List(values) => {
+                let values: Vec<String> = values.iter().map(|value| value.to_string()).collect();
+                write!(f, "[{}]", values.join(", "))
+            }
+        }
+    }
+}
+
+// renders the expression back as sql, used to name computed output columns
+impl Display for Expr {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Expr::Column(name) => write!(f, "{}", name),
+            Expr::Literal(value) => write!(f, "{}", value),
+            Expr::BinaryOp { left, operator, right } => write!(f, "{} {} {}", operand(left), operator, operand(right)),
+            Expr::UnaryOp { operator: UnaryOperator::Minus, expr } => write!(f, "-{}", operand(expr)),
+            Expr::UnaryOp { operator: UnaryOperator::Not, expr } => write!(f, "NOT {}", operand(expr)),
+            Expr::Aggregate { func, expr: Some(expr), distinct } => {
+                write!(f, "{}({}{}", func, if *distinct { "DISTINCT " } else { "" }, expr)?;
+                if let Some(parameter) = func.parameter() {
+                    write!(f, ", {}", parameter)?;
+                }
+                write!(f, ")")
+            },
+            Expr::Aggregate { func, expr: None, .. } => write!(f, "{}(*)", func),
+            Expr::InList { expr, list, negated } => {
+                let list: Vec<String> = list.iter().map(|item| item.to_string()).collect();
+                write!(f, "{} {}IN ({})", operand(expr), not(*negated), list.join(", "))
+            },
+            Expr::Between { expr, low, high, negated } => {
+                write!(f, "{} {}BETWEEN {} AND {}", operand(expr), not(*negated), operand(low), operand(high))
+            },
+            Expr::Like { expr, pattern, case_insensitive, negated } => {
+                let like = if *case_insensitive { "ILIKE" } else { "LIKE" };
+                write!(f, "{} {}{} {}", operand(expr), not(*negated), like, operand(pattern))
+            },
+            Expr::IsNull { expr, negated } => write!(f, "{} IS {}NULL", operand(expr), not(*negated)),
+            Expr::Regexp { expr, pattern, negated } => {
+                write!(f, "{} {}REGEXP {}", operand(expr), not(*negated), operand(pattern))
+            },
+            Expr::Function { name, args } if name.eq_ignore_ascii_case("extract") && args.len() == 2
+                && let Expr::Literal(Value::String(field)) = &args[0] => {
+                write!(f, "{}({} FROM {})", name, field, args[1])
+            },
+            Expr::Function { name, args } if name.eq_ignore_ascii_case("current_date") && args.is_empty() => {
+                write!(f, "{}", name)
+            },
+            Expr::Function { name, args } if name.eq_ignore_ascii_case("position") && args.len() == 2 => {
+                write!(f, "{}({} IN {})", name, operand(&args[0]), operand(&args[1]))
+            },
+            Expr::Function { name, args } => {
+                let args: Vec<String> = args.iter().map(|arg| arg.to_string()).collect();
+                write!(f, "{}({})", name, args.join(", "))
+            },
+            Expr::Cast { expr, type_name, try_cast } => {
+                write!(f, "{}CAST({} AS {})", if *try_cast { "TRY_" } else { "" }, expr, type_name)
+            },
+            Expr::Case { operand, branches, else_result } => {
+                write!(f, "CASE")?;
+                if let Some(operand) = operand {
+                    write!(f, " {}", operand)?;
+                }
+                for (when, then) in branches {
+                    write!(f, " WHEN {} THEN {}", when, then)?;
+                }
+                if let Some(result) = else_result {
+                    write!(f, " ELSE {}", result)?;
+                }
+                write!(f, " END")
+            },
+            Expr::Subquery(query) => write!(f, "({})", query),
+            Expr::Exists(query) => write!(f, "EXISTS ({})", query),
+            Expr::InSubquery { expr, query, negated } => write!(f, "{} {}IN ({})", operand(expr), not(*negated), query),
+            Expr::Window { func: func @ WindowFunc::Aggregate(AggregateFunc::Count), args, over } if args.is_empty() => {
+                write!(f, "{}(*) OVER ({})", func, over)
+            },
+            Expr::Window { func, args, over } => {
+                let mut args: Vec<String> = args.iter().map(|arg| arg.to_string()).collect();
+                if let WindowFunc::Aggregate(aggregate) = func {
+                    args.extend(aggregate.parameter().map(|parameter| parameter.to_string()));
+                }
+                write!(f, "{}({}) OVER ({})", func, args.join(", "), over)
+            }
+        }
+    }
+}
+
+// renders the query back as sql, without the closing semicolon
+impl Display for SelectStatement {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if let Some(with) = &self.with {
+            let ctes: Vec<String> = with.ctes.iter()
+                .map(|cte| match cte.columns.is_empty() {
+                    true => format!("{} AS ({})", cte.name, cte.query),
+                    false => format!("{}({}) AS ({})", cte.name, cte.columns.join(", "), cte.query)
+                })
+                .collect();
+            write!(f, "WITH {}{} ", if with.recursive { "RECURSIVE " } else { "" }, ctes.join(", "))?;
+        }
+        let columns: Vec<String> = self.columns.iter().map(|item| item.to_string()).collect();
+        write!(f, "SELECT {}{}", if self.distinct { "DISTINCT " } else { "" }, columns.join(", "))?;
+        if let Some(from) = &self.from {
+            write!(f, " FROM {}", from)?;
+        }
+        if let Some(condition) = &self.where_clause {
+            write!(f, " WHERE {}", condition)?;
+        }
+        if let Some(group_by) = &self.group_by {
+            let group_by: Vec<String> = group_by.iter().map(|item| item.to_string()).collect();
+            write!(f, " GROUP BY {}", group_by.join(", "))?;
+        }
+        for operation in &self.set_operations {
+            write!(f, " {}{} {}", operation.operator, if operation.all { " ALL" } else { "" }, operation.query)?;
+        }
+        if let Some(order_by) = &self.order_by {
+            let order_by: Vec<String> = order_by.iter().map(|item| item.to_string()).collect();
+            write!(f, " ORDER BY {}", order_by.join(", "))?;
+        }
+        if let Some(limit) = self.limit {
+            write!(f, " LIMIT {}", limit)?;
+        }
+        Ok(())
+    }
+}
+
+impl Display for OrderByItem {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}{}", self.expr, if self.asc { "" } else { " DESC" })
+    }
+}
+
+impl Display for WindowFunc {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let func = match self {
+            WindowFunc::RowNumber => "ROW_NUMBER",
+            WindowFunc::Rank => "RANK",
+            WindowFunc::DenseRank => "DENSE_RANK",
+            WindowFunc::Ntile => "NTILE",
+            WindowFunc::Lag => "LAG",
+            WindowFunc::Lead => "LEAD",
+            WindowFunc::FirstValue => "FIRST_VALUE",
+            WindowFunc::LastValue => "LAST_VALUE",
+            WindowFunc::Aggregate(func) => return write!(f, "{}", func)
+        };
+
+        write!(f, "{}", func)
+    }
+}
+
+impl Display for WindowSpec {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut parts = Vec::new();
+        if !self.partition_by.is_empty() {
+            let partition_by: Vec<String> = self.partition_by.iter().map(|expr| expr.to_string()).collect();
+            parts.push(format!("PARTITION BY {}", partition_by.join(", ")));
+        }
+        if !self.order_by.is_empty() {
+            let order_by: Vec<String> = self.order_by.iter().map(|item| item.to_string()).collect();
+            parts.push(format!("ORDER BY {}", order_by.join(", ")));
+        }
+        if let Some(frame) = &self.frame {
+            parts.push(frame.to_string());
+        }
+
+        write!(f, "{}", parts.join(" "))
+    }
+}
+
+impl Display for WindowFrame {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let units = match self.units {
+            FrameUnits::Rows => "ROWS",
+            FrameUnits::Range => "RANGE"
+        };
+        write!(f, "{} BETWEEN {} AND {}", units, self.start, self.end)
+    }
+}
+
+impl Display for FrameBound {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FrameBound::UnboundedPreceding => write!(f, "UNBOUNDED PRECEDING"),
+            FrameBound::Preceding(offset) => write!(f, "{} PRECEDING", offset),
+            FrameBound::CurrentRow => write!(f, "CURRENT ROW"),
+            FrameBound::Following(offset) => write!(f, "{} FOLLOWING", offset),
+            FrameBound::UnboundedFollowing => write!(f, "UNBOUNDED FOLLOWING")
+        }
+    }
+}
+
+impl Display for SelectItem {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SelectItem::Wildcard => write!(f, "*"),
+            SelectItem::Column(name) => write!(f, "{}", name),
+            SelectItem::Aggregate { func, expr, distinct } => {
+                write!(f, "{}", Expr::Aggregate { func: func.clone(), expr: expr.clone(), distinct: *distinct })
+            },
+            SelectItem::Expr { expr, alias: Some(alias) } => write!(f, "{} AS {}", expr, alias),
+            SelectItem::Expr { expr, alias: None } => write!(f, "{}", expr)
+        }
+    }
+}
+
+impl Display for FromClause {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FromClause::File { source, alias: Some(alias) } => write!(f, "'{}' AS {}", source, alias),
+            FromClause::File { source, alias: None } => write!(f, "'{}'", source),
+            FromClause::Subquery { query, alias } => write!(f, "({}) AS {}", query, alias),
+            FromClause::Join { left, kind: JoinKind::Cross, right, .. } => write!(f, "{} CROSS JOIN {}", left, right),
+            FromClause::Join { left, kind, right, condition } => {
+                write!(f, "{} {} JOIN {}", left, kind, right)?;
+                if let Some(condition) = condition {
+                    write!(f, " ON {}", condition)?;
+                }
+                Ok(())
+            },
+            FromClause::Pivot { input, aggregate, column, values, alias } => {
+                let values: Vec<String> = values.iter()
+                    .map(|(value, alias)| match alias {
+                        Some(alias) => format!("{} AS {}", value, alias),
+                        None => value.to_string()
+                    })
+                    .collect();
+                write!(f, "{} PIVOT ({} FOR {} IN ({}))", input, aggregate, column, values.join(", "))?;
+                if let Some(alias) = alias {
+                    write!(f, " AS {}", alias)?;
+                }
+                Ok(())
+            },
+            FromClause::Unpivot { input, value, name, columns, include_nulls, alias } => {
+                let nulls = if *include_nulls { " INCLUDE NULLS" } else { "" };
+                write!(f, "{} UNPIVOT{} ({} FOR {} IN ({}))", input, nulls, value, name, columns.join(", "))?;
+                if let Some(alias) = alias {
+                    write!(f, " AS {}", alias)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+fn not(negated: bool) -> &'static str {
+    if negated { "NOT " } else { "" }
+}
+
+// nested operations are parenthesised, so that the rendered text keeps the meaning of the tree
+fn operand(expr: &Expr) -> String {
+    match expr {
+        Expr::BinaryOp { .. } | Expr::InList { .. } | Expr::Between { .. } | Expr::Like { .. } | Expr::IsNull { .. }
+        | Expr::Regexp { .. } => {
+            format!("({})", expr)
+        },
+        _ => expr.to_string()
+    }
+}
@@ -1,3 +1,29 @@
+use std::fmt::Display;
+
+#[derive(Debug, Clone, PartialEq)]
 pub struct LexerError {
-    pub message: String
-}
\ No newline at end of file
+    pub message: String,
+    // 1 based line and column of the offending character
+    pub line: usize,
+    pub column: usize
+}
+
+impl LexerError {
+    pub fn new(message: &str) -> Self {
+        LexerError { message: message.to_string(), line: 0, column: 0 }
+    }
+
+    pub fn at(self, (line, column): (usize, usize)) -> Self {
+        // keep the location if the error already knows where it happened
+        if self.line > 0 {
+            return self
+        }
+        LexerError { line, column, ..self }
+    }
+}
+
+impl Display for LexerError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} at line {}, column {}", self.message, self.line, self.column)
+    }
+}
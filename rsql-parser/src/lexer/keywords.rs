@@ -1,4 +1,5 @@
 use std::fmt::Display;
+use crate::suggest::closest_match;
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum KeywordType {
@@ -18,7 +19,50 @@ pub enum KeywordType {
     Sum,
     Avg,
     Min,
-    Max
+    Max,
+    As,
+    Explain,
+    Analyze,
+    Distinct,
+    Not,
+    Null,
+    Is,
+    In,
+    Between,
+    Like,
+    Ilike,
+    Regexp,
+    Rlike,
+    Case,
+    When,
+    Then,
+    Else,
+    End,
+    Join,
+    Inner,
+    Left,
+    Right,
+    Full,
+    Outer,
+    Cross,
+    On,
+    Exists,
+    With,
+    Recursive,
+    Union,
+    All,
+    Intersect,
+    Except,
+    Limit,
+    Over,
+    Partition,
+    Rows,
+    Range,
+    Unbounded,
+    Preceding,
+    Following,
+    Current,
+    Row
 }
 
 impl Display for KeywordType {
@@ -40,7 +84,50 @@ impl Display for KeywordType {
             KeywordType::Min => "MIN",
             KeywordType::Max => "MAX",
             KeywordType::True => "TRUE",
-            KeywordType::False => "FALSE"
+            KeywordType::False => "FALSE",
+            KeywordType::As => "AS",
+            KeywordType::Explain => "EXPLAIN",
+            KeywordType::Analyze => "ANALYZE",
+            KeywordType::Distinct => "DISTINCT",
+            KeywordType::Not => "NOT",
+            KeywordType::Null => "NULL",
+            KeywordType::Is => "IS",
+            KeywordType::In => "IN",
+            KeywordType::Between => "BETWEEN",
+            KeywordType::Like => "LIKE",
+            KeywordType::Ilike => "ILIKE",
+            KeywordType::Regexp => "REGEXP",
+            KeywordType::Rlike => "RLIKE",
+            KeywordType::Case => "CASE",
+            KeywordType::When => "WHEN",
+            KeywordType::Then => "THEN",
+            KeywordType::Else => "ELSE",
+            KeywordType::End => "END",
+            KeywordType::Join => "JOIN",
+            KeywordType::Inner => "INNER",
+            KeywordType::Left => "LEFT",
+            KeywordType::Right => "RIGHT",
+            KeywordType::Full => "FULL",
+            KeywordType::Outer => "OUTER",
+            KeywordType::Cross => "CROSS",
+            KeywordType::On => "ON",
+            KeywordType::Exists => "EXISTS",
+            KeywordType::With => "WITH",
+            KeywordType::Recursive => "RECURSIVE",
+            KeywordType::Union => "UNION",
+            KeywordType::All => "ALL",
+            KeywordType::Intersect => "INTERSECT",
+            KeywordType::Except => "EXCEPT",
+            KeywordType::Limit => "LIMIT",
+            KeywordType::Over => "OVER",
+            KeywordType::Partition => "PARTITION",
+            KeywordType::Rows => "ROWS",
+            KeywordType::Range => "RANGE",
+            KeywordType::Unbounded => "UNBOUNDED",
+            KeywordType::Preceding => "PRECEDING",
+            KeywordType::Following => "FOLLOWING",
+            KeywordType::Current => "CURRENT",
+            KeywordType::Row => "ROW"
         };
 
         write!(f, "{}", keyword)
@@ -62,9 +149,70 @@ pub fn is_keyword(word: &str) -> Option<KeywordType> {
         "ASC" => Some(KeywordType::Asc),
         "DESC" => Some(KeywordType::Desc),
         "COUNT" => Some(KeywordType::Count),
+        "SUM" => Some(KeywordType::Sum),
         "AVG" => Some(KeywordType::Avg),
         "MIN" => Some(KeywordType::Min),
         "MAX" => Some(KeywordType::Max),
+        "AS" => Some(KeywordType::As),
+        "EXPLAIN" => Some(KeywordType::Explain),
+        "ANALYZE" => Some(KeywordType::Analyze),
+        "DISTINCT" => Some(KeywordType::Distinct),
+        "NOT" => Some(KeywordType::Not),
+        "NULL" => Some(KeywordType::Null),
+        "IS" => Some(KeywordType::Is),
+        "IN" => Some(KeywordType::In),
+        "BETWEEN" => Some(KeywordType::Between),
+        "LIKE" => Some(KeywordType::Like),
+        "ILIKE" => Some(KeywordType::Ilike),
+        "REGEXP" => Some(KeywordType::Regexp),
+        "RLIKE" => Some(KeywordType::Rlike),
+        "CASE" => Some(KeywordType::Case),
+        "WHEN" => Some(KeywordType::When),
+        "THEN" => Some(KeywordType::Then),
+        "ELSE" => Some(KeywordType::Else),
+        "END" => Some(KeywordType::End),
+        "JOIN" => Some(KeywordType::Join),
+        "INNER" => Some(KeywordType::Inner),
+        "LEFT" => Some(KeywordType::Left),
+        "RIGHT" => Some(KeywordType::Right),
+        "FULL" => Some(KeywordType::Full),
+        "OUTER" => Some(KeywordType::Outer),
+        "CROSS" => Some(KeywordType::Cross),
+        "ON" => Some(KeywordType::On),
+        "EXISTS" => Some(KeywordType::Exists),
+        "WITH" => Some(KeywordType::With),
+        "RECURSIVE" => Some(KeywordType::Recursive),
+        "UNION" => Some(KeywordType::Union),
+        "ALL" => Some(KeywordType::All),
+        "INTERSECT" => Some(KeywordType::Intersect),
+        "EXCEPT" => Some(KeywordType::Except),
+        "LIMIT" => Some(KeywordType::Limit),
+        "OVER" => Some(KeywordType::Over),
+        "PARTITION" => Some(KeywordType::Partition),
+        "ROWS" => Some(KeywordType::Rows),
+        "RANGE" => Some(KeywordType::Range),
+        "UNBOUNDED" => Some(KeywordType::Unbounded),
+        "PRECEDING" => Some(KeywordType::Preceding),
+        "FOLLOWING" => Some(KeywordType::Following),
+        "CURRENT" => Some(KeywordType::Current),
+        "ROW" => Some(KeywordType::Row),
         _ => None,
     }
-}
\ No newline at end of file
+}
+
+const KEYWORDS: &[&str] = &[
+    "SELECT", "FROM", "WHERE", "TRUE", "FALSE", "ORDER", "GROUP", "BY", "AND", "OR", "ASC", "DESC",
+    "COUNT", "SUM", "AVG", "MIN", "MAX", "AS", "EXPLAIN", "ANALYZE", "DISTINCT",
+    "NOT", "NULL", "IS", "IN", "BETWEEN", "LIKE", "ILIKE", "REGEXP", "RLIKE",
+    "CASE", "WHEN", "THEN", "ELSE", "END", "JOIN", "INNER", "LEFT", "RIGHT", "FULL", "OUTER", "CROSS", "ON",
+    "EXISTS", "WITH", "RECURSIVE", "UNION", "ALL", "INTERSECT", "EXCEPT", "LIMIT",
+    "OVER", "PARTITION", "ROWS", "RANGE", "UNBOUNDED", "PRECEDING", "FOLLOWING", "CURRENT", "ROW"
+];
+
+// "did you mean" lookup for words that are not keywords but look like a misspelled one
+pub fn suggest_keyword(word: &str) -> Option<KeywordType> {
+    if is_keyword(word).is_some() {
+        return None
+    }
+    closest_match(word, KEYWORDS).and_then(is_keyword)
+}
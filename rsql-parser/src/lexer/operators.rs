@@ -13,7 +13,10 @@ pub enum OperatorType {
     Add,
     Subtract,
     Divide,
-    Multiply
+    Multiply,
+    Modulo,
+    Concat,
+    Cast
 }
 
 impl Display for OperatorType {
@@ -30,13 +33,21 @@ impl Display for OperatorType {
             OperatorType::Add => "+",
             OperatorType::Subtract => "-",
             OperatorType::Divide => "/",
-            OperatorType::Multiply => "*"
+            OperatorType::Multiply => "*",
+            OperatorType::Modulo => "%",
+            OperatorType::Concat => "||",
+            OperatorType::Cast => "::"
         };
         
         write!(f, "{}", operator)
     }
 }
 
+// every operator the lexer can produce, the tokenizer uses this for maximal munch
+pub const OPERATOR_LEXEMES: &[&str] = &[
+    "=", "!=", "<>", "<", ">", "<=", ">=", "+", "-", "*", "/", "%", "||", "::"
+];
+
 pub fn is_operator(word: &str) -> Option<OperatorType> {
     match word.to_ascii_uppercase().as_str() {
         "=" => Some(OperatorType::Equals),
@@ -45,12 +56,15 @@ pub fn is_operator(word: &str) -> Option<OperatorType> {
         ">=" => Some(OperatorType::GreaterThanOrEqual),
         "<=" => Some(OperatorType::SmallerThanOrEqual),
         "<>" => Some(OperatorType::NotEquals),
+        "!=" => Some(OperatorType::NotEquals),
         "&&" => Some(OperatorType::And),
-        "||" => Some(OperatorType::Or),
+        "||" => Some(OperatorType::Concat),
         "+" => Some(OperatorType::Add),
         "-" => Some(OperatorType::Subtract),
         "/" => Some(OperatorType::Divide),
         "*" => Some(OperatorType::Multiply),
+        "%" => Some(OperatorType::Modulo),
+        "::" => Some(OperatorType::Cast),
         _ => None
     }
-}
\ No newline at end of file
+}
@@ -1,283 +1,465 @@
 use crate::lexer::errors::LexerError;
-use crate::lexer::grammar::is_grammar_type;
+use crate::lexer::grammar::{is_grammar_type, GrammarType};
 use crate::lexer::keywords::is_keyword;
-use crate::lexer::operators::is_operator;
-use crate::lexer::tokenizer::LEXER_STATE::{IN_IDENTIFIER, START};
+use crate::lexer::keywords::KeywordType;
+use crate::lexer::operators::{is_operator, OperatorType, OPERATOR_LEXEMES};
+use crate::lexer::tokenizer::LexerState::{InIdentifier, Start};
 use crate::lexer::tokens::Token;
-use crate::lexer::tokens::Token::{Float, Grammar, Identifier, Integer, Keyword, Operator, StringLiteral};
+use crate::lexer::tokens::Token::{Comment, Float, Grammar, Identifier, Integer, Keyword, Operator, StringLiteral};
+use std::iter::Peekable;
+use std::str::Chars;
 
 #[derive(PartialEq, Eq)]
-enum LEXER_STATE {
-    START,
-    IN_IDENTIFIER,
-    IN_NUMBER,
-    IN_STRING(char),
-    IN_OPERATOR
+enum LexerState {
+    Start,
+    InIdentifier,
+    InNumber,
+    InString(char),
+    InOperator,
+    InLineComment,
+    // nesting depth, /* a /* b */ c */ is a single comment
+    InBlockComment(usize)
 }
 
-const WHITESPACE_TERMINATORS: &[char] = &[' ', '\t', '\n'];
 const PUNCTUATION_TERMINATORS: &[char] = &[',', ';', '(', ')', '*'];
 
-const OPERATORS: &[char] = &['=', '!', '<', '>'];
+const OPERATORS: &[char] = &['=', '!', '<', '>', '+', '-', '/', '%', '|', ':'];
+
+// single quotes delimit string literals, double quotes and backticks delimit identifiers such as "First Name"
+const IDENTIFIER_QUOTES: &[char] = &['"', '`'];
+
+// walks the sql one character at a time, keeping track of where we are for error reporting
+struct Cursor<'a> {
+    chars: Peekable<Chars<'a>>,
+    line: usize,
+    column: usize
+}
+
+impl<'a> Cursor<'a> {
+    fn new(sql: &'a str) -> Self {
+        Cursor { chars: sql.chars().peekable(), line: 1, column: 1 }
+    }
+
+    fn peek(&mut self) -> Option<char> {
+        self.chars.peek().copied()
+    }
+
+    fn location(&self) -> (usize, usize) {
+        (self.line, self.column)
+    }
+}
+
+impl Iterator for Cursor<'_> {
+    type Item = char;
+
+    fn next(&mut self) -> Option<char> {
+        let ch = self.chars.next()?;
+        if ch == '\n' {
+            self.line += 1;
+            self.column = 1;
+        } else {
+            self.column += 1;
+        }
+        Some(ch)
+    }
+}
 
 pub fn tokenize(sql: &str) -> Vec<Token> {
     let response = state_based_tokenize(sql);
     match response {
         Ok(tokens) => tokens,
         Err(error) => {
-            println!("Error while parsing lexer: {}", error.message);
+            println!("Error while parsing lexer: {}", error);
             panic!("{:?}", "Error while parsing sql");
         }
     }
 }
 
+// tokens without comments, which is what the parser expects
 pub fn state_based_tokenize(sql: &str) -> Result<Vec<Token>, LexerError> {
+    let mut tokens = tokenize_with_trivia(sql)?;
+    tokens.retain(|token| !matches!(token, Comment(_)));
+    Ok(tokens)
+}
+
+// keeps comments as Token::Comment, so that a formatter can write them back
+pub fn tokenize_with_trivia(sql: &str) -> Result<Vec<Token>, LexerError> {
     let mut words: Vec<String> = Vec::new();
-    let trimmed = sql.trim();
-    let mut chars = trimmed.chars();
-    let mut state = START;
+    let mut cursor = Cursor::new(sql);
+    let mut state = Start;
     let mut current_word = String::new();
-    while let Some(current_char) = chars.next() {
+    // where the word being built started, used to report unterminated strings and comments
+    let mut word_start = cursor.location();
+    loop {
+        let location = cursor.location();
+        let Some(current_char) = cursor.next() else { break };
+        if state == Start {
+            word_start = location;
+        }
         match &mut state {
-            START => {
-                let response = handle_start_state(&current_char, &mut current_word, &mut state);
+            Start => {
+                let response = handle_start_state(&current_char, &mut cursor, &mut current_word, &mut state);
                 match response {
                     Ok(possible_word) => {
                         if let Some(word) = possible_word {
                             words.push(word.to_string());
                         }
                     }
-                    Err(e) => return Err(e)
+                    Err(e) => return Err(e.at(location))
                 }
             }
-            IN_IDENTIFIER => {
+            InIdentifier => {
                 let response = handle_in_identifier_state(&current_char, &mut current_word, &mut state);
                 match response {
                     Ok(possible_word) => {
                         if let Some(terminator) = possible_word {
                             words.push(current_word.clone());
                             current_word.clear();
-                            if PUNCTUATION_TERMINATORS.contains(&terminator.chars().next().unwrap()) {
-                                words.push(terminator);
-                            }
+                            word_start = location;
+                            start_next_word(&terminator, &mut cursor, &mut current_word, &mut state, &mut words)
+                                .map_err(|e| e.at(location))?;
                         }
                     }
-                    Err(e) => return Err(e)
+                    Err(e) => return Err(e.at(location))
                 }
             }
-            LEXER_STATE::IN_STRING(_) => {
-                let response = handle_in_string_state(&current_char, &mut current_word, &mut state);
+            LexerState::InString(_) => {
+                let response = handle_in_string_state(&current_char, &mut cursor, &mut current_word, &mut state);
                 match response {
                     Ok(possible_word) => {
                         if let Some(word) = possible_word {
                             words.push(word);
                         }
                     }
-                    Err(e) => return Err(e)
+                    Err(e) => return Err(e.at(location))
                 }
             }
-            LEXER_STATE::IN_NUMBER => {
+            LexerState::InNumber => {
                 let response = handle_in_number_state(&current_char, &mut current_word, &mut state);
                 match response {
                     Ok(possible_word) => {
                         if let Some(terminator) = possible_word {
                             words.push(current_word.clone());
                             current_word.clear();
-                            if PUNCTUATION_TERMINATORS.contains(&terminator.chars().next().unwrap()) {
-                                words.push(terminator);
-                            }
+                            word_start = location;
+                            start_next_word(&terminator, &mut cursor, &mut current_word, &mut state, &mut words)
+                                .map_err(|e| e.at(location))?;
                         }
                     }
-                    Err(e) => return Err(e)
+                    Err(e) => return Err(e.at(location))
                 }
             }
-            LEXER_STATE::IN_OPERATOR => {
-                let response = handle_in_operator_state(&current_char, &mut current_word, &mut state);
+            LexerState::InOperator => {
+                let response = handle_in_operator_state(&current_char, &mut cursor, &mut current_word, &mut state);
                 match response {
                     Ok(possible_word) => {
                         if let Some(terminator) = possible_word {
                             words.push(current_word.clone());
                             current_word.clear();
-                            if PUNCTUATION_TERMINATORS.contains(&terminator.chars().next().unwrap()) {
-                                words.push(terminator);
-                            }
+                            word_start = location;
+                            start_next_word(&terminator, &mut cursor, &mut current_word, &mut state, &mut words)
+                                .map_err(|e| e.at(location))?;
                         }
                     },
-                    Err(e) => return Err(e)
+                    // point at the operator, not at the character which ended it
+                    Err(e) => return Err(e.at(word_start))
+                }
+            }
+            LexerState::InLineComment | LexerState::InBlockComment(_) => {
+                if let Some(comment) = handle_in_comment_state(&current_char, &mut cursor, &mut current_word, &mut state) {
+                    words.push(comment);
                 }
             }
-            _ => return Err(LexerError{message: "Error".to_string()})
         }
     }
 
-    if current_word.len() > 0 {
+    match state {
+        LexerState::InString(quote_char) => {
+            let kind = if quote_char == '\'' { "string literal" } else { "quoted identifier" };
+            return Err(LexerError::new(&format!("Unterminated {}, missing closing {}", kind, quote_char)).at(word_start))
+        },
+        LexerState::InBlockComment(_) => {
+            return Err(LexerError::new("Unterminated block comment, missing closing */").at(word_start))
+        },
+        LexerState::InOperator => check_operator(&current_word).map_err(|e| e.at(word_start))?,
+        _ => {}
+    }
+    if !current_word.is_empty() {
         words.push(current_word);
     }
     Ok(assign_tokens(&words))
 }
 
+// the character which ended a word is not part of it, but it can be the start of the next one
+fn start_next_word(
+    terminator: &str,
+    cursor: &mut Cursor,
+    current_word: &mut String,
+    state: &mut LexerState,
+    words: &mut Vec<String>
+) -> Result<(), LexerError> {
+    let ch = terminator.chars().next().unwrap();
+    if let Some(word) = handle_start_state(&ch, cursor, current_word, state)? {
+        words.push(word);
+    }
+    Ok(())
+}
+
+fn handle_in_comment_state(
+    ch: &char,
+    cursor: &mut Cursor,
+    current_word: &mut String,
+    state: &mut LexerState
+) -> Option<String> {
+    match *state {
+        LexerState::InLineComment => {
+            if *ch == '\n' {
+                // the line break belongs to the whitespace after the comment, as does a windows \r
+                let comment = current_word.trim_end_matches('\r').to_string();
+                current_word.clear();
+                *state = Start;
+                return Some(comment)
+            }
+            current_word.push(*ch);
+            None
+        },
+        LexerState::InBlockComment(depth) => {
+            current_word.push(*ch);
+            if *ch == '/' && cursor.peek() == Some('*') {
+                current_word.push(cursor.next().unwrap());
+                *state = LexerState::InBlockComment(depth + 1);
+            } else if *ch == '*' && cursor.peek() == Some('/') {
+                current_word.push(cursor.next().unwrap());
+                if depth == 1 {
+                    let comment = current_word.clone();
+                    current_word.clear();
+                    *state = Start;
+                    return Some(comment)
+                }
+                *state = LexerState::InBlockComment(depth - 1);
+            }
+            None
+        },
+        _ => None
+    }
+}
+
 fn handle_in_operator_state(
     ch: &char,
+    cursor: &mut Cursor,
     current_word: &mut String,
-    state: &mut LEXER_STATE
+    state: &mut LexerState
 ) -> Result<Option<String>, LexerError> {
-    if is_operator_char(*ch) {
+    // maximal munch: keep extending the operator while it is still the beginning of a known one,
+    // so >=-1 is >= followed by -1 rather than an unknown >=- operator
+    let mut longer = current_word.clone();
+    longer.push(*ch);
+    if !starts_comment(*ch, cursor) && OPERATOR_LEXEMES.iter().any(|operator| operator.starts_with(&longer)) {
         current_word.push(*ch);
-        Ok(None)
-    } else if WHITESPACE_TERMINATORS.contains(ch)
-        || PUNCTUATION_TERMINATORS.contains(ch)
-        || ch.is_alphanumeric()
-    {
-        *state = START;
-        Ok(Some(ch.to_string()))
+        return Ok(None)
+    }
+    check_operator(current_word)?;
+    *state = Start;
+    Ok(Some(ch.to_string()))
+}
+
+fn check_operator(word: &str) -> Result<(), LexerError> {
+    if OPERATOR_LEXEMES.contains(&word) {
+        Ok(())
     } else {
-        Err(LexerError {
-            message: "Unexpected character in operator".to_string(),
-        })
+        Err(LexerError::new(&format!("Unknown operator {}", word)))
     }
 }
 
 fn handle_in_number_state(
     ch: &char,
     current_word: &mut String,
-    state: &mut LEXER_STATE
+    state: &mut LexerState
 ) -> Result<Option<String>, LexerError> {
-    if(ch.is_digit(10)) {
+    if ch.is_ascii_digit() {
         current_word.push(*ch);
         Ok(None)
     } else if *ch == '.' {
-        if(current_word.contains(".")) {
-            return Err(LexerError { message: "More than one decimals in number".to_string() })
+        if current_word.contains(".") {
+            return Err(LexerError::new("More than one decimals in number"))
         }
         current_word.push(*ch);
-        return Ok(None)
-    } else if WHITESPACE_TERMINATORS.contains(ch) || PUNCTUATION_TERMINATORS.contains(ch) {
-        *state = START;
-        return Ok(Some(ch.to_string()));
+        Ok(None)
+    } else if is_whitespace(*ch) || PUNCTUATION_TERMINATORS.contains(ch) || is_operator_char(*ch) {
+        *state = Start;
+        Ok(Some(ch.to_string()))
     } else {
-        return Err(LexerError {message: "Unexpected character".to_string()});
+        Err(LexerError::new("Unexpected character"))
     }
 }
 
-
-// TODO YET TO HANDLE Escaped quotes inside strings("\"Hi\" All")
+// a quote inside a quoted word is written twice ('it''s', "say ""hi"""), string literals additionally
+// accept backslash escapes ('it\'s', 'line\n')
 fn handle_in_string_state(
     ch: &char,
+    cursor: &mut Cursor,
     current_word: &mut String,
-    state: &mut LEXER_STATE
+    state: &mut LexerState
 ) -> Result<Option<String>, LexerError> {
-    if let LEXER_STATE::IN_STRING(quote_char) = *state {
-        if *ch != quote_char {
-            current_word.push(*ch);
-            Ok(None)
-        } else {
+    if let LexerState::InString(quote_char) = *state {
+        if *ch == quote_char {
+            if cursor.peek() == Some(quote_char) {
+                cursor.next();
+                current_word.push(quote_char);
+                return Ok(None)
+            }
+            if IDENTIFIER_QUOTES.contains(&quote_char) && current_word.len() == quote_char.len_utf8() {
+                return Err(LexerError::new("Quoted identifier can not be empty"))
+            }
             current_word.push(*ch);
             let word = current_word.clone();
             current_word.clear();
-            *state = LEXER_STATE::START;
+            *state = Start;
             Ok(Some(word))
+        } else if *ch == '\\' && quote_char == '\'' {
+            match cursor.next() {
+                Some('n') => current_word.push('\n'),
+                Some('t') => current_word.push('\t'),
+                Some('r') => current_word.push('\r'),
+                Some('0') => current_word.push('\0'),
+                Some(escaped @ ('\\' | '\'' | '"')) => current_word.push(escaped),
+                // unknown escapes are kept as written, LIKE patterns rely on \% and \_ reaching the matcher
+                Some(other) => {
+                    current_word.push('\\');
+                    current_word.push(other);
+                },
+                None => current_word.push('\\')
+            }
+            Ok(None)
+        } else {
+            current_word.push(*ch);
+            Ok(None)
         }
     } else {
-        return Err(LexerError {message: "Not IN_STRING state".to_string()})
+        Err(LexerError::new("Not IN_STRING state"))
     }
 }
 
-fn handle_in_identifier_state(ch: &char, current_word: &mut String, state: &mut LEXER_STATE)
+fn handle_in_identifier_state(ch: &char, current_word: &mut String, state: &mut LexerState)
     -> Result<Option<String>, LexerError> {
-    if ch.is_alphanumeric() || *ch == '_' {
+    // a dot continues the word, for qualified columns (o.amount) and unquoted file names (orders.csv)
+    if ch.is_alphanumeric() || *ch == '_' || *ch == '.' {
         current_word.push(*ch);
-        return Ok(None);
-    } else if WHITESPACE_TERMINATORS.contains(ch) || PUNCTUATION_TERMINATORS.contains(ch) {
-        *state = START;
-        return Ok(Some(ch.to_string()));
+        Ok(None)
+    } else if is_whitespace(*ch) || PUNCTUATION_TERMINATORS.contains(ch) || is_operator_char(*ch) {
+        *state = Start;
+        Ok(Some(ch.to_string()))
     } else {
-        return Err(LexerError {message: "Unexpected character".to_string()});
+        Err(LexerError::new("Unexpected character"))
     }
 }
 
-fn handle_start_state(ch: &char, current_word: &mut String, state: &mut LEXER_STATE)
+fn handle_start_state(ch: &char, cursor: &mut Cursor, current_word: &mut String, state: &mut LexerState)
     -> Result<Option<String>, LexerError> {
-    if(WHITESPACE_TERMINATORS.contains(ch)) {
-        return Ok(None)
-    } else if(ch.is_alphabetic() || *ch == '_') {
-        *state = IN_IDENTIFIER;
+    if is_whitespace(*ch) {
+        Ok(None)
+    } else if *ch == '-' && cursor.peek() == Some('-') {
+        *state = LexerState::InLineComment;
         current_word.push(*ch);
-        return Ok(None)
-    } else if ch.is_digit(10) {
-        *state = LEXER_STATE::IN_NUMBER;
+        Ok(None)
+    } else if *ch == '/' && cursor.peek() == Some('*') {
+        *state = LexerState::InBlockComment(1);
         current_word.push(*ch);
-        return Ok(None)
-    } else if *ch == '"' || *ch == '\'' {
-        *state = LEXER_STATE::IN_STRING(*ch);
+        current_word.push(cursor.next().unwrap());
+        Ok(None)
+    } else if ch.is_alphabetic() || *ch == '_' {
+        *state = InIdentifier;
         current_word.push(*ch);
-        return Ok(None)
+        Ok(None)
+    } else if ch.is_ascii_digit() {
+        *state = LexerState::InNumber;
+        current_word.push(*ch);
+        Ok(None)
+    } else if *ch == '\'' || IDENTIFIER_QUOTES.contains(ch) {
+        *state = LexerState::InString(*ch);
+        current_word.push(*ch);
+        Ok(None)
     } else if is_operator_char(*ch) {
-        *state = LEXER_STATE::IN_OPERATOR;
+        *state = LexerState::InOperator;
         current_word.push(*ch);
-        return Ok(None)
+        Ok(None)
     } else if PUNCTUATION_TERMINATORS.contains(ch) {
-        return Ok(Some(ch.to_string()))
+        Ok(Some(ch.to_string()))
     } else  {
-        return Err(LexerError {message: "Unexpected character".to_string()})
+        Err(LexerError::new("Unexpected character"))
     }
 }
 
-// TODO add more operators as and when needed
-fn is_operator_char(c: char) -> bool {
-    //matches!(c, '=' | '!' | '<' | '>')
-    OPERATORS.contains(&c)
+// any unicode whitespace, plus the byte order mark some windows editors put in front of a file
+fn is_whitespace(ch: char) -> bool {
+    ch.is_whitespace() || ch == '\u{feff}'
 }
 
-fn split_based_tokenize(sql: &str) -> Vec<Token> {
-    let trimmed = sql.trim();
-    let mut chars = trimmed.chars();
-    if trimmed.ends_with(";") {
-        chars.next_back();
-    }
-    let mut current_word = String::new();
-    let mut words: Vec<String> = Vec::new();
-    while let Some(current) = chars.next() {
-        if(current.is_whitespace()) {
-            // add current word only if it has some characters, as in case of multiple whitespaces
-            // we may have already added thr word ot list
-            if(current_word.len() > 0) {
-                words.push(current_word.clone());
-                current_word.clear();
-            }
-            continue
-        }
-        current_word.push(current);
-    }
-    if(current_word.len() > 0) {
-        words.push(current_word);
-    }
+fn starts_comment(ch: char, cursor: &mut Cursor) -> bool {
+    (ch == '-' && cursor.peek() == Some('-')) || (ch == '/' && cursor.peek() == Some('*'))
+}
 
-    assign_tokens(&words)
+fn is_operator_char(c: char) -> bool {
+    OPERATORS.contains(&c)
 }
 
-fn assign_tokens(words: &Vec<String>) -> Vec<Token> {
+fn assign_tokens(words: &[String]) -> Vec<Token> {
     let mut tokens: Vec<Token> = Vec::new();
-    for word in words {
-        if let Some(keyword_type) = is_keyword(word) {
+    let mut words = words.iter().peekable();
+    while let Some(word) = words.next() {
+        if word.starts_with("--") || word.starts_with("/*") {
+            tokens.push(Comment(word.to_string()));
+        } else if word == "*" {
+            // after an operand * multiplies, anywhere else (SELECT *, COUNT(*)) it is the wildcard
+            if follows_operand(&tokens) {
+                tokens.push(Operator(OperatorType::Multiply));
+            } else {
+                tokens.push(Grammar(GrammarType::Asterisk));
+            }
+        } else if word == "-" && !follows_operand(&tokens) && words.peek().is_some_and(|next| is_number(next)) {
+            // a minus which can not be a subtraction belongs to the number, age > -5
+            let number = format!("-{}", words.next().unwrap());
+            tokens.push(to_number(&number));
+        } else if let Some(keyword_type) = is_keyword(word) {
             tokens.push(Keyword(keyword_type))
         } else if let Some(grammar_type) = is_grammar_type(word) {
             tokens.push(Grammar(grammar_type));
         } else if let Some(operator) = is_operator(word) {
             tokens.push(Operator(operator));
-        } else if word.parse::<i64>().is_ok() {
-            tokens.push(Integer(word.parse::<i64>().unwrap()));
-        } else if word.parse::<f64>().is_ok() {
-            tokens.push(Float(word.parse::<f64>().unwrap()));
-        } else if word.starts_with("\"") && word.ends_with("\"") {
+        } else if is_number(word) {
+            tokens.push(to_number(word));
+        } else if word.starts_with('\'') {
             let trimmed = &word[1..word.len() - 1];
-            tokens.push(StringLiteral(trimmed.parse().unwrap()));
-        } else if word.starts_with("\'") && word.ends_with("\'") {
-            let trimmed = &word[1..word.len() - 1];
-            tokens.push(StringLiteral(trimmed.parse().unwrap()));
+            tokens.push(StringLiteral(trimmed.to_string()));
+        } else if let Some(quote) = word.chars().next().filter(|c| IDENTIFIER_QUOTES.contains(c)) {
+            let trimmed = &word[quote.len_utf8()..word.len() - quote.len_utf8()];
+            tokens.push(Identifier(trimmed.to_string()));
         } else {
             tokens.push(Identifier(word.to_string()));
         }
     }
 
     tokens
-}
\ No newline at end of file
+}
+
+fn is_number(word: &str) -> bool {
+    word.starts_with(|c: char| c.is_ascii_digit()) && word.parse::<f64>().is_ok()
+}
+
+fn to_number(word: &str) -> Token {
+    match word.parse::<i64>() {
+        Ok(integer) => Integer(integer),
+        Err(_) => Float(word.parse::<f64>().unwrap())
+    }
+}
+
+// whether the last real token ends a value, which decides between binary and unary readings of * and -
+fn follows_operand(tokens: &[Token]) -> bool {
+    let previous = tokens.iter().rev().find(|token| !matches!(token, Comment(_)));
+    matches!(previous,
+        Some(Identifier(_)) | Some(Integer(_)) | Some(Float(_)) | Some(StringLiteral(_)) |
+        Some(Grammar(GrammarType::CloseParen)) |
+        Some(Keyword(KeywordType::True)) | Some(Keyword(KeywordType::False)) | Some(Keyword(KeywordType::Null)) |
+        Some(Keyword(KeywordType::End))
+    )
+}
@@ -12,6 +12,8 @@ pub enum Token {
     StringLiteral(String),  // quoted strings
     Grammar(GrammarType),
     Operator(OperatorType),
+    // only produced by tokenize_with_trivia, the text includes the -- or /* */ markers
+    Comment(String),
 }
 
 impl Display for Token {
@@ -23,7 +25,8 @@ impl Display for Token {
             Token::Float(f) => format!("Float {}", f),
             Token::StringLiteral(s) => format!("String literal {}", s),
             Token::Grammar(gr) => format!("Grammar marker {}", gr),
-            Token::Operator(o) => format!("Operator {}", o)
+            Token::Operator(o) => format!("Operator {}", o),
+            Token::Comment(c) => format!("Comment {}", c)
         };
 
         write!(f, "{}", token)
@@ -1,3 +1,4 @@
 pub mod lexer;
 pub mod parser;
-pub mod ast;
\ No newline at end of file
+pub mod ast;
+pub mod suggest;
\ No newline at end of file
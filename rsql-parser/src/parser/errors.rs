@@ -1,6 +1,34 @@
+use std::fmt::Display;
+
 # [derive (Debug, Clone)]
 pub struct ParserError {
     pub message: String,
     pub position: usize
 }
 
+// a problem reported by the recovering parser, position is the index of the offending token
+#[derive(Debug, Clone, PartialEq)]
+pub struct Diagnostic {
+    pub message: String,
+    pub position: usize,
+    pub suggestion: Option<String>
+}
+
+impl From<ParserError> for Diagnostic {
+    fn from(error: ParserError) -> Self {
+        Diagnostic {
+            message: error.message,
+            position: error.position,
+            suggestion: None
+        }
+    }
+}
+
+impl Display for Diagnostic {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.suggestion {
+            Some(suggestion) => write!(f, "{} at token {}; did you mean {}?", self.message, self.position, suggestion),
+            None => write!(f, "{} at token {}", self.message, self.position)
+        }
+    }
+}
@@ -1,33 +1,234 @@
 use crate::ast::constructs::SelectItem::{Column, Wildcard};
-use crate::ast::constructs::{AggregateFunc, BinaryOperator, Expr, FromClause, OrderByItem, SelectItem, SelectStatement, Statement, Value};
+use crate::ast::constructs::{AggregateFunc, BinaryOperator, Cte, Expr, FrameBound, FrameUnits, FromClause, GroupByItem, JoinKind, OrderByItem, SelectItem, SelectStatement, SetOperation, SetOperator, Statement, TypeName, UnaryOperator, Value, WindowFrame, WindowFunc, WindowSpec, With};
 use crate::ast::constructs::Expr::Literal;
 use crate::lexer::grammar::GrammarType;
-use crate::lexer::keywords::KeywordType;
+use crate::lexer::keywords::{suggest_keyword, KeywordType};
 use crate::lexer::operators::OperatorType;
 use crate::lexer::tokens::Token;
 use crate::lexer::tokens::Token::{Float, Grammar, Identifier, Integer, Keyword, Operator, StringLiteral};
-use crate::parser::errors::ParserError;
+use crate::parser::errors::{Diagnostic, ParserError};
+
+const CLAUSE_KEYWORDS: &[KeywordType] = &[KeywordType::From, KeywordType::Where, KeywordType::Group, KeywordType::Order, KeywordType::Limit];
+
+const SET_OPERATORS: &[KeywordType] = &[KeywordType::Union, KeywordType::Intersect, KeywordType::Except];
 
 pub struct Parser {
     tokens: Vec<Token>,
-    position: usize
+    position: usize,
+    diagnostics: Vec<Diagnostic>
+}
+
+// result of a recovering parse: whatever could be parsed, plus everything that went wrong on the way
+#[derive(Debug)]
+pub struct ParseOutcome {
+    pub statement: Option<Statement>,
+    pub diagnostics: Vec<Diagnostic>
 }
 
 impl Parser {
-    pub fn new(tokens: Vec<Token>) -> Self {
-        Parser {tokens, position: 0}
+    pub fn new(mut tokens: Vec<Token>) -> Self {
+        // comments kept by tokenize_with_trivia mean nothing to the grammar
+        tokens.retain(|token| !matches!(token, Token::Comment(_)));
+        Parser {tokens, position: 0, diagnostics: Vec::new()}
     }
 
     pub fn parse(&mut self) -> Result<Statement, ParserError> {
         self.parse_statement()
     }
 
+    // unlike parse, does not stop at the first error: on a bad clause it records a diagnostic, skips ahead to
+    // the next clause keyword (FROM, WHERE, GROUP, ORDER, LIMIT), set operator or semicolon and carries on
+    pub fn parse_with_recovery(&mut self) -> ParseOutcome {
+        let statement = self.parse_statement_recovering();
+        ParseOutcome {
+            statement,
+            diagnostics: std::mem::take(&mut self.diagnostics)
+        }
+    }
+
+    fn parse_statement_recovering(&mut self) -> Option<Statement> {
+        if let Some(analyze) = self.parse_explain_prefix() {
+            return self.parse_statement_recovering()
+                .map(|statement| Statement::Explain { analyze, statement: Box::new(statement) })
+        }
+        let with = self.recover_clause(Self::parse_with);
+        self.correct_misspelled_keyword(&[KeywordType::Select]);
+        if let Err(error) = self.expect_keyword(KeywordType::Select) {
+            self.record(error);
+            return None
+        }
+
+        let distinct = self.consume_keyword(KeywordType::Distinct);
+        let columns = self.parse_columns_recovering();
+        self.expect_clause_boundary();
+        let from = self.recover_clause(Self::parse_from);
+        self.expect_clause_boundary();
+        let where_clause = self.recover_clause(Self::parse_where);
+        self.expect_clause_boundary();
+        let group_by = self.recover_clause(Self::parse_group_by);
+        let mut statement = SelectStatement {
+            with, distinct, columns, from, where_clause, group_by, set_operations: Vec::new(), order_by: None, limit: None
+        };
+        if let Err(error) = self.parse_set_operations(&mut statement) {
+            self.record(error);
+            self.synchronize();
+        }
+        self.expect_clause_boundary();
+        statement.order_by = self.recover_clause(Self::parse_order_by);
+        self.expect_clause_boundary();
+        statement.limit = self.recover_clause(Self::parse_limit);
+        self.expect_end_of_statement();
+
+        Some(Statement::Select(statement))
+    }
+
+    fn parse_columns_recovering(&mut self) -> Vec<SelectItem> {
+        let mut selected_items = Vec::new();
+        loop {
+            match self.parse_select_item() {
+                Ok(item) => selected_items.push(item),
+                Err(error) => {
+                    self.record(error);
+                    // skip only up to the next comma, so that the remaining columns are still parsed
+                    while let Some(token) = self.tokens.get(self.position) {
+                        if matches!(token, Grammar(GrammarType::Comma)) || self.is_synchronization_point() {
+                            break
+                        }
+                        self.position += 1;
+                    }
+                }
+            }
+
+            match self.tokens.get(self.position) {
+                Some(Grammar(GrammarType::Comma)) => self.position += 1,
+                _ => break
+            }
+        }
+
+        selected_items
+    }
+
+    // the end of input ends the statement like a semicolon, the clauses after it are missing rather than wrong
+    fn recover_clause<T>(
+        &mut self,
+        parse_clause: fn(&mut Self) -> Result<Option<T>, ParserError>
+    ) -> Option<T> {
+        if self.position >= self.tokens.len() {
+            return None
+        }
+        match parse_clause(self) {
+            Ok(clause) => clause,
+            Err(error) => {
+                self.record(error);
+                self.synchronize();
+                None
+            }
+        }
+    }
+
+    // called between clauses, anything which does not start the next clause is reported and skipped
+    fn expect_clause_boundary(&mut self) {
+        self.correct_misspelled_keyword(CLAUSE_KEYWORDS);
+        if self.position < self.tokens.len() && !self.is_synchronization_point() {
+            let token = self.tokens[self.position].clone();
+            self.record(ParserError {
+                message: format!("Unexpected {}", token),
+                position: self.position
+            });
+            self.synchronize();
+        }
+    }
+
+    fn expect_end_of_statement(&mut self) {
+        match self.tokens.get(self.position) {
+            Some(Grammar(GrammarType::Semicolon)) => {
+                if let Some(token) = self.tokens.get(self.position + 1) {
+                    let token = token.clone();
+                    self.record(ParserError {
+                        message: format!("Unexpected {} after end of statement", token),
+                        position: self.position + 1
+                    });
+                }
+            },
+            Some(token) => {
+                let token = token.clone();
+                self.record(ParserError {
+                    message: format!("Expected semicolon, {} found", token),
+                    position: self.position
+                });
+            },
+            None => {}
+        }
+    }
+
+    // skips tokens until a clause keyword or a semicolon, a word that looks like a misspelled clause keyword
+    // is also a synchronization point
+    fn synchronize(&mut self) {
+        while self.position < self.tokens.len() && !self.is_synchronization_point() {
+            self.position += 1;
+        }
+        self.correct_misspelled_keyword(CLAUSE_KEYWORDS);
+    }
+
+    fn is_synchronization_point(&self) -> bool {
+        match self.tokens.get(self.position) {
+            Some(Keyword(kw)) => CLAUSE_KEYWORDS.contains(kw) || SET_OPERATORS.contains(kw),
+            Some(Grammar(GrammarType::Semicolon)) => true,
+            Some(Identifier(word)) => suggest_keyword(word).is_some_and(|kw| CLAUSE_KEYWORDS.contains(&kw)),
+            _ => false
+        }
+    }
+
+    // replaces an identifier such as FORM by the keyword it is a typo of, so that parsing can continue
+    fn correct_misspelled_keyword(&mut self, expected: &[KeywordType]) {
+        if let Some(Identifier(word)) = self.tokens.get(self.position)
+            && let Some(keyword) = suggest_keyword(word).filter(|kw| expected.contains(kw)) {
+            self.diagnostics.push(Diagnostic {
+                message: format!("Unknown word '{}'", word),
+                position: self.position,
+                suggestion: Some(keyword.to_string())
+            });
+            self.tokens[self.position] = Keyword(keyword);
+        }
+    }
+
+    // an input cut short is reported once, not by each clause that finds nothing left
+    fn record(&mut self, error: ParserError) {
+        let end = self.tokens.len();
+        if error.position >= end && self.diagnostics.iter().any(|diagnostic| diagnostic.position >= end) {
+            return
+        }
+        let mut diagnostic = Diagnostic::from(error);
+        if let Some(Identifier(word)) = self.tokens.get(diagnostic.position) {
+            diagnostic.suggestion = suggest_keyword(word).map(|kw| kw.to_string());
+        }
+        self.diagnostics.push(diagnostic);
+    }
+
+    // consumes EXPLAIN [ANALYZE], returns whether ANALYZE was given
+    fn parse_explain_prefix(&mut self) -> Option<bool> {
+        if !matches!(self.tokens.get(self.position), Some(Keyword(KeywordType::Explain))) {
+            return None
+        }
+        self.position += 1;
+        Some(self.consume_keyword(KeywordType::Analyze))
+    }
+
     fn parse_statement(&mut self) -> Result<Statement, ParserError> {
+        if let Some(analyze) = self.parse_explain_prefix() {
+            return match self.peek()? {
+                Keyword(KeywordType::Explain) => Err(ParserError {
+                    message: "EXPLAIN can not be nested".to_string(),
+                    position: self.position
+                }),
+                _ => self.parse_statement().map(|statement| Statement::Explain { analyze, statement: Box::new(statement) })
+            }
+        }
         match self.peek()? {
-            Keyword(KeywordType::Select) => {
-                return self.parse_select().map(Statement::Select)
+            Keyword(KeywordType::Select) | Keyword(KeywordType::With) => {
+                self.parse_select().map(Statement::Select)
             },
-            _ => return Err(ParserError{
+            _ => Err(ParserError{
                 message: "Expected SELECT keyword".to_string(),
                 position: self.position,
             }),
@@ -35,16 +236,114 @@ impl Parser {
     }
 
     fn parse_select(&mut self) -> Result<SelectStatement, ParserError> {
+        let select_statement = self.parse_query()?;
+        self.expect_statement_end()?;
+
+        Ok(select_statement)
+    }
+
+    // a SELECT up to its end, which is the end of the statement or the parenthesis closing a subquery
+    fn parse_query(&mut self) -> Result<SelectStatement, ParserError> {
+        let with = self.parse_with()?;
+        let mut query = self.parse_select_core()?;
+        self.parse_set_operations(&mut query)?;
+        query.order_by = self.parse_order_by()?;
+        query.limit = self.parse_limit()?;
+        query.with = with;
+        Ok(query)
+    }
+
+    // the selects combined with the query from left to right, INTERSECT binds tighter than UNION and EXCEPT so
+    // the selects it follows take the INTERSECTs after them along
+    fn parse_set_operations(&mut self, query: &mut SelectStatement) -> Result<(), ParserError> {
+        while let Some(Keyword(keyword)) = self.tokens.get(self.position).filter(|token| matches!(token, Keyword(kw) if SET_OPERATORS.contains(kw))) {
+            let operator = match keyword {
+                KeywordType::Union => SetOperator::Union,
+                KeywordType::Intersect => SetOperator::Intersect,
+                _ => SetOperator::Except
+            };
+            self.position += 1;
+            let all = self.consume_keyword(KeywordType::All);
+            let mut right = self.parse_select_core()?;
+            while operator != SetOperator::Intersect && matches!(self.tokens.get(self.position), Some(Keyword(KeywordType::Intersect))) {
+                self.position += 1;
+                let all = self.consume_keyword(KeywordType::All);
+                let intersected = self.parse_select_core()?;
+                right.set_operations.push(SetOperation { operator: SetOperator::Intersect, all, query: Box::new(intersected) });
+            }
+            query.set_operations.push(SetOperation { operator, all, query: Box::new(right) });
+        }
+        Ok(())
+    }
+
+    fn parse_limit(&mut self) -> Result<Option<u64>, ParserError> {
+        if !self.consume_keyword(KeywordType::Limit) {
+            return Ok(None)
+        }
+        let count = self.expect_integer()?;
+        u64::try_from(count).map(Some).map_err(|_| ParserError {
+            message: "LIMIT can not be negative".to_string(),
+            position: self.position - 1
+        })
+    }
+
+    // a SELECT without WITH, ORDER BY and LIMIT, which belong to the whole query
+    fn parse_select_core(&mut self) -> Result<SelectStatement, ParserError> {
         self.expect_keyword(KeywordType::Select)?;
-        let select_statement = SelectStatement {
+        Ok(SelectStatement {
+            with: None,
+            distinct: self.consume_keyword(KeywordType::Distinct),
             columns: self.parse_columns()?,
             from: self.parse_from()?,
             where_clause: self.parse_where()?,
             group_by: self.parse_group_by()?,
-            order_by: self.parse_order_by()?,
-        };
+            set_operations: Vec::new(),
+            order_by: None,
+            limit: None
+        })
+    }
 
-        Ok(select_statement)
+    // WITH [RECURSIVE] name [(column, ...)] AS (SELECT ...), ...
+    fn parse_with(&mut self) -> Result<Option<With>, ParserError> {
+        if !self.consume_keyword(KeywordType::With) {
+            return Ok(None)
+        }
+        let recursive = self.consume_keyword(KeywordType::Recursive);
+        let mut ctes = Vec::new();
+        loop {
+            let name = self.expect_identifier()?;
+            let mut columns = Vec::new();
+            if matches!(self.peek()?, Grammar(GrammarType::OpenParen)) {
+                self.advance()?;
+                columns.push(self.expect_identifier()?);
+                while matches!(self.peek()?, Grammar(GrammarType::Comma)) {
+                    self.advance()?;
+                    columns.push(self.expect_identifier()?);
+                }
+                self.expect_grammar(GrammarType::CloseParen)?;
+            }
+            self.expect_keyword(KeywordType::As)?;
+            let query = self.parse_subquery()?;
+            ctes.push(Cte { name, columns, query: Box::new(query) });
+            if !matches!(self.peek()?, Grammar(GrammarType::Comma)) {
+                break
+            }
+            self.advance()?;
+        }
+        Ok(Some(With { recursive, ctes }))
+    }
+
+    // SELECT or WITH after the token at the position, the opening parenthesis of a subquery
+    fn starts_subquery(&self) -> bool {
+        matches!(self.tokens.get(self.position + 1), Some(Keyword(KeywordType::Select) | Keyword(KeywordType::With)))
+    }
+
+    // (SELECT ...)
+    fn parse_subquery(&mut self) -> Result<SelectStatement, ParserError> {
+        self.expect_grammar(GrammarType::OpenParen)?;
+        let query = self.parse_query()?;
+        self.expect_grammar(GrammarType::CloseParen)?;
+        Ok(query)
     }
 
     fn parse_order_by(&mut self) -> Result<Option<Vec<OrderByItem>>, ParserError> {
@@ -62,10 +361,9 @@ impl Parser {
                 Grammar(GrammarType::Comma) => {
                     self.advance()?;  // consume comma, continue loop
                 }
-                Grammar(GrammarType::Semicolon) => {
-                    break;  // order by completed
+                Grammar(GrammarType::Semicolon) | Grammar(GrammarType::CloseParen) | Keyword(KeywordType::Limit) => {
+                    break;  // order by completed, the parenthesis ends a subquery
                 },
-                // TODO: extend later for LIMIT or other clauses that come after ORDER BY
                 other => {
                     return Err(ParserError {
                         message: format!("Expected semicolon, {} found", other),
@@ -79,45 +377,42 @@ impl Parser {
     }
 
     fn parse_order_by_item(&mut self) -> Result<OrderByItem, ParserError> {
-        match self.peek()? {
-            // order by column
-            Identifier(_) => {
-                let name = self.expect_identifier()?;
-                let expr = Expr::Column(name);
-
-                let order_flag = self.peek()?;
-                match order_flag {
-                    Keyword(KeywordType::Desc) => {
-                        self.advance()?;
-                        Ok(OrderByItem {
-                            expr,
-                            asc: false
-                        })
-                    },
-                    Keyword(KeywordType::Asc) => {
-                        self.advance()?;
-                        Ok(OrderByItem {
-                            expr,
-                            asc: true
-                        })
-                    },
-                    _ => {
-                        Ok(OrderByItem {
-                            expr,
-                            asc: true
-                        })
-                    }
-                }
-            }
-
-            other => Err(ParserError {
-                message: format!("Expected column for order by, found {:?}", other),
+        let token = self.peek()?;
+        if !self.can_start_expression(token) {
+            return Err(ParserError {
+                message: format!("Expected column for order by, found {:?}", token),
                 position: self.position
             })
         }
+        // order by column, alias or expression
+        let expr = self.parse_or_expression()?;
+
+        let order_flag = self.tokens.get(self.position);
+        match order_flag {
+            Some(Keyword(KeywordType::Desc)) => {
+                self.advance()?;
+                Ok(OrderByItem {
+                    expr,
+                    asc: false
+                })
+            },
+            Some(Keyword(KeywordType::Asc)) => {
+                self.advance()?;
+                Ok(OrderByItem {
+                    expr,
+                    asc: true
+                })
+            },
+            _ => {
+                Ok(OrderByItem {
+                    expr,
+                    asc: true
+                })
+            }
+        }
     }
 
-    fn parse_group_by(&mut self) -> Result<Option<Vec<Expr>>, ParserError> {
+    fn parse_group_by(&mut self) -> Result<Option<Vec<GroupByItem>>, ParserError> {
         if !matches!(self.peek()?, Token::Keyword(KeywordType::Group)) {
             return Ok(None)
         }
@@ -125,7 +420,7 @@ impl Parser {
         self.expect_keyword(KeywordType::By)?;
         let mut group_by_items = Vec::new();
         loop {
-            let item = self.parse_group_by_expr()?;
+            let item = self.parse_group_by_item()?;
             group_by_items.push(item);
 
             match self.peek()? {
@@ -135,10 +430,10 @@ impl Parser {
                 Grammar(GrammarType::Semicolon) => {
                     break;  // consume comma, continue loop
                 },
-                Keyword(KeywordType::Order) => {
+                Keyword(KeywordType::Order) | Keyword(KeywordType::Limit) | Grammar(GrammarType::CloseParen) => {
                     break;  // group by completed
                 },
-                // TODO: extend later for LIMIT or other clauses that may come after GROUP BY
+                Keyword(kw) if SET_OPERATORS.contains(kw) => break,
                 other => {
                     return Err(ParserError {
                         message: format!("Expected  ORDER BY or COMMA {} found", other),
@@ -151,119 +446,58 @@ impl Parser {
         Ok(Some(group_by_items))
     }
 
-    fn parse_group_by_expr(&mut self) -> Result<Expr, ParserError> {
-        let mut first_candidate: Option<Expr> = None;
-        let mut binary_operator: Option<BinaryOperator> = None;
-        let mut second_candidate: Option<Expr> = None;
-        // TODO: adjust for other clauses like LIMIT etc later on
-        while !matches!(self.peek()?, Grammar(GrammarType::Comma)) &&
-            !matches!(self.peek()?, Grammar(GrammarType::Semicolon)) {
-            match self.peek()? {
-                Identifier(ident) => {
-                    match first_candidate {
-                        Some(_) => {
-                            // if first candidate is present, then we check that binary operator must be already present
-                            match binary_operator {
-                                Some(_) => {
-                                    second_candidate = Some(Expr::Column(ident.clone()));
-                                    self.advance()?;
-                                },
-                                None => return Err(ParserError {
-                                        message: "Expected binary operator before a column or number".to_string(),
-                                        position: self.position
-                                    })
-                            }
-                        },
-                        // its first candidate
-                        None => {
-                            first_candidate = Some(Expr::Column(ident.clone()));
-                            self.advance()?;
-                        }
-                    }
-                },
-                Integer(i) => {
-                    match first_candidate {
-                        Some(_) => {
-                            // if first candidate is present, then we check that binary operator must be already present
-                            match binary_operator {
-                                Some(_) => {
-                                    second_candidate = Some(Expr::Literal(Value::Int(*i)));
-                                    self.advance()?;
-                                },
-                                None => return Err(ParserError {
-                                    message: "Expected binary operator before a column or number".to_string(),
-                                    position: self.position
-                                })
-                            }
-                        },
-                        // its first candidate
-                        None => {
-                            first_candidate = Some(Expr::Literal(Value::Int(*i)));
-                            self.advance()?;
-                        }
-                    }
-                },
-                Float(f) => {
-                    match first_candidate {
-                        Some(_) => {
-                            // if first candidate is present, then we check that binary operator must be already present
-                            match binary_operator {
-                                Some(_) => {
-                                    second_candidate = Some(Expr::Literal(Value::Float(*f)));
-                                    self.advance()?;
-                                },
-                                None => return Err(ParserError {
-                                    message: "Expected binary operator before a column or number".to_string(),
-                                    position: self.position
-                                })
-                            }
-                        },
-                        // its first candidate
-                        None => {
-                            first_candidate = Some(Expr::Literal(Value::Float(*f)));
-                            self.advance()?;
-                        }
+    // ROLLUP, CUBE and GROUPING SETS are told apart from columns and functions of those names by what follows them
+    fn parse_group_by_item(&mut self) -> Result<GroupByItem, ParserError> {
+        let next = self.tokens.get(self.position + 1);
+        match self.peek()? {
+            Identifier(name) if (name.eq_ignore_ascii_case("rollup") || name.eq_ignore_ascii_case("cube"))
+                && matches!(next, Some(Grammar(GrammarType::OpenParen))) => {
+                let rollup = name.eq_ignore_ascii_case("rollup");
+                let name = name.to_ascii_uppercase();
+                self.advance()?;
+                let exprs = self.parse_grouping_set()?;
+                if exprs.is_empty() {
+                    return Err(ParserError { message: format!("{} needs at least one expression", name), position: self.position })
+                }
+                Ok(if rollup { GroupByItem::Rollup(exprs) } else { GroupByItem::Cube(exprs) })
+            },
+            Identifier(name) if name.eq_ignore_ascii_case("grouping")
+                && matches!(next, Some(Identifier(sets)) if sets.eq_ignore_ascii_case("sets")) => {
+                self.advance()?;
+                self.advance()?;
+                self.expect_grammar(GrammarType::OpenParen)?;
+                // a set is a list in parentheses, () being the empty one, or a single expression
+                let mut sets = Vec::new();
+                loop {
+                    match self.peek()? {
+                        Grammar(GrammarType::OpenParen) => sets.push(self.parse_grouping_set()?),
+                        _ => sets.push(vec![self.parse_concat()?])
                     }
-                },
-                Operator(OperatorType::Add) | Operator(OperatorType::Subtract) | Operator(OperatorType::Multiply)
-                 => {
-                    match first_candidate {
-                        Some(_) => {
-                            binary_operator = Some(BinaryOperator::Add);
-                            self.advance()?;
-                        },
-                        other => return Err(ParserError {
-                            message: format!("Expected identifier or a number as LHS of expression, but found {:?}", other),
-                            position: self.position
-                        })
+                    if !matches!(self.peek()?, Grammar(GrammarType::Comma)) {
+                        break
                     }
-                },
-                other => return Err(ParserError {
-                    message: format!("Expected identifier or operator or a number, but found {:?}", other),
-                    position: self.position
-                })
-            }
+                    self.advance()?;
+                }
+                self.expect_grammar(GrammarType::CloseParen)?;
+                Ok(GroupByItem::GroupingSets(sets))
+            },
+            _ => Ok(GroupByItem::Expr(self.parse_concat()?))
         }
+    }
 
-        match binary_operator {
-            Some(_) => {
-                Ok(Expr::BinaryOp {
-                    left: Box::new(first_candidate.unwrap()),
-                    operator: binary_operator.unwrap(),
-                    right: Box::new(second_candidate.unwrap())
-                })
-            },
-            None => {
-                match first_candidate {
-                    Some(lhs) => Ok(lhs),
-                    _ => return Err(ParserError {
-                                       message: format!("Expected expr after GROUP BY, but found"),
-                                       position: self.position
-                                   })
-                }
+    // (expr, ...), possibly empty
+    fn parse_grouping_set(&mut self) -> Result<Vec<Expr>, ParserError> {
+        self.expect_grammar(GrammarType::OpenParen)?;
+        let mut exprs = Vec::new();
+        if !matches!(self.peek()?, Grammar(GrammarType::CloseParen)) {
+            exprs.push(self.parse_concat()?);
+            while matches!(self.peek()?, Grammar(GrammarType::Comma)) {
+                self.advance()?;
+                exprs.push(self.parse_concat()?);
             }
         }
-
+        self.expect_grammar(GrammarType::CloseParen)?;
+        Ok(exprs)
     }
 
     fn parse_columns(&mut self) -> Result<Vec<SelectItem>, ParserError> {
@@ -299,15 +533,15 @@ impl Parser {
                 Ok(Wildcard)
             }
 
-            // aggregate functions: COUNT, AVG, SUM, MIN, MAX
-            Keyword(kw) if self.is_aggregate_keyword(kw) => {
-                self.parse_aggregate()
-            }
-
-            // Regular column: SELECT name
-            Identifier(_) => {
-                let name = self.expect_identifier()?;
-                Ok(Column(name))
+            // columns, aggregates and expressions: SELECT name, COUNT(*), salary * 12 AS yearly
+            token if self.can_start_expression(token) => {
+                let expr = self.parse_or_expression()?;
+                let alias = self.parse_alias()?;
+                match (expr, alias) {
+                    (Expr::Column(name), None) => Ok(Column(name)),
+                    (Expr::Aggregate { func, expr, distinct }, None) => Ok(SelectItem::Aggregate { func, expr, distinct }),
+                    (expr, alias) => Ok(SelectItem::Expr { expr, alias })
+                }
             }
 
             other => Err(ParserError {
@@ -317,6 +551,26 @@ impl Parser {
         }
     }
 
+    fn can_start_expression(&self, token: &Token) -> bool {
+        match token {
+            Identifier(_) | Integer(_) | Float(_) | StringLiteral(_) => true,
+            Keyword(KeywordType::True) | Keyword(KeywordType::False) | Keyword(KeywordType::Null) => true,
+            Keyword(KeywordType::Not) | Keyword(KeywordType::Case) | Keyword(KeywordType::Exists) => true,
+            Keyword(kw) => self.is_aggregate_keyword(kw),
+            Grammar(GrammarType::OpenParen) => true,
+            Operator(OperatorType::Subtract) | Operator(OperatorType::Add) => true,
+            _ => false
+        }
+    }
+
+    fn parse_alias(&mut self) -> Result<Option<String>, ParserError> {
+        if !matches!(self.tokens.get(self.position), Some(Keyword(KeywordType::As))) {
+            return Ok(None)
+        }
+        self.advance()?;
+        self.expect_identifier().map(Some)
+    }
+
     fn is_aggregate_keyword(&self, kw: &KeywordType) -> bool {
         matches!(kw,
             KeywordType::Count |
@@ -327,7 +581,7 @@ impl Parser {
         )
     }
 
-    fn parse_aggregate(&mut self) -> Result<SelectItem, ParserError> {
+    fn parse_aggregate(&mut self) -> Result<Expr, ParserError> {
         // get the aggregate function type
         let func = match self.advance()? {
             Keyword(KeywordType::Count) => AggregateFunc::Count,
@@ -335,6 +589,7 @@ impl Parser {
             Keyword(KeywordType::Sum) => AggregateFunc::Sum,
             Keyword(KeywordType::Min) => AggregateFunc::Min,
             Keyword(KeywordType::Max) => AggregateFunc::Max,
+            Identifier(name) if aggregate_function(&name).is_some() => aggregate_function(&name).unwrap(),
             other => return Err(ParserError {
                 message: format!("Expected one of COUNT, SUM, AVG, MIN, MAX, found {}", other),
                 position: self.position
@@ -344,37 +599,333 @@ impl Parser {
         // expect opening parenthesis
         self.expect_grammar(GrammarType::OpenParen)?;
 
-        // parse between parentheses: either * or expression
-        let expr = match self.peek()? {
-            // TODO: but this means even non-count aggregate functions may be paired with *,
-            // so need to handle case by case
-            Grammar(GrammarType::Asterisk) => {
+        // parse between parentheses: either * or expression, optionally preceded by DISTINCT
+        let distinct = self.consume_keyword(KeywordType::Distinct);
+        let mut expr = match self.peek()? {
+            Grammar(GrammarType::Asterisk) if distinct => {
+                return Err(ParserError {
+                    message: format!("DISTINCT needs an expression, {}(DISTINCT *) is not valid", func),
+                    position: self.position
+                })
+            }
+            Grammar(GrammarType::Asterisk) if func == AggregateFunc::Count => {
                 self.advance()?;
                 None  // COUNT(*) case
             }
-            _ => {
-                // for now, just parse simple column, later parse full expressions
-                let col = self.expect_identifier()?;
-                Some(Box::new(Expr::Column(col)))
+            Grammar(GrammarType::Asterisk) => {
+                return Err(ParserError {
+                    message: format!("Only COUNT accepts *, {} needs an expression", func),
+                    position: self.position
+                })
             }
+            _ => Some(Box::new(self.parse_or_expression()?))
+        };
+
+        // STRING_AGG, the percentiles and APPROX_TOP_K take a constant after the expression
+        let within = within_group(&func, self.peek()?);
+        let func = match func {
+            AggregateFunc::StringAgg(_) => {
+                self.expect_grammar(GrammarType::Comma)?;
+                AggregateFunc::StringAgg(self.expect_string_literal()?)
+            },
+            AggregateFunc::ApproxTopK(_) => {
+                self.expect_grammar(GrammarType::Comma)?;
+                match self.expect_integer()? {
+                    k if k > 0 => AggregateFunc::ApproxTopK(k as u64),
+                    k => return Err(ParserError {
+                        message: format!("the k of {} must be positive, found {}", func, k),
+                        position: self.position - 1
+                    })
+                }
+            },
+            AggregateFunc::PercentileCont(_) | AggregateFunc::PercentileDisc(_) | AggregateFunc::ApproxPercentile(_) => {
+                let fraction = if within {
+                    match expr.as_deref() {
+                        Some(Literal(Value::Int(number))) => *number as f64,
+                        Some(Literal(Value::Float(number))) => *number,
+                        _ => return Err(ParserError {
+                            message: format!("Expected the fraction of {} before WITHIN GROUP, found an expression", func),
+                            position: self.position - 1
+                        })
+                    }
+                } else {
+                    self.expect_grammar(GrammarType::Comma)?;
+                    match self.advance()? {
+                        Integer(number) => number as f64,
+                        Float(number) => number,
+                        other => return Err(ParserError {
+                            message: format!("Expected the fraction of {}, found {}", func, other),
+                            position: self.position - 1
+                        })
+                    }
+                };
+                if !(0.0..=1.0).contains(&fraction) {
+                    return Err(ParserError {
+                        message: format!("the fraction of {} must be between 0 and 1, found {}", func, fraction),
+                        position: self.position - 1
+                    })
+                }
+                match func {
+                    AggregateFunc::PercentileCont(_) => AggregateFunc::PercentileCont(fraction),
+                    AggregateFunc::PercentileDisc(_) => AggregateFunc::PercentileDisc(fraction),
+                    _ => AggregateFunc::ApproxPercentile(fraction)
+                }
+            },
+            func => func
         };
 
         // Expect closing parenthesis
         self.expect_grammar(GrammarType::CloseParen)?;
 
-        Ok(SelectItem::Aggregate { func, expr })
+        // PERCENTILE_CONT(0.9) WITHIN GROUP (ORDER BY expr) is the standard spelling of PERCENTILE_CONT(expr, 0.9)
+        if within {
+            self.expect_word("within")?;
+            self.expect_keyword(KeywordType::Group)?;
+            self.expect_grammar(GrammarType::OpenParen)?;
+            self.expect_keyword(KeywordType::Order)?;
+            self.expect_keyword(KeywordType::By)?;
+            let position = self.position;
+            let item = self.parse_order_by_item()?;
+            if !item.asc {
+                return Err(ParserError { message: format!("{} only supports WITHIN GROUP (ORDER BY ... ASC)", func), position })
+            }
+            self.expect_grammar(GrammarType::CloseParen)?;
+            expr = Some(Box::new(item.expr));
+        }
+
+        // SUM(amount) OVER (...) aggregates the window of each row
+        if matches!(self.tokens.get(self.position), Some(Keyword(KeywordType::Over))) {
+            if distinct {
+                return Err(ParserError {
+                    message: format!("DISTINCT is not supported in window functions, found {}(DISTINCT ...) OVER", func),
+                    position: self.position
+                })
+            }
+            let over = self.parse_over()?;
+            return Ok(Expr::Window { func: WindowFunc::Aggregate(func), args: expr.into_iter().map(|expr| *expr).collect(), over: Box::new(over) })
+        }
+
+        Ok(Expr::Aggregate { func, expr, distinct })
+    }
+
+    // OVER ([PARTITION BY expr, ...] [ORDER BY item, ...] [ROWS | RANGE frame])
+    fn parse_over(&mut self) -> Result<WindowSpec, ParserError> {
+        self.expect_keyword(KeywordType::Over)?;
+        self.expect_grammar(GrammarType::OpenParen)?;
+        let mut partition_by = Vec::new();
+        if self.consume_keyword(KeywordType::Partition) {
+            self.expect_keyword(KeywordType::By)?;
+            partition_by.push(self.parse_or_expression()?);
+            while matches!(self.peek()?, Grammar(GrammarType::Comma)) {
+                self.advance()?;
+                partition_by.push(self.parse_or_expression()?);
+            }
+        }
+        let mut order_by = Vec::new();
+        if self.consume_keyword(KeywordType::Order) {
+            self.expect_keyword(KeywordType::By)?;
+            order_by.push(self.parse_order_by_item()?);
+            while matches!(self.peek()?, Grammar(GrammarType::Comma)) {
+                self.advance()?;
+                order_by.push(self.parse_order_by_item()?);
+            }
+        }
+        let frame = self.parse_window_frame()?;
+        self.expect_grammar(GrammarType::CloseParen)?;
+
+        Ok(WindowSpec { partition_by, order_by, frame })
+    }
+
+    // ROWS BETWEEN start AND end, a frame given by its start alone ends at the current row
+    fn parse_window_frame(&mut self) -> Result<Option<WindowFrame>, ParserError> {
+        let units = if self.consume_keyword(KeywordType::Rows) {
+            FrameUnits::Rows
+        } else if self.consume_keyword(KeywordType::Range) {
+            FrameUnits::Range
+        } else {
+            return Ok(None)
+        };
+        if !self.consume_keyword(KeywordType::Between) {
+            let start = self.parse_frame_bound()?;
+            return Ok(Some(WindowFrame { units, start, end: FrameBound::CurrentRow }))
+        }
+        let start = self.parse_frame_bound()?;
+        self.expect_keyword(KeywordType::And)?;
+        let end = self.parse_frame_bound()?;
+
+        Ok(Some(WindowFrame { units, start, end }))
     }
 
+    fn parse_frame_bound(&mut self) -> Result<FrameBound, ParserError> {
+        if self.consume_keyword(KeywordType::Current) {
+            self.expect_keyword(KeywordType::Row)?;
+            return Ok(FrameBound::CurrentRow)
+        }
+        let offset = match self.advance()? {
+            Keyword(KeywordType::Unbounded) => None,
+            Integer(offset) => Some(Value::Int(offset)),
+            Float(offset) => Some(Value::Float(offset)),
+            other => return Err(ParserError {
+                message: format!("Expected UNBOUNDED, CURRENT ROW or an offset in the window frame, found {}", other),
+                position: self.position - 1
+            })
+        };
+        let preceding = match self.advance()? {
+            Keyword(KeywordType::Preceding) => true,
+            Keyword(KeywordType::Following) => false,
+            other => return Err(ParserError {
+                message: format!("Expected PRECEDING or FOLLOWING, found {}", other),
+                position: self.position - 1
+            })
+        };
+
+        Ok(match (offset, preceding) {
+            (None, true) => FrameBound::UnboundedPreceding,
+            (None, false) => FrameBound::UnboundedFollowing,
+            (Some(offset), true) => FrameBound::Preceding(offset),
+            (Some(offset), false) => FrameBound::Following(offset)
+        })
+    }
+
+    // joins are read from left to right, a JOIN b JOIN c joins c to the join of a and b
     fn parse_from(&mut self) -> Result<Option<FromClause>, ParserError> {
         self.expect_keyword(KeywordType::From)?;
-        let source = self.expect_string_literal()
-            .map_err(|e| ParserError {
-                message: format!("Expected string literal after FROM: {}", e.message),
-                position: e.position
-            })?;
-        Ok(Some(FromClause { source }))
+        let mut from = self.parse_table("FROM")?;
+        while let Some(kind) = self.parse_join_kind()? {
+            let right = self.parse_table("JOIN")?;
+            let condition = match kind {
+                JoinKind::Cross => None,
+                _ => {
+                    self.expect_keyword(KeywordType::On)?;
+                    Some(self.parse_or_expression()?)
+                }
+            };
+            from = FromClause::Join { left: Box::new(from), kind, right: Box::new(right), condition };
+        }
+        Ok(Some(from))
     }
 
+    // a file or a subquery, with its alias, then the PIVOT and UNPIVOT operators applied to it in turn
+    fn parse_table(&mut self, clause: &str) -> Result<FromClause, ParserError> {
+        let mut table = if matches!(self.tokens.get(self.position), Some(Grammar(GrammarType::OpenParen))) {
+            let query = self.parse_subquery()?;
+            let position = self.position;
+            match self.parse_table_alias()? {
+                Some(alias) => FromClause::Subquery { query: Box::new(query), alias },
+                None => return Err(ParserError { message: format!("Subquery in {} needs an alias, (SELECT ...) AS t", clause), position })
+            }
+        } else {
+            let source = self.parse_source(clause)?;
+            let alias = self.parse_table_alias()?;
+            FromClause::File { source, alias }
+        };
+        while let Some(Identifier(name)) = self.tokens.get(self.position)
+            && is_table_operator(name, self.tokens.get(self.position + 1)) {
+            table = match name.eq_ignore_ascii_case("pivot") {
+                true => self.parse_pivot(table)?,
+                false => self.parse_unpivot(table)?
+            };
+        }
+        Ok(table)
+    }
+
+    // PIVOT (aggregate FOR column IN (value [AS name], ...)) [AS alias]
+    fn parse_pivot(&mut self, input: FromClause) -> Result<FromClause, ParserError> {
+        self.advance()?;
+        self.expect_grammar(GrammarType::OpenParen)?;
+        let position = self.position;
+        let aggregate = self.parse_concat()?;
+        if !matches!(aggregate, Expr::Aggregate { .. }) {
+            return Err(ParserError { message: format!("PIVOT expects an aggregate function, found {}", aggregate), position })
+        }
+        self.expect_word("for")?;
+        let column = self.expect_identifier()?;
+        self.expect_keyword(KeywordType::In)?;
+        self.expect_grammar(GrammarType::OpenParen)?;
+        let mut values = Vec::new();
+        loop {
+            let value = self.parse_concat()?;
+            values.push((value, self.parse_alias()?));
+            if !matches!(self.peek()?, Grammar(GrammarType::Comma)) {
+                break
+            }
+            self.advance()?;
+        }
+        self.expect_grammar(GrammarType::CloseParen)?;
+        self.expect_grammar(GrammarType::CloseParen)?;
+        let alias = self.parse_table_alias()?;
+        Ok(FromClause::Pivot { input: Box::new(input), aggregate, column, values, alias })
+    }
+
+    // UNPIVOT [INCLUDE NULLS | EXCLUDE NULLS] (value FOR name IN (column, ...)) [AS alias]
+    fn parse_unpivot(&mut self, input: FromClause) -> Result<FromClause, ParserError> {
+        self.advance()?;
+        let mut include_nulls = false;
+        if let Some(Identifier(word)) = self.tokens.get(self.position)
+            && (word.eq_ignore_ascii_case("include") || word.eq_ignore_ascii_case("exclude")) {
+            include_nulls = word.eq_ignore_ascii_case("include");
+            self.advance()?;
+            self.expect_word("nulls")?;
+        }
+        self.expect_grammar(GrammarType::OpenParen)?;
+        let value = self.expect_identifier()?;
+        self.expect_word("for")?;
+        let name = self.expect_identifier()?;
+        self.expect_keyword(KeywordType::In)?;
+        self.expect_grammar(GrammarType::OpenParen)?;
+        let mut columns = vec![self.expect_identifier()?];
+        while matches!(self.peek()?, Grammar(GrammarType::Comma)) {
+            self.advance()?;
+            columns.push(self.expect_identifier()?);
+        }
+        self.expect_grammar(GrammarType::CloseParen)?;
+        self.expect_grammar(GrammarType::CloseParen)?;
+        let alias = self.parse_table_alias()?;
+        Ok(FromClause::Unpivot { input: Box::new(input), value, name, columns, include_nulls, alias })
+    }
+
+    // the file can be given as 'people.csv', "people.csv" or just people
+    fn parse_source(&mut self, clause: &str) -> Result<String, ParserError> {
+        match self.advance()? {
+            StringLiteral(source) | Identifier(source) => Ok(source),
+            other => Err(ParserError {
+                message: format!("Expected file name after {}, found {}", clause, other),
+                position: self.position - 1
+            })
+        }
+    }
+
+    // AS is optional before a table alias, a word which looks like a misspelled clause keyword is not an alias
+    fn parse_table_alias(&mut self) -> Result<Option<String>, ParserError> {
+        match self.tokens.get(self.position) {
+            Some(Keyword(KeywordType::As)) => self.parse_alias(),
+            Some(Identifier(word)) if !suggest_keyword(word).is_some_and(|kw| CLAUSE_KEYWORDS.contains(&kw))
+                && !is_table_operator(word, self.tokens.get(self.position + 1)) => {
+                self.expect_identifier().map(Some)
+            },
+            _ => Ok(None)
+        }
+    }
+
+    // [INNER] JOIN, LEFT [OUTER] JOIN, RIGHT [OUTER] JOIN, FULL [OUTER] JOIN or CROSS JOIN
+    fn parse_join_kind(&mut self) -> Result<Option<JoinKind>, ParserError> {
+        let kind = match self.tokens.get(self.position) {
+            Some(Keyword(KeywordType::Join) | Keyword(KeywordType::Inner)) => JoinKind::Inner,
+            Some(Keyword(KeywordType::Left)) => JoinKind::Left,
+            Some(Keyword(KeywordType::Right)) => JoinKind::Right,
+            Some(Keyword(KeywordType::Full)) => JoinKind::Full,
+            Some(Keyword(KeywordType::Cross)) => JoinKind::Cross,
+            _ => return Ok(None)
+        };
+        if !self.consume_keyword(KeywordType::Join) {
+            self.position += 1;
+            if matches!(kind, JoinKind::Left | JoinKind::Right | JoinKind::Full) {
+                self.consume_keyword(KeywordType::Outer);
+            }
+            self.expect_keyword(KeywordType::Join)?;
+        }
+        Ok(Some(kind))
+    }
 
     fn parse_where(&mut self) -> Result<Option<Expr>, ParserError> {
         if !matches!(self.peek()?, Token::Keyword(KeywordType::Where)) {
@@ -403,11 +954,11 @@ impl Parser {
     }
 
     fn parse_and_expression(&mut self) -> Result<Expr, ParserError> {
-        let mut left = self.parse_comparison()?;
+        let mut left = self.parse_not()?;
 
         while matches!(self.peek()?, Token::Keyword(KeywordType::And)) {
             self.advance()?;
-            let right = self.parse_comparison()?;
+            let right = self.parse_not()?;
 
             left = Expr::BinaryOp {
                 left: Box::new(left),
@@ -419,15 +970,80 @@ impl Parser {
         Ok(left)
     }
 
+    // NOT binds looser than comparisons, NOT a = b negates the comparison
+    fn parse_not(&mut self) -> Result<Expr, ParserError> {
+        if matches!(self.peek()?, Keyword(KeywordType::Not)) {
+            self.advance()?;
+            let expr = self.parse_not()?;
+            return Ok(Expr::UnaryOp {
+                operator: UnaryOperator::Not,
+                expr: Box::new(expr),
+            })
+        }
+
+        self.parse_comparison()
+    }
+
     fn parse_comparison(&mut self) -> Result<Expr, ParserError> {
         // Left side
-        let left = self.parse_primary()?;
+        let left = self.parse_concat()?;
+
+        // IS [NOT] NULL
+        if matches!(self.tokens.get(self.position), Some(Keyword(KeywordType::Is))) {
+            self.advance()?;
+            let negated = self.consume_keyword(KeywordType::Not);
+            self.expect_keyword(KeywordType::Null)?;
+            return Ok(Expr::IsNull { expr: Box::new(left), negated })
+        }
+
+        // [NOT] IN, [NOT] BETWEEN, [NOT] LIKE, [NOT] ILIKE
+        let negated = matches!(self.tokens.get(self.position), Some(Keyword(KeywordType::Not)))
+            && matches!(self.tokens.get(self.position + 1), Some(Keyword(
+                KeywordType::In | KeywordType::Between | KeywordType::Like | KeywordType::Ilike
+                | KeywordType::Regexp | KeywordType::Rlike
+            )));
+        if negated {
+            self.advance()?;
+        }
+        match self.tokens.get(self.position) {
+            Some(Keyword(KeywordType::In)) => {
+                self.advance()?;
+                return self.parse_in_list(left, negated)
+            },
+            Some(Keyword(KeywordType::Between)) => {
+                self.advance()?;
+                // the bounds stop before AND, so BETWEEN 1 AND 2 AND flag keeps the last AND a conjunction
+                let low = self.parse_concat()?;
+                self.expect_keyword(KeywordType::And)?;
+                let high = self.parse_concat()?;
+                return Ok(Expr::Between { expr: Box::new(left), low: Box::new(low), high: Box::new(high), negated })
+            },
+            Some(Keyword(keyword @ (KeywordType::Like | KeywordType::Ilike))) => {
+                let case_insensitive = *keyword == KeywordType::Ilike;
+                self.advance()?;
+                let pattern = self.parse_concat()?;
+                return Ok(Expr::Like { expr: Box::new(left), pattern: Box::new(pattern), case_insensitive, negated })
+            },
+            Some(Keyword(KeywordType::Regexp | KeywordType::Rlike)) => {
+                self.advance()?;
+                let pattern = self.parse_concat()?;
+                return Ok(Expr::Regexp { expr: Box::new(left), pattern: Box::new(pattern), negated })
+            },
+            _ => {}
+        }
 
-        // Operator
-        let operator = self.parse_comparison_operator()?;
+        // Operator, a lone operand such as a boolean column is a condition by itself
+        let operator = match self.tokens.get(self.position) {
+            Some(Operator(op)) => match to_comparison_operator(op) {
+                Some(operator) => operator,
+                None => return Ok(left)
+            },
+            _ => return Ok(left)
+        };
+        self.advance()?;
 
         // Right side
-        let right = self.parse_primary()?;
+        let right = self.parse_concat()?;
 
         Ok(Expr::BinaryOp {
             left: Box::new(left),
@@ -436,33 +1052,187 @@ impl Parser {
         })
     }
 
-    fn parse_comparison_operator(&mut self) -> Result<BinaryOperator, ParserError> {
-        let token = self.advance()?;
+    fn parse_in_list(&mut self, expr: Expr, negated: bool) -> Result<Expr, ParserError> {
+        if self.starts_subquery() {
+            let query = self.parse_subquery()?;
+            return Ok(Expr::InSubquery { expr: Box::new(expr), query: Box::new(query), negated })
+        }
+        self.expect_grammar(GrammarType::OpenParen)?;
+        let mut list = vec![self.parse_concat()?];
+        while matches!(self.peek()?, Grammar(GrammarType::Comma)) {
+            self.advance()?;
+            list.push(self.parse_concat()?);
+        }
+        self.expect_grammar(GrammarType::CloseParen)?;
 
-        match token {
-            Token::Operator(op) => {
-                match op {
-                    OperatorType::Equals => Ok(BinaryOperator::Equals),
-                    OperatorType::NotEquals => Ok(BinaryOperator::NotEquals),
-                    OperatorType::GreaterThan => Ok(BinaryOperator::GreaterThan),
-                    OperatorType::SmallerThan => Ok(BinaryOperator::LessThan),
-                    OperatorType::GreaterThanOrEqual => Ok(BinaryOperator::GreaterThanOrEquals),
-                    OperatorType::SmallerThanOrEqual => Ok(BinaryOperator::LessThanOrEquals),
-                    _ => Err(ParserError {
-                        message: format!("Expected comparison operator, found {}", op),
-                        position: self.position - 1
-                    })
-                }
-            }
-            other => Err(ParserError {
-                message: format!("Expected operator, found {}", other),
-                position: self.position - 1
+        Ok(Expr::InList { expr: Box::new(expr), list, negated })
+    }
+
+    fn consume_keyword(&mut self, keyword: KeywordType) -> bool {
+        let found = matches!(self.tokens.get(self.position), Some(Keyword(kw)) if *kw == keyword);
+        if found {
+            self.position += 1;
+        }
+        found
+    }
+
+    // string concatenation binds looser than arithmetic, 'id-' || id + 1 concatenates the sum
+    fn parse_concat(&mut self) -> Result<Expr, ParserError> {
+        let mut left = self.parse_additive()?;
+
+        while matches!(self.tokens.get(self.position), Some(Operator(OperatorType::Concat))) {
+            self.advance()?;
+            let right = self.parse_additive()?;
+            left = Expr::BinaryOp {
+                left: Box::new(left),
+                operator: BinaryOperator::Concat,
+                right: Box::new(right),
+            };
+        }
+
+        Ok(left)
+    }
+
+    fn parse_additive(&mut self) -> Result<Expr, ParserError> {
+        let mut left = self.parse_multiplicative()?;
+
+        loop {
+            let operator = match self.tokens.get(self.position) {
+                Some(Operator(OperatorType::Add)) => BinaryOperator::Add,
+                Some(Operator(OperatorType::Subtract)) => BinaryOperator::Subtract,
+                _ => break
+            };
+            self.advance()?;
+            let right = self.parse_multiplicative()?;
+            left = Expr::BinaryOp {
+                left: Box::new(left),
+                operator,
+                right: Box::new(right),
+            };
+        }
+
+        Ok(left)
+    }
+
+    fn parse_multiplicative(&mut self) -> Result<Expr, ParserError> {
+        let mut left = self.parse_unary()?;
+
+        loop {
+            let operator = match self.tokens.get(self.position) {
+                Some(Operator(OperatorType::Multiply)) => BinaryOperator::Multiply,
+                Some(Operator(OperatorType::Divide)) => BinaryOperator::Divide,
+                Some(Operator(OperatorType::Modulo)) => BinaryOperator::Modulo,
+                _ => break
+            };
+            self.advance()?;
+            let right = self.parse_unary()?;
+            left = Expr::BinaryOp {
+                left: Box::new(left),
+                operator,
+                right: Box::new(right),
+            };
+        }
+
+        Ok(left)
+    }
+
+    fn parse_unary(&mut self) -> Result<Expr, ParserError> {
+        if matches!(self.peek()?, Operator(OperatorType::Subtract)) {
+            self.advance()?;
+            let expr = self.parse_unary()?;
+            return Ok(Expr::UnaryOp {
+                operator: UnaryOperator::Minus,
+                expr: Box::new(expr),
             })
         }
+        if matches!(self.peek()?, Operator(OperatorType::Add)) {
+            self.advance()?;
+            return self.parse_unary()
+        }
+
+        self.parse_postfix_cast()
+    }
+
+    // price::DOUBLE binds tighter than unary minus, -x::INTEGER negates the cast value
+    fn parse_postfix_cast(&mut self) -> Result<Expr, ParserError> {
+        let mut expr = self.parse_primary()?;
+        while matches!(self.tokens.get(self.position), Some(Operator(OperatorType::Cast))) {
+            self.advance()?;
+            let type_name = self.parse_type_name()?;
+            expr = Expr::Cast { expr: Box::new(expr), type_name, try_cast: false };
+        }
+        Ok(expr)
+    }
+
+    // CAST(expr AS type) or TRY_CAST(expr AS type)
+    fn parse_cast(&mut self) -> Result<Expr, ParserError> {
+        let try_cast = self.expect_identifier()?.eq_ignore_ascii_case("try_cast");
+        self.expect_grammar(GrammarType::OpenParen)?;
+        let expr = self.parse_or_expression()?;
+        self.expect_keyword(KeywordType::As)?;
+        let type_name = self.parse_type_name()?;
+        self.expect_grammar(GrammarType::CloseParen)?;
+
+        Ok(Expr::Cast { expr: Box::new(expr), type_name, try_cast })
+    }
+
+    // the common spellings of each type, a length or precision such as VARCHAR(20) or DECIMAL(10, 2) is accepted and
+    // ignored as values are not stored
+    fn parse_type_name(&mut self) -> Result<TypeName, ParserError> {
+        let position = self.position;
+        let name = self.expect_identifier()
+            .map_err(|_| ParserError { message: "Expected a type name".to_string(), position })?;
+        let type_name = match name.to_ascii_uppercase().as_str() {
+            "BOOLEAN" | "BOOL" => TypeName::Boolean,
+            "INTEGER" | "INT" | "BIGINT" | "SMALLINT" | "TINYINT" => TypeName::Integer,
+            "DOUBLE" | "FLOAT" | "REAL" | "DECIMAL" | "NUMERIC" => TypeName::Double,
+            "VARCHAR" | "TEXT" | "STRING" | "CHAR" => TypeName::Varchar,
+            "DATE" => TypeName::Date,
+            _ => return Err(ParserError {
+                message: format!("Unknown type {}, expected BOOLEAN, INTEGER, DOUBLE, VARCHAR or DATE", name),
+                position
+            })
+        };
+        if matches!(self.tokens.get(self.position), Some(Grammar(GrammarType::OpenParen))) {
+            self.advance()?;
+            self.expect_integer()?;
+            if matches!(self.peek()?, Grammar(GrammarType::Comma)) {
+                self.advance()?;
+                self.expect_integer()?;
+            }
+            self.expect_grammar(GrammarType::CloseParen)?;
+        }
+
+        Ok(type_name)
     }
 
     fn parse_primary(&mut self) -> Result<Expr, ParserError> {
         match self.peek()? {
+            Identifier(name) if (name.eq_ignore_ascii_case("cast") || name.eq_ignore_ascii_case("try_cast"))
+                && matches!(self.tokens.get(self.position + 1), Some(Grammar(GrammarType::OpenParen))) => {
+                self.parse_cast()
+            },
+            Identifier(name) if aggregate_function(name).is_some()
+                && matches!(self.tokens.get(self.position + 1), Some(Grammar(GrammarType::OpenParen))) => {
+                self.parse_aggregate()
+            },
+            Identifier(_) if matches!(self.tokens.get(self.position + 1), Some(Grammar(GrammarType::OpenParen))) => {
+                self.parse_function_call()
+            },
+            // typed literal, DATE '2024-01-31'
+            Identifier(name) if name.eq_ignore_ascii_case("date")
+                && matches!(self.tokens.get(self.position + 1), Some(StringLiteral(_))) => {
+                self.advance()?;
+                let position = self.position;
+                let text = self.expect_string_literal()?;
+                chrono::NaiveDate::parse_from_str(&text, "%Y-%m-%d")
+                    .map(|date| Literal(Value::Date(date)))
+                    .map_err(|_| ParserError { message: format!("Invalid date '{}', expected YYYY-MM-DD", text), position })
+            },
+            Identifier(name) if name.eq_ignore_ascii_case("current_date") => {
+                let name = self.expect_identifier()?;
+                Ok(Expr::Function { name, args: Vec::new() })
+            },
             Identifier(_) => {
                 let identifier = self.expect_identifier()?;
                 Ok(Expr::Column(identifier))
@@ -487,6 +1257,27 @@ impl Parser {
                 self.advance()?;
                 Ok(Literal(Value::Bool(false)))
             },
+            Keyword(KeywordType::Null) => {
+                self.advance()?;
+                Ok(Literal(Value::Null))
+            },
+            Keyword(kw) if self.is_aggregate_keyword(kw) => {
+                self.parse_aggregate()
+            },
+            Keyword(KeywordType::Case) => self.parse_case(),
+            Keyword(KeywordType::Exists) => {
+                self.advance()?;
+                Ok(Expr::Exists(Box::new(self.parse_subquery()?)))
+            },
+            Grammar(GrammarType::OpenParen) if self.starts_subquery() => {
+                Ok(Expr::Subquery(Box::new(self.parse_subquery()?)))
+            },
+            Grammar(GrammarType::OpenParen) => {
+                self.advance()?;
+                let expr = self.parse_or_expression()?;
+                self.expect_grammar(GrammarType::CloseParen)?;
+                Ok(expr)
+            },
             _ => Err(ParserError {
                 message: "Expected column or literal".to_string(),
                 position: self.position
@@ -494,6 +1285,76 @@ impl Parser {
         }
     }
 
+    fn parse_case(&mut self) -> Result<Expr, ParserError> {
+        self.expect_keyword(KeywordType::Case)?;
+        let operand = match self.peek()? {
+            Keyword(KeywordType::When) => None,
+            _ => Some(Box::new(self.parse_or_expression()?))
+        };
+        let mut branches = Vec::new();
+        while self.consume_keyword(KeywordType::When) {
+            let when = self.parse_or_expression()?;
+            self.expect_keyword(KeywordType::Then)?;
+            branches.push((when, self.parse_or_expression()?));
+        }
+        if branches.is_empty() {
+            return Err(ParserError { message: "CASE needs at least one WHEN ... THEN branch".to_string(), position: self.position })
+        }
+        let else_result = if self.consume_keyword(KeywordType::Else) {
+            Some(Box::new(self.parse_or_expression()?))
+        } else {
+            None
+        };
+        self.expect_keyword(KeywordType::End)?;
+
+        Ok(Expr::Case { operand, branches, else_result })
+    }
+
+    // name(arg, ...), the name is checked against the known functions by the executor
+    fn parse_function_call(&mut self) -> Result<Expr, ParserError> {
+        let name = self.expect_identifier()?;
+        self.expect_grammar(GrammarType::OpenParen)?;
+        let mut args = Vec::new();
+        if name.eq_ignore_ascii_case("extract") {
+            // EXTRACT(field FROM date), the field is passed as a string
+            let field = self.expect_identifier()?;
+            self.expect_keyword(KeywordType::From)?;
+            args.push(Literal(Value::String(field.to_ascii_uppercase())));
+            args.push(self.parse_or_expression()?);
+        } else if name.eq_ignore_ascii_case("position") {
+            // POSITION(substring IN string), the operands can not be IN predicates themselves
+            args.push(self.parse_concat()?);
+            self.expect_keyword(KeywordType::In)?;
+            args.push(self.parse_concat()?);
+        } else if !matches!(self.peek()?, Grammar(GrammarType::CloseParen)) {
+            args.push(self.parse_or_expression()?);
+            while matches!(self.peek()?, Grammar(GrammarType::Comma)) {
+                self.advance()?;
+                args.push(self.parse_or_expression()?);
+            }
+        }
+        self.expect_grammar(GrammarType::CloseParen)?;
+
+        let over = matches!(self.tokens.get(self.position), Some(Keyword(KeywordType::Over)));
+        match window_function(&name) {
+            Some(func) if over => Ok(Expr::Window { func, args, over: Box::new(self.parse_over()?) }),
+            Some(_) => Err(ParserError { message: format!("{} needs an OVER clause", name.to_ascii_uppercase()), position: self.position }),
+            None if over => Err(ParserError { message: format!("{} is not a window function", name), position: self.position }),
+            None => Ok(Expr::Function { name, args })
+        }
+    }
+
+    // a statement ends with a semicolon or with the input
+    fn expect_statement_end(&self) -> Result<(), ParserError> {
+        match self.tokens.get(self.position) {
+            None | Some(Grammar(GrammarType::Semicolon)) => Ok(()),
+            Some(other) => Err(ParserError {
+                message: format!("Expected semicolon, {} found", other),
+                position: self.position
+            })
+        }
+    }
+
     fn peek(&self) -> Result<&Token, ParserError> {
         // TODO: in sql parser module, ensure that white space is ignored so that a token is captured
         // in entirety ..SELECT Avg  (salary)      FROM employees.csv
@@ -535,6 +1396,20 @@ impl Parser {
         }
     }
 
+    // a word which is not a keyword of its own, FOR in PIVOT (... FOR month IN ...)
+    fn expect_word(&mut self, word: &str) -> Result<(), ParserError> {
+        match self.peek()? {
+            Identifier(found) if found.eq_ignore_ascii_case(word) => {
+                self.advance()?;
+                Ok(())
+            },
+            other => Err(ParserError {
+                message: format!("Expected {}, found {}", word.to_ascii_uppercase(), other),
+                position: self.position
+            })
+        }
+    }
+
     fn expect_identifier(&mut self) -> Result<String, ParserError> {
         let token = self.advance()?;
         match token {
@@ -574,15 +1449,70 @@ impl Parser {
             _ => Err(ParserError{message: "Expected a float literal".to_string(), position: self.position - 1})
         }
     }
+}
 
-    fn expect_operator(&mut self, operator: OperatorType) -> Result<(), ParserError> {
-        let token = self.peek()?;
-        match token {
-            Token::Operator(o) if o == &operator => {
-                self.advance()?;
-                Ok(())
-            },
-            _ => Err(ParserError{message: format!("Expected operator '{}'", operator), position: self.position})
-        }
+// the aggregates named by identifiers rather than keywords, the constant parameters are parsed after the expression
+// the fraction of PERCENTILE_CONT and PERCENTILE_DISC is their only argument when the values follow in WITHIN GROUP
+fn within_group(func: &AggregateFunc, next: &Token) -> bool {
+    matches!(func, AggregateFunc::PercentileCont(_) | AggregateFunc::PercentileDisc(_))
+        && matches!(next, Grammar(GrammarType::CloseParen))
+}
+
+fn aggregate_function(name: &str) -> Option<AggregateFunc> {
+    match name.to_ascii_uppercase().as_str() {
+        "STDDEV" | "STDDEV_SAMP" => Some(AggregateFunc::Stddev),
+        "STDDEV_POP" => Some(AggregateFunc::StddevPop),
+        "VARIANCE" | "VAR_SAMP" => Some(AggregateFunc::Variance),
+        "VAR_POP" => Some(AggregateFunc::VarPop),
+        "MEDIAN" => Some(AggregateFunc::Median),
+        "PERCENTILE_CONT" => Some(AggregateFunc::PercentileCont(0.5)),
+        "PERCENTILE_DISC" => Some(AggregateFunc::PercentileDisc(0.5)),
+        "MODE" => Some(AggregateFunc::Mode),
+        "STRING_AGG" => Some(AggregateFunc::StringAgg(String::new())),
+        "ARRAY_AGG" => Some(AggregateFunc::ArrayAgg),
+        "BOOL_AND" => Some(AggregateFunc::BoolAnd),
+        "BOOL_OR" => Some(AggregateFunc::BoolOr),
+        "COUNT_IF" => Some(AggregateFunc::CountIf),
+        "APPROX_COUNT_DISTINCT" => Some(AggregateFunc::ApproxCountDistinct),
+        "APPROX_PERCENTILE" => Some(AggregateFunc::ApproxPercentile(0.5)),
+        "APPROX_TOP_K" => Some(AggregateFunc::ApproxTopK(1)),
+        _ => None
+    }
+}
+
+// the functions only computed over a window, aggregates are also computed over one when OVER follows them
+fn window_function(name: &str) -> Option<WindowFunc> {
+    match name.to_ascii_uppercase().as_str() {
+        "ROW_NUMBER" => Some(WindowFunc::RowNumber),
+        "RANK" => Some(WindowFunc::Rank),
+        "DENSE_RANK" => Some(WindowFunc::DenseRank),
+        "NTILE" => Some(WindowFunc::Ntile),
+        "LAG" => Some(WindowFunc::Lag),
+        "LEAD" => Some(WindowFunc::Lead),
+        "FIRST_VALUE" => Some(WindowFunc::FirstValue),
+        "LAST_VALUE" => Some(WindowFunc::LastValue),
+        _ => None
     }
-}
\ No newline at end of file
+}
+
+// PIVOT and UNPIVOT are names of columns or tables unless their parenthesis, or the NULLS option of UNPIVOT, follows
+fn is_table_operator(name: &str, next: Option<&Token>) -> bool {
+    match next {
+        Some(Grammar(GrammarType::OpenParen)) => name.eq_ignore_ascii_case("pivot") || name.eq_ignore_ascii_case("unpivot"),
+        Some(Identifier(word)) => name.eq_ignore_ascii_case("unpivot")
+            && (word.eq_ignore_ascii_case("include") || word.eq_ignore_ascii_case("exclude")),
+        _ => false
+    }
+}
+
+fn to_comparison_operator(op: &OperatorType) -> Option<BinaryOperator> {
+    match op {
+        OperatorType::Equals => Some(BinaryOperator::Equals),
+        OperatorType::NotEquals => Some(BinaryOperator::NotEquals),
+        OperatorType::GreaterThan => Some(BinaryOperator::GreaterThan),
+        OperatorType::SmallerThan => Some(BinaryOperator::LessThan),
+        OperatorType::GreaterThanOrEqual => Some(BinaryOperator::GreaterThanOrEquals),
+        OperatorType::SmallerThanOrEqual => Some(BinaryOperator::LessThanOrEquals),
+        _ => None
+    }
+}
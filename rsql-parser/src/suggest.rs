@@ -0,0 +1,38 @@
+// optimal string alignment distance, so that a swapped pair of letters (FORM vs FROM) counts as a single edit
+pub fn edit_distance(left: &str, right: &str) -> usize {
+    let left: Vec<char> = left.chars().collect();
+    let right: Vec<char> = right.chars().collect();
+    let mut distances = vec![vec![0usize; right.len() + 1]; left.len() + 1];
+    for (i, row) in distances.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    for (j, distance) in distances[0].iter_mut().enumerate() {
+        *distance = j;
+    }
+    for i in 1..=left.len() {
+        for j in 1..=right.len() {
+            let cost = if left[i - 1] == right[j - 1] { 0 } else { 1 };
+            let mut distance = (distances[i - 1][j] + 1)
+                .min(distances[i][j - 1] + 1)
+                .min(distances[i - 1][j - 1] + cost);
+            if i > 1 && j > 1 && left[i - 1] == right[j - 2] && left[i - 2] == right[j - 1] {
+                distance = distance.min(distances[i - 2][j - 2] + 1);
+            }
+            distances[i][j] = distance;
+        }
+    }
+
+    distances[left.len()][right.len()]
+}
+
+// picks the candidate closest to word, ignoring case, as long as it is close enough to be a plausible typo
+pub fn closest_match<'a>(word: &str, candidates: &[&'a str]) -> Option<&'a str> {
+    let word = word.to_ascii_lowercase();
+    // short words get a single edit, otherwise almost every short identifier is "close" to something
+    let max_distance = if word.chars().count() <= 4 { 1 } else { 2 };
+    candidates.iter()
+        .map(|candidate| (*candidate, edit_distance(&word, &candidate.to_ascii_lowercase())))
+        .filter(|(_, distance)| *distance > 0 && *distance <= max_distance)
+        .min_by_key(|(_, distance)| *distance)
+        .map(|(candidate, _)| candidate)
+}
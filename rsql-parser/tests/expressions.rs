@@ -0,0 +1,267 @@
+use rsql_parser::ast::constructs::{BinaryOperator, Expr, GroupByItem, Statement, TypeName, UnaryOperator, Value};
+use rsql_parser::lexer::tokenizer::tokenize;
+use rsql_parser::parser::parser::Parser;
+
+fn where_clause(condition: &str) -> Expr {
+    let sql = format!("SELECT a FROM t WHERE {};", condition);
+    let mut parser = Parser::new(tokenize(&sql));
+    let Statement::Select(select) = parser.parse().unwrap() else { panic!("Expected select statement") };
+    select.where_clause.unwrap()
+}
+
+fn column(name: &str) -> Box<Expr> {
+    Box::new(Expr::Column(name.to_string()))
+}
+
+fn int(value: i64) -> Box<Expr> {
+    Box::new(Expr::Literal(Value::Int(value)))
+}
+
+fn binary(left: Box<Expr>, operator: BinaryOperator, right: Box<Expr>) -> Box<Expr> {
+    Box::new(Expr::BinaryOp { left, operator, right })
+}
+
+#[test]
+fn arithmetic_precedence() {
+    // a + b * c % 2 > 10 - -3
+    let expected = binary(
+        binary(column("a"), BinaryOperator::Add, binary(binary(column("b"), BinaryOperator::Multiply, column("c")), BinaryOperator::Modulo, int(2))),
+        BinaryOperator::GreaterThan,
+        binary(int(10), BinaryOperator::Subtract, int(-3)),
+    );
+    assert_eq!(where_clause("a + b * c % 2 > 10 - -3"), *expected);
+
+    // left associative
+    let expected = binary(binary(column("a"), BinaryOperator::Subtract, column("b")), BinaryOperator::Subtract, column("c"));
+    assert_eq!(where_clause("a-b-c = 0"), *binary(expected, BinaryOperator::Equals, int(0)));
+}
+
+#[test]
+fn parentheses_and_unary_minus() {
+    let expected = binary(
+        binary(binary(column("a"), BinaryOperator::Add, column("b")), BinaryOperator::Divide, int(2)),
+        BinaryOperator::NotEquals,
+        Box::new(Expr::UnaryOp { operator: UnaryOperator::Minus, expr: column("c") }),
+    );
+    assert_eq!(where_clause("(a + b) / 2 != -c"), *expected);
+
+    let expected = binary(
+        binary(binary(column("age"), BinaryOperator::GreaterThan, int(18)), BinaryOperator::And, binary(column("city"), BinaryOperator::Equals, Box::new(Expr::Literal(Value::String("Delhi".to_string()))))),
+        BinaryOperator::Or,
+        binary(column("city"), BinaryOperator::Equals, Box::new(Expr::Literal(Value::String("Mumbai".to_string())))),
+    );
+    assert_eq!(where_clause("(age > 18 AND city = 'Delhi') OR city = 'Mumbai'"), *expected);
+}
+
+#[test]
+fn concatenation_binds_looser_than_arithmetic() {
+    let expected = binary(
+        binary(Box::new(Expr::Literal(Value::String("id-".to_string()))), BinaryOperator::Concat, binary(column("id"), BinaryOperator::Add, int(1))),
+        BinaryOperator::Equals,
+        column("code"),
+    );
+    assert_eq!(where_clause("'id-' || id + 1 = code"), *expected);
+}
+
+#[test]
+fn boolean_column_as_condition() {
+    assert_eq!(where_clause("is_adult"), *column("is_adult"));
+    assert_eq!(where_clause("is_adult AND age > 1"), *binary(column("is_adult"), BinaryOperator::And, binary(column("age"), BinaryOperator::GreaterThan, int(1))));
+}
+
+#[test]
+fn group_by_expressions() {
+    let mut parser = Parser::new(tokenize("SELECT a FROM t GROUP BY a * 2, b - 1 ORDER BY a;"));
+    let Statement::Select(select) = parser.parse().unwrap() else { panic!("Expected select statement") };
+    assert_eq!(select.group_by, Some(vec![
+        GroupByItem::Expr(*binary(column("a"), BinaryOperator::Multiply, int(2))),
+        GroupByItem::Expr(*binary(column("b"), BinaryOperator::Subtract, int(1))),
+    ]));
+}
+
+#[test]
+fn trailing_tokens_are_an_error() {
+    let mut parser = Parser::new(tokenize("SELECT a FROM t WHERE a 30;"));
+    let error = parser.parse().err().unwrap();
+    assert_eq!(error.message, "Expected semicolon, Integer 30 found");
+    assert_eq!(error.position, 6);
+}
+
+fn string(value: &str) -> Box<Expr> {
+    Box::new(Expr::Literal(Value::String(value.to_string())))
+}
+
+#[test]
+fn in_between_like_and_is_null() {
+    assert_eq!(
+        where_clause("status NOT IN ('a', 'b')"),
+        Expr::InList { expr: column("status"), list: vec![*string("a"), *string("b")], negated: true }
+    );
+    assert_eq!(
+        where_clause("age BETWEEN 18 AND 65 AND active"),
+        *binary(
+            Box::new(Expr::Between { expr: column("age"), low: int(18), high: int(65), negated: false }),
+            BinaryOperator::And,
+            column("active")
+        )
+    );
+    assert_eq!(
+        where_clause("name ILIKE 'sh%'"),
+        Expr::Like { expr: column("name"), pattern: string("sh%"), case_insensitive: true, negated: false }
+    );
+    assert_eq!(
+        where_clause("email IS NOT NULL OR phone IS NULL"),
+        *binary(
+            Box::new(Expr::IsNull { expr: column("email"), negated: true }),
+            BinaryOperator::Or,
+            Box::new(Expr::IsNull { expr: column("phone"), negated: false })
+        )
+    );
+}
+
+#[test]
+fn not_binds_looser_than_comparison() {
+    assert_eq!(
+        where_clause("NOT a = 1 AND b"),
+        *binary(
+            Box::new(Expr::UnaryOp { operator: UnaryOperator::Not, expr: binary(column("a"), BinaryOperator::Equals, int(1)) }),
+            BinaryOperator::And,
+            column("b")
+        )
+    );
+    assert_eq!(where_clause("x = NULL"), *binary(column("x"), BinaryOperator::Equals, Box::new(Expr::Literal(Value::Null))));
+    assert_eq!(where_clause("name NOT LIKE 'a_' || suffix").to_string(), "name NOT LIKE ('a_' || suffix)");
+}
+
+#[test]
+fn regexp_predicates_and_function_calls() {
+    assert_eq!(
+        where_clause("email NOT RLIKE '^[a-z]+@'"),
+        Expr::Regexp { expr: column("email"), pattern: string("^[a-z]+@"), negated: true }
+    );
+    assert_eq!(
+        where_clause("regexp_extract(phone, '([0-9]+)', 1) = '42'"),
+        *binary(
+            Box::new(Expr::Function {
+                name: "regexp_extract".to_string(),
+                args: vec![*column("phone"), *string("([0-9]+)"), *int(1)]
+            }),
+            BinaryOperator::Equals,
+            string("42")
+        )
+    );
+    assert_eq!(where_clause("now() IS NULL").to_string(), "now() IS NULL");
+}
+
+#[test]
+fn position_uses_in_between_its_arguments() {
+    assert_eq!(
+        where_clause("POSITION('@' IN email) > 1"),
+        *binary(
+            Box::new(Expr::Function { name: "POSITION".to_string(), args: vec![*string("@"), *column("email")] }),
+            BinaryOperator::GreaterThan,
+            int(1)
+        )
+    );
+    assert_eq!(where_clause("position(a || b IN c) = 0").to_string(), "position((a || b) IN c) = 0");
+}
+
+#[test]
+fn date_literals_and_date_function_syntax() {
+    let date = chrono::NaiveDate::from_ymd_opt(2024, 1, 31).unwrap();
+    assert_eq!(
+        where_clause("d >= DATE '2024-01-31'"),
+        *binary(column("d"), BinaryOperator::GreaterThanOrEquals, Box::new(Expr::Literal(Value::Date(date))))
+    );
+    assert_eq!(
+        where_clause("extract(year from d) = 2024"),
+        *binary(
+            Box::new(Expr::Function { name: "extract".to_string(), args: vec![*string("YEAR"), *column("d")] }),
+            BinaryOperator::Equals,
+            int(2024)
+        )
+    );
+    assert_eq!(where_clause("d < CURRENT_DATE").to_string(), "d < CURRENT_DATE");
+    assert_eq!(where_clause("date = 'x'"), *binary(column("date"), BinaryOperator::Equals, string("x")));
+
+    let mut parser = Parser::new(tokenize("SELECT a FROM t WHERE d = DATE '2024-02-30';"));
+    assert_eq!(parser.parse().err().unwrap().message, "Invalid date '2024-02-30', expected YYYY-MM-DD");
+}
+
+#[test]
+fn searched_and_simple_case() {
+    assert_eq!(
+        where_clause("CASE WHEN a > 1 THEN 'big' ELSE 'small' END = 'big'"),
+        *binary(
+            Box::new(Expr::Case {
+                operand: None,
+                branches: vec![(*binary(column("a"), BinaryOperator::GreaterThan, int(1)), *string("big"))],
+                else_result: Some(string("small"))
+            }),
+            BinaryOperator::Equals,
+            string("big")
+        )
+    );
+    assert_eq!(
+        where_clause("case status when 1 then true when 2 then x is null end").to_string(),
+        "CASE status WHEN 1 THEN TRUE WHEN 2 THEN x IS NULL END"
+    );
+
+    let mut parser = Parser::new(tokenize("SELECT a FROM t WHERE CASE a ELSE 1 END;"));
+    assert_eq!(parser.parse().err().unwrap().message, "CASE needs at least one WHEN ... THEN branch");
+    let mut parser = Parser::new(tokenize("SELECT a FROM t WHERE CASE WHEN a THEN 1;"));
+    assert_eq!(parser.parse().err().unwrap().message, "Expected keyword 'END'");
+}
+
+#[test]
+fn arithmetic_after_end_and_null() {
+    let case = || Box::new(Expr::Case {
+        operand: None,
+        branches: vec![(*binary(column("a"), BinaryOperator::GreaterThan, int(1)), *column("a"))],
+        else_result: None
+    });
+    assert_eq!(
+        where_clause("CASE WHEN a > 1 THEN a END * 2 > 0"),
+        *binary(binary(case(), BinaryOperator::Multiply, int(2)), BinaryOperator::GreaterThan, int(0))
+    );
+    assert_eq!(
+        where_clause("CASE WHEN a > 1 THEN a END - 1 > 0"),
+        *binary(binary(case(), BinaryOperator::Subtract, int(1)), BinaryOperator::GreaterThan, int(0))
+    );
+    let null = Box::new(Expr::Literal(Value::Null));
+    assert_eq!(
+        where_clause("NULL * 2 IS NULL"),
+        Expr::IsNull { expr: binary(null, BinaryOperator::Multiply, int(2)), negated: false }
+    );
+}
+
+#[test]
+fn cast_try_cast_and_shorthand() {
+    assert_eq!(
+        where_clause("CAST(zip AS VARCHAR(10)) = '02134'"),
+        *binary(
+            Box::new(Expr::Cast { expr: column("zip"), type_name: TypeName::Varchar, try_cast: false }),
+            BinaryOperator::Equals,
+            string("02134")
+        )
+    );
+    assert_eq!(
+        where_clause("-amount::int > 0"),
+        *binary(
+            Box::new(Expr::UnaryOp {
+                operator: UnaryOperator::Minus,
+                expr: Box::new(Expr::Cast { expr: column("amount"), type_name: TypeName::Integer, try_cast: false })
+            }),
+            BinaryOperator::GreaterThan,
+            int(0)
+        )
+    );
+    assert_eq!(
+        where_clause("try_cast(x as decimal(10, 2)) IS NULL").to_string(),
+        "TRY_CAST(x AS DOUBLE) IS NULL"
+    );
+    assert_eq!(where_clause("'2024-01-31'::DATE::text = d").to_string(), "CAST(CAST('2024-01-31' AS DATE) AS VARCHAR) = d");
+
+    let mut parser = Parser::new(tokenize("SELECT a FROM t WHERE CAST(a AS money) = 1;"));
+    assert_eq!(parser.parse().err().unwrap().message, "Unknown type money, expected BOOLEAN, INTEGER, DOUBLE, VARCHAR or DATE");
+}
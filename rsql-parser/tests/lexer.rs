@@ -0,0 +1,231 @@
+use rsql_parser::ast::constructs::{FromClause, SelectItem, Statement};
+use rsql_parser::lexer::grammar::GrammarType;
+use rsql_parser::lexer::keywords::KeywordType;
+use rsql_parser::lexer::operators::OperatorType;
+use rsql_parser::lexer::tokenizer::{state_based_tokenize, tokenize, tokenize_with_trivia};
+use rsql_parser::lexer::tokens::Token;
+use rsql_parser::parser::parser::Parser;
+
+#[test]
+fn escaped_quotes_in_string_literals() {
+    let tokens = tokenize("SELECT name FROM people WHERE title = 'O''Brien''s';");
+    assert_eq!(tokens[7], Token::StringLiteral("O'Brien's".to_string()));
+
+    let tokens = tokenize(r"SELECT name FROM people WHERE title = 'it\'s';");
+    assert_eq!(tokens[7], Token::StringLiteral("it's".to_string()));
+
+    let tokens = tokenize("SELECT name FROM people WHERE title = '';");
+    assert_eq!(tokens[7], Token::StringLiteral("".to_string()));
+}
+
+#[test]
+fn backslash_escape_sequences() {
+    let tokens = tokenize(r"SELECT name FROM people WHERE note = 'a\tb\nc\\d\'e';");
+    assert_eq!(tokens[7], Token::StringLiteral("a\tb\nc\\d'e".to_string()));
+
+    // unknown escapes are left alone for LIKE patterns
+    let tokens = tokenize(r"SELECT name FROM people WHERE note = '100\%';");
+    assert_eq!(tokens[7], Token::StringLiteral(r"100\%".to_string()));
+
+    // double quoted identifiers do not use backslash escapes
+    let tokens = tokenize(r#"SELECT "C:\temp" FROM people;"#);
+    assert_eq!(tokens[1], Token::Identifier(r"C:\temp".to_string()));
+}
+
+#[test]
+fn quoted_identifiers() {
+    let tokens = tokenize("SELECT \"First Name\", `Last Name`, \"say \"\"hi\"\"\" FROM 'people.csv';");
+    assert_eq!(tokens.len(), 9);
+    assert_eq!(tokens[1], Token::Identifier("First Name".to_string()));
+    assert_eq!(tokens[3], Token::Identifier("Last Name".to_string()));
+    assert_eq!(tokens[5], Token::Identifier("say \"hi\"".to_string()));
+    assert_eq!(tokens[7], Token::StringLiteral("people.csv".to_string()));
+
+    // keywords lose their meaning once quoted
+    let tokens = tokenize("SELECT \"from\" FROM people;");
+    assert_eq!(tokens[1], Token::Identifier("from".to_string()));
+    assert_eq!(tokens[2], Token::Keyword(KeywordType::From));
+
+    let mut parser = Parser::new(tokenize("SELECT \"First Name\" FROM \"people.csv\" WHERE `Last Name` = 'Shah';"));
+    let Statement::Select(select) = parser.parse().unwrap() else { panic!("Expected select statement") };
+    assert_eq!(select.columns, vec![SelectItem::Column("First Name".to_string())]);
+    assert_eq!(select.from, Some(FromClause::File { source: "people.csv".to_string(), alias: None }));
+}
+
+#[test]
+fn unterminated_strings_report_their_location() {
+    let error = state_based_tokenize("SELECT name\nFROM people WHERE name = 'Ram;").err().unwrap();
+    assert_eq!(error.message, "Unterminated string literal, missing closing '");
+    assert_eq!((error.line, error.column), (2, 26));
+
+    let error = state_based_tokenize("SELECT \"First Name FROM people;").err().unwrap();
+    assert_eq!(error.message, "Unterminated quoted identifier, missing closing \"");
+    assert_eq!((error.line, error.column), (1, 8));
+
+    let error = state_based_tokenize("SELECT `` FROM people;").err().unwrap();
+    assert_eq!(error.message, "Quoted identifier can not be empty");
+    assert_eq!((error.line, error.column), (1, 9));
+}
+
+#[test]
+fn errors_carry_location() {
+    let error = state_based_tokenize("SELECT name FROM people\nWHERE age > 3.1.4;").err().unwrap();
+    assert_eq!(error.message, "More than one decimals in number");
+    assert_eq!((error.line, error.column), (2, 16));
+    assert_eq!(error.to_string(), "More than one decimals in number at line 2, column 16");
+
+    let tokens = tokenize("SELECT name FROM people WHERE age >= 30;");
+    assert_eq!(tokens[6], Token::Operator(OperatorType::GreaterThanOrEqual));
+}
+
+#[test]
+fn windows_line_endings_and_unicode_whitespace() {
+    let expected = tokenize("SELECT name FROM people WHERE age > 30;");
+    assert_eq!(tokenize("SELECT name\r\nFROM people\r\nWHERE age > 30;\r\n"), expected);
+    assert_eq!(tokenize("\u{feff}SELECT\u{a0}name\u{2003}FROM\tpeople\u{2028}WHERE age\u{3000}> 30;"), expected);
+}
+
+#[test]
+fn comments_are_skipped() {
+    let sql = "-- people over thirty\r\n\
+        SELECT name, -- the full name\n\
+        age/* in years */FROM people /* outer /* nested */ still a comment */ WHERE age > 30--trailing";
+    let tokens = tokenize(sql);
+    assert_eq!(tokens, vec![
+        Token::Keyword(KeywordType::Select),
+        Token::Identifier("name".to_string()),
+        Token::Grammar(GrammarType::Comma),
+        Token::Identifier("age".to_string()),
+        Token::Keyword(KeywordType::From),
+        Token::Identifier("people".to_string()),
+        Token::Keyword(KeywordType::Where),
+        Token::Identifier("age".to_string()),
+        Token::Operator(OperatorType::GreaterThan),
+        Token::Integer(30),
+    ]);
+
+    // comment markers inside strings are just text
+    let tokens = tokenize("SELECT name FROM people WHERE note = '-- not /* a comment */';");
+    assert_eq!(tokens[7], Token::StringLiteral("-- not /* a comment */".to_string()));
+}
+
+#[test]
+fn comments_can_be_kept_as_trivia() {
+    let tokens = tokenize_with_trivia("SELECT name -- the name\r\nFROM /* a /* b */ */ people;").unwrap();
+    assert_eq!(tokens, vec![
+        Token::Keyword(KeywordType::Select),
+        Token::Identifier("name".to_string()),
+        Token::Comment("-- the name".to_string()),
+        Token::Keyword(KeywordType::From),
+        Token::Comment("/* a /* b */ */".to_string()),
+        Token::Identifier("people".to_string()),
+        Token::Grammar(GrammarType::Semicolon),
+    ]);
+
+    let mut parser = Parser::new(tokens);
+    let Statement::Select(select) = parser.parse().unwrap() else { panic!("Expected select statement") };
+    assert_eq!(select.columns, vec![SelectItem::Column("name".to_string())]);
+}
+
+#[test]
+fn unterminated_block_comment() {
+    let error = state_based_tokenize("SELECT name\n  /* a /* b */ FROM people;").err().unwrap();
+    assert_eq!(error.message, "Unterminated block comment, missing closing */");
+    assert_eq!((error.line, error.column), (2, 3));
+}
+
+fn operators(sql: &str) -> Vec<Token> {
+    tokenize(sql).into_iter().filter(|token| matches!(token, Token::Operator(_))).collect()
+}
+
+#[test]
+fn complete_operator_set() {
+    assert_eq!(operators("a + b - c * d / e % f || g"), vec![
+        Token::Operator(OperatorType::Add),
+        Token::Operator(OperatorType::Subtract),
+        Token::Operator(OperatorType::Multiply),
+        Token::Operator(OperatorType::Divide),
+        Token::Operator(OperatorType::Modulo),
+        Token::Operator(OperatorType::Concat),
+    ]);
+    assert_eq!(operators("a = b != c <> d < e > f <= g >= h"), vec![
+        Token::Operator(OperatorType::Equals),
+        Token::Operator(OperatorType::NotEquals),
+        Token::Operator(OperatorType::NotEquals),
+        Token::Operator(OperatorType::SmallerThan),
+        Token::Operator(OperatorType::GreaterThan),
+        Token::Operator(OperatorType::SmallerThanOrEqual),
+        Token::Operator(OperatorType::GreaterThanOrEqual),
+    ]);
+    assert_eq!(tokenize("zip::varchar"), vec![
+        Token::Identifier("zip".to_string()),
+        Token::Operator(OperatorType::Cast),
+        Token::Identifier("varchar".to_string()),
+    ]);
+}
+
+#[test]
+fn operators_need_no_surrounding_whitespace() {
+    assert_eq!(tokenize("age>=30"), vec![
+        Token::Identifier("age".to_string()),
+        Token::Operator(OperatorType::GreaterThanOrEqual),
+        Token::Integer(30),
+    ]);
+    assert_eq!(tokenize("name='Ram'||'!'"), vec![
+        Token::Identifier("name".to_string()),
+        Token::Operator(OperatorType::Equals),
+        Token::StringLiteral("Ram".to_string()),
+        Token::Operator(OperatorType::Concat),
+        Token::StringLiteral("!".to_string()),
+    ]);
+    // maximal munch splits >=- into >= and a negative number
+    assert_eq!(tokenize("x>=-1.5"), vec![
+        Token::Identifier("x".to_string()),
+        Token::Operator(OperatorType::GreaterThanOrEqual),
+        Token::Float(-1.5),
+    ]);
+    assert_eq!(tokenize("(a)<>b"), vec![
+        Token::Grammar(GrammarType::OpenParen),
+        Token::Identifier("a".to_string()),
+        Token::Grammar(GrammarType::CloseParen),
+        Token::Operator(OperatorType::NotEquals),
+        Token::Identifier("b".to_string()),
+    ]);
+}
+
+#[test]
+fn negative_literals() {
+    assert_eq!(tokenize("age > -5"), vec![
+        Token::Identifier("age".to_string()),
+        Token::Operator(OperatorType::GreaterThan),
+        Token::Integer(-5),
+    ]);
+    assert_eq!(tokenize("SELECT -9223372036854775808")[1], Token::Integer(i64::MIN));
+    // after an operand the minus is a subtraction
+    assert_eq!(tokenize("a -5"), vec![
+        Token::Identifier("a".to_string()),
+        Token::Operator(OperatorType::Subtract),
+        Token::Integer(5),
+    ]);
+    assert_eq!(tokenize("(1)-2")[3], Token::Operator(OperatorType::Subtract));
+}
+
+#[test]
+fn asterisk_is_wildcard_or_multiply() {
+    assert_eq!(tokenize("SELECT * FROM t")[1], Token::Grammar(GrammarType::Asterisk));
+    assert_eq!(tokenize("SELECT COUNT(*) FROM t")[3], Token::Grammar(GrammarType::Asterisk));
+    assert_eq!(tokenize("SELECT a, * FROM t")[3], Token::Grammar(GrammarType::Asterisk));
+    assert_eq!(tokenize("SELECT a * 2 FROM t")[2], Token::Operator(OperatorType::Multiply));
+    assert_eq!(tokenize("SELECT 2*3 FROM t")[2], Token::Operator(OperatorType::Multiply));
+    assert_eq!(tokenize("SELECT (a)*(b) FROM t")[4], Token::Operator(OperatorType::Multiply));
+}
+
+#[test]
+fn unknown_operators() {
+    let error = state_based_tokenize("SELECT a FROM t WHERE a ! b").err().unwrap();
+    assert_eq!(error.message, "Unknown operator !");
+    assert_eq!((error.line, error.column), (1, 25));
+
+    let error = state_based_tokenize("a |").err().unwrap();
+    assert_eq!(error.message, "Unknown operator |");
+}
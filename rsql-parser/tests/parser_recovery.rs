@@ -0,0 +1,124 @@
+use rsql_parser::ast::constructs::{BinaryOperator, Expr, FromClause, GroupByItem, OrderByItem, SelectItem, SelectStatement, Statement, Value};
+use rsql_parser::lexer::keywords::{suggest_keyword, KeywordType};
+use rsql_parser::lexer::tokenizer::tokenize;
+use rsql_parser::parser::parser::Parser;
+
+fn parse_with_recovery(sql: &str) -> (Option<SelectStatement>, Vec<(String, Option<String>)>) {
+    let mut parser = Parser::new(tokenize(sql));
+    let outcome = parser.parse_with_recovery();
+    let statement = outcome.statement.map(|statement| match statement {
+        Statement::Select(select) => select,
+        _ => panic!("Expected select statement")
+    });
+    let diagnostics = outcome.diagnostics.into_iter()
+        .map(|diagnostic| (diagnostic.message, diagnostic.suggestion))
+        .collect();
+
+    (statement, diagnostics)
+}
+
+#[test]
+fn valid_query_has_no_diagnostics() {
+    let (statement, diagnostics) =
+        parse_with_recovery("SELECT name, age FROM 'users' WHERE age > 30 GROUP BY name ORDER BY age DESC;");
+    assert!(diagnostics.is_empty());
+    let statement = statement.unwrap();
+    assert_eq!(statement.columns.len(), 2);
+    assert_eq!(statement.from, Some(FromClause::File { source: "users".to_string(), alias: None }));
+    assert_eq!(statement.group_by, Some(vec![GroupByItem::Expr(Expr::Column("name".to_string()))]));
+    assert_eq!(statement.order_by, Some(vec![OrderByItem { expr: Expr::Column("age".to_string()), asc: false }]));
+}
+
+#[test]
+fn misspelled_clause_keywords_are_corrected() {
+    let (statement, diagnostics) = parse_with_recovery("SELCT name FORM 'users' WHRE age > 30;");
+    assert_eq!(diagnostics.len(), 3);
+    assert_eq!(diagnostics[0], ("Unknown word 'SELCT'".to_string(), Some("SELECT".to_string())));
+    assert_eq!(diagnostics[1], ("Unknown word 'FORM'".to_string(), Some("FROM".to_string())));
+    assert_eq!(diagnostics[2], ("Unknown word 'WHRE'".to_string(), Some("WHERE".to_string())));
+
+    let statement = statement.unwrap();
+    assert_eq!(statement.columns, vec![SelectItem::Column("name".to_string())]);
+    assert_eq!(statement.from, Some(FromClause::File { source: "users".to_string(), alias: None }));
+    assert_eq!(statement.where_clause, Some(Expr::BinaryOp {
+        left: Box::new(Expr::Column("age".to_string())),
+        operator: BinaryOperator::GreaterThan,
+        right: Box::new(Expr::Literal(Value::Int(30))),
+    }));
+}
+
+#[test]
+fn collects_diagnostics_from_every_clause() {
+    let (statement, diagnostics) =
+        parse_with_recovery("SELECT name, , age FROM 42 WHERE age > ORDER BY age;");
+    assert_eq!(diagnostics.len(), 3);
+    assert!(diagnostics[0].0.starts_with("Expected column, *, or aggregate"));
+    assert_eq!(diagnostics[1].0, "Expected file name after FROM, found Integer 42");
+    assert_eq!(diagnostics[2].0, "Expected column or literal");
+
+    // the clauses which could be parsed are kept
+    let statement = statement.unwrap();
+    assert_eq!(statement.columns, vec![
+        SelectItem::Column("name".to_string()),
+        SelectItem::Column("age".to_string())
+    ]);
+    assert_eq!(statement.from, None);
+    assert_eq!(statement.where_clause, None);
+    assert_eq!(statement.order_by, Some(vec![OrderByItem { expr: Expr::Column("age".to_string()), asc: true }]));
+}
+
+#[test]
+fn reports_unexpected_tokens_between_clauses() {
+    let (statement, diagnostics) = parse_with_recovery("SELECT name age FROM 'users' ORDER BY age DSEC;");
+    assert_eq!(diagnostics.len(), 2);
+    assert_eq!(diagnostics[0], ("Unexpected Identifier age".to_string(), None));
+    assert_eq!(diagnostics[1], ("Expected semicolon, Identifier DSEC found".to_string(), Some("DESC".to_string())));
+
+    let statement = statement.unwrap();
+    assert_eq!(statement.columns, vec![SelectItem::Column("name".to_string())]);
+    assert_eq!(statement.from, Some(FromClause::File { source: "users".to_string(), alias: None }));
+    assert_eq!(statement.order_by, None);
+}
+
+#[test]
+fn set_operations_and_limit_are_recovered() {
+    let (statement, diagnostics) =
+        parse_with_recovery("SELECT name FROM 'users' WHERE age > UNION SELECT name FROM 'admins' LIMIT 5;");
+    assert_eq!(diagnostics.len(), 1);
+    let statement = statement.unwrap();
+    assert_eq!(statement.where_clause, None);
+    assert_eq!(statement.set_operations.len(), 1);
+    assert_eq!(statement.limit, Some(5));
+}
+
+#[test]
+fn missing_select_gives_no_statement() {
+    let (statement, diagnostics) = parse_with_recovery("name FROM 'users';");
+    assert!(statement.is_none());
+    assert_eq!(diagnostics, vec![("Expected keyword 'SELECT'".to_string(), None)]);
+}
+
+#[test]
+fn keyword_suggestions() {
+    assert_eq!(suggest_keyword("form"), Some(KeywordType::From));
+    assert_eq!(suggest_keyword("GROPU"), Some(KeywordType::Group));
+    assert_eq!(suggest_keyword("sume"), Some(KeywordType::Sum));
+    assert_eq!(suggest_keyword("FROM"), None);
+    assert_eq!(suggest_keyword("salary"), None);
+}
+
+#[test]
+fn end_of_input_ends_the_statement() {
+    let (statement, diagnostics) = parse_with_recovery("SELECT a FROM t");
+    assert!(diagnostics.is_empty());
+    assert_eq!(statement.unwrap().from, Some(FromClause::File { source: "t".to_string(), alias: None }));
+
+    let (statement, diagnostics) = parse_with_recovery("SELECT a FROM 't' WHERE a > 1 GROUP BY a ORDER BY a LIMIT 3");
+    assert!(diagnostics.is_empty());
+    assert_eq!(statement.unwrap().limit, Some(3));
+
+    // an input cut short is reported once
+    let (statement, diagnostics) = parse_with_recovery("SELECT a FROM");
+    assert_eq!(diagnostics, vec![("Unexpected end of tokens".to_string(), None)]);
+    assert_eq!(statement.unwrap().from, None);
+}
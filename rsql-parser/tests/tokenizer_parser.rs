@@ -1,4 +1,4 @@
-use rsql_parser::ast::constructs::{AggregateFunc, BinaryOperator, Expr, FromClause, OrderByItem, SelectItem, SelectStatement, Statement, Value};
+use rsql_parser::ast::constructs::{AggregateFunc, BinaryOperator, Expr, FrameBound, FrameUnits, FromClause, GroupByItem, JoinKind, OrderByItem, SelectItem, SelectStatement, SetOperator, Statement, Value, WindowFrame, WindowFunc};
 use rsql_parser::ast::constructs::Expr::{BinaryOp, Column};
 use rsql_parser::lexer::grammar::GrammarType;
 use rsql_parser::lexer::keywords::KeywordType;
@@ -15,7 +15,7 @@ fn tokenize_test() {
     assert_eq!(tokens[0], Token::Keyword(KeywordType::Select));
     assert_eq!(tokens[1], Token::Grammar(GrammarType::Asterisk));
     assert_eq!(tokens[2], Token::Keyword(KeywordType::From));
-    assert_eq!(tokens[3], Token::StringLiteral("users.csv".to_string()));
+    assert_eq!(tokens[3], Token::Identifier("users.csv".to_string()));
     assert_eq!(tokens[4], Token::Grammar(GrammarType::Semicolon));
     // test parser
     let mut parser = Parser::new(tokens);
@@ -27,10 +27,10 @@ fn tokenize_test() {
             assert_eq!(columns[0], SelectItem::Wildcard);
             let from = statement.from;
             match from {
-                Some(FromClause { source }) => {
+                Some(FromClause::File { source, .. }) => {
                     assert_eq!(source, "users.csv");
                 },
-                None => panic!("Expected from clause")
+                _ => panic!("Expected file in from clause")
             }
             assert_eq!(statement.group_by, None);
             assert_eq!(statement.order_by, None);
@@ -56,10 +56,10 @@ fn tokenize_test() {
             assert_eq!(columns[0], SelectItem::Column("name".to_string()));
             let from = statement.from;
             match from {
-                Some(FromClause { source }) => {
+                Some(FromClause::File { source, .. }) => {
                     assert_eq!(source, "users");
                 },
-                None => panic!("Expected from clause")
+                _ => panic!("Expected file in from clause")
             }
             assert_eq!(statement.group_by, None);
             assert_eq!(statement.order_by, None);
@@ -77,27 +77,29 @@ fn tokenize_test() {
     assert_eq!(tokens[4], Token::Grammar(GrammarType::Comma));
     assert_eq!(tokens[5], Token::Identifier("age".to_string()));
     assert_eq!(tokens[6], Token::Keyword(KeywordType::From));
-    assert_eq!(tokens[7], Token::StringLiteral("employees".to_string()));
+    assert_eq!(tokens[7], Token::Identifier("employees".to_string()));
     assert_eq!(tokens[8], Token::Grammar(GrammarType::Semicolon));
     // test parser
     parser = Parser::new(tokens);
     response = parser.parse().unwrap();
     match response {
         Statement::Select(SelectStatement {
+              distinct: false,
               columns   ,
-              from, where_clause,
+              from, where_clause: _,
               group_by,
-              order_by
+              order_by,
+              ..
         } ) => {
             assert_eq!(columns.len(), 3);
             assert_eq!(columns[0], SelectItem::Column("id".to_string()));
             assert_eq!(columns[1], SelectItem::Column("name".to_string()));
             assert_eq!(columns[2], SelectItem::Column("age".to_string()));
             match from {
-                Some(FromClause { source }) => {
+                Some(FromClause::File { source, .. }) => {
                     assert_eq!(source, "employees");
                 },
-                None => panic!("Expected from clause")
+                _ => panic!("Expected file in from clause")
             }
             assert_eq!(group_by, None);
             assert_eq!(order_by, None);
@@ -124,19 +126,21 @@ fn tokenize_test() {
     response = parser.parse().unwrap();
     match response {
         Statement::Select(SelectStatement {
+                              distinct: false,
                               columns   ,
                               from, where_clause,
                               group_by,
-                              order_by
+                              order_by,
+                              ..
                           } ) => {
             assert_eq!(columns.len(), 2);
             assert_eq!(columns[0], SelectItem::Column("name".to_string()));
             assert_eq!(columns[1], SelectItem::Column("salary".to_string()));
             match from {
-                Some(FromClause { source }) => {
+                Some(FromClause::File { source, .. }) => {
                     assert_eq!(source, "employees");
                 },
-                None => panic!("Expected from clause")
+                _ => panic!("Expected file in from clause")
             }
             let expected_where_expr = BinaryOp {
                 left: Box::new(Expr::Column("salary".to_string())),
@@ -170,23 +174,25 @@ fn tokenize_test() {
     response = parser.parse().unwrap();
     match response {
         Statement::Select(SelectStatement {
+                              distinct: false,
                               columns,
                               from,
                               where_clause,
                               group_by,
-                              order_by
+                              order_by,
+                              ..
                           } ) => {
             assert_eq!(columns.len(), 1);
             assert_eq!(columns[0], SelectItem::Column("name".to_string()));
-            assert_eq!(from, Some(FromClause { source: "employees".to_string() }));
+            assert_eq!(from, Some(FromClause::File { source: "employees".to_string(), alias: None }));
             match from {
-                Some(FromClause { source }) => {
+                Some(FromClause::File { source, .. }) => {
                     assert_eq!(source, "employees");
                 },
-                None => panic!("Expected from clause")
+                _ => panic!("Expected file in from clause")
             }
             match where_clause {
-                Some(Expr::BinaryOp {left, operator, right}) => {
+                Some(Expr::BinaryOp {left, ..}) => {
                     let expected_left = Box::new(Expr::BinaryOp {
                         left: Box::new(Expr::Column(String::from("department"))),
                         operator: BinaryOperator::Equals,
@@ -210,7 +216,7 @@ fn tokenize_test() {
     assert_eq!(tokens[2], Token::Grammar(GrammarType::Comma));
     assert_eq!(tokens[3], Token::Identifier("age".to_string()));
     assert_eq!(tokens[4], Token::Keyword(KeywordType::From));
-    assert_eq!(tokens[5], Token::StringLiteral("users".to_string()));
+    assert_eq!(tokens[5], Token::Identifier("users".to_string()));
     assert_eq!(tokens[6], Token::Keyword(KeywordType::Order));
     assert_eq!(tokens[7], Token::Keyword(KeywordType::By));
     assert_eq!(tokens[8], Token::Identifier("age".to_string()));
@@ -220,20 +226,22 @@ fn tokenize_test() {
     response = parser.parse().unwrap();
     match response {
         Statement::Select(SelectStatement {
+                              distinct: false,
                               columns,
                               from,
                               where_clause,
                               group_by,
-                              order_by
+                              order_by,
+                              ..
                           }) => {
             assert_eq!(columns.len(), 2);
             assert_eq!(columns[0], SelectItem::Column("name".to_string()));
             assert_eq!(columns[1], SelectItem::Column("age".to_string()));
             match from {
-                Some(FromClause { source }) => {
+                Some(FromClause::File { source, .. }) => {
                     assert_eq!(source, "users");
                 },
-                None => panic!("Expected from clause")
+                _ => panic!("Expected file in from clause")
             }
             assert_eq!(where_clause, None);
             assert_eq!(group_by, None);
@@ -273,30 +281,33 @@ fn tokenize_test() {
     response = parser.parse().unwrap();
     match response {
         Statement::Select(SelectStatement {
+                              distinct: false,
                               columns,
                               from,
                               where_clause,
                               group_by,
-                              order_by
+                              order_by,
+                              ..
                           }) => {
             assert_eq!(columns.len(), 2);
             assert_eq!(columns[0], SelectItem::Column("department".to_string()));
             assert_eq!(columns[1], SelectItem::Aggregate{
                 func: AggregateFunc::Count,
-                expr: None
+                expr: None,
+                distinct: false
             });
             match from {
-                Some(FromClause { source }) => {
+                Some(FromClause::File { source, .. }) => {
                     assert_eq!(source, "employees");
                 },
-                None => panic!("Expected from clause")
+                _ => panic!("Expected file in from clause")
             }
             assert_eq!(where_clause, None);
             assert_eq!(order_by, None);
             match group_by {
                 Some(group_by_items) => {
                     assert_eq!(group_by_items.len(), 1);
-                    let expected = Expr::Column("department".to_string());
+                    let expected = GroupByItem::Expr(Expr::Column("department".to_string()));
                     assert_eq!(group_by_items[0], expected);
                 },
                 None => panic!("Expected order by item")
@@ -329,33 +340,36 @@ fn tokenize_test() {
     response = parser.parse().unwrap();
     match response {
         Statement::Select(SelectStatement {
+                              distinct: false,
                               columns,
                               from,
                               where_clause,
                               group_by,
-                              order_by
+                              order_by,
+                              ..
                           }) => {
             assert_eq!(columns.len(), 3);
             assert_eq!(columns[0], SelectItem::Column("department".to_string()));
             assert_eq!(columns[1], SelectItem::Column("name".to_string()));
             assert_eq!(columns[2], SelectItem::Aggregate{
                 func: AggregateFunc::Count,
-                expr: None
+                expr: None,
+                distinct: false
             });
             match from {
-                Some(FromClause { source }) => {
+                Some(FromClause::File { source, .. }) => {
                     assert_eq!(source, "employees");
                 },
-                None => panic!("Expected from clause")
+                _ => panic!("Expected file in from clause")
             }
             assert_eq!(where_clause, None);
             assert_eq!(order_by, None);
             match group_by {
                 Some(group_by_items) => {
                     assert_eq!(group_by_items.len(), 2);
-                    let expected = Expr::Column("department".to_string());
+                    let expected = GroupByItem::Expr(Expr::Column("department".to_string()));
                     assert_eq!(group_by_items[0], expected);
-                    let expected = Expr::Column("name".to_string());
+                    let expected = GroupByItem::Expr(Expr::Column("name".to_string()));
                     assert_eq!(group_by_items[1], expected);
                 },
                 None => panic!("Expected order by item")
@@ -457,4 +471,304 @@ fn tokenize_test() {
     assert_eq!(tokens[6], Token::Operator(OperatorType::Equals));
     assert_eq!(tokens[7], Token::Keyword(KeywordType::True));
     assert_eq!(tokens[8], Token::Grammar(GrammarType::Semicolon));
-}
\ No newline at end of file
+}
+#[test]
+fn parse_explain() {
+    let mut parser = Parser::new(tokenize("EXPLAIN SELECT name FROM 'users.csv';"));
+    match parser.parse().unwrap() {
+        Statement::Explain { analyze, statement } => {
+            assert!(!analyze);
+            assert!(matches!(*statement, Statement::Select(_)));
+        },
+        _ => panic!("Expected explain statement")
+    }
+
+    parser = Parser::new(tokenize("explain analyze SELECT name FROM 'users.csv';"));
+    match parser.parse().unwrap() {
+        Statement::Explain { analyze, .. } => assert!(analyze),
+        _ => panic!("Expected explain statement")
+    }
+
+    parser = Parser::new(tokenize("EXPLAIN EXPLAIN SELECT name FROM 'users.csv';"));
+    assert_eq!(parser.parse().unwrap_err().message, "EXPLAIN can not be nested");
+}
+
+#[test]
+fn parse_distinct() {
+    let mut parser = Parser::new(tokenize("SELECT DISTINCT department, COUNT(DISTINCT name) FROM 'users.csv' GROUP BY department;"));
+    match parser.parse().unwrap() {
+        Statement::Select(statement) => {
+            assert!(statement.distinct);
+            assert_eq!(statement.columns[1], SelectItem::Aggregate {
+                func: AggregateFunc::Count,
+                expr: Some(Box::new(Column("name".to_string()))),
+                distinct: true
+            });
+        },
+        _ => panic!("Expected select statement")
+    }
+
+    parser = Parser::new(tokenize("SELECT SUM(DISTINCT salary) / 2 FROM 'users.csv';"));
+    match parser.parse().unwrap() {
+        Statement::Select(statement) => {
+            assert!(!statement.distinct);
+            let SelectItem::Expr { expr, .. } = &statement.columns[0] else { panic!("Expected expression") };
+            assert_eq!(expr.to_string(), "SUM(DISTINCT salary) / 2");
+        },
+        _ => panic!("Expected select statement")
+    }
+
+    parser = Parser::new(tokenize("SELECT COUNT(DISTINCT *) FROM 'users.csv';"));
+    assert_eq!(parser.parse().unwrap_err().message, "DISTINCT needs an expression, COUNT(DISTINCT *) is not valid");
+}
+
+#[test]
+fn parse_joins() {
+    let mut parser = Parser::new(tokenize(
+        "SELECT o.amount, c.city FROM 'orders.csv' AS o LEFT OUTER JOIN customers.csv c ON o.customer = c.customer \
+         CROSS JOIN 'regions.csv' WHERE c.city = 'Paris';"
+    ));
+    let Statement::Select(statement) = parser.parse().unwrap() else { panic!("Expected select statement") };
+    assert_eq!(statement.columns[0], SelectItem::Column("o.amount".to_string()));
+    let orders = FromClause::File { source: "orders.csv".to_string(), alias: Some("o".to_string()) };
+    let customers = FromClause::File { source: "customers.csv".to_string(), alias: Some("c".to_string()) };
+    assert_eq!(statement.from, Some(FromClause::Join {
+        left: Box::new(FromClause::Join {
+            left: Box::new(orders),
+            kind: JoinKind::Left,
+            right: Box::new(customers),
+            condition: Some(Expr::BinaryOp {
+                left: Box::new(Column("o.customer".to_string())),
+                operator: BinaryOperator::Equals,
+                right: Box::new(Column("c.customer".to_string()))
+            })
+        }),
+        kind: JoinKind::Cross,
+        right: Box::new(FromClause::File { source: "regions.csv".to_string(), alias: None }),
+        condition: None
+    }));
+    assert!(statement.where_clause.is_some());
+
+    parser = Parser::new(tokenize("SELECT * FROM 'a.csv' FULL JOIN 'b.csv' ON a.id = b.id;"));
+    let Statement::Select(statement) = parser.parse().unwrap() else { panic!("Expected select statement") };
+    assert!(matches!(statement.from, Some(FromClause::Join { kind: JoinKind::Full, .. })));
+
+    parser = Parser::new(tokenize("SELECT * FROM 'a.csv' JOIN 'b.csv' WHERE a.id = b.id;"));
+    assert_eq!(parser.parse().unwrap_err().message, "Expected keyword 'ON'");
+    parser = Parser::new(tokenize("SELECT * FROM 'a.csv' LEFT 'b.csv' ON a.id = b.id;"));
+    assert_eq!(parser.parse().unwrap_err().message, "Expected keyword 'JOIN'");
+}
+
+#[test]
+fn parse_subqueries() {
+    let mut parser = Parser::new(tokenize(
+        "SELECT id, (SELECT MAX(id) FROM 'b.csv') AS top FROM (SELECT id FROM 'a.csv') AS t \
+         WHERE id NOT IN (SELECT id FROM 'blocked.csv') AND EXISTS (SELECT * FROM 'c.csv' WHERE c.id = t.id);"
+    ));
+    let Statement::Select(statement) = parser.parse().unwrap() else { panic!("Expected select statement") };
+    let SelectItem::Expr { expr: Expr::Subquery(top), alias } = &statement.columns[1] else { panic!("Expected scalar subquery") };
+    assert_eq!(alias.as_deref(), Some("top"));
+    assert_eq!(top.to_string(), "SELECT MAX(id) FROM 'b.csv'");
+    let Some(FromClause::Subquery { query, alias }) = &statement.from else { panic!("Expected subquery in from clause") };
+    assert_eq!(alias, "t");
+    assert_eq!(query.from, Some(FromClause::File { source: "a.csv".to_string(), alias: None }));
+    let Some(BinaryOp { left, operator: BinaryOperator::And, right }) = &statement.where_clause else { panic!("Expected AND") };
+    assert!(matches!(left.as_ref(), Expr::InSubquery { negated: true, .. }));
+    let Expr::Exists(exists) = right.as_ref() else { panic!("Expected EXISTS") };
+    assert!(exists.where_clause.is_some());
+
+    parser = Parser::new(tokenize("SELECT * FROM (SELECT id FROM 'a.csv');"));
+    assert_eq!(parser.parse().unwrap_err().message, "Subquery in FROM needs an alias, (SELECT ...) AS t");
+}
+
+#[test]
+fn parse_common_table_expressions() {
+    let sql = "WITH RECURSIVE chain(id, level) AS (SELECT id, 0 FROM 'org.csv' WHERE manager IS NULL \
+               UNION ALL SELECT o.id, level + 1 FROM 'org.csv' AS o JOIN chain ON o.manager = chain.id), \
+               top AS (SELECT id FROM chain WHERE level = 0) SELECT id FROM top ORDER BY id";
+    let mut parser = Parser::new(tokenize(&format!("{};", sql)));
+    let Statement::Select(statement) = parser.parse().unwrap() else { panic!("Expected select statement") };
+    let with = statement.with.as_ref().expect("Expected WITH");
+    assert!(with.recursive);
+    assert_eq!(with.ctes.iter().map(|cte| cte.name.as_str()).collect::<Vec<&str>>(), vec!["chain", "top"]);
+    assert_eq!(with.ctes[0].columns, vec!["id".to_string(), "level".to_string()]);
+    assert_eq!(with.ctes[0].query.set_operations.len(), 1);
+    assert!(with.ctes[0].query.set_operations[0].all);
+    assert_eq!(statement.from, Some(FromClause::File { source: "top".to_string(), alias: None }));
+    assert!(statement.order_by.is_some());
+    // printed back, the statement parses the same
+    let reparsed = Parser::new(tokenize(&format!("{};", statement))).parse().unwrap();
+    assert_eq!(reparsed, Statement::Select(statement));
+
+    parser = Parser::new(tokenize("WITH t AS SELECT id FROM 'a.csv' SELECT id FROM t;"));
+    assert_eq!(parser.parse().unwrap_err().message, "Expected grammar element '('");
+}
+
+#[test]
+fn parse_set_operations() {
+    let sql = "SELECT id FROM 'a.csv' EXCEPT ALL SELECT id FROM 'b.csv' INTERSECT SELECT id FROM 'c.csv' \
+               UNION SELECT id FROM 'd.csv' ORDER BY id LIMIT 10";
+    let mut parser = Parser::new(tokenize(&format!("{};", sql)));
+    let Statement::Select(statement) = parser.parse().unwrap() else { panic!("Expected select statement") };
+    let operators: Vec<(SetOperator, bool)> = statement.set_operations.iter().map(|operation| (operation.operator, operation.all)).collect();
+    assert_eq!(operators, vec![(SetOperator::Except, true), (SetOperator::Union, false)]);
+    // INTERSECT binds tighter, it belongs to the select before it
+    let intersected = &statement.set_operations[0].query;
+    assert_eq!(intersected.set_operations.len(), 1);
+    assert_eq!(intersected.set_operations[0].operator, SetOperator::Intersect);
+    assert!(statement.order_by.is_some());
+    assert_eq!(statement.limit, Some(10));
+    let reparsed = Parser::new(tokenize(&format!("{};", statement))).parse().unwrap();
+    assert_eq!(reparsed, Statement::Select(statement));
+
+    parser = Parser::new(tokenize("SELECT id FROM 'a.csv' LIMIT id;"));
+    assert_eq!(parser.parse().unwrap_err().message, "Expected an integer literal");
+}
+
+#[test]
+fn parse_window_functions() {
+    let sql = "SELECT ROW_NUMBER() OVER (PARTITION BY dept ORDER BY salary DESC), \
+               SUM(salary) OVER (ORDER BY hired ROWS BETWEEN 2 PRECEDING AND CURRENT ROW), \
+               lag(salary, 1, 0) OVER (ORDER BY hired RANGE UNBOUNDED PRECEDING) FROM 'staff.csv'";
+    let mut parser = Parser::new(tokenize(&format!("{};", sql)));
+    let Statement::Select(statement) = parser.parse().unwrap() else { panic!("Expected select statement") };
+    let SelectItem::Expr { expr: Expr::Window { func, args, over }, .. } = &statement.columns[1] else {
+        panic!("Expected a window function")
+    };
+    assert_eq!(*func, WindowFunc::Aggregate(AggregateFunc::Sum));
+    assert_eq!(args.len(), 1);
+    assert_eq!(over.order_by.len(), 1);
+    assert_eq!(over.frame, Some(WindowFrame { units: FrameUnits::Rows, start: FrameBound::Preceding(Value::Int(2)), end: FrameBound::CurrentRow }));
+    // a single bound is the start of a frame which ends with the current row
+    let SelectItem::Expr { expr: Expr::Window { func, over, .. }, .. } = &statement.columns[2] else {
+        panic!("Expected a window function")
+    };
+    assert_eq!(*func, WindowFunc::Lag);
+    assert_eq!(over.frame.as_ref().map(|frame| &frame.end), Some(&FrameBound::CurrentRow));
+    let reparsed = Parser::new(tokenize(&format!("{};", statement))).parse().unwrap();
+    assert_eq!(reparsed, Statement::Select(statement));
+
+    parser = Parser::new(tokenize("SELECT RANK() FROM 'staff.csv';"));
+    assert_eq!(parser.parse().unwrap_err().message, "RANK needs an OVER clause");
+    parser = Parser::new(tokenize("SELECT UPPER(name) OVER () FROM 'staff.csv';"));
+    assert_eq!(parser.parse().unwrap_err().message, "UPPER is not a window function");
+    parser = Parser::new(tokenize("SELECT COUNT(DISTINCT name) OVER () FROM 'staff.csv';"));
+    assert_eq!(parser.parse().unwrap_err().message, "DISTINCT is not supported in window functions, found COUNT(DISTINCT ...) OVER");
+    parser = Parser::new(tokenize("SELECT SUM(salary) OVER (ROWS BETWEEN 1 AND CURRENT ROW) FROM 'staff.csv';"));
+    assert_eq!(parser.parse().unwrap_err().message, "Expected PRECEDING or FOLLOWING, found Keyword AND");
+}
+
+#[test]
+fn parse_statistical_aggregates() {
+    let sql = "SELECT stddev_samp(salary), MEDIAN(salary), PERCENTILE_CONT(salary, 0.9), STRING_AGG(DISTINCT name, '; '), \
+               COUNT_IF(age > 30) FROM 'staff.csv' GROUP BY dept";
+    let mut parser = Parser::new(tokenize(&format!("{};", sql)));
+    let Statement::Select(statement) = parser.parse().unwrap() else { panic!("Expected select statement") };
+    let funcs: Vec<AggregateFunc> = statement.columns.iter()
+        .map(|item| match item {
+            SelectItem::Aggregate { func, .. } => func.clone(),
+            other => panic!("Expected an aggregate, found {}", other)
+        })
+        .collect();
+    assert_eq!(funcs, vec![
+        AggregateFunc::Stddev,
+        AggregateFunc::Median,
+        AggregateFunc::PercentileCont(0.9),
+        AggregateFunc::StringAgg("; ".to_string()),
+        AggregateFunc::CountIf
+    ]);
+    assert_eq!(statement.columns[3].to_string(), "STRING_AGG(DISTINCT name, '; ')");
+    let reparsed = Parser::new(tokenize(&format!("{};", statement))).parse().unwrap();
+    assert_eq!(reparsed, Statement::Select(statement));
+
+    parser = Parser::new(tokenize("SELECT PERCENTILE_DISC(salary, 2) FROM 'staff.csv';"));
+    assert_eq!(parser.parse().unwrap_err().message, "the fraction of PERCENTILE_DISC must be between 0 and 1, found 2");
+
+    // the standard spelling names the fraction first and orders the values in WITHIN GROUP
+    let sql = "SELECT PERCENTILE_CONT(0.9) WITHIN GROUP (ORDER BY salary), percentile_disc(1) within group (order by age + 1 asc) \
+               FROM 'staff.csv';";
+    let Statement::Select(statement) = Parser::new(tokenize(sql)).parse().unwrap() else { panic!("Expected select statement") };
+    let rendered: Vec<String> = statement.columns.iter().map(|item| item.to_string()).collect();
+    assert_eq!(rendered, vec!["PERCENTILE_CONT(salary, 0.9)", "PERCENTILE_DISC(age + 1, 1)"]);
+    parser = Parser::new(tokenize("SELECT PERCENTILE_CONT(0.5) WITHIN GROUP (ORDER BY salary DESC) FROM 'staff.csv';"));
+    assert_eq!(parser.parse().unwrap_err().message, "PERCENTILE_CONT only supports WITHIN GROUP (ORDER BY ... ASC)");
+    parser = Parser::new(tokenize("SELECT PERCENTILE_CONT(1.5) WITHIN GROUP (ORDER BY salary) FROM 'staff.csv';"));
+    assert_eq!(parser.parse().unwrap_err().message, "the fraction of PERCENTILE_CONT must be between 0 and 1, found 1.5");
+    parser = Parser::new(tokenize("SELECT PERCENTILE_CONT(0.5) FROM 'staff.csv';"));
+    assert_eq!(parser.parse().unwrap_err().message, "Expected WITHIN, found Keyword FROM");
+    parser = Parser::new(tokenize("SELECT STRING_AGG(name) FROM 'staff.csv';"));
+    assert_eq!(parser.parse().unwrap_err().message, "Expected grammar element ','");
+}
+
+#[test]
+fn parse_approximate_aggregates() {
+    let sql = "SELECT APPROX_COUNT_DISTINCT(user_id), approx_percentile(latency, 0.99), APPROX_TOP_K(path, 10) FROM 'logs.csv'";
+    let mut parser = Parser::new(tokenize(&format!("{};", sql)));
+    let Statement::Select(statement) = parser.parse().unwrap() else { panic!("Expected select statement") };
+    let rendered: Vec<String> = statement.columns.iter().map(|item| item.to_string()).collect();
+    assert_eq!(rendered, vec!["APPROX_COUNT_DISTINCT(user_id)", "APPROX_PERCENTILE(latency, 0.99)", "APPROX_TOP_K(path, 10)"]);
+    let reparsed = Parser::new(tokenize(&format!("{};", statement))).parse().unwrap();
+    assert_eq!(reparsed, Statement::Select(statement));
+
+    parser = Parser::new(tokenize("SELECT APPROX_TOP_K(path, 0) FROM 'logs.csv';"));
+    assert_eq!(parser.parse().unwrap_err().message, "the k of APPROX_TOP_K must be positive, found 0");
+}
+
+#[test]
+fn parse_grouping_sets() {
+    let sql = "SELECT region, city, SUM(amount), GROUPING(region, city) FROM 'sales.csv' \
+               GROUP BY year, ROLLUP(region, city), CUBE(a, b), GROUPING SETS ((c, d), (c), ())";
+    let mut parser = Parser::new(tokenize(&format!("{};", sql)));
+    let Statement::Select(statement) = parser.parse().unwrap() else { panic!("Expected select statement") };
+    let column = |name: &str| Expr::Column(name.to_string());
+    assert_eq!(statement.group_by, Some(vec![
+        GroupByItem::Expr(column("year")),
+        GroupByItem::Rollup(vec![column("region"), column("city")]),
+        GroupByItem::Cube(vec![column("a"), column("b")]),
+        GroupByItem::GroupingSets(vec![vec![column("c"), column("d")], vec![column("c")], vec![]])
+    ]));
+    let reparsed = Parser::new(tokenize(&format!("{};", statement))).parse().unwrap();
+    assert_eq!(reparsed, Statement::Select(statement));
+
+    // ROLLUP and CUBE are still names of columns elsewhere
+    parser = Parser::new(tokenize("SELECT rollup, cube FROM 'sales.csv' GROUP BY rollup, cube;"));
+    assert!(parser.parse().is_ok());
+
+    parser = Parser::new(tokenize("SELECT region FROM 'sales.csv' GROUP BY ROLLUP();"));
+    assert_eq!(parser.parse().unwrap_err().message, "ROLLUP needs at least one expression");
+}
+
+#[test]
+fn parse_pivot_and_unpivot() {
+    let sql = "SELECT * FROM 'monthly.csv' UNPIVOT INCLUDE NULLS (amount FOR month IN (jan, feb)) AS u \
+               PIVOT (SUM(amount) FOR month IN ('jan' AS january, 'feb')) AS p";
+    let mut parser = Parser::new(tokenize(&format!("{};", sql)));
+    let Statement::Select(statement) = parser.parse().unwrap() else { panic!("Expected select statement") };
+    let unpivot = FromClause::Unpivot {
+        input: Box::new(FromClause::File { source: "monthly.csv".to_string(), alias: None }),
+        value: "amount".to_string(),
+        name: "month".to_string(),
+        columns: vec!["jan".to_string(), "feb".to_string()],
+        include_nulls: true,
+        alias: Some("u".to_string())
+    };
+    assert_eq!(statement.from, Some(FromClause::Pivot {
+        input: Box::new(unpivot),
+        aggregate: Expr::Aggregate { func: AggregateFunc::Sum, expr: Some(Box::new(Column("amount".to_string()))), distinct: false },
+        column: "month".to_string(),
+        values: vec![
+            (Expr::Literal(Value::String("jan".to_string())), Some("january".to_string())),
+            (Expr::Literal(Value::String("feb".to_string())), None)
+        ],
+        alias: Some("p".to_string())
+    }));
+    let reparsed = Parser::new(tokenize(&format!("{};", statement))).parse().unwrap();
+    assert_eq!(reparsed, Statement::Select(statement));
+
+    // without its parenthesis PIVOT is an alias
+    parser = Parser::new(tokenize("SELECT pivot.amount FROM 'sales.csv' pivot;"));
+    assert!(parser.parse().is_ok());
+
+    parser = Parser::new(tokenize("SELECT * FROM 'sales.csv' PIVOT (amount FOR month IN ('jan'));"));
+    assert_eq!(parser.parse().unwrap_err().message, "PIVOT expects an aggregate function, found amount");
+}